@@ -1,4 +1,5 @@
 pub mod mbus_api;
+pub mod metrics;
 pub mod store;
 pub mod types;
 
@@ -108,6 +108,16 @@ impl Bus for NatsMessageBus {
             })
     }
 
+    async fn publish_subject(&self, subject: &str, message: &[u8]) -> BusResult<()> {
+        self.connection
+            .publish(subject, message)
+            .await
+            .context(Publish {
+                channel: subject.to_string(),
+                payload: String::from_utf8(Vec::from(message)),
+            })
+    }
+
     async fn send(&self, _channel: Channel, _message: &[u8]) -> BusResult<()> {
         unimplemented!()
     }
@@ -175,6 +185,12 @@ impl Bus for NatsMessageBus {
             })
     }
 
+    async fn subscribe_subject(&self, subject: &str) -> BusResult<BusSubscription> {
+        self.connection.subscribe(subject).await.context(Subscribe {
+            channel: subject.to_string(),
+        })
+    }
+
     fn client_name(&self) -> &BusClient {
         &self.client_name
     }
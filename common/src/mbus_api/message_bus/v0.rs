@@ -5,13 +5,15 @@ pub use crate::mbus_api::{v0::*, Message};
 use crate::{
     mbus_api::{ReplyError, ReplyErrorKind, ResourceKind},
     types::v0::message_bus::{
-        AddNexusChild, AddVolumeNexus, Child, CreateNexus, CreatePool, CreateReplica, CreateVolume,
-        DestroyNexus, DestroyPool, DestroyReplica, DestroyVolume, Filter, GetBlockDevices,
-        GetNexuses, GetNodes, GetPools, GetReplicas, GetSpecs, GetStates, GetVolumes,
-        JsonGrpcRequest, Nexus, Node, NodeId, Pool, PublishVolume, RemoveNexusChild,
-        RemoveVolumeNexus, Replica, SetVolumeReplica, ShareNexus, ShareReplica, ShareVolume, Specs,
-        States, UnpublishVolume, UnshareNexus, UnshareReplica, UnshareVolume, Volume, VolumeId,
-        VolumeShareProtocol,
+        AddNexusChild, AddVolumeNexus, Child, CordonNode, CreateNexus, CreatePool, CreateReplica,
+        CreateVolume, CreateVolumeSnapshot, DestroyNexus, DestroyPool, DestroyReplica,
+        DestroyVolume, DestroyVolumeSnapshot, DrainNode, Filter, GetBlockDevices, GetEvents,
+        GetNexuses, GetNodes, GetPools, GetReplicas, GetSpecs, GetStates, GetVolumeSnapshots,
+        GetVolumes, JsonGrpcRequest, LabelNode, Nexus, Node, NodeId, Pool, PublishVolume,
+        RemoveNexusChild, RemoveVolumeNexus, Replica, SetPoolMaintenance, SetVolumeReplica,
+        ShareNexus, ShareReplica, ShareVolume, SnapshotId, Specs, States, UncordonNode,
+        UnlabelNode, UnpublishVolume, UnshareNexus, UnshareReplica, UnshareVolume, Volume,
+        VolumeId, VolumeShareProtocol, VolumeSnapshot,
     },
 };
 use async_trait::async_trait;
@@ -64,6 +66,36 @@ pub trait MessageBusTrait: Sized {
         only_one!(nodes, ResourceKind::Node)
     }
 
+    /// Cordon node with `id`
+    #[tracing::instrument(level = "debug", err)]
+    async fn cordon_node(id: &NodeId) -> BusResult<Node> {
+        Ok(CordonNode::new(id).request().await?)
+    }
+
+    /// Uncordon node with `id`
+    #[tracing::instrument(level = "debug", err)]
+    async fn uncordon_node(id: &NodeId) -> BusResult<Node> {
+        Ok(UncordonNode::new(id).request().await?)
+    }
+
+    /// Drain node with `id`
+    #[tracing::instrument(level = "debug", err)]
+    async fn drain_node(id: &NodeId) -> BusResult<Node> {
+        Ok(DrainNode::new(id).request().await?)
+    }
+
+    /// Set the label `key` to `value` on node with `id`
+    #[tracing::instrument(level = "debug", err)]
+    async fn label_node(id: &NodeId, key: &str, value: &str) -> BusResult<Node> {
+        Ok(LabelNode::new(id, key, value).request().await?)
+    }
+
+    /// Remove the label `key` from node with `id`
+    #[tracing::instrument(level = "debug", err)]
+    async fn unlabel_node(id: &NodeId, key: &str) -> BusResult<Node> {
+        Ok(UnlabelNode::new(id, key).request().await?)
+    }
+
     /// Get pool with filter
     #[tracing::instrument(level = "debug", err)]
     async fn get_pool(filter: Filter) -> BusResult<Pool> {
@@ -91,6 +123,12 @@ pub trait MessageBusTrait: Sized {
         Ok(())
     }
 
+    /// start or stop the maintenance of a pool
+    #[tracing::instrument(level = "debug", err)]
+    async fn set_pool_maintenance(request: SetPoolMaintenance) -> BusResult<Pool> {
+        Ok(request.request().await?)
+    }
+
     /// Get replica with filter
     #[tracing::instrument(level = "debug", err)]
     async fn get_replica(filter: Filter) -> BusResult<Replica> {
@@ -233,8 +271,9 @@ pub trait MessageBusTrait: Sized {
         uuid: VolumeId,
         node: Option<NodeId>,
         protocol: Option<VolumeShareProtocol>,
+        num_paths: Option<u8>,
     ) -> BusResult<Volume> {
-        let request = PublishVolume::new(uuid, node, protocol);
+        let request = PublishVolume::new(uuid, node, protocol).with_num_paths(num_paths);
         Ok(request.request().await?)
     }
 
@@ -267,6 +306,38 @@ pub trait MessageBusTrait: Sized {
         Ok(())
     }
 
+    /// Get volume snapshots with filter
+    #[tracing::instrument(level = "debug", err)]
+    async fn get_volume_snapshots(filter: Filter) -> BusResult<Vec<VolumeSnapshot>> {
+        let snapshots = GetVolumeSnapshots { filter }.request().await?;
+        Ok(snapshots.into_inner())
+    }
+
+    /// Get volume snapshot with filter
+    #[tracing::instrument(level = "debug", err)]
+    async fn get_volume_snapshot(filter: Filter) -> BusResult<VolumeSnapshot> {
+        let snapshots = Self::get_volume_snapshots(filter.clone()).await?;
+        only_one!(snapshots, ResourceKind::Snapshot)
+    }
+
+    /// create volume snapshot
+    #[tracing::instrument(level = "debug", err)]
+    async fn create_volume_snapshot(
+        volume: VolumeId,
+        snapshot: SnapshotId,
+    ) -> BusResult<VolumeSnapshot> {
+        let request = CreateVolumeSnapshot::new(&volume, &snapshot);
+        Ok(request.request().await?)
+    }
+
+    /// delete volume snapshot
+    #[tracing::instrument(level = "debug", err)]
+    async fn delete_volume_snapshot(volume: VolumeId, snapshot: SnapshotId) -> BusResult<()> {
+        let request = DestroyVolumeSnapshot::new(&volume, &snapshot);
+        request.request().await?;
+        Ok(())
+    }
+
     /// Generic JSON gRPC call
     #[tracing::instrument(level = "debug", err)]
     async fn json_grpc_call(request: JsonGrpcRequest) -> BusResult<serde_json::Value> {
@@ -290,6 +361,12 @@ pub trait MessageBusTrait: Sized {
     async fn get_states(request: GetStates) -> BusResult<States> {
         Ok(request.request().await?)
     }
+
+    /// Get the audit log events, optionally only those of the given resource
+    #[tracing::instrument(level = "debug", err)]
+    async fn get_events(request: GetEvents) -> BusResult<Events> {
+        Ok(request.request().await?)
+    }
 }
 
 /// Implementation of the bus interface trait
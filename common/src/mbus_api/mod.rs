@@ -327,6 +327,8 @@ pub enum ResourceKind {
     Block,
     /// Watch
     Watch,
+    /// Volume snapshot
+    Snapshot,
 }
 
 /// Error type which is returned over the bus
@@ -634,6 +636,9 @@ pub trait Bus: Clone + Send + Sync {
     /// publish a message - not guaranteed to be sent or received (fire and
     /// forget)
     async fn publish(&self, channel: Channel, message: &[u8]) -> BusResult<()>;
+    /// publish a message on a subject which is not a bus channel, eg: to notify external
+    /// subscribers - not guaranteed to be received (fire and forget)
+    async fn publish_subject(&self, subject: &str, message: &[u8]) -> BusResult<()>;
     /// Send a message and wait for it to be received by the target component
     async fn send(&self, channel: Channel, message: &[u8]) -> BusResult<()>;
     /// Send a message and request a reply from the target component
@@ -649,6 +654,9 @@ pub trait Bus: Clone + Send + Sync {
     /// polled for messages until it is either explicitly closed or
     /// when the bus is closed
     async fn subscribe(&self, channel: Channel) -> BusResult<BusSubscription>;
+    /// Create a subscription on a subject which is not a bus channel, eg: the subject on which
+    /// the notifications of a watch are published
+    async fn subscribe_subject(&self, subject: &str) -> BusResult<BusSubscription>;
     /// Get this client's name
     fn client_name(&self) -> &BusClient;
     /// Get the configured timeout options
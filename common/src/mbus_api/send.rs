@@ -357,6 +357,17 @@ where
 
     /// Sends the message and requests a reply.
     pub(crate) async fn request(&mut self, options: Option<TimeoutOptions>) -> BusResult<R> {
+        let started = std::time::Instant::now();
+        let result = self.request_inner(options).await;
+        crate::metrics::observe_bus_request(
+            &self.payload.id.to_string(),
+            crate::metrics::result_label(&result),
+            started.elapsed(),
+        );
+        result
+    }
+
+    async fn request_inner(&mut self, options: Option<TimeoutOptions>) -> BusResult<R> {
         let context = self.trace_context();
         let options = self.timeout_opts(options);
         let payload = serde_json::to_vec(&self.payload).context(SerializeSend {
@@ -31,11 +31,18 @@ bus_impl_message_all!(Deregister, Deregister, (), Registry);
 
 bus_impl_vector_request!(Nodes, Node);
 bus_impl_message_all!(GetNodes, GetNodes, Nodes, Node);
+bus_impl_message_all!(CordonNode, CordonNode, Node, Node);
+bus_impl_message_all!(UncordonNode, UncordonNode, Node, Node);
+bus_impl_message_all!(DrainNode, DrainNode, Node, Node);
+bus_impl_message_all!(LabelNode, LabelNode, Node, Node);
+bus_impl_message_all!(UnlabelNode, UnlabelNode, Node, Node);
 
 bus_impl_message_all!(CreatePool, CreatePool, Pool, Pool);
 
 bus_impl_message_all!(DestroyPool, DestroyPool, (), Pool);
 
+bus_impl_message_all!(SetPoolMaintenance, SetPoolMaintenance, Pool, Pool);
+
 bus_impl_vector_request!(Pools, Pool);
 bus_impl_message_all!(GetPools, GetPools, Pools, Pool);
 
@@ -84,6 +91,23 @@ bus_impl_message_all!(RemoveVolumeNexus, RemoveVolumeNexus, (), Volume);
 
 bus_impl_message_all!(SetVolumeReplica, SetVolumeReplica, Volume, Volume);
 
+bus_impl_vector_request!(VolumeSnapshots, VolumeSnapshot);
+bus_impl_message_all!(
+    GetVolumeSnapshots,
+    GetVolumeSnapshots,
+    VolumeSnapshots,
+    Volume
+);
+
+bus_impl_message_all!(
+    CreateVolumeSnapshot,
+    CreateVolumeSnapshot,
+    VolumeSnapshot,
+    Volume
+);
+
+bus_impl_message_all!(DestroyVolumeSnapshot, DestroyVolumeSnapshot, (), Volume);
+
 bus_impl_message_all!(JsonGrpcRequest, JsonGrpc, Value, JsonGrpc);
 
 bus_impl_vector_request!(BlockDevices, BlockDevice);
@@ -100,3 +124,6 @@ bus_impl_message_all!(DeleteWatch, DeleteWatch, (), Watcher);
 bus_impl_message_all!(GetSpecs, GetSpecs, Specs, Registry);
 
 bus_impl_message_all!(GetStates, GetStates, States, Registry);
+
+bus_impl_vector_request!(Events, Event);
+bus_impl_message_all!(GetEvents, GetEvents, Events, Registry);
@@ -0,0 +1,98 @@
+//! Prometheus metrics which are common to the control plane components.
+//! Each component registers its own metrics with the default registry so they can all be
+//! exported together through `encode`.
+
+use once_cell::sync::Lazy;
+use prometheus::{histogram_opts, register_histogram_vec, Encoder, HistogramVec, TextEncoder};
+use std::time::Duration;
+
+/// Namespace of the metrics of all the control plane components, which keeps them apart from
+/// the metrics of other exporters
+pub const NAMESPACE: &str = "mayastor_control_plane";
+
+/// Content type of the encoded metrics
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Latency of the message bus requests sent by this component
+static BUS_REQUEST_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        histogram_opts!(
+            "mbus_request_duration_seconds",
+            "Latency of the message bus requests sent by this component"
+        )
+        .namespace(NAMESPACE),
+        &["message_id", "result"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Latency of the message bus requests handled by this component
+static BUS_HANDLER_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        histogram_opts!(
+            "mbus_handler_duration_seconds",
+            "Latency of the message bus requests handled by this component"
+        )
+        .namespace(NAMESPACE),
+        &["message_id", "result"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Label value for the result of an operation
+pub fn result_label<T, E>(result: &Result<T, E>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    }
+}
+
+/// Record the latency of a message bus request sent by this component
+pub fn observe_bus_request(message_id: &str, result: &'static str, duration: Duration) {
+    BUS_REQUEST_DURATION
+        .with_label_values(&[message_id, result])
+        .observe(duration.as_secs_f64());
+}
+
+/// Record the latency of a message bus request handled by this component
+pub fn observe_bus_handler(message_id: &str, result: &'static str, duration: Duration) {
+    BUS_HANDLER_DURATION
+        .with_label_values(&[message_id, result])
+        .observe(duration.as_secs_f64());
+}
+
+/// Encode all the metrics from the default registry using the prometheus text format
+pub fn encode() -> Result<String, prometheus::Error> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bus_latencies() {
+        let ok: Result<(), ()> = Ok(());
+        let error: Result<(), ()> = Err(());
+        assert_eq!(result_label(&ok), "ok");
+        assert_eq!(result_label(&error), "error");
+
+        observe_bus_request("v0/Test", result_label(&ok), Duration::from_millis(10));
+        observe_bus_request("v0/Test", result_label(&ok), Duration::from_millis(20));
+        observe_bus_handler("v0/Test", result_label(&error), Duration::from_millis(30));
+
+        let metrics = encode().unwrap();
+        let lines = metrics.lines().collect::<Vec<_>>();
+        assert!(lines.contains(
+            &"mayastor_control_plane_mbus_request_duration_seconds_count{message_id=\"v0/Test\",result=\"ok\"} 2"
+        ));
+        assert!(lines.contains(
+            &"mayastor_control_plane_mbus_handler_duration_seconds_count{message_id=\"v0/Test\",result=\"error\"} 1"
+        ));
+        assert!(!lines.iter().any(|l| l.starts_with(
+            "mbus_handler_duration_seconds_count{message_id=\"v0/Test\",result=\"ok\"}"
+        )));
+    }
+}
@@ -3,15 +3,18 @@ use crate::{
     types::v0::store::{
         definitions::{
             Connect, Delete, DeserialiseValue, Get, GetPrefix, KeyString, ObjectKey, Put,
-            SerialiseValue, StorableObject, Store, StoreError, StoreError::MissingEntry, StoreKey,
-            StoreValue, ValueString, Watch, WatchEvent,
+            RevisionWatchEvent, SerialiseValue, StorableObject, Store, StoreError,
+            StoreError::MissingEntry, StoreKey, StoreRevisionWatchReceiver, StoreValue,
+            ValueString, Watch, WatchEvent,
         },
         registry::ControlPlaneService,
+        schema::{object_value, stored_value},
     },
 };
 use async_trait::async_trait;
 use etcd_client::{
-    Client, Compare, CompareOp, EventType, GetOptions, KeyValue, Txn, TxnOp, WatchStream, Watcher,
+    Client, Compare, CompareOp, EventType, GetOptions, KeyValue, Txn, TxnOp, WatchOptions,
+    WatchStream, Watcher,
 };
 use serde_json::Value;
 use snafu::ResultExt;
@@ -182,13 +185,40 @@ impl Store for Etcd {
             .context(Watch {
                 key: key.to_string(),
             })?;
-        watch(watcher, stream, sender);
+        watch(key.to_string(), watcher, stream, sender, |event, _| event);
+        Ok(receiver)
+    }
+
+    /// 'Watch' the etcd entry with the given key, starting from the given revision.
+    /// A receiver channel is returned which is signalled when the entry with
+    /// the given key is changed, along with the revision of the change.
+    async fn watch_kv_from<K: StoreKey>(
+        &mut self,
+        key: &K,
+        revision: Option<i64>,
+    ) -> Result<StoreRevisionWatchReceiver, StoreError> {
+        let (sender, receiver) = channel(100);
+        let options = revision.map(|revision| WatchOptions::new().with_start_revision(revision));
+        let (watcher, stream) =
+            self.client
+                .watch(key.to_string(), options)
+                .await
+                .context(Watch {
+                    key: key.to_string(),
+                })?;
+        watch(
+            key.to_string(),
+            watcher,
+            stream,
+            sender,
+            |event, revision| RevisionWatchEvent { event, revision },
+        );
         Ok(receiver)
     }
 
     async fn put_obj<O: StorableObject>(&mut self, object: &O) -> Result<(), StoreError> {
         let key = object.key().key();
-        let vec_value = serde_json::to_vec(object).context(SerialiseValue)?;
+        let vec_value = serde_json::to_vec(&stored_value(object)?).context(SerialiseValue)?;
 
         if let Some((lease_id, lock_key)) = self.lease_lock()? {
             let cmp = Compare::lease(lock_key.clone(), CompareOp::Equal, lease_id);
@@ -226,11 +256,17 @@ impl Store for Etcd {
             .await
             .context(Get { key: key.key() })?;
         match resp.kvs().first() {
-            Some(kv) => Ok(
-                serde_json::from_slice(kv.value()).context(DeserialiseValue {
+            Some(kv) => {
+                let value = serde_json::from_slice(kv.value()).context(DeserialiseValue {
                     value: kv.value_str().context(ValueString {})?,
-                })?,
-            ),
+                })?;
+                let value = object_value(key.key_type(), value)?;
+                Ok(
+                    serde_json::from_value(value.clone()).context(DeserialiseValue {
+                        value: value.to_string(),
+                    })?,
+                )
+            }
             None => Err(MissingEntry { key: key.key() }),
         }
     }
@@ -268,7 +304,7 @@ impl Store for Etcd {
             .watch(key.key(), None)
             .await
             .context(Watch { key: key.key() })?;
-        watch(watcher, stream, sender);
+        watch(key.key(), watcher, stream, sender, |event, _| event);
         Ok(receiver)
     }
 
@@ -278,12 +314,15 @@ impl Store for Etcd {
 }
 
 /// Watch for events in the key-value store.
-/// When an event occurs, a WatchEvent is sent over the channel.
+/// When an event occurs, a WatchEvent is sent over the channel, mapped along with its revision
+/// by `map`.
 /// When a 'delete' event is received, the watcher stops watching.
-fn watch(
+fn watch<E: Send + 'static>(
+    key: String,
     _watcher: Watcher,
     mut stream: WatchStream,
-    sender: Sender<Result<WatchEvent, StoreError>>,
+    sender: Sender<Result<E, StoreError>>,
+    map: fn(WatchEvent, i64) -> E,
 ) {
     // For now we spawn a thread for each value that is watched.
     // If we find that we are watching lots of events, this can be optimised.
@@ -306,12 +345,24 @@ fn watch(
                 }
             };
 
+            if response.compact_revision() > 0 {
+                // the start revision has been compacted so the watch has been cancelled
+                let _ = sender
+                    .send(Err(StoreError::WatchCompacted {
+                        key,
+                        compacted: response.compact_revision(),
+                    }))
+                    .await;
+                return;
+            }
+
             for event in response.events() {
+                let revision = event.kv().map(|kv| kv.mod_revision()).unwrap_or_default();
                 match event.event_type() {
                     EventType::Put => {
                         if let Some(kv) = event.kv() {
                             let result = match deserialise_kv(kv) {
-                                Ok((key, value)) => Ok(WatchEvent::Put(key, value)),
+                                Ok((key, value)) => Ok(map(WatchEvent::Put(key, value), revision)),
                                 Err(e) => Err(e),
                             };
                             if sender.send(result).await.is_err() {
@@ -325,7 +376,7 @@ fn watch(
                         // Send only fails if the receiver is closed. We are
                         // returning here anyway, so the error doesn't need to
                         // be handled.
-                        let _ = sender.send(Ok(WatchEvent::Delete)).await;
+                        let _ = sender.send(Ok(map(WatchEvent::Delete, revision))).await;
                         return;
                     }
                 }
@@ -0,0 +1,487 @@
+use crate::types::v0::store::{
+    definitions::{
+        DeserialiseValue, ObjectKey, RevisionWatchEvent, SerialiseValue, StorableObject, Store,
+        StoreError, StoreError::MissingEntry, StoreFile, StoreKey, StoreRevisionWatchReceiver,
+        StoreValue, WatchEvent,
+    },
+    registry::{ControlPlaneService, StoreLeaseLockKey, StoreLeaseOwner},
+    schema::{object_value, stored_value},
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::ResultExt;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::{
+    broadcast,
+    mpsc::{channel, Receiver, Sender},
+};
+
+/// Number of changes kept in the history from which the watches can be resumed.
+/// Resuming a watch from an older revision fails with `StoreError::WatchCompacted`.
+const HISTORY_SIZE: usize = 1000;
+/// Capacity of the channel which broadcasts the changes to the watchers
+const CHANGES_CAPACITY: usize = 1024;
+
+/// In-memory key-value store, optionally backed by a file, with the same semantics as `Etcd`.
+/// Meant for tests and single-node development deployments which don't want to run etcd.
+/// Clones share the same content.
+#[derive(Clone)]
+pub struct MemoryStore {
+    inner: Arc<parking_lot::Mutex<MemoryStoreInner>>,
+    /// held while changing the store, so that the changes are saved to the backing file in
+    /// order without holding the `inner` lock across the file writes
+    writer: Arc<tokio::sync::Mutex<()>>,
+    /// broadcasts every change to the watchers
+    changes: broadcast::Sender<Change>,
+    /// the lease lock pair, (lease_id, lock_key), which must be held for writes to succeed
+    lease_lock: Option<(i64, String)>,
+}
+
+impl std::fmt::Debug for MemoryStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryStore").finish_non_exhaustive()
+    }
+}
+
+/// A change of an entry of the store
+#[derive(Debug, Clone)]
+struct Change {
+    key: String,
+    revision: i64,
+    /// the new value of the entry, or None if it was deleted
+    value: Option<Value>,
+}
+
+/// An entry of the store along with the revision of its last modification
+#[derive(Serialize, Deserialize, Debug)]
+struct Entry {
+    value: Value,
+    revision: i64,
+}
+
+/// Content of the store, which is saved to the backing file, if any
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct Content {
+    /// revision of the last change
+    revision: i64,
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Content {
+    /// Set the entry with the given `key` to `value` at `revision`, or delete it if None.
+    /// Returns the previous entry, if any.
+    fn set(&mut self, key: &str, value: Option<Value>, revision: i64) -> Option<Entry> {
+        let previous = match value {
+            Some(value) => self
+                .entries
+                .insert(key.to_string(), Entry { value, revision }),
+            None => self.entries.remove(key),
+        };
+        self.revision = revision;
+        previous
+    }
+    /// Serialise the content as it would be with the entry with the given `key` set to `value`
+    /// at `revision`, leaving the content itself unchanged.
+    fn serialise_with(
+        &mut self,
+        key: &str,
+        value: Option<Value>,
+        revision: i64,
+    ) -> Result<Vec<u8>, StoreError> {
+        let current = self.revision;
+        let previous = self.set(key, value, revision);
+        let content = serde_json::to_vec(&self).context(SerialiseValue);
+        match previous {
+            Some(previous) => self.entries.insert(key.to_string(), previous),
+            None => self.entries.remove(key),
+        };
+        self.revision = current;
+        content
+    }
+}
+
+#[derive(Default)]
+struct MemoryStoreInner {
+    content: Content,
+    /// the last `HISTORY_SIZE` changes
+    history: VecDeque<Change>,
+    /// the changes up to this revision are no longer in the history
+    compacted: i64,
+    /// held lease locks, with the id of their lease
+    locks: HashMap<String, i64>,
+    /// id of the last granted lease
+    last_lease: i64,
+    /// the backing file, if any
+    path: Option<PathBuf>,
+}
+
+/// Save the `content` to the backing file at `path` by replacing it atomically
+async fn save(path: &Path, content: Vec<u8>) -> Result<(), StoreError> {
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, content)
+        .await
+        .context(StoreFile {
+            path: tmp_path.display().to_string(),
+        })?;
+    tokio::fs::rename(&tmp_path, path).await.context(StoreFile {
+        path: path.display().to_string(),
+    })
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    /// Create a new empty in-memory store, which is lost when dropped
+    pub fn new() -> Self {
+        Self::from_inner(MemoryStoreInner::default())
+    }
+
+    /// Create a new in-memory store backed by the file at `path`, which is loaded if it exists
+    /// and is then saved on every change.
+    pub fn new_file(path: &Path) -> Result<Self, StoreError> {
+        let content = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content).context(DeserialiseValue {
+                value: path.display().to_string(),
+            })?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Content::default(),
+            Err(source) => {
+                return Err(StoreError::StoreFile {
+                    path: path.display().to_string(),
+                    source,
+                })
+            }
+        };
+        Ok(Self::from_inner(MemoryStoreInner {
+            compacted: content.revision,
+            content,
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        }))
+    }
+
+    fn from_inner(inner: MemoryStoreInner) -> Self {
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+        Self {
+            inner: Arc::new(parking_lot::Mutex::new(inner)),
+            writer: Arc::new(tokio::sync::Mutex::new(())),
+            changes,
+            lease_lock: None,
+        }
+    }
+
+    /// Return a clone of this store which grabs the lease lock of `service_name`, as
+    /// `EtcdSingletonLock` does, and records itself as the lease owner.
+    /// Only one instance of the service may hold the lock: grabbing it fails if it's already
+    /// held, and the writes fail once it's been revoked.
+    /// The lock of a file backed store is also held by the process through a lock file, next
+    /// to the backing file, which is taken over if its process is no longer running.
+    /// Unlike the etcd lease, the lease has no ttl: it lasts until it's revoked or the process
+    /// exits, as the lock can't be lost while the process is running.
+    pub async fn new_leased(&self, service_name: ControlPlaneService) -> Result<Self, StoreError> {
+        let lock_key = StoreLeaseLockKey::new(&service_name).key();
+        let (lease_id, path) = {
+            let mut inner = self.inner.lock();
+            if let Some(lease_id) = inner.locks.get(&lock_key) {
+                return Err(StoreError::FailedLock {
+                    reason: format!("lock '{}' is held by lease '{:x}'", lock_key, lease_id),
+                });
+            }
+            inner.last_lease += 1;
+            let lease_id = inner.last_lease;
+            inner.locks.insert(lock_key.clone(), lease_id);
+            (lease_id, inner.path.clone())
+        };
+        let mut store = Self {
+            lease_lock: Some((lease_id, lock_key)),
+            ..self.clone()
+        };
+        if let Some(path) = path {
+            if let Err(error) = Self::lock_file(&path).await {
+                if let Some((_, lock_key)) = &store.lease_lock {
+                    store.inner.lock().locks.remove(lock_key);
+                }
+                return Err(error);
+            }
+        }
+        if let Err(error) = store
+            .put_obj(&StoreLeaseOwner::new(&service_name, lease_id))
+            .await
+        {
+            store.revoke().await;
+            return Err(error);
+        }
+        Ok(store)
+    }
+
+    /// Grab the lock file of the store backed by the file at `path`
+    async fn lock_file(path: &Path) -> Result<(), StoreError> {
+        let lock_path = path.with_extension("lock");
+        if let Ok(pid) = tokio::fs::read_to_string(&lock_path).await {
+            let pid = pid.trim();
+            if pid != std::process::id().to_string() && Path::new("/proc").join(pid).exists() {
+                return Err(StoreError::FailedLock {
+                    reason: format!(
+                        "lock file '{}' is held by process '{}'",
+                        lock_path.display(),
+                        pid
+                    ),
+                });
+            }
+        }
+        tokio::fs::write(&lock_path, std::process::id().to_string())
+            .await
+            .context(StoreFile {
+                path: lock_path.display().to_string(),
+            })
+    }
+
+    /// Revokes the lease and releases the associated lock
+    pub async fn revoke(&self) {
+        if let Some((lease_id, lock_key)) = &self.lease_lock {
+            let path = {
+                let mut inner = self.inner.lock();
+                if inner.locks.get(lock_key) != Some(lease_id) {
+                    return;
+                }
+                inner.locks.remove(lock_key);
+                inner.path.clone()
+            };
+            if let Some(path) = path {
+                tokio::fs::remove_file(path.with_extension("lock"))
+                    .await
+                    .ok();
+            }
+        }
+    }
+
+    /// Apply the change of the entry with the given `key` to the new `value`, or delete it if
+    /// None, and broadcast it to the watchers.
+    /// The change is only applied once it's been saved to the backing file, if any.
+    async fn apply(&self, key: String, value: Option<Value>) -> Result<(), StoreError> {
+        let _writer = self.writer.lock().await;
+        let (revision, saved) = {
+            let mut inner = self.inner.lock();
+            if let Some((lease_id, lock_key)) = &self.lease_lock {
+                if inner.locks.get(lock_key) != Some(lease_id) {
+                    return Err(StoreError::FailedLock {
+                        reason: format!(
+                            "Compare key '{}' to lease id '{:x}' failed",
+                            lock_key, lease_id
+                        ),
+                    });
+                }
+            }
+            if value.is_none() && !inner.content.entries.contains_key(&key) {
+                // as with etcd, deleting a missing entry is not a change
+                return Ok(());
+            }
+            let revision = inner.content.revision + 1;
+            let saved = match inner.path.clone() {
+                Some(path) => Some((
+                    path,
+                    inner
+                        .content
+                        .serialise_with(&key, value.clone(), revision)?,
+                )),
+                None => None,
+            };
+            (revision, saved)
+        };
+        if let Some((path, content)) = saved {
+            save(&path, content).await?;
+        }
+
+        let mut inner = self.inner.lock();
+        inner.content.set(&key, value.clone(), revision);
+        let change = Change {
+            key,
+            revision,
+            value,
+        };
+        inner.history.push_back(change.clone());
+        if inner.history.len() > HISTORY_SIZE {
+            if let Some(compacted) = inner.history.pop_front() {
+                inner.compacted = compacted.revision;
+            }
+        }
+        // sent with the lock held so that the watchers receive the changes in order
+        self.changes.send(change).ok();
+        Ok(())
+    }
+
+    /// Watch for the changes of the entry with the given `key`, from the given `revision`
+    /// (inclusive), if any.
+    /// When a change occurs, a WatchEvent is sent over the channel, mapped along with its
+    /// revision by `map`.
+    /// When a 'delete' event is sent, the watcher stops watching.
+    fn watch<E: Send + 'static>(
+        &self,
+        key: String,
+        revision: Option<i64>,
+        map: fn(WatchEvent, i64) -> E,
+    ) -> Receiver<Result<E, StoreError>> {
+        let (sender, receiver) = channel(100);
+        let (replay, mut changes) = {
+            // subscribe with the lock held so that no change is missed or replayed twice
+            let inner = self.inner.lock();
+            let replay = match revision {
+                Some(revision) if revision <= inner.compacted => Err(inner.compacted),
+                Some(revision) => Ok(inner
+                    .history
+                    .iter()
+                    .filter(|change| change.key == key && change.revision >= revision)
+                    .cloned()
+                    .collect::<Vec<_>>()),
+                None => Ok(vec![]),
+            };
+            (replay, self.changes.subscribe())
+        };
+
+        tokio::spawn(async move {
+            let replay = match replay {
+                Ok(replay) => replay,
+                Err(compacted) => {
+                    let _ = sender
+                        .send(Err(StoreError::WatchCompacted { key, compacted }))
+                        .await;
+                    return;
+                }
+            };
+            for change in replay {
+                if !Self::send_change(&sender, change, map).await {
+                    return;
+                }
+            }
+            loop {
+                match changes.recv().await {
+                    Ok(change) if change.key == key => {
+                        if !Self::send_change(&sender, change, map).await {
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        // the watcher can't be told about the missed changes, so end the watch
+                        let _ = sender
+                            .send(Err(StoreError::WatchLagged { key, missed }))
+                            .await;
+                        return;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Send the change over the watch channel.
+    /// Returns false when the watch is over, ie: the entry was deleted or the receiver is closed.
+    async fn send_change<E>(
+        sender: &Sender<Result<E, StoreError>>,
+        change: Change,
+        map: fn(WatchEvent, i64) -> E,
+    ) -> bool {
+        match change.value {
+            Some(value) => sender
+                .send(Ok(map(WatchEvent::Put(change.key, value), change.revision)))
+                .await
+                .is_ok(),
+            None => {
+                let _ = sender
+                    .send(Ok(map(WatchEvent::Delete, change.revision)))
+                    .await;
+                false
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Store for MemoryStore {
+    async fn put_kv<K: StoreKey, V: StoreValue>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), StoreError> {
+        let value = serde_json::to_value(value).context(SerialiseValue)?;
+        self.apply(key.to_string(), Some(value)).await
+    }
+
+    async fn get_kv<K: StoreKey>(&mut self, key: &K) -> Result<Value, StoreError> {
+        let inner = self.inner.lock();
+        match inner.content.entries.get(&key.to_string()) {
+            Some(entry) => Ok(entry.value.clone()),
+            None => Err(MissingEntry {
+                key: key.to_string(),
+            }),
+        }
+    }
+
+    async fn delete_kv<K: StoreKey>(&mut self, key: &K) -> Result<(), StoreError> {
+        self.apply(key.to_string(), None).await
+    }
+
+    async fn watch_kv<K: StoreKey>(
+        &mut self,
+        key: &K,
+    ) -> Result<Receiver<Result<WatchEvent, StoreError>>, StoreError> {
+        Ok(self.watch(key.to_string(), None, |event, _| event))
+    }
+
+    async fn watch_kv_from<K: StoreKey>(
+        &mut self,
+        key: &K,
+        revision: Option<i64>,
+    ) -> Result<StoreRevisionWatchReceiver, StoreError> {
+        Ok(self.watch(key.to_string(), revision, |event, revision| {
+            RevisionWatchEvent { event, revision }
+        }))
+    }
+
+    async fn put_obj<O: StorableObject>(&mut self, object: &O) -> Result<(), StoreError> {
+        self.put_kv(&object.key().key(), &stored_value(object)?)
+            .await
+    }
+
+    async fn get_obj<O: StorableObject>(&mut self, key: &O::Key) -> Result<O, StoreError> {
+        let value = object_value(key.key_type(), self.get_kv(&key.key()).await?)?;
+        serde_json::from_value(value.clone()).context(DeserialiseValue {
+            value: value.to_string(),
+        })
+    }
+
+    /// Retrieve objects with the given key prefix
+    async fn get_values_prefix(
+        &mut self,
+        key_prefix: &str,
+    ) -> Result<Vec<(String, Value)>, StoreError> {
+        let inner = self.inner.lock();
+        Ok(inner
+            .content
+            .entries
+            .range(key_prefix.to_string() ..)
+            .take_while(|(key, _)| key.starts_with(key_prefix))
+            .map(|(key, entry)| (key.clone(), entry.value.clone()))
+            .collect())
+    }
+
+    async fn watch_obj<K: ObjectKey>(
+        &mut self,
+        key: &K,
+    ) -> Result<Receiver<Result<WatchEvent, StoreError>>, StoreError> {
+        Ok(self.watch(key.key(), None, |event, _| event))
+    }
+
+    async fn online(&mut self) -> bool {
+        true
+    }
+}
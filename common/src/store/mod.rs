@@ -1,2 +1,4 @@
 pub mod etcd;
 mod etcd_keep_alive;
+pub mod memory;
+pub mod persistent;
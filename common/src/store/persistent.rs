@@ -0,0 +1,128 @@
+use crate::{
+    store::{etcd::Etcd, memory::MemoryStore},
+    types::v0::store::{
+        definitions::{
+            ObjectKey, StorableObject, Store, StoreError, StoreKey, StoreRevisionWatchReceiver,
+            StoreValue, StoreWatchReceiver,
+        },
+        registry::ControlPlaneService,
+    },
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+
+/// Store url scheme which selects the volatile in-memory store, eg: memory://
+pub const MEMORY_STORE_SCHEME: &str = "memory://";
+/// Store url scheme which selects the in-memory store backed by a file,
+/// eg: file:///var/lib/control-plane/store.json
+pub const FILE_STORE_SCHEME: &str = "file://";
+
+/// The persistent store, selected through its url
+#[derive(Clone, Debug)]
+pub enum PersistentStore {
+    /// etcd, the default
+    Etcd(Etcd),
+    /// in-memory store, optionally backed by a file, for tests and single-node deployments
+    Memory(MemoryStore),
+}
+
+impl PersistentStore {
+    /// Create a new store for the given `url`, with a lease associated with `service_name`:
+    /// - `memory://` selects a volatile in-memory store
+    /// - `file:///path/to/store.json` selects an in-memory store backed by the given file
+    /// - anything else is an etcd endpoint
+    ///
+    /// The `lease_time` only applies to etcd: the lease of an in-memory store has no ttl and is
+    /// held until it's revoked or the process exits.
+    pub async fn new_leased(
+        url: &str,
+        service_name: ControlPlaneService,
+        lease_time: std::time::Duration,
+    ) -> Result<Self, StoreError> {
+        if url.starts_with(MEMORY_STORE_SCHEME) {
+            let store = MemoryStore::new().new_leased(service_name).await?;
+            Ok(Self::Memory(store))
+        } else if let Some(path) = url.strip_prefix(FILE_STORE_SCHEME) {
+            let store = MemoryStore::new_file(Path::new(path))?
+                .new_leased(service_name)
+                .await?;
+            Ok(Self::Memory(store))
+        } else {
+            let store = Etcd::new_leased([url], service_name, lease_time).await?;
+            Ok(Self::Etcd(store))
+        }
+    }
+
+    /// Revokes the lease and releases the associated lock
+    pub async fn revoke(&self) {
+        match self {
+            Self::Etcd(store) => store.revoke().await,
+            Self::Memory(store) => store.revoke().await,
+        }
+    }
+}
+
+/// Forward the call to the selected store
+macro_rules! forward {
+    ($self:ident, $store:ident => $call:expr) => {
+        match $self {
+            PersistentStore::Etcd($store) => $call,
+            PersistentStore::Memory($store) => $call,
+        }
+    };
+}
+
+#[async_trait]
+impl Store for PersistentStore {
+    async fn put_kv<K: StoreKey, V: StoreValue>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), StoreError> {
+        forward!(self, store => store.put_kv(key, value).await)
+    }
+
+    async fn get_kv<K: StoreKey>(&mut self, key: &K) -> Result<Value, StoreError> {
+        forward!(self, store => store.get_kv(key).await)
+    }
+
+    async fn delete_kv<K: StoreKey>(&mut self, key: &K) -> Result<(), StoreError> {
+        forward!(self, store => store.delete_kv(key).await)
+    }
+
+    async fn watch_kv<K: StoreKey>(&mut self, key: &K) -> Result<StoreWatchReceiver, StoreError> {
+        forward!(self, store => store.watch_kv(key).await)
+    }
+
+    async fn watch_kv_from<K: StoreKey>(
+        &mut self,
+        key: &K,
+        revision: Option<i64>,
+    ) -> Result<StoreRevisionWatchReceiver, StoreError> {
+        forward!(self, store => store.watch_kv_from(key, revision).await)
+    }
+
+    async fn put_obj<O: StorableObject>(&mut self, object: &O) -> Result<(), StoreError> {
+        forward!(self, store => store.put_obj(object).await)
+    }
+
+    async fn get_obj<O: StorableObject>(&mut self, key: &O::Key) -> Result<O, StoreError> {
+        forward!(self, store => store.get_obj(key).await)
+    }
+
+    async fn get_values_prefix(
+        &mut self,
+        key_prefix: &str,
+    ) -> Result<Vec<(String, Value)>, StoreError> {
+        forward!(self, store => store.get_values_prefix(key_prefix).await)
+    }
+
+    async fn watch_obj<K: ObjectKey>(&mut self, key: &K) -> Result<StoreWatchReceiver, StoreError> {
+        forward!(self, store => store.watch_obj(key).await)
+    }
+
+    async fn online(&mut self) -> bool {
+        forward!(self, store => store.online().await)
+    }
+}
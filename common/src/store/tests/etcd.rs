@@ -1,4 +1,6 @@
-use common_lib::types::v0::store::definitions::{Store, WatchEvent};
+use common_lib::types::v0::store::definitions::{
+    RevisionWatchEvent, Store, StoreRevisionWatchReceiver, WatchEvent,
+};
 use composer::{Binary, Builder, ContainerSpec};
 use oneshot::Receiver;
 use serde::{Deserialize, Serialize};
@@ -86,6 +88,35 @@ async fn etcd() {
     };
     assert_eq!(result, data);
 
+    // Start a watcher from the current revision and modify the entry again.
+    let mut watcher = store.watch_kv_from(&key, None).await.unwrap();
+    data.value = 300;
+    store
+        .put_kv(&key.to_string(), &serde_json::json!(&data))
+        .await
+        .expect("Failed to 'put' to etcd");
+    let (revision, result) = match recv_revision_event(&mut watcher).await {
+        RevisionWatchEvent {
+            event: WatchEvent::Put(_k, v),
+            revision,
+        } => (revision, v),
+        _ => panic!("Expected a 'put' event"),
+    };
+    assert_eq!(result, serde_json::json!(&data));
+
+    // Resuming the watch from that revision replays the same modification.
+    let mut watcher = store.watch_kv_from(&key, Some(revision)).await.unwrap();
+    match recv_revision_event(&mut watcher).await {
+        RevisionWatchEvent {
+            event: WatchEvent::Put(_k, v),
+            revision: replayed,
+        } => {
+            assert_eq!(replayed, revision);
+            assert_eq!(v, result);
+        }
+        _ => panic!("Expected a 'put' event"),
+    };
+
     // Start a watcher which should send a message when the subsequent 'delete'
     // event occurs.
     let (del_hdl, r) = spawn_watcher(&key, &mut store).await;
@@ -131,6 +162,15 @@ async fn spawn_watcher<W: Store>(
     (hdl, r)
 }
 
+/// Wait up to 1 second for the next event of a watcher started from a revision.
+async fn recv_revision_event(watcher: &mut StoreRevisionWatchReceiver) -> RevisionWatchEvent {
+    tokio::time::timeout(Duration::from_secs(1), watcher.recv())
+        .await
+        .expect("Timed out waiting for message")
+        .expect("Watcher closed")
+        .expect("Failed to receive event")
+}
+
 /// Wait to establish a connection to etcd.
 /// Returns 'Ok' if connected otherwise 'Err' is returned.
 fn wait_for_etcd_ready(endpoint: &str) -> io::Result<TcpStream> {
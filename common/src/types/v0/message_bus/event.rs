@@ -0,0 +1,109 @@
+use super::*;
+
+use crate::mbus_api::{MessageId, ResourceKind};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, time::SystemTime};
+
+/// Audit log entry of an operation performed on a resource spec
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// monotonically increasing id of the event
+    pub id: u64,
+    /// time at which the operation completed
+    pub timestamp: SystemTime,
+    /// kind of the resource
+    pub kind: ResourceKind,
+    /// id of the resource
+    pub resource: String,
+    /// operation performed on the resource
+    pub operation: String,
+    /// result of the operation
+    pub result: EventResult,
+    /// error chain, if the operation failed
+    pub error: Option<String>,
+    /// the request, the reconciler or the watcher which caused the operation
+    pub source: EventSource,
+}
+
+impl From<Event> for models::Event {
+    fn from(src: Event) -> Self {
+        Self::new_all(
+            src.id,
+            humantime::format_rfc3339_seconds(src.timestamp).to_string(),
+            src.kind.to_string(),
+            src.resource,
+            src.operation,
+            src.result.into(),
+            src.error,
+            src.source.to_string(),
+        )
+    }
+}
+
+/// Result of an operation recorded in the audit log
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EventResult {
+    /// The operation succeeded
+    Succeeded,
+    /// The operation failed
+    Failed,
+}
+
+impl From<EventResult> for models::EventResult {
+    fn from(src: EventResult) -> Self {
+        match src {
+            EventResult::Succeeded => Self::Succeeded,
+            EventResult::Failed => Self::Failed,
+        }
+    }
+}
+
+/// The request, the reconciler or the watcher which caused an operation
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventSource {
+    /// A message bus request, identified by the versionless id of its request type,
+    /// eg: createVolume
+    Request(String),
+    /// A reconciliation loop, identified by its name
+    Reconciler(String),
+    /// A resource watcher, identified by its callback
+    Watcher(String),
+    /// Not known, eg: the operation was not performed on behalf of a request or a reconciler
+    Unknown,
+}
+
+impl EventSource {
+    /// The source of the operations performed on behalf of a request with the message `id`
+    pub fn request(id: &MessageId) -> Self {
+        match id {
+            MessageId::v0(id) => Self::Request(id.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for EventSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventSource::Request(id) => write!(f, "request/{}", id),
+            EventSource::Reconciler(name) => write!(f, "reconciler/{}", name),
+            EventSource::Watcher(callback) => write!(f, "watcher/{}", callback),
+            EventSource::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Get the audit log events
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEvents {
+    /// only get the events of the resource with this id
+    pub resource: Option<String>,
+}
+impl GetEvents {
+    /// Return new `Self` to get the events of the given resource, or of all resources if None
+    pub fn new(resource: Option<String>) -> Self {
+        Self { resource }
+    }
+}
@@ -39,6 +39,12 @@ pub enum Filter {
     Nexus(NexusId),
     /// Filter by Volume
     Volume(VolumeId),
+    /// Snapshot filters
+    ///
+    /// Filter by Volume and Snapshot
+    VolumeSnapshot(VolumeId, SnapshotId),
+    /// Filter by Snapshot
+    Snapshot(SnapshotId),
 }
 impl Default for Filter {
     fn default() -> Self {
@@ -1,11 +1,13 @@
 pub mod blockdevice;
 pub mod child;
+pub mod event;
 pub mod jsongrpc;
 pub mod misc;
 pub mod nexus;
 pub mod node;
 pub mod pool;
 pub mod replica;
+pub mod snapshot;
 pub mod spec;
 pub mod state;
 pub mod volume;
@@ -13,12 +15,14 @@ pub mod watch;
 
 pub use blockdevice::*;
 pub use child::*;
+pub use event::*;
 pub use jsongrpc::*;
 pub use misc::*;
 pub use nexus::*;
 pub use node::*;
 pub use pool::*;
 pub use replica::*;
+pub use snapshot::*;
 pub use spec::*;
 pub use state::*;
 pub use volume::*;
@@ -95,6 +99,16 @@ pub enum MessageIdVs {
     /// Node Service
     /// Get all node information
     GetNodes,
+    /// Cordon a node
+    CordonNode,
+    /// Uncordon a node
+    UncordonNode,
+    /// Drain a node
+    DrainNode,
+    /// Add or overwrite a node label
+    LabelNode,
+    /// Remove a node label
+    UnlabelNode,
     /// Pool Service
     ///
     /// Get pools with filter
@@ -103,6 +117,8 @@ pub enum MessageIdVs {
     CreatePool,
     /// Destroy Pool,
     DestroyPool,
+    /// Start or stop the maintenance of a Pool,
+    SetPoolMaintenance,
     /// Get replicas with filter
     GetReplicas,
     /// Create Replica,
@@ -149,6 +165,12 @@ pub enum MessageIdVs {
     RemoveVolumeNexus,
     /// Set replica count
     SetVolumeReplica,
+    /// Get volume snapshots
+    GetVolumeSnapshots,
+    /// Create volume snapshot
+    CreateVolumeSnapshot,
+    /// Destroy volume snapshot
+    DestroyVolumeSnapshot,
     /// Generic JSON gRPC message
     JsonGrpc,
     /// Get block devices
@@ -163,6 +185,8 @@ pub enum MessageIdVs {
     GetSpecs,
     /// Get States
     GetStates,
+    /// Get the audit log events
+    GetEvents,
 }
 
 impl MessageIdTimeout for MessageIdVs {
@@ -179,6 +203,8 @@ impl MessageIdTimeout for MessageIdVs {
                     MessageIdVs::DestroyVolume => min_timeouts.replica() * 3 + min_timeouts.nexus(),
                     MessageIdVs::PublishVolume => min_timeouts.nexus(),
                     MessageIdVs::UnpublishVolume => min_timeouts.nexus(),
+                    MessageIdVs::CreateVolumeSnapshot => min_timeouts.nexus(),
+                    MessageIdVs::DestroyVolumeSnapshot => min_timeouts.replica() * 3,
 
                     MessageIdVs::CreateNexus => min_timeouts.nexus(),
                     MessageIdVs::DestroyNexus => min_timeouts.nexus(),
@@ -195,6 +195,23 @@ impl NvmfControllerIdRange {
         let rand_min = u16::min(rand::random::<u16>() + min, max);
         Self(rand_min ..= max)
     }
+    /// create `Self` with the slice of the controller id range used by the given `path` out of
+    /// `num_paths` nexuses of the same volume, so that the controller ids of the paths of a
+    /// multipath volume never overlap
+    pub fn path_slice(path: u8, num_paths: u8) -> Self {
+        let min = *Self::controller_id_range().start();
+        let max = *Self::controller_id_range().end();
+        let num_paths = u16::max(num_paths as u16, 1);
+        let path = u16::min(path as u16, num_paths - 1);
+        let slice = (max - min + 1) / num_paths;
+        let start = min + path * slice;
+        let end = if path == num_paths - 1 {
+            max
+        } else {
+            start + slice - 1
+        };
+        Self(start ..= end)
+    }
     /// minimum controller id
     pub fn min(&self) -> &u16 {
         self.0.start()
@@ -228,6 +245,27 @@ pub struct NexusNvmfConfig {
 }
 
 impl NexusNvmfConfig {
+    /// Create the configuration of the nvmf target of the given `path` out of `num_paths`
+    /// nexuses of a multipath volume.
+    /// Each path gets its own slice of the controller id range and its own reservation key.
+    pub fn multipath(path: u8, num_paths: u8) -> Self {
+        Self {
+            controller_id_range: NvmfControllerIdRange::path_slice(path, num_paths),
+            reservation_key: path as u64 + 1,
+            preempt_reservation_key: None,
+        }
+    }
+    /// Create the configuration of the nvmf target which replaces the nexus configured with
+    /// `previous`, eg: when the target fails over to another node.
+    /// The new target keeps the controller id range but uses the next reservation key and
+    /// preempts the reservation of the previous target, fencing it off from the replicas.
+    pub fn failover(previous: &Self) -> Self {
+        Self {
+            controller_id_range: previous.controller_id_range.clone(),
+            reservation_key: previous.reservation_key + 1,
+            preempt_reservation_key: Some(previous.reservation_key),
+        }
+    }
     /// minimum controller id that can be used by the nvmf target
     pub fn min_cntl_id(&self) -> u16 {
         *self.controller_id_range.min()
@@ -46,6 +46,90 @@ impl GetNodes {
     }
 }
 
+/// Cordon a node, so that no new replicas or targets are placed on it
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CordonNode {
+    /// id of the mayastor instance
+    pub id: NodeId,
+}
+impl CordonNode {
+    /// Return new `Self` to cordon the given node
+    pub fn new(id: &NodeId) -> Self {
+        Self { id: id.clone() }
+    }
+}
+
+/// Uncordon a node, cancelling any drain in progress
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UncordonNode {
+    /// id of the mayastor instance
+    pub id: NodeId,
+}
+impl UncordonNode {
+    /// Return new `Self` to uncordon the given node
+    pub fn new(id: &NodeId) -> Self {
+        Self { id: id.clone() }
+    }
+}
+
+/// Cordon a node and move all volume targets and replicas off it
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainNode {
+    /// id of the mayastor instance
+    pub id: NodeId,
+}
+impl DrainNode {
+    /// Return new `Self` to drain the given node
+    pub fn new(id: &NodeId) -> Self {
+        Self { id: id.clone() }
+    }
+}
+
+/// Add or overwrite a node label, which can be used to define failure domains for the replica
+/// topology spread
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelNode {
+    /// id of the mayastor instance
+    pub id: NodeId,
+    /// label key
+    pub key: String,
+    /// label value
+    pub value: String,
+}
+impl LabelNode {
+    /// Return new `Self` to set the label `key` to `value` on the given node
+    pub fn new(id: &NodeId, key: &str, value: &str) -> Self {
+        Self {
+            id: id.clone(),
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Remove a node label
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlabelNode {
+    /// id of the mayastor instance
+    pub id: NodeId,
+    /// label key
+    pub key: String,
+}
+impl UnlabelNode {
+    /// Return new `Self` to remove the label `key` from the given node
+    pub fn new(id: &NodeId, key: &str) -> Self {
+        Self {
+            id: id.clone(),
+            key: key.to_string(),
+        }
+    }
+}
+
 /// Node information
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
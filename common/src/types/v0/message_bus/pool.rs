@@ -73,6 +73,24 @@ pub struct PoolState {
     pub used: u64,
 }
 
+/// Percentage of its capacity at which the actual usage of a pool is nearing the capacity
+pub const POOL_USAGE_WARNING_PERCENT: u64 = 85;
+
+impl PoolState {
+    /// Get the actual usage of the pool as a percentage of its capacity
+    pub fn usage_percent(&self) -> u64 {
+        if self.capacity == 0 {
+            100
+        } else {
+            (self.used as u128 * 100 / self.capacity as u128) as u64
+        }
+    }
+    /// Check if the actual usage of the pool is nearing its capacity
+    pub fn usage_warning(&self) -> bool {
+        self.usage_percent() >= POOL_USAGE_WARNING_PERCENT
+    }
+}
+
 impl From<PoolState> for models::PoolState {
     fn from(src: PoolState) -> Self {
         Self::new(
@@ -252,6 +270,9 @@ pub struct CreatePool {
     pub disks: Vec<PoolDeviceUri>,
     /// labels to be set on the pool
     pub labels: Option<PoolLabel>,
+    /// overcommit ratio of the pool, as a percentage of its capacity
+    #[serde(default)]
+    pub overcommit: Option<u32>,
 }
 
 impl CreatePool {
@@ -267,8 +288,14 @@ impl CreatePool {
             id: id.clone(),
             disks: disks.to_vec(),
             labels: labels.clone(),
+            overcommit: None,
         }
     }
+    /// With the overcommit ratio of the pool, as a percentage of its capacity
+    pub fn with_overcommit(mut self, overcommit: Option<u32>) -> Self {
+        self.overcommit = overcommit;
+        self
+    }
 }
 
 /// Destroy Pool Request
@@ -280,3 +307,23 @@ pub struct DestroyPool {
     /// id of the pool
     pub id: PoolId,
 }
+
+/// Start or stop the maintenance of a Pool Request
+/// Pools in maintenance are not used for new replicas and their replicas are moved to other pools
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SetPoolMaintenance {
+    /// id of the pool
+    pub id: PoolId,
+    /// whether the pool is in maintenance
+    pub maintenance: bool,
+}
+impl SetPoolMaintenance {
+    /// Return new `Self` to start or stop the maintenance of the given pool
+    pub fn new(id: &PoolId, maintenance: bool) -> Self {
+        Self {
+            id: id.clone(),
+            maintenance,
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use super::*;
+
+use crate::types::v0::store::snapshot::SnapshotSpec;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+bus_impl_string_uuid!(SnapshotId, "UUID of a mayastor volume snapshot");
+
+/// Volume Snapshots
+///
+/// Volume snapshot information
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeSnapshot {
+    /// Specification of the snapshot.
+    spec: SnapshotSpec,
+}
+
+impl VolumeSnapshot {
+    /// Construct a new volume snapshot.
+    pub fn new(spec: SnapshotSpec) -> Self {
+        Self { spec }
+    }
+
+    /// Get the snapshot spec.
+    pub fn spec(&self) -> SnapshotSpec {
+        self.spec.clone()
+    }
+
+    /// Get the snapshot's uuid.
+    pub fn uuid(&self) -> &SnapshotId {
+        &self.spec.uuid
+    }
+
+    /// Get the uuid of the snapshotted volume.
+    pub fn volume(&self) -> &VolumeId {
+        &self.spec.volume
+    }
+}
+
+impl From<VolumeSnapshot> for models::VolumeSnapshot {
+    fn from(snapshot: VolumeSnapshot) -> Self {
+        snapshot.spec().into()
+    }
+}
+
+/// Create a snapshot of a volume
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateVolumeSnapshot {
+    /// uuid of the volume
+    pub volume: VolumeId,
+    /// uuid of the new snapshot
+    pub uuid: SnapshotId,
+}
+impl CreateVolumeSnapshot {
+    /// Create new `Self` to snapshot the given volume
+    pub fn new(volume: &VolumeId, uuid: &SnapshotId) -> Self {
+        Self {
+            volume: volume.clone(),
+            uuid: uuid.clone(),
+        }
+    }
+}
+
+/// Destroy a snapshot of a volume
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DestroyVolumeSnapshot {
+    /// uuid of the volume
+    pub volume: VolumeId,
+    /// uuid of the snapshot
+    pub uuid: SnapshotId,
+}
+impl DestroyVolumeSnapshot {
+    /// Create new `Self` to destroy the given volume snapshot
+    pub fn new(volume: &VolumeId, uuid: &SnapshotId) -> Self {
+        Self {
+            volume: volume.clone(),
+            uuid: uuid.clone(),
+        }
+    }
+}
+
+/// Get volume snapshots
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetVolumeSnapshots {
+    /// filter volume snapshots
+    pub filter: Filter,
+}
+
+/// Snapshot of a single replica, taken through the volume's nexus
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicaSnapshot {
+    /// uuid of the snapshotted replica
+    pub replica: ReplicaId,
+    /// id of the mayastor instance where the replica lives
+    pub node: NodeId,
+    /// id of the pool where the replica lives
+    pub pool: PoolId,
+    /// name of the snapshot lvol created by mayastor
+    pub name: String,
+    /// uuid of the snapshot lvol, as reported by mayastor
+    pub uuid: ReplicaId,
+}
+impl ReplicaSnapshot {
+    /// Create new `Self` based on the provided arguments
+    pub fn new(
+        replica: &ReplicaId,
+        node: &NodeId,
+        pool: &PoolId,
+        name: &str,
+        uuid: &ReplicaId,
+    ) -> Self {
+        Self {
+            replica: replica.clone(),
+            node: node.clone(),
+            pool: pool.clone(),
+            name: name.to_string(),
+            uuid: uuid.clone(),
+        }
+    }
+}
+
+impl From<&ReplicaSnapshot> for models::ReplicaSnapshot {
+    fn from(src: &ReplicaSnapshot) -> Self {
+        Self::new_all(
+            src.name.clone(),
+            src.node.to_string(),
+            src.pool.to_string(),
+            src.replica.clone(),
+        )
+    }
+}
+
+/// Snapshot a nexus, which in turn snapshots all of its healthy replicas
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNexusSnapshot {
+    /// id of the mayastor instance
+    pub node: NodeId,
+    /// uuid of the nexus
+    pub nexus: NexusId,
+}
+impl CreateNexusSnapshot {
+    /// Create new `Self` based on the provided arguments
+    pub fn new(node: &NodeId, nexus: &NexusId) -> Self {
+        Self {
+            node: node.clone(),
+            nexus: nexus.clone(),
+        }
+    }
+}
+
+/// Destroy the snapshot of a single replica
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DestroyReplicaSnapshot {
+    /// id of the mayastor instance
+    pub node: NodeId,
+    /// id of the pool
+    pub pool: PoolId,
+    /// name of the snapshot lvol
+    pub name: String,
+    /// uuid of the snapshot lvol
+    pub uuid: ReplicaId,
+}
+impl From<&ReplicaSnapshot> for DestroyReplicaSnapshot {
+    fn from(snapshot: &ReplicaSnapshot) -> Self {
+        Self {
+            node: snapshot.node.clone(),
+            pool: snapshot.pool.clone(),
+            name: snapshot.name.clone(),
+            uuid: snapshot.uuid.clone(),
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use super::*;
 
-use crate::{types::v0::store::volume::VolumeSpec, IntoOption};
+use crate::{
+    mbus_api::{ReplyError, ReplyErrorKind, ResourceKind},
+    types::v0::store::volume::VolumeSpec,
+    IntoOption, IntoVec,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, convert::TryFrom, fmt::Debug};
 
@@ -65,6 +69,9 @@ pub struct VolumeState {
     pub target: Option<Nexus>,
     /// replica topology information
     pub replica_topology: HashMap<ReplicaId, ReplicaTopology>,
+    /// pools holding thin provisioned replicas of the volume and nearing their capacity
+    #[serde(default)]
+    pub usage_warnings: Vec<PoolUsageWarning>,
 }
 
 impl From<VolumeState> for models::VolumeState {
@@ -79,10 +86,46 @@ impl From<VolumeState> for models::VolumeState {
                 .iter()
                 .map(|(k, v)| (k.into(), v.into()))
                 .collect(),
+            usage_warnings: if volume.usage_warnings.is_empty() {
+                None
+            } else {
+                Some(volume.usage_warnings.into_vec())
+            },
         }
     }
 }
 
+/// Warning raised when the actual usage of a pool which holds a thin provisioned replica nears
+/// the physical capacity of the pool
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolUsageWarning {
+    /// id of the mayastor instance
+    pub node: NodeId,
+    /// id of the pool
+    pub pool: PoolId,
+    /// size of the pool in bytes
+    pub capacity: u64,
+    /// used bytes from the pool
+    pub used: u64,
+}
+
+impl From<&PoolState> for PoolUsageWarning {
+    fn from(pool: &PoolState) -> Self {
+        Self {
+            node: pool.node.clone(),
+            pool: pool.id.clone(),
+            capacity: pool.capacity,
+            used: pool.used,
+        }
+    }
+}
+impl From<PoolUsageWarning> for models::PoolUsageWarning {
+    fn from(src: PoolUsageWarning) -> Self {
+        Self::new(src.capacity, src.node, src.pool, src.used)
+    }
+}
+
 impl VolumeState {
     /// Get the target node if the volume is published
     pub fn target_node(&self) -> Option<Option<NodeId>> {
@@ -110,6 +153,7 @@ impl From<(&VolumeId, &Nexus)> for VolumeState {
             status: nexus.status.clone(),
             target: Some(nexus.clone()),
             replica_topology: HashMap::new(),
+            usage_warnings: vec![],
         }
     }
 }
@@ -158,6 +202,48 @@ pub struct LabelledTopology {
     /// inclusive labels
     #[serde(default)]
     pub inclusion: ::std::collections::HashMap<String, String>,
+    /// label expressions which must all be matched
+    #[serde(default)]
+    pub expressions: Vec<LabelExpression>,
+}
+impl LabelledTopology {
+    /// Check if a resource with the given `labels` can be used on its own, ie: if it has all
+    /// the inclusive labels with the same value, none of the exclusive labels which have a value
+    /// and if it matches all the label expressions
+    /// Exclusive labels without a value can only be checked against the other resources in use,
+    /// see `apart`
+    pub fn matches(&self, labels: &::std::collections::HashMap<String, String>) -> bool {
+        self.inclusion
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+            && self
+                .exclusion
+                .iter()
+                .filter(|(_, value)| !value.is_empty())
+                .all(|(key, value)| labels.get(key) != Some(value))
+            && self.expressions.iter().all(|e| e.matches(labels))
+    }
+    /// Get the exclusive label keys without a value: resources with the same value for any of
+    /// these labels cannot be paired up
+    pub fn exclusion_keys(&self) -> impl Iterator<Item = &String> {
+        self.exclusion
+            .iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(key, _)| key)
+    }
+    /// Check if the resources with labels `a` and `b` can be paired up, ie: if they don't have
+    /// the same value for any of the exclusive labels without a value
+    pub fn apart(
+        &self,
+        a: &::std::collections::HashMap<String, String>,
+        b: &::std::collections::HashMap<String, String>,
+    ) -> bool {
+        self.exclusion_keys()
+            .all(|key| match (a.get(key), b.get(key)) {
+                (Some(a_value), Some(b_value)) => a_value != b_value,
+                _ => true,
+            })
+    }
 }
 
 impl From<models::LabelledTopology> for LabelledTopology {
@@ -165,12 +251,94 @@ impl From<models::LabelledTopology> for LabelledTopology {
         Self {
             exclusion: src.exclusion,
             inclusion: src.inclusion,
+            expressions: src.expressions.unwrap_or_default().into_vec(),
         }
     }
 }
 impl From<LabelledTopology> for models::LabelledTopology {
     fn from(src: LabelledTopology) -> Self {
-        Self::new(src.exclusion, src.inclusion)
+        let expressions = if src.expressions.is_empty() {
+            None
+        } else {
+            Some(src.expressions.into_vec())
+        };
+        Self::new_all(src.exclusion, expressions, src.inclusion)
+    }
+}
+
+/// Operator of a label expression
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum LabelOperator {
+    /// the label value must be one of the expression values
+    In,
+    /// the label, if present, must not have any of the expression values
+    NotIn,
+    /// the label must be present, with any value
+    Exists,
+    /// the label must not be present
+    DoesNotExist,
+}
+impl From<models::LabelOperator> for LabelOperator {
+    fn from(src: models::LabelOperator) -> Self {
+        match src {
+            models::LabelOperator::In => Self::In,
+            models::LabelOperator::NotIn => Self::NotIn,
+            models::LabelOperator::Exists => Self::Exists,
+            models::LabelOperator::DoesNotExist => Self::DoesNotExist,
+        }
+    }
+}
+impl From<LabelOperator> for models::LabelOperator {
+    fn from(src: LabelOperator) -> Self {
+        match src {
+            LabelOperator::In => Self::In,
+            LabelOperator::NotIn => Self::NotIn,
+            LabelOperator::Exists => Self::Exists,
+            LabelOperator::DoesNotExist => Self::DoesNotExist,
+        }
+    }
+}
+
+/// Label expression, eg: "zone In [a, b]"
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct LabelExpression {
+    /// label key
+    pub key: String,
+    /// operator applied to the label value
+    pub operator: LabelOperator,
+    /// values used by the `In` and `NotIn` operators
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+impl LabelExpression {
+    /// Check if the given `labels` match this expression
+    pub fn matches(&self, labels: &::std::collections::HashMap<String, String>) -> bool {
+        let value = labels.get(&self.key);
+        match self.operator {
+            LabelOperator::In => value.map(|v| self.values.contains(v)).unwrap_or(false),
+            LabelOperator::NotIn => value.map(|v| !self.values.contains(v)).unwrap_or(true),
+            LabelOperator::Exists => value.is_some(),
+            LabelOperator::DoesNotExist => value.is_none(),
+        }
+    }
+}
+impl From<models::LabelExpression> for LabelExpression {
+    fn from(src: models::LabelExpression) -> Self {
+        Self {
+            key: src.key,
+            operator: src.operator.into(),
+            values: src.values.unwrap_or_default(),
+        }
+    }
+}
+impl From<LabelExpression> for models::LabelExpression {
+    fn from(src: LabelExpression) -> Self {
+        let values = if src.values.is_empty() {
+            None
+        } else {
+            Some(src.values)
+        };
+        Self::new_all(src.key, src.operator.into(), values)
     }
 }
 
@@ -180,16 +348,32 @@ impl From<LabelledTopology> for models::LabelledTopology {
 pub struct Topology {
     pub node: Option<NodeTopology>,
     pub pool: Option<PoolTopology>,
+    #[serde(default)]
+    pub spread: Option<SpreadTopology>,
+    #[serde(default)]
+    pub anti_affinity: Option<VolumeAntiAffinity>,
 }
 impl Topology {
     /// Get a reference to the explicit topology
     pub fn explicit(&self) -> Option<&ExplicitNodeTopology> {
         self.node.as_ref().and_then(|n| n.explicit())
     }
+    /// Get the node label keys across which the replicas must be spread
+    pub fn spread_labels(&self) -> &[String] {
+        self.spread
+            .as_ref()
+            .map(|s| s.labels.as_slice())
+            .unwrap_or_default()
+    }
 }
 impl From<Topology> for models::Topology {
     fn from(src: Topology) -> Self {
-        Self::new_all(src.node.into_opt(), src.pool.into_opt())
+        Self::new_all(
+            src.node.into_opt(),
+            src.pool.into_opt(),
+            src.spread.into_opt(),
+            src.anti_affinity.into_opt(),
+        )
     }
 }
 impl From<models::Topology> for Topology {
@@ -197,9 +381,68 @@ impl From<models::Topology> for Topology {
         Self {
             node: src.node_topology.into_opt(),
             pool: src.pool_topology.into_opt(),
+            spread: src.spread_topology.into_opt(),
+            anti_affinity: src.volume_anti_affinity.into_opt(),
+        }
+    }
+}
+
+/// Spreads the replicas of a volume across failure domains defined by node labels, eg:
+/// with the "zone" label no two replicas are placed on nodes with the same "zone" value
+/// Nodes which do not have all of the labels are not used
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct SpreadTopology {
+    /// node label keys which define the failure domains
+    pub labels: Vec<String>,
+}
+impl From<models::SpreadTopology> for SpreadTopology {
+    fn from(src: models::SpreadTopology) -> Self {
+        Self { labels: src.labels }
+    }
+}
+impl From<SpreadTopology> for models::SpreadTopology {
+    fn from(src: SpreadTopology) -> Self {
+        Self::new(src.labels)
+    }
+}
+
+/// Keeps the replicas of a volume away from the pools which hold replicas of other volumes
+/// This is only enforced when placing the replicas of the volume which defines it
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct VolumeAntiAffinity {
+    /// the other volumes, by uuid
+    #[serde(default)]
+    pub volumes: Vec<VolumeId>,
+    /// the other volumes, by labels (all labels must match)
+    #[serde(default)]
+    pub labels: ::std::collections::HashMap<String, String>,
+}
+impl VolumeAntiAffinity {
+    /// Check if the volume with the given `uuid` and `labels` is selected by the anti-affinity
+    pub fn selects(&self, uuid: &VolumeId, labels: Option<&VolumeLabels>) -> bool {
+        self.volumes.contains(uuid)
+            || (!self.labels.is_empty()
+                && labels
+                    .map(|labels| self.labels.iter().all(|(k, v)| labels.get(k) == Some(v)))
+                    .unwrap_or_default())
+    }
+}
+impl From<models::VolumeAntiAffinity> for VolumeAntiAffinity {
+    fn from(src: models::VolumeAntiAffinity) -> Self {
+        Self {
+            volumes: src.volumes.into_iter().map(Into::into).collect(),
+            labels: src.labels,
         }
     }
 }
+impl From<VolumeAntiAffinity> for models::VolumeAntiAffinity {
+    fn from(src: VolumeAntiAffinity) -> Self {
+        Self::new(
+            src.labels,
+            src.volumes.into_iter().map(Into::into).collect(),
+        )
+    }
+}
 
 /// Excludes resources with the same $label name, eg:
 /// "Zone" would not allow for resources with the same "Zone" value
@@ -306,11 +549,18 @@ pub struct VolumePolicy {
     /// the server will attempt to heal the volume by itself
     /// the client should not attempt to do the same if this is enabled
     pub self_heal: bool,
+    /// the server will move the volume target to another node when the node of the target
+    /// has been offline for longer than the failover grace period
+    #[serde(default)]
+    pub target_failover: bool,
 }
 
 impl Default for VolumePolicy {
     fn default() -> Self {
-        Self { self_heal: true }
+        Self {
+            self_heal: true,
+            target_failover: false,
+        }
     }
 }
 
@@ -318,12 +568,13 @@ impl From<models::VolumePolicy> for VolumePolicy {
     fn from(src: models::VolumePolicy) -> Self {
         Self {
             self_heal: src.self_heal,
+            target_failover: src.target_failover.unwrap_or_default(),
         }
     }
 }
 impl From<VolumePolicy> for models::VolumePolicy {
     fn from(src: VolumePolicy) -> Self {
-        Self::new_all(src.self_heal)
+        Self::new_all(src.self_heal, Some(src.target_failover))
     }
 }
 
@@ -359,6 +610,49 @@ pub struct CreateVolume {
     pub topology: Option<Topology>,
     /// volume labels
     pub labels: Option<VolumeLabels>,
+    /// thin provisioning of the volume replicas
+    #[serde(default)]
+    pub thin: bool,
+    /// existing volume or snapshot whose content is cloned into the new volume
+    #[serde(default)]
+    pub source: Option<VolumeContentSource>,
+}
+
+/// Source of the content of a new volume
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum VolumeContentSource {
+    /// clone of an existing volume
+    Volume(VolumeId),
+    /// clone of an existing volume snapshot
+    Snapshot(SnapshotId),
+}
+impl From<VolumeContentSource> for models::VolumeContentSource {
+    fn from(src: VolumeContentSource) -> Self {
+        match src {
+            VolumeContentSource::Volume(volume) => {
+                Self::new_all(Some(uuid::Uuid::from(volume)), None)
+            }
+            VolumeContentSource::Snapshot(snapshot) => {
+                Self::new_all(None, Some(uuid::Uuid::from(snapshot)))
+            }
+        }
+    }
+}
+impl TryFrom<models::VolumeContentSource> for VolumeContentSource {
+    type Error = ReplyError;
+    fn try_from(src: models::VolumeContentSource) -> Result<Self, Self::Error> {
+        match (src.volume, src.snapshot) {
+            (Some(volume), None) => Ok(Self::Volume(volume.into())),
+            (None, Some(snapshot)) => Ok(Self::Snapshot(snapshot.into())),
+            _ => Err(ReplyError {
+                kind: ReplyErrorKind::InvalidArgument,
+                resource: ResourceKind::Volume,
+                source: "VolumeContentSource".to_string(),
+                extra: "exactly one of volume or snapshot must be specified".to_string(),
+            }),
+        }
+    }
 }
 
 /// Volume label information
@@ -409,6 +703,10 @@ pub struct PublishVolume {
     pub target_node: Option<NodeId>,
     /// share protocol
     pub share: Option<VolumeShareProtocol>,
+    /// number of paths through which the volume is published, each path being a nexus on a
+    /// different node (the first one on the `target_node`). Defaults to a single path.
+    #[serde(default)]
+    pub num_paths: Option<u8>,
 }
 impl PublishVolume {
     /// Create new `PublishVolume` based on the provided arguments
@@ -421,8 +719,14 @@ impl PublishVolume {
             uuid,
             target_node,
             share,
+            num_paths: None,
         }
     }
+    /// Publish the volume through `num_paths` nexuses on different nodes
+    pub fn with_num_paths(mut self, num_paths: Option<u8>) -> Self {
+        self.num_paths = num_paths;
+        self
+    }
 }
 
 /// Unpublish a volume from any node where it may be published
@@ -554,3 +858,124 @@ impl From<&ReplicaTopology> for models::ReplicaTopology {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[(&str, &str)]) -> HashMap<String, String> {
+        labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn expression(key: &str, operator: LabelOperator, values: &[&str]) -> LabelExpression {
+        LabelExpression {
+            key: key.to_string(),
+            operator,
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn inclusion() {
+        let topology = LabelledTopology {
+            inclusion: labels(&[("zone", "a")]),
+            ..Default::default()
+        };
+        assert!(topology.matches(&labels(&[("zone", "a"), ("rack", "1")])));
+        assert!(!topology.matches(&labels(&[("zone", "b")])));
+        assert!(!topology.matches(&labels(&[("rack", "1")])));
+
+        // an empty value must be matched as such, it's not a wildcard
+        let topology = LabelledTopology {
+            inclusion: labels(&[("zone", "")]),
+            ..Default::default()
+        };
+        assert!(topology.matches(&labels(&[("zone", "")])));
+        assert!(!topology.matches(&labels(&[("zone", "a")])));
+        assert!(!topology.matches(&labels(&[])));
+
+        assert!(LabelledTopology::default().matches(&labels(&[])));
+    }
+
+    #[test]
+    fn exclusion() {
+        let topology = LabelledTopology {
+            exclusion: labels(&[("zone", "a"), ("rack", "")]),
+            ..Default::default()
+        };
+        assert!(!topology.matches(&labels(&[("zone", "a")])));
+        assert!(topology.matches(&labels(&[("zone", "b")])));
+        // the exclusive labels without a value only apply to pairs of resources
+        assert!(topology.matches(&labels(&[("zone", "b"), ("rack", "1")])));
+        assert_eq!(topology.exclusion_keys().collect::<Vec<_>>(), vec!["rack"]);
+
+        let rack1 = labels(&[("zone", "b"), ("rack", "1")]);
+        let rack2 = labels(&[("zone", "b"), ("rack", "2")]);
+        assert!(!topology.apart(&rack1, &rack1));
+        assert!(topology.apart(&rack1, &rack2));
+        assert!(topology.apart(&rack1, &labels(&[("zone", "b")])));
+
+        // without exclusive labels any resources can be paired up
+        assert!(LabelledTopology::default().apart(&rack1, &rack1));
+    }
+
+    #[test]
+    fn expressions() {
+        let zone = labels(&[("zone", "a")]);
+        let none = labels(&[]);
+
+        let in_ab = expression("zone", LabelOperator::In, &["a", "b"]);
+        assert!(in_ab.matches(&zone));
+        assert!(!in_ab.matches(&labels(&[("zone", "c")])));
+        assert!(!in_ab.matches(&none));
+
+        let not_in_ab = expression("zone", LabelOperator::NotIn, &["a", "b"]);
+        assert!(!not_in_ab.matches(&zone));
+        assert!(not_in_ab.matches(&labels(&[("zone", "c")])));
+        assert!(not_in_ab.matches(&none));
+
+        let exists = expression("zone", LabelOperator::Exists, &[]);
+        assert!(exists.matches(&zone));
+        assert!(!exists.matches(&none));
+
+        let does_not_exist = expression("zone", LabelOperator::DoesNotExist, &[]);
+        assert!(!does_not_exist.matches(&zone));
+        assert!(does_not_exist.matches(&none));
+
+        // all the expressions must be matched
+        let topology = LabelledTopology {
+            expressions: vec![exists, expression("rack", LabelOperator::DoesNotExist, &[])],
+            ..Default::default()
+        };
+        assert!(topology.matches(&zone));
+        assert!(!topology.matches(&labels(&[("zone", "a"), ("rack", "1")])));
+    }
+
+    #[test]
+    fn anti_affinity() {
+        let primary = VolumeId::new();
+        let secondary = VolumeId::new();
+        let db = labels(&[("app", "db"), ("role", "primary")]);
+
+        let anti_affinity = VolumeAntiAffinity {
+            volumes: vec![primary.clone()],
+            labels: Default::default(),
+        };
+        assert!(anti_affinity.selects(&primary, None));
+        assert!(!anti_affinity.selects(&secondary, Some(&db)));
+
+        let anti_affinity = VolumeAntiAffinity {
+            volumes: vec![],
+            labels: labels(&[("app", "db")]),
+        };
+        assert!(anti_affinity.selects(&secondary, Some(&db)));
+        assert!(!anti_affinity.selects(&secondary, Some(&labels(&[("app", "web")]))));
+        assert!(!anti_affinity.selects(&secondary, None));
+
+        // no volume is selected without any volumes or labels
+        assert!(!VolumeAntiAffinity::default().selects(&primary, Some(&db)));
+    }
+}
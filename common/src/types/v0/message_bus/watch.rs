@@ -1,7 +1,7 @@
 use super::*;
 
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt::Debug};
+use std::{convert::TryFrom, fmt::Debug, str::FromStr, time::Duration};
 
 ///
 /// Watcher Agent
@@ -20,22 +20,65 @@ pub struct Watch {
     pub callback: WatchCallback,
     /// type of watch
     pub watch_type: WatchType,
+    /// filter and delivery options of the watch
+    #[serde(default)]
+    pub options: WatchOptions,
 }
 
 impl TryFrom<&Watch> for models::RestWatch {
     type Error = ();
     fn try_from(value: &Watch) -> Result<Self, Self::Error> {
-        match &value.callback {
-            WatchCallback::Uri(uri) => Ok(Self {
-                resource: value.id.to_string(),
-                callback: uri.to_string(),
-            }),
-            /* other types are not implemented yet and should map to an error
-             * _ => Err(()), */
-        }
+        Ok(Self {
+            resource: value.id.to_string(),
+            callback: value.callback.to_string(),
+        })
+    }
+}
+
+/// Default maximum number of delivery attempts of a watch notification
+pub const DEFAULT_WATCH_MAX_ATTEMPTS: u32 = 10;
+/// Default timeout of each delivery attempt of a watch notification
+pub const DEFAULT_WATCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Filter and delivery options of a watch
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOptions {
+    /// Only notify the watcher when one of these fields of the resource changes, given as JSON
+    /// pointers, eg: "/spec/status". Any change is notified if empty.
+    pub fields: Vec<String>,
+    /// Maximum number of delivery attempts of each notification, after which the notification
+    /// is dead-lettered, ie: dropped and recorded as a failed event in the audit log.
+    /// Defaults to `DEFAULT_WATCH_MAX_ATTEMPTS`
+    pub max_attempts: Option<u32>,
+    /// Timeout of each delivery attempt, defaults to `DEFAULT_WATCH_TIMEOUT`
+    pub timeout: Option<Duration>,
+}
+impl WatchOptions {
+    /// Get the maximum number of delivery attempts of each notification
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+            .unwrap_or(DEFAULT_WATCH_MAX_ATTEMPTS)
+            .max(1)
+    }
+    /// Get the timeout of each delivery attempt
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(DEFAULT_WATCH_TIMEOUT)
     }
 }
 
+/// Notification sent to the watchers when a watched resource changes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchNotification {
+    /// the watched resource, eg: "volumes/{uuid}"
+    pub resource: String,
+    /// store revision of the change
+    pub revision: i64,
+    /// new value of the resource, or None if the resource has been deleted
+    pub value: Option<serde_json::Value>,
+}
+
 /// Get Resource Watches
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -91,6 +134,31 @@ impl ToString for WatchResourceId {
         }
     }
 }
+impl FromStr for WatchResourceId {
+    type Err = String;
+
+    /// Parse a resource in the form returned by `to_string`, eg: "volumes/{uuid}"
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let (resource, id) = source
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid watch resource '{}'", source))?;
+        let uuid_err = |error: uuid::Error| format!("Invalid {} id '{}': {}", resource, id, error);
+        Ok(match resource {
+            "nodes" => WatchResourceId::Node(id.into()),
+            "pools" => WatchResourceId::Pool(id.into()),
+            "replicas" => WatchResourceId::Replica(ReplicaId::try_from(id).map_err(uuid_err)?),
+            "replicas_state" => {
+                WatchResourceId::ReplicaState(ReplicaId::try_from(id).map_err(uuid_err)?)
+            }
+            "replicas_spec" => {
+                WatchResourceId::ReplicaSpec(ReplicaId::try_from(id).map_err(uuid_err)?)
+            }
+            "nexuses" => WatchResourceId::Nexus(NexusId::try_from(id).map_err(uuid_err)?),
+            "volumes" => WatchResourceId::Volume(VolumeId::try_from(id).map_err(uuid_err)?),
+            _ => return Err(format!("Invalid watch resource type '{}'", resource)),
+        })
+    }
+}
 
 /// The difference types of watches
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -126,11 +194,39 @@ pub struct DeleteWatch {
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum WatchCallback {
-    /// HTTP URI callback
+    /// HTTP URI callback, which receives the notification as the JSON body of a PUT request
     Uri(String),
+    /// NATS subject on which the JSON notification is published
+    Nats(String),
+    /// gRPC endpoint, eg: "grpc://10.1.0.1:50051", which implements the `WatchCallback` service
+    Grpc(String),
 }
 impl Default for WatchCallback {
     fn default() -> Self {
         Self::Uri(Default::default())
     }
 }
+/// Prefix of the NATS subject callbacks, eg: "nats:watches.volumes"
+const NATS_CALLBACK_PREFIX: &str = "nats:";
+/// Prefix of the gRPC endpoint callbacks, eg: "grpc://10.1.0.1:50051"
+const GRPC_CALLBACK_PREFIX: &str = "grpc://";
+impl From<&str> for WatchCallback {
+    fn from(callback: &str) -> Self {
+        if let Some(subject) = callback.strip_prefix(NATS_CALLBACK_PREFIX) {
+            Self::Nats(subject.to_string())
+        } else if callback.starts_with(GRPC_CALLBACK_PREFIX) {
+            Self::Grpc(callback.to_string())
+        } else {
+            Self::Uri(callback.to_string())
+        }
+    }
+}
+impl ToString for WatchCallback {
+    fn to_string(&self) -> String {
+        match self {
+            WatchCallback::Uri(uri) => uri.to_string(),
+            WatchCallback::Nats(subject) => format!("{}{}", NATS_CALLBACK_PREFIX, subject),
+            WatchCallback::Grpc(endpoint) => endpoint.to_string(),
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use async_trait::async_trait;
 use etcd_client::Error;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{Error as SerdeError, Value};
 use snafu::Snafu;
-use strum_macros::Display;
+use strum_macros::{Display, EnumIter};
 use tokio::sync::mpsc::Receiver;
 
 /// Definition of errors that can be returned from the key-value store.
@@ -62,6 +62,50 @@ pub enum StoreError {
     FailedLock { reason: String },
     #[snafu(display("Etcd is not ready, reason: '{}'", reason))]
     NotReady { reason: String },
+    /// Failed to 'watch' an entry from a revision which has already been compacted.
+    #[snafu(display(
+        "Failed to 'watch' entry with key {} as its history is compacted up to revision {}",
+        key,
+        compacted
+    ))]
+    WatchCompacted { key: String, compacted: i64 },
+    /// Failed to 'watch' an entry as the watcher fell too far behind its changes.
+    #[snafu(display(
+        "Failed to 'watch' entry with key {} as {} of its changes were missed",
+        key,
+        missed
+    ))]
+    WatchLagged { key: String, missed: u64 },
+    /// Failed to read or write the file backing the in-memory store.
+    #[snafu(display("Failed to access the store file {}. Error {}", path, source))]
+    StoreFile {
+        path: String,
+        source: std::io::Error,
+    },
+    /// Failed to migrate an object to the current schema version.
+    #[snafu(display(
+        "Failed to migrate {} from schema version {}. Error {}",
+        obj_type,
+        version,
+        reason
+    ))]
+    Migration {
+        obj_type: StorableObjectType,
+        version: u32,
+        reason: String,
+    },
+    /// The object was written with a schema version newer than the supported version.
+    #[snafu(display(
+        "{} has schema version {} which is newer than the supported version {}",
+        obj_type,
+        version,
+        supported
+    ))]
+    SchemaVersion {
+        obj_type: StorableObjectType,
+        version: u32,
+        supported: u32,
+    },
 }
 
 /// Representation of a watch event.
@@ -73,6 +117,15 @@ pub enum WatchEvent {
     Delete,
 }
 
+/// Representation of a watch event along with the store revision at which it occurred.
+#[derive(Debug)]
+pub struct RevisionWatchEvent {
+    /// The watch event
+    pub event: WatchEvent,
+    /// Store revision of the event, from which the watch can be resumed
+    pub revision: i64,
+}
+
 /// Store keys type trait
 pub trait StoreKey: Sync + ToString {}
 impl<T> StoreKey for T where T: Sync + ToString {}
@@ -99,6 +152,15 @@ pub trait Store: Sync + Send + Clone {
         &mut self,
         key: &K,
     ) -> Result<Receiver<Result<WatchEvent, StoreError>>, StoreError>;
+    /// Watch for changes to the entry with the given key, starting from the given store
+    /// `revision` (inclusive) so that previous changes are replayed, or from the current
+    /// revision if None.
+    /// Returns a channel which will be signalled when an event occurs.
+    async fn watch_kv_from<K: StoreKey>(
+        &mut self,
+        key: &K,
+        revision: Option<i64>,
+    ) -> Result<StoreRevisionWatchReceiver, StoreError>;
 
     async fn put_obj<O: StorableObject>(&mut self, object: &O) -> Result<(), StoreError>;
 
@@ -116,6 +178,7 @@ pub trait Store: Sync + Send + Clone {
 }
 
 pub type StoreWatchReceiver = Receiver<Result<WatchEvent, StoreError>>;
+pub type StoreRevisionWatchReceiver = Receiver<Result<RevisionWatchEvent, StoreError>>;
 
 /// Implemented by Keys of Storable Objects, eg: VolumeId
 pub trait ObjectKey: Sync + Send {
@@ -135,7 +198,7 @@ pub trait StorableObject: Serialize + Sync + Send + DeserializeOwned {
 }
 
 /// All types of objects which are storable in our store
-#[derive(Display, Copy, Clone, Debug)]
+#[derive(Display, EnumIter, Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum StorableObjectType {
     WatchConfig,
     Volume,
@@ -152,11 +215,13 @@ pub enum StorableObjectType {
     ReplicaSpec,
     VolumeSpec,
     VolumeState,
+    SnapshotSpec,
     ChildSpec,
     ChildState,
     CoreRegistryConfig,
     StoreLeaseLock,
     StoreLeaseOwner,
+    Event,
 }
 
 pub fn key_prefix(obj_type: StorableObjectType) -> String {
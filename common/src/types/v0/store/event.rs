@@ -0,0 +1,41 @@
+use crate::types::v0::{
+    message_bus::Event,
+    store::definitions::{ObjectKey, StorableObject, StorableObjectType},
+};
+use serde::{Deserialize, Serialize};
+
+/// Key used by the store to uniquely identify an audit log `Event`.
+/// The id is zero padded so that the keys sort in the same order as the ids.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct EventKey(u64);
+
+impl EventKey {
+    /// Get the id of the event
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for EventKey {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl ObjectKey for EventKey {
+    fn key_type(&self) -> StorableObjectType {
+        StorableObjectType::Event
+    }
+
+    fn key_uuid(&self) -> String {
+        format!("{:020}", self.0)
+    }
+}
+
+impl StorableObject for Event {
+    type Key = EventKey;
+
+    fn key(&self) -> Self::Key {
+        self.id.into()
+    }
+}
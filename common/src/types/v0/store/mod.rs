@@ -1,5 +1,6 @@
 pub mod child;
 pub mod definitions;
+pub mod event;
 pub mod nexus;
 pub mod nexus_child;
 pub mod nexus_persistence;
@@ -7,6 +8,8 @@ pub mod node;
 pub mod pool;
 pub mod registry;
 pub mod replica;
+pub mod schema;
+pub mod snapshot;
 pub mod volume;
 pub mod watch;
 
@@ -62,6 +65,8 @@ impl<T: std::cmp::PartialEq> SpecStatus<T> {
 pub trait SpecTransaction<Operation> {
     /// Check for a pending operation
     fn pending_op(&self) -> bool;
+    /// Get a description of the pending operation, if any
+    fn pending_op_name(&self) -> Option<String>;
     /// Commit the operation to the spec and clear it
     fn commit_op(&mut self);
     /// Clear the operation
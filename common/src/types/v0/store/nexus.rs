@@ -3,7 +3,7 @@
 use crate::types::v0::{
     message_bus::{
         self, ChildState, ChildUri, CreateNexus, DestroyNexus, Nexus as MbusNexus, NexusId,
-        NexusShareProtocol, NodeId, Protocol, ReplicaId, VolumeId,
+        NexusNvmfConfig, NexusShareProtocol, NodeId, Protocol, ReplicaId, VolumeId,
     },
     openapi::models,
     store::{
@@ -102,6 +102,9 @@ pub struct NexusSpec {
     pub sequencer: OperationSequence,
     /// Record of the operation in progress
     pub operation: Option<NexusOperationState>,
+    /// Nexus Nvmf Configuration
+    #[serde(default)]
+    pub config: Option<NexusNvmfConfig>,
 }
 impl NexusSpec {
     /// Check if the spec contains the provided replica by it's `ReplicaId`
@@ -170,7 +173,7 @@ impl From<&NexusSpec> for CreateNexus {
             &spec.children,
             spec.managed,
             spec.owner.as_ref(),
-            None,
+            spec.config.clone(),
         )
     }
 }
@@ -246,6 +249,12 @@ impl SpecTransaction<NexusOperation> for NexusSpec {
         self.operation.is_some()
     }
 
+    fn pending_op_name(&self) -> Option<String> {
+        self.operation
+            .as_ref()
+            .map(|op| format!("{:?}", op.operation))
+    }
+
     fn commit_op(&mut self) {
         if let Some(op) = self.operation.clone() {
             match op.operation {
@@ -338,6 +347,7 @@ impl From<&CreateNexus> for NexusSpec {
             owner: request.owner.clone(),
             sequencer: OperationSequence::new(request.uuid.clone()),
             operation: None,
+            config: request.config.clone(),
         }
     }
 }
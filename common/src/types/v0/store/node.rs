@@ -1,12 +1,15 @@
 //! Definition of node types that can be saved to the persistent store.
 
-use crate::types::v0::{
-    message_bus::{self, NodeId},
-    openapi::models,
-    store::{
-        definitions::{ObjectKey, StorableObject, StorableObjectType},
-        ResourceUuid,
+use crate::{
+    types::v0::{
+        message_bus::{self, NodeId, VolumeId},
+        openapi::models,
+        store::{
+            definitions::{ObjectKey, StorableObject, StorableObjectType},
+            ResourceUuid,
+        },
     },
+    IntoVec,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -26,6 +29,24 @@ pub struct NodeState {
     pub node: message_bus::NodeState,
 }
 
+/// Drain state of a node
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum NodeDrainState {
+    /// Targets and replicas are being moved off the node
+    Draining,
+    /// No targets or replicas are left on the node
+    Drained,
+}
+
+impl From<NodeDrainState> for models::NodeDrainState {
+    fn from(src: NodeDrainState) -> Self {
+        match src {
+            NodeDrainState::Draining => Self::Draining,
+            NodeDrainState::Drained => Self::Drained,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct NodeSpec {
     /// Node identification.
@@ -34,6 +55,15 @@ pub struct NodeSpec {
     endpoint: String,
     /// Node labels.
     labels: NodeLabels,
+    /// Cordoned nodes are not used for new replicas and targets.
+    #[serde(default)]
+    cordoned: bool,
+    /// Drain state of the node, if a drain was requested.
+    #[serde(default)]
+    drain_state: Option<NodeDrainState>,
+    /// Volumes whose target on the node may be in use, which blocks the drain.
+    #[serde(default)]
+    drain_blockers: Vec<VolumeId>,
 }
 impl NodeSpec {
     /// Return a new `Self`
@@ -42,6 +72,9 @@ impl NodeSpec {
             id,
             endpoint,
             labels,
+            cordoned: false,
+            drain_state: None,
+            drain_blockers: vec![],
         }
     }
     /// Node identification
@@ -56,11 +89,86 @@ impl NodeSpec {
     pub fn set_endpoint(&mut self, endpoint: String) {
         self.endpoint = endpoint
     }
+    /// Node labels
+    pub fn labels(&self) -> &NodeLabels {
+        &self.labels
+    }
+    /// Get the value of the node label `key`
+    pub fn label(&self, key: &str) -> Option<&String> {
+        self.labels.get(key)
+    }
+    /// Add or overwrite the node label `key` with `value`
+    pub fn set_label(&mut self, key: &str, value: &str) {
+        self.labels.insert(key.to_string(), value.to_string());
+    }
+    /// Remove the node label `key`
+    pub fn remove_label(&mut self, key: &str) {
+        self.labels.remove(key);
+    }
+    /// Check if the node is cordoned
+    pub fn cordoned(&self) -> bool {
+        self.cordoned
+    }
+    /// Cordon the node, preventing new replicas and targets from being placed on it
+    pub fn cordon(&mut self) {
+        self.cordoned = true;
+    }
+    /// Uncordon the node, which also cancels any drain
+    pub fn uncordon(&mut self) {
+        self.cordoned = false;
+        self.drain_state = None;
+        self.drain_blockers.clear();
+    }
+    /// Drain state of the node
+    pub fn drain_state(&self) -> Option<NodeDrainState> {
+        self.drain_state
+    }
+    /// Check if the node is being drained
+    pub fn draining(&self) -> bool {
+        self.drain_state == Some(NodeDrainState::Draining)
+    }
+    /// Cordon the node and start draining it
+    pub fn drain(&mut self) {
+        self.cordoned = true;
+        self.drain_state = Some(NodeDrainState::Draining);
+    }
+    /// Mark the drain as complete, unless it was cancelled meanwhile
+    pub fn set_drained(&mut self) {
+        if self.draining() {
+            self.drain_state = Some(NodeDrainState::Drained);
+            self.drain_blockers.clear();
+        }
+    }
+    /// Volumes whose target on the node may be in use, which blocks the drain
+    pub fn drain_blockers(&self) -> &[VolumeId] {
+        &self.drain_blockers
+    }
+    /// Set the volumes which block the drain, unless the drain was cancelled meanwhile
+    pub fn set_drain_blockers(&mut self, volumes: &[VolumeId]) {
+        if self.draining() {
+            self.drain_blockers = volumes.to_vec();
+        }
+    }
 }
 
 impl From<NodeSpec> for models::NodeSpec {
     fn from(src: NodeSpec) -> Self {
-        Self::new(src.endpoint, src.id)
+        Self::new_all(
+            src.cordoned,
+            if src.drain_blockers.is_empty() {
+                None
+            } else {
+                Some(src.drain_blockers.into_vec())
+            },
+            src.drain_state.map(Into::into),
+            src.endpoint,
+            src.id,
+            if src.labels.is_empty() {
+                None
+            } else {
+                Some(src.labels)
+            },
+        )
     }
 }
 
@@ -12,6 +12,9 @@ use crate::types::v0::{
 // PoolLabel is the type for the labels
 pub type PoolLabel = ::std::collections::HashMap<String, String>;
 
+/// The default overcommit of a pool, which allows no overcommitment
+pub const DEFAULT_POOL_OVERCOMMIT: u32 = 100;
+
 use serde::{Deserialize, Serialize};
 use std::{convert::From, fmt::Debug};
 /// Pool data structure used by the persistent store.
@@ -55,6 +58,8 @@ impl From<&CreatePool> for PoolSpec {
             disks: request.disks.clone(),
             status: PoolSpecStatus::Creating,
             labels: request.labels.clone(),
+            overcommit: request.overcommit,
+            maintenance: false,
             sequencer: OperationSequence::new(request.id.clone()),
             operation: None,
         }
@@ -65,6 +70,7 @@ impl PartialEq<CreatePool> for PoolSpec {
         let mut other = PoolSpec::from(other);
         other.status = self.status.clone();
         other.sequencer = self.sequencer.clone();
+        other.maintenance = self.maintenance;
         &other == self
     }
 }
@@ -82,6 +88,14 @@ pub struct PoolSpec {
     pub status: PoolSpecStatus,
     /// labels to be set on the pool
     pub labels: Option<PoolLabel>,
+    /// The capacity which can be allocated to replicas, as a percentage of the pool capacity.
+    /// Only thin provisioned replicas can make use of an overcommit above 100%.
+    #[serde(default)]
+    pub overcommit: Option<u32>,
+    /// Pools in maintenance are not used for new replicas and their replicas are moved to
+    /// other pools, so that the pool can be safely destroyed once empty.
+    #[serde(default)]
+    pub maintenance: bool,
     /// Update in progress
     #[serde(skip)]
     pub sequencer: OperationSequence,
@@ -104,6 +118,13 @@ macro_rules! pool_span {
 }
 crate::impl_trace_span!(pool_span, PoolSpec);
 
+impl PoolSpec {
+    /// Get the overcommit of the pool, as a percentage of its capacity
+    pub fn overcommit(&self) -> u32 {
+        self.overcommit.unwrap_or(DEFAULT_POOL_OVERCOMMIT)
+    }
+}
+
 impl OperationSequencer for PoolSpec {
     fn as_ref(&self) -> &OperationSequence {
         &self.sequencer
@@ -123,7 +144,15 @@ impl ResourceUuid for PoolSpec {
 
 impl From<PoolSpec> for models::PoolSpec {
     fn from(src: PoolSpec) -> Self {
-        Self::new_all(src.disks, src.id, src.labels, src.node, src.status)
+        Self::new_all(
+            src.disks,
+            src.id,
+            src.labels,
+            src.maintenance,
+            src.node,
+            src.overcommit,
+            src.status,
+        )
     }
 }
 
@@ -140,6 +169,12 @@ impl SpecTransaction<PoolOperation> for PoolSpec {
         self.operation.is_some()
     }
 
+    fn pending_op_name(&self) -> Option<String> {
+        self.operation
+            .as_ref()
+            .map(|op| format!("{:?}", op.operation))
+    }
+
     fn commit_op(&mut self) {
         if let Some(op) = self.operation.clone() {
             match op.operation {
@@ -9,6 +9,10 @@ pub struct CoreRegistryConfig {
     id: CoreRegistryConfigKey,
     /// Node registration
     registration: NodeRegistration,
+    /// Schema version which all the objects in the persistent store have been migrated to.
+    /// Configurations written before the objects were versioned are schema version 0.
+    #[serde(default)]
+    schema_version: u32,
 }
 
 impl CoreRegistryConfig {
@@ -17,12 +21,21 @@ impl CoreRegistryConfig {
         Self {
             id: CoreRegistryConfigKey::default(),
             registration,
+            schema_version: 0,
         }
     }
     /// Get a reference to the `NodeRegistration`
     pub fn node_registration(&self) -> &NodeRegistration {
         &self.registration
     }
+    /// Get the schema version which the persistent store objects have been migrated to
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+    /// Set the schema version which the persistent store objects have been migrated to
+    pub fn set_schema_version(&mut self, schema_version: u32) {
+        self.schema_version = schema_version;
+    }
 }
 
 /// How the Node Registration is handled
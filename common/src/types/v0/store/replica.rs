@@ -137,6 +137,12 @@ impl SpecTransaction<ReplicaOperation> for ReplicaSpec {
         self.operation.is_some()
     }
 
+    fn pending_op_name(&self) -> Option<String> {
+        self.operation
+            .as_ref()
+            .map(|op| format!("{:?}", op.operation))
+    }
+
     fn commit_op(&mut self) {
         if let Some(op) = self.operation.clone() {
             match op.operation {
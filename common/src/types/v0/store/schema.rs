@@ -0,0 +1,182 @@
+//! Versioning of the objects kept in the persistent store.
+//!
+//! The objects owned by the control-plane are written within a `VersionedObject` envelope
+//! which records the schema version of the object. Objects written before the envelope was
+//! introduced are the raw object, which is schema version 0.
+//! When the schema of an object changes the `STORE_SCHEMA_VERSION` must be bumped and a
+//! `Migration` added to `MIGRATIONS` which upgrades the objects from the previous version.
+//! Objects are upgraded in memory as they are read, and the core agent upgrades the stored
+//! objects on startup.
+
+use crate::types::v0::store::definitions::{
+    SerialiseValue, StorableObject, StorableObjectType, StoreError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::ResultExt;
+
+/// The current schema version of the objects kept in the persistent store
+pub const STORE_SCHEMA_VERSION: u32 = 1;
+
+/// Types of the objects which are owned by the control-plane and therefore versioned.
+/// Other objects are kept as is, eg: the `NexusInfo` which is written by the data-plane or the
+/// `StoreLeaseOwner` which must remain readable by previous versions of the core agent.
+pub const VERSIONED_OBJECT_TYPES: [StorableObjectType; 9] = [
+    StorableObjectType::CoreRegistryConfig,
+    StorableObjectType::NodeSpec,
+    StorableObjectType::PoolSpec,
+    StorableObjectType::ReplicaSpec,
+    StorableObjectType::NexusSpec,
+    StorableObjectType::VolumeSpec,
+    StorableObjectType::SnapshotSpec,
+    StorableObjectType::WatchConfig,
+    StorableObjectType::Event,
+];
+
+/// Check if the objects of the given type are versioned
+pub fn is_versioned(obj_type: StorableObjectType) -> bool {
+    VERSIONED_OBJECT_TYPES.contains(&obj_type)
+}
+
+/// Envelope with which the objects are written to the persistent store
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VersionedObject<T> {
+    /// schema version of the object
+    schema_version: u32,
+    /// the object itself
+    object: T,
+}
+
+impl<T> VersionedObject<T> {
+    /// Return a new `Self` with the `object` at the current schema version
+    pub fn new(object: T) -> Self {
+        Self {
+            schema_version: STORE_SCHEMA_VERSION,
+            object,
+        }
+    }
+    /// Get the schema version of the object
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+    /// Get a reference to the object
+    pub fn object(&self) -> &T {
+        &self.object
+    }
+    /// Get the object, discarding its envelope
+    pub fn into_object(self) -> T {
+        self.object
+    }
+}
+
+impl VersionedObject<Value> {
+    /// Split a `value` read from the store into its schema version and object.
+    /// A value which is not within an envelope is an object of schema version 0.
+    pub fn from_value(value: Value) -> Self {
+        match value {
+            Value::Object(mut map) if map.len() == 2 && map.contains_key("object") => {
+                match map.get("schema_version").and_then(Value::as_u64) {
+                    Some(schema_version) => Self {
+                        schema_version: schema_version as u32,
+                        object: map.remove("object").unwrap_or_default(),
+                    },
+                    None => Self::unversioned(Value::Object(map)),
+                }
+            }
+            value => Self::unversioned(value),
+        }
+    }
+    /// Return a new `Self` for an object written before the objects were versioned
+    fn unversioned(object: Value) -> Self {
+        Self {
+            schema_version: 0,
+            object,
+        }
+    }
+}
+
+/// Upgrades the objects of a type from one schema version to the next
+#[derive(Debug)]
+pub struct Migration {
+    /// schema version which the objects are upgraded from, to `from + 1`
+    pub from: u32,
+    /// type of the objects which are upgraded, or all versioned types if None
+    pub obj_type: Option<StorableObjectType>,
+    /// description of the changes made to the objects
+    pub description: &'static str,
+    /// upgrade the object
+    pub migrate: fn(Value) -> Result<Value, String>,
+}
+
+impl Migration {
+    /// Check if this migration upgrades objects of `obj_type` at schema `version`
+    fn applies_to(&self, obj_type: StorableObjectType, version: u32) -> bool {
+        self.from >= version && self.obj_type.map_or(true, |t| t == obj_type)
+    }
+}
+
+/// All migrations, ordered by the schema version they upgrade from
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    obj_type: None,
+    description: "Write the object within a versioned envelope",
+    migrate: unchanged,
+}];
+
+/// The object itself is unchanged, only its envelope
+fn unchanged(object: Value) -> Result<Value, String> {
+    Ok(object)
+}
+
+/// Get the migrations which upgrade an object of `obj_type` from schema `version` to the
+/// current schema version
+pub fn pending_migrations(
+    obj_type: StorableObjectType,
+    version: u32,
+) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(move |migration| migration.applies_to(obj_type, version))
+}
+
+/// Upgrade the `object` of `obj_type` from schema `version` to the current schema version
+pub fn migrate(
+    obj_type: StorableObjectType,
+    version: u32,
+    object: Value,
+) -> Result<Value, StoreError> {
+    if version > STORE_SCHEMA_VERSION {
+        return Err(StoreError::SchemaVersion {
+            obj_type,
+            version,
+            supported: STORE_SCHEMA_VERSION,
+        });
+    }
+    pending_migrations(obj_type, version).try_fold(object, |object, migration| {
+        (migration.migrate)(object).map_err(|reason| StoreError::Migration {
+            obj_type,
+            version: migration.from,
+            reason,
+        })
+    })
+}
+
+/// Get the object of `obj_type` from the `value` read from the store, upgraded to the current
+/// schema version
+pub fn object_value(obj_type: StorableObjectType, value: Value) -> Result<Value, StoreError> {
+    if !is_versioned(obj_type) {
+        return Ok(value);
+    }
+    let versioned = VersionedObject::from_value(value);
+    migrate(obj_type, versioned.schema_version, versioned.object)
+}
+
+/// Get the value with which the `object` is written to the store
+pub fn stored_value<O: StorableObject>(object: &O) -> Result<Value, StoreError> {
+    let value = serde_json::to_value(object).context(SerialiseValue)?;
+    if is_versioned(object.key().key_type()) {
+        serde_json::to_value(VersionedObject::new(value)).context(SerialiseValue)
+    } else {
+        Ok(value)
+    }
+}
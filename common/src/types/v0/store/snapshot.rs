@@ -0,0 +1,228 @@
+//! Definition of volume snapshot types that can be saved to the persistent store.
+
+use crate::types::v0::{
+    message_bus::{CreateVolumeSnapshot, ReplicaSnapshot, SnapshotId, VolumeId, VolumeSnapshot},
+    openapi::models,
+    store::{
+        definitions::{ObjectKey, StorableObject, StorableObjectType},
+        OperationSequence, OperationSequencer, ResourceUuid, SpecStatus, SpecTransaction,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Status of the Snapshot Spec
+pub type SnapshotSpecStatus = SpecStatus<()>;
+
+/// User specification of a volume snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SnapshotSpec {
+    /// uuid of the snapshot
+    pub uuid: SnapshotId,
+    /// uuid of the snapshotted volume
+    pub volume: VolumeId,
+    /// size of the volume when the snapshot was taken
+    pub size: u64,
+    /// name of the snapshot as reported by the volume nexus
+    pub name: Option<String>,
+    /// snapshots of each healthy replica of the volume
+    pub replicas: Vec<ReplicaSnapshot>,
+    /// time at which the snapshot was taken
+    #[serde(default)]
+    pub timestamp: Option<SystemTime>,
+    /// status of the snapshot
+    pub status: SnapshotSpecStatus,
+    /// Update in progress
+    #[serde(skip)]
+    pub sequencer: OperationSequence,
+    /// Record of the operation in progress
+    pub operation: Option<SnapshotOperationState>,
+}
+
+impl SnapshotSpec {
+    /// Record the snapshot `name` returned by the nexus, the snapshotted `replicas` and the
+    /// `timestamp` at which the snapshot was taken.
+    pub fn set_taken(&mut self, name: &str, replicas: Vec<ReplicaSnapshot>, timestamp: SystemTime) {
+        self.name = Some(name.to_string());
+        self.replicas = replicas;
+        self.timestamp = Some(timestamp);
+    }
+}
+
+macro_rules! snapshot_log {
+    ($Self:tt, $Level:expr, $Message:tt) => {
+        match tracing::Span::current().field("snapshot.uuid") {
+            None => {
+                let _span = tracing::span!($Level, "log_event", snapshot.uuid = %$Self.uuid).entered();
+                tracing::event!($Level, snapshot.uuid = %$Self.uuid, $Message);
+            }
+            Some(_) => {
+                tracing::event!($Level, snapshot.uuid = %$Self.uuid, $Message);
+            }
+        }
+    };
+}
+crate::impl_trace_str_log!(snapshot_log, SnapshotSpec);
+
+macro_rules! snapshot_span {
+    ($Self:tt, $Level:expr, $func:expr) => {
+        match tracing::Span::current().field("snapshot.uuid") {
+            None => {
+                let _span = tracing::span!($Level, "log_event", snapshot.uuid = %$Self.uuid).entered();
+                $func();
+            }
+            Some(_) => {
+                $func();
+            }
+        }
+    };
+}
+crate::impl_trace_span!(snapshot_span, SnapshotSpec);
+
+impl OperationSequencer for SnapshotSpec {
+    fn as_ref(&self) -> &OperationSequence {
+        &self.sequencer
+    }
+
+    fn as_mut(&mut self) -> &mut OperationSequence {
+        &mut self.sequencer
+    }
+}
+
+impl ResourceUuid for SnapshotSpec {
+    type Id = SnapshotId;
+    fn uuid(&self) -> Self::Id {
+        self.uuid.clone()
+    }
+}
+
+impl From<&CreateVolumeSnapshot> for SnapshotSpec {
+    fn from(request: &CreateVolumeSnapshot) -> Self {
+        Self {
+            uuid: request.uuid.clone(),
+            volume: request.volume.clone(),
+            size: 0,
+            name: None,
+            replicas: vec![],
+            timestamp: None,
+            status: SnapshotSpecStatus::Creating,
+            sequencer: OperationSequence::new(request.uuid.clone()),
+            operation: None,
+        }
+    }
+}
+impl PartialEq<CreateVolumeSnapshot> for SnapshotSpec {
+    fn eq(&self, other: &CreateVolumeSnapshot) -> bool {
+        self.uuid == other.uuid && self.volume == other.volume
+    }
+}
+impl PartialEq<VolumeSnapshot> for SnapshotSpec {
+    fn eq(&self, other: &VolumeSnapshot) -> bool {
+        self == &other.spec()
+    }
+}
+
+impl From<SnapshotSpec> for models::VolumeSnapshot {
+    fn from(src: SnapshotSpec) -> Self {
+        Self::new_all(
+            src.name,
+            src.replicas
+                .iter()
+                .map(models::ReplicaSnapshot::from)
+                .collect::<Vec<_>>(),
+            src.size,
+            src.status,
+            src.timestamp
+                .map(|timestamp| humantime::format_rfc3339_seconds(timestamp).to_string()),
+            src.uuid,
+            src.volume,
+        )
+    }
+}
+
+/// Operation State for a Snapshot resource
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotOperationState {
+    /// Record of the operation
+    pub operation: SnapshotOperation,
+    /// Result of the operation
+    pub result: Option<bool>,
+}
+
+/// Available Snapshot Operations
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SnapshotOperation {
+    Create,
+    Destroy,
+}
+
+impl SpecTransaction<SnapshotOperation> for SnapshotSpec {
+    fn pending_op(&self) -> bool {
+        self.operation.is_some()
+    }
+
+    fn pending_op_name(&self) -> Option<String> {
+        self.operation
+            .as_ref()
+            .map(|op| format!("{:?}", op.operation))
+    }
+
+    fn commit_op(&mut self) {
+        if let Some(op) = self.operation.clone() {
+            match op.operation {
+                SnapshotOperation::Create => {
+                    self.status = SpecStatus::Created(());
+                }
+                SnapshotOperation::Destroy => {
+                    self.status = SpecStatus::Deleted;
+                }
+            }
+        }
+        self.clear_op();
+    }
+
+    fn clear_op(&mut self) {
+        self.operation = None;
+    }
+
+    fn start_op(&mut self, operation: SnapshotOperation) {
+        self.operation = Some(SnapshotOperationState {
+            operation,
+            result: None,
+        })
+    }
+
+    fn set_op_result(&mut self, result: bool) {
+        if let Some(op) = &mut self.operation {
+            op.result = Some(result);
+        }
+    }
+}
+
+/// Key used by the store to uniquely identify a SnapshotSpec structure.
+pub struct SnapshotSpecKey(SnapshotId);
+
+impl From<&SnapshotId> for SnapshotSpecKey {
+    fn from(id: &SnapshotId) -> Self {
+        Self(id.clone())
+    }
+}
+
+impl ObjectKey for SnapshotSpecKey {
+    fn key_type(&self) -> StorableObjectType {
+        StorableObjectType::SnapshotSpec
+    }
+
+    fn key_uuid(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl StorableObject for SnapshotSpec {
+    type Key = SnapshotSpecKey;
+
+    fn key(&self) -> Self::Key {
+        SnapshotSpecKey(self.uuid.clone())
+    }
+}
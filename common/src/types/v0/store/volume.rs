@@ -10,11 +10,13 @@ use crate::types::v0::{
 
 use crate::{
     types::v0::{
-        message_bus::{ReplicaId, Topology, VolumeLabels, VolumePolicy, VolumeStatus},
+        message_bus::{
+            ReplicaId, Topology, VolumeContentSource, VolumeLabels, VolumePolicy, VolumeStatus,
+        },
         openapi::models,
         store::{OperationSequence, OperationSequencer, ResourceUuid},
     },
-    IntoOption,
+    IntoOption, IntoVec,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -76,6 +78,21 @@ impl From<VolumeTarget> for models::VolumeTarget {
     }
 }
 
+/// Seeding of new replicas of a volume with its existing content, which is rebuilt onto them
+/// through a temporary nexus by the volume reconciler.
+/// The replicas of a volume clone are seeded from its content source, whereas the replacement of
+/// a replica is seeded from the other replicas of the volume.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct VolumeSeeding {
+    /// The replicas being seeded
+    pub replicas: Vec<ReplicaId>,
+    /// The replica replaced by the seeded replicas, which is removed once they're seeded
+    pub replaces: Option<ReplicaId>,
+    /// Shared source replicas, which the temporary nexus opens locally, to share again once the
+    /// seeding is over
+    pub reshare: Vec<ReplicaId>,
+}
+
 /// User specification of a volume.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct VolumeSpec {
@@ -91,10 +108,27 @@ pub struct VolumeSpec {
     pub status: VolumeSpecStatus,
     /// The target where front-end IO will be sent to
     pub target: Option<VolumeTarget>,
+    /// Additional targets on other nodes through which front-end IO may also be sent to, as
+    /// further NVMe multipath paths of the volume
+    #[serde(default)]
+    pub paths: Vec<VolumeTarget>,
     /// volume policy
     pub policy: VolumePolicy,
     /// replica placement topology for the volume creation only
     pub topology: Option<Topology>,
+    /// Thin provisioning of the volume replicas.
+    #[serde(default)]
+    pub thin: bool,
+    /// Existing volume or snapshot whose content was cloned into the volume on creation.
+    #[serde(default)]
+    pub source: Option<VolumeContentSource>,
+    /// Seeding of the replicas in progress, during which the volume cannot be used
+    #[serde(default)]
+    pub seeding: Option<VolumeSeeding>,
+    /// Replica being moved to another pool by the rebalancer, which is removed once its new
+    /// replica is rebuilt
+    #[serde(default)]
+    pub replica_move: Option<ReplicaId>,
     /// Update of the state in progress
     #[serde(skip)]
     pub sequencer: OperationSequence,
@@ -155,6 +189,18 @@ impl VolumeSpec {
                 .unwrap_or_default(),
         }
     }
+    /// all the targets of the volume, starting with the main target
+    pub fn targets(&self) -> impl Iterator<Item = &VolumeTarget> {
+        self.target.iter().chain(self.paths.iter())
+    }
+    /// number of paths (ie: targets) through which the volume is published
+    pub fn num_paths(&self) -> u8 {
+        self.targets().count() as u8
+    }
+    /// check if the given nexus is one of the targets of the volume
+    pub fn is_target_nexus(&self, nexus: &NexusId) -> bool {
+        self.targets().any(|target| target.nexus() == nexus)
+    }
     /// desired volume replica count if during `SetReplica` operation
     /// or otherwise the current num_replicas
     pub fn desired_num_replicas(&self) -> u8 {
@@ -195,6 +241,12 @@ impl SpecTransaction<VolumeOperation> for VolumeSpec {
         self.operation.is_some()
     }
 
+    fn pending_op_name(&self) -> Option<String> {
+        self.operation
+            .as_ref()
+            .map(|op| format!("{:?}", op.operation))
+    }
+
     fn commit_op(&mut self) {
         if let Some(op) = self.operation.clone() {
             match op.operation {
@@ -220,9 +272,30 @@ impl SpecTransaction<VolumeOperation> for VolumeSpec {
                     self.target = Some(VolumeTarget::new(node, nexus.clone(), protocol));
                     self.last_nexus_id = Some(nexus);
                 }
+                VolumeOperation::AddPath((node, nexus, protocol)) => {
+                    self.paths.push(VolumeTarget::new(node, nexus, protocol));
+                }
+                VolumeOperation::MovePath((previous, node, nexus)) => {
+                    if let Some(path) = self.paths.iter_mut().find(|p| p.nexus == previous) {
+                        path.node = node;
+                        path.nexus = nexus;
+                    }
+                }
+                VolumeOperation::Failover((node, nexus)) => {
+                    if let Some(target) = self.target.as_mut() {
+                        target.node = node;
+                        target.nexus = nexus.clone();
+                    }
+                    self.last_nexus_id = Some(nexus);
+                }
                 VolumeOperation::Unpublish => {
                     self.target = None;
+                    self.paths.clear();
                 }
+                VolumeOperation::Seed(seeding) => self.seeding = Some(seeding),
+                VolumeOperation::Seeded => self.seeding = None,
+                VolumeOperation::MoveReplica(replica) => self.replica_move = Some(replica),
+                VolumeOperation::ReplicaMoved => self.replica_move = None,
             }
         }
         self.clear_op();
@@ -255,8 +328,15 @@ pub enum VolumeOperation {
     Unshare,
     SetReplica(u8),
     Publish((NodeId, NexusId, Option<VolumeShareProtocol>)),
+    AddPath((NodeId, NexusId, Option<VolumeShareProtocol>)),
+    MovePath((NexusId, NodeId, NexusId)),
+    Failover((NodeId, NexusId)),
     Unpublish,
     RemoveUnusedReplica(ReplicaId),
+    Seed(VolumeSeeding),
+    Seeded,
+    MoveReplica(ReplicaId),
+    ReplicaMoved,
 }
 
 impl From<VolumeOperation> for models::volume_spec_operation::Operation {
@@ -268,10 +348,19 @@ impl From<VolumeOperation> for models::volume_spec_operation::Operation {
             VolumeOperation::Unshare => models::volume_spec_operation::Operation::Unshare,
             VolumeOperation::SetReplica(_) => models::volume_spec_operation::Operation::SetReplica,
             VolumeOperation::Publish(_) => models::volume_spec_operation::Operation::Publish,
+            VolumeOperation::AddPath(_) => models::volume_spec_operation::Operation::AddPath,
+            VolumeOperation::MovePath(_) => models::volume_spec_operation::Operation::MovePath,
+            VolumeOperation::Failover(_) => models::volume_spec_operation::Operation::Failover,
             VolumeOperation::Unpublish => models::volume_spec_operation::Operation::Unpublish,
             VolumeOperation::RemoveUnusedReplica(_) => {
                 models::volume_spec_operation::Operation::RemoveUnusedReplica
             }
+            VolumeOperation::Seed(_) => models::volume_spec_operation::Operation::Seed,
+            VolumeOperation::Seeded => models::volume_spec_operation::Operation::Seeded,
+            VolumeOperation::MoveReplica(_) => {
+                models::volume_spec_operation::Operation::MoveReplica
+            }
+            VolumeOperation::ReplicaMoved => models::volume_spec_operation::Operation::ReplicaMoved,
         }
     }
 }
@@ -315,8 +404,13 @@ impl From<&CreateVolume> for VolumeSpec {
             num_replicas: request.replicas as u8,
             status: VolumeSpecStatus::Creating,
             target: None,
+            paths: vec![],
             policy: request.policy.clone(),
             topology: request.topology.clone(),
+            thin: request.thin,
+            source: request.source.clone(),
+            seeding: None,
+            replica_move: None,
             sequencer: OperationSequence::new(request.uuid.clone()),
             last_nexus_id: None,
             operation: None,
@@ -328,6 +422,8 @@ impl PartialEq<CreateVolume> for VolumeSpec {
         let mut other = VolumeSpec::from(other);
         other.status = self.status.clone();
         other.sequencer = self.sequencer.clone();
+        other.seeding = self.seeding.clone();
+        other.replica_move = self.replica_move.clone();
         &other == self
     }
 }
@@ -339,6 +435,7 @@ impl From<&VolumeSpec> for message_bus::VolumeState {
             status: message_bus::VolumeStatus::Unknown,
             target: None,
             replica_topology: HashMap::new(),
+            usage_warnings: vec![],
         }
     }
 }
@@ -359,14 +456,19 @@ impl From<VolumeSpec> for models::VolumeSpec {
     fn from(src: VolumeSpec) -> Self {
         Self::new_all(
             src.labels,
+            src.num_paths(),
             src.num_replicas,
             src.operation.into_opt(),
             src.size,
             src.status,
             src.target.into_opt(),
+            src.paths.into_vec(),
             src.uuid,
             src.topology.into_opt(),
             src.policy,
+            src.thin,
+            src.source.into_opt(),
+            src.seeding.is_some(),
         )
     }
 }
@@ -0,0 +1,242 @@
+use common_lib::{
+    store::memory::MemoryStore,
+    types::v0::store::{
+        definitions::{
+            ObjectKey, RevisionWatchEvent, StorableObject, Store, StoreError,
+            StoreRevisionWatchReceiver, WatchEvent,
+        },
+        registry::{
+            ControlPlaneService, CoreRegistryConfig, NodeRegistration, StoreLeaseOwner,
+            StoreLeaseOwnerKey,
+        },
+        schema::{VersionedObject, STORE_SCHEMA_VERSION},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct TestStruct {
+    name: String,
+    value: u64,
+    msg: String,
+}
+
+#[tokio::test]
+async fn memory() {
+    let mut store = MemoryStore::new();
+
+    let key = serde_json::json!("key");
+    let mut data = TestStruct {
+        name: "John Doe".to_string(),
+        value: 100,
+        msg: "Hello memory".to_string(),
+    };
+
+    // Add an entry to the store and read it back.
+    store
+        .put_kv(&key.to_string(), &serde_json::json!(&data))
+        .await
+        .unwrap();
+    let v = store.get_kv(&key).await.unwrap();
+    let result: TestStruct = serde_json::from_value(v).unwrap();
+    assert_eq!(data, result);
+
+    // Only the entries with the prefix are returned.
+    store
+        .put_kv(&"key/other", &serde_json::json!("other"))
+        .await
+        .unwrap();
+    store
+        .put_kv(&"kez", &serde_json::json!("kez"))
+        .await
+        .unwrap();
+    let values = store.get_values_prefix("key/").await.unwrap();
+    assert_eq!(
+        values,
+        vec![("key/other".to_string(), serde_json::json!("other"))]
+    );
+
+    // Modify the entry while watching it.
+    let mut watcher = store.watch_kv_from(&key, None).await.unwrap();
+    data.value = 200;
+    store
+        .put_kv(&key.to_string(), &serde_json::json!(&data))
+        .await
+        .unwrap();
+    let revision = match recv_revision_event(&mut watcher).await {
+        RevisionWatchEvent {
+            event: WatchEvent::Put(_k, v),
+            revision,
+        } => {
+            assert_eq!(v, serde_json::json!(&data));
+            revision
+        }
+        _ => panic!("Expected a 'put' event"),
+    };
+
+    // Resuming the watch from that revision replays the same modification.
+    let mut watcher = store.watch_kv_from(&key, Some(revision)).await.unwrap();
+    match recv_revision_event(&mut watcher).await {
+        RevisionWatchEvent {
+            event: WatchEvent::Put(_k, v),
+            revision: replayed,
+        } => {
+            assert_eq!(replayed, revision);
+            assert_eq!(v, serde_json::json!(&data));
+        }
+        _ => panic!("Expected a 'put' event"),
+    };
+
+    // The deletion is watched and ends the watch.
+    let mut watcher = store.watch_kv(&key).await.unwrap();
+    store.delete_kv(&key).await.unwrap();
+    match tokio::time::timeout(Duration::from_secs(1), watcher.recv())
+        .await
+        .unwrap()
+    {
+        Some(Ok(WatchEvent::Delete)) => {}
+        _ => panic!("Expected a 'delete' event"),
+    }
+    assert!(watcher.recv().await.is_none());
+    store
+        .get_kv(&key)
+        .await
+        .expect_err("Entry should have been deleted");
+}
+
+#[tokio::test]
+async fn memory_watch_lagged() {
+    let mut store = MemoryStore::new();
+    let key = "key";
+    store.put_kv(&key, &serde_json::json!(0)).await.unwrap();
+    let mut watcher = store.watch_kv(&key).await.unwrap();
+
+    // more changes than the watcher can be behind of
+    for value in 1 ..= 2000 {
+        store.put_kv(&key, &serde_json::json!(value)).await.unwrap();
+    }
+
+    // the watcher gets the changes it kept up with, and is then told that it fell behind
+    loop {
+        match watcher.recv().await {
+            Some(Ok(WatchEvent::Put(_, _))) => {}
+            Some(Err(StoreError::WatchLagged { missed, .. })) => {
+                assert!(missed > 0);
+                break;
+            }
+            event => panic!("Expected the watch to lag, got {:?}", event),
+        }
+    }
+    assert!(watcher.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn memory_lease_lock() {
+    let store = MemoryStore::new();
+    let svc = ControlPlaneService::CoreAgent;
+
+    let mut core_agent = store.new_leased(svc.clone()).await.unwrap();
+    let owner: StoreLeaseOwner = core_agent
+        .get_obj(&StoreLeaseOwnerKey::new(&svc))
+        .await
+        .expect("Should exist!");
+    assert_eq!(owner.lease_id(), "1");
+
+    store
+        .new_leased(svc.clone())
+        .await
+        .expect_err("One core-agent is already running!");
+
+    core_agent
+        .put_kv(&"key", &serde_json::json!("value"))
+        .await
+        .unwrap();
+    core_agent.revoke().await;
+    let error = core_agent
+        .put_kv(&"key", &serde_json::json!("value"))
+        .await
+        .expect_err("The lease has been revoked");
+    assert!(matches!(error, StoreError::FailedLock { .. }));
+
+    // now another instance can take over
+    let mut core_agent2 = store.new_leased(svc).await.unwrap();
+    core_agent2
+        .put_kv(&"key", &serde_json::json!("value"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn memory_file() {
+    let path = std::env::temp_dir().join(format!("memory-store-{}.json", std::process::id()));
+    let key = "key";
+    {
+        let mut store = MemoryStore::new_file(&path).unwrap();
+        store
+            .put_kv(&key, &serde_json::json!("value"))
+            .await
+            .unwrap();
+    }
+    let mut store = MemoryStore::new_file(&path).unwrap();
+    assert_eq!(
+        store.get_kv(&key).await.unwrap(),
+        serde_json::json!("value")
+    );
+
+    // the history is not saved, so the watches can't be resumed from previous revisions
+    let mut watcher = store.watch_kv_from(&key, Some(1)).await.unwrap();
+    match watcher.recv().await {
+        Some(Err(StoreError::WatchCompacted { .. })) => {}
+        _ => panic!("Expected a compacted error"),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn memory_schema_versions() {
+    let mut store = MemoryStore::new();
+    let config = CoreRegistryConfig::new(NodeRegistration::Manual);
+    let key = config.key();
+
+    // objects are written within a versioned envelope
+    store.put_obj(&config).await.unwrap();
+    let value = VersionedObject::from_value(store.get_kv(&key.key()).await.unwrap());
+    assert_eq!(value.schema_version(), STORE_SCHEMA_VERSION);
+    let read: CoreRegistryConfig = store.get_obj(&key).await.unwrap();
+    assert_eq!(read.node_registration(), &NodeRegistration::Manual);
+
+    // objects written before the objects were versioned are still readable
+    store
+        .put_kv(&key.key(), &serde_json::json!(&config))
+        .await
+        .unwrap();
+    let read: CoreRegistryConfig = store.get_obj(&key).await.unwrap();
+    assert_eq!(read.node_registration(), &NodeRegistration::Manual);
+
+    // but not objects written by a newer version
+    store
+        .put_kv(
+            &key.key(),
+            &serde_json::json!({
+                "schema_version": STORE_SCHEMA_VERSION + 1,
+                "object": &config,
+            }),
+        )
+        .await
+        .unwrap();
+    let error = store
+        .get_obj::<CoreRegistryConfig>(&key)
+        .await
+        .expect_err("Newer schema versions are not supported");
+    assert!(matches!(error, StoreError::SchemaVersion { .. }));
+}
+
+/// Wait up to 1 second for the next event of a watcher started from a revision.
+async fn recv_revision_event(watcher: &mut StoreRevisionWatchReceiver) -> RevisionWatchEvent {
+    tokio::time::timeout(Duration::from_secs(1), watcher.recv())
+        .await
+        .expect("Timed out waiting for message")
+        .expect("Watcher closed")
+        .expect("Failed to receive event")
+}
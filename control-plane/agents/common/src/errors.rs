@@ -190,6 +190,27 @@ pub enum SvcError {
     StoreMissingEntry { key: String },
     #[snafu(display("The uuid '{}' for kind '{}' is not valid.", uuid, kind.to_string()))]
     InvalidUuid { uuid: String, kind: ResourceKind },
+    #[snafu(display(
+        "{} '{}' of '{}' bytes cannot be cloned into a volume of '{}' bytes",
+        kind.to_string(),
+        id,
+        size,
+        clone_size
+    ))]
+    CloneSourceSize {
+        kind: ResourceKind,
+        id: String,
+        size: u64,
+        clone_size: u64,
+    },
+    #[snafu(display(
+        "Failed to seed replica '{}' of Volume '{}' from its content source",
+        replica,
+        vol_id
+    ))]
+    VolumeSeed { vol_id: String, replica: String },
+    #[snafu(display("Volume '{}' still has {} snapshots", vol_id, count))]
+    VolumeHasSnapshots { vol_id: String, count: usize },
 }
 
 impl From<StoreError> for SvcError {
@@ -526,6 +547,24 @@ impl From<SvcError> for ReplyError {
                 source: desc.to_string(),
                 extra: error.full_string(),
             },
+            SvcError::CloneSourceSize { ref kind, .. } => ReplyError {
+                kind: ReplyErrorKind::InvalidArgument,
+                resource: kind.clone(),
+                source: desc.to_string(),
+                extra: error.full_string(),
+            },
+            SvcError::VolumeSeed { .. } => ReplyError {
+                kind: ReplyErrorKind::Aborted,
+                resource: ResourceKind::Volume,
+                source: desc.to_string(),
+                extra: error.full_string(),
+            },
+            SvcError::VolumeHasSnapshots { .. } => ReplyError {
+                kind: ReplyErrorKind::InUse,
+                resource: ResourceKind::Volume,
+                source: desc.to_string(),
+                extra: error.full_string(),
+            },
         }
     }
 }
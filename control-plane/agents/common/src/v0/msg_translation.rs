@@ -318,3 +318,21 @@ impl MessageBusToRpc for message_bus::RemoveNexusChild {
         }
     }
 }
+
+impl MessageBusToRpc for message_bus::CreateNexusSnapshot {
+    type RpcMessage = rpc::CreateSnapshotRequest;
+    fn to_rpc(&self) -> Self::RpcMessage {
+        Self::RpcMessage {
+            uuid: self.nexus.clone().into(),
+        }
+    }
+}
+
+impl MessageBusToRpc for message_bus::DestroyReplicaSnapshot {
+    type RpcMessage = rpc::DestroyReplicaRequest;
+    fn to_rpc(&self) -> Self::RpcMessage {
+        Self::RpcMessage {
+            uuid: self.name.clone(),
+        }
+    }
+}
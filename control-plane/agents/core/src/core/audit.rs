@@ -0,0 +1,232 @@
+use crate::core::registry::Registry;
+use common_lib::{
+    mbus_api::ResourceKind,
+    types::v0::{
+        message_bus::{Event, EventResult},
+        store::{
+            definitions::{key_prefix, ObjectKey, StorableObjectType, Store},
+            event::EventKey,
+            schema::object_value,
+        },
+    },
+};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+/// Maximum number of events kept in the audit log, the oldest events are removed first
+pub(crate) const EVENT_LOG_CAPACITY: usize = 1000;
+/// Number of the oldest events which are removed from the persistent store at once
+const EVENT_LOG_TRIM_BATCH: usize = 100;
+
+/// Append-only audit log of the operations performed on the resource specs.
+/// The events are kept in the persistent store and, once the log reaches its capacity, the
+/// oldest events are removed in batches.
+/// The events are also kept in memory, from where they are served, and the lock is never held
+/// while writing to the store, so that the operations are not serialized behind the log.
+#[derive(Debug, Default)]
+pub(crate) struct EventLog {
+    log: Mutex<Log>,
+}
+
+/// The in-memory copy of the audit log
+#[derive(Debug, Default)]
+struct Log {
+    /// id of the next event
+    next_id: u64,
+    /// the recorded events, oldest first
+    events: VecDeque<Event>,
+    /// ids of the events which no longer fit in the log, yet to be removed from the store
+    removed: Vec<u64>,
+}
+
+impl Log {
+    /// Allocate the id of a new event
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Add the recorded `event`, in the order of the ids as concurrent events may be written to
+    /// the store out of order.
+    /// Returns the ids of the events to remove from the store, once there's a full batch of them.
+    fn insert(&mut self, event: Event) -> Vec<u64> {
+        let index = self
+            .events
+            .iter()
+            .rposition(|e| e.id < event.id)
+            .map(|index| index + 1)
+            .unwrap_or_default();
+        self.events.insert(index, event);
+        while self.events.len() > EVENT_LOG_CAPACITY {
+            if let Some(oldest) = self.events.pop_front() {
+                self.removed.push(oldest.id);
+            }
+        }
+        if self.removed.len() >= EVENT_LOG_TRIM_BATCH {
+            std::mem::take(&mut self.removed)
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl EventLog {
+    /// Load the events from the persistent store, so that new events carry on from the last
+    /// recorded id
+    pub(crate) async fn init<S: Store>(&self, store: &mut S) {
+        match store
+            .get_values_prefix(&key_prefix(StorableObjectType::Event))
+            .await
+        {
+            Ok(values) => {
+                let mut events = values
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        let event = object_value(StorableObjectType::Event, value)
+                            .ok()
+                            .and_then(|value| serde_json::from_value::<Event>(value).ok());
+                        if event.is_none() {
+                            tracing::warn!(key = %key, "Failed to load the audit log event");
+                        }
+                        event
+                    })
+                    .collect::<Vec<_>>();
+                events.sort_unstable_by_key(|event| event.id);
+
+                let mut log = self.log.lock();
+                log.next_id = events.last().map(|e| e.id + 1).unwrap_or_default();
+                let excess = events.len().saturating_sub(EVENT_LOG_CAPACITY);
+                log.removed = events.drain(.. excess).map(|e| e.id).collect();
+                log.events = events.into();
+            }
+            Err(error) => {
+                tracing::error!(
+                    error = %error,
+                    "Failed to load the audit log events from the persistent store"
+                );
+            }
+        }
+    }
+
+    /// Record the outcome of the `operation` on the `resource` of the given `kind`, on behalf of
+    /// the event source of the `registry`.
+    /// Failing to record the event does not fail the operation itself.
+    pub(crate) async fn record(
+        &self,
+        registry: &Registry,
+        kind: ResourceKind,
+        resource: String,
+        operation: String,
+        error: Option<String>,
+    ) {
+        let event = Event {
+            id: self.log.lock().next_id(),
+            timestamp: std::time::SystemTime::now(),
+            kind,
+            resource,
+            operation,
+            result: match error {
+                None => EventResult::Succeeded,
+                Some(_) => EventResult::Failed,
+            },
+            error,
+            source: registry.event_source().clone(),
+        };
+        if let Err(error) = registry.store_obj(&event).await {
+            tracing::error!(
+                event.id = event.id,
+                error = %error,
+                "Failed to record the audit log event {:?}",
+                event
+            );
+            return;
+        }
+
+        let removed = self.log.lock().insert(event);
+        if !removed.is_empty() {
+            // the operation need not wait for the oldest events to be removed
+            let registry = registry.clone();
+            tokio::spawn(async move { registry.events().remove(&registry, removed).await });
+        }
+    }
+
+    /// Remove the events with the given `ids` from the persistent store
+    async fn remove(&self, registry: &Registry, ids: Vec<u64>) {
+        let mut failed = vec![];
+        for id in ids {
+            if let Err(error) = registry.delete_kv(&EventKey::from(id).key()).await {
+                tracing::warn!(
+                    event.id = id,
+                    error = %error,
+                    "Failed to remove the oldest audit log event"
+                );
+                failed.push(id);
+            }
+        }
+        // try again along with the next batch
+        self.log.lock().removed.extend(failed);
+    }
+
+    /// Get the recorded events, oldest first, optionally only those of the given `resource`
+    pub(crate) fn get(&self, resource: Option<&str>) -> Vec<Event> {
+        self.log
+            .lock()
+            .events
+            .iter()
+            .filter(|event| resource.map(|r| event.resource == r).unwrap_or(true))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_lib::types::v0::message_bus::EventSource;
+
+    fn event(id: u64) -> Event {
+        Event {
+            id,
+            timestamp: std::time::SystemTime::now(),
+            kind: ResourceKind::Volume,
+            resource: id.to_string(),
+            operation: "Create".to_string(),
+            result: EventResult::Succeeded,
+            error: None,
+            source: EventSource::Unknown,
+        }
+    }
+
+    #[test]
+    fn events_out_of_order() {
+        let mut log = Log::default();
+        let ids = (0 .. 3).map(|_| log.next_id()).collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        // the events are written to the store in a different order than their ids
+        for id in &[1, 2, 0] {
+            assert!(log.insert(event(*id)).is_empty());
+        }
+        let ids = log.events.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn events_removed_in_batches() {
+        let mut log = Log::default();
+        for _ in 0 .. EVENT_LOG_CAPACITY + EVENT_LOG_TRIM_BATCH - 1 {
+            let id = log.next_id();
+            assert!(log.insert(event(id)).is_empty());
+        }
+        assert_eq!(log.events.len(), EVENT_LOG_CAPACITY);
+        assert_eq!(log.removed.len(), EVENT_LOG_TRIM_BATCH - 1);
+
+        let id = log.next_id();
+        let removed = log.insert(event(id));
+        let expected = (0 .. EVENT_LOG_TRIM_BATCH as u64).collect::<Vec<_>>();
+        assert_eq!(removed, expected);
+        assert!(log.removed.is_empty());
+        assert_eq!(log.events.front().unwrap().id, EVENT_LOG_TRIM_BATCH as u64);
+    }
+}
@@ -0,0 +1,267 @@
+use common_lib::types::v0::message_bus::{VolumeId, VolumeStatus};
+use k8s_openapi::{
+    api::core::v1::{Event, ObjectReference, PersistentVolume},
+    apimachinery::pkg::apis::meta::v1::{MicroTime, Time},
+    chrono::Utc,
+};
+use kube::{
+    api::{Api, ObjectMeta, PostParams},
+    Client, ResourceExt,
+};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc};
+
+/// Name of the component which reports the events
+const REPORTING_COMPONENT: &str = "core-agent";
+
+/// Kubernetes events emitter, only set when the core agent is started with k8s events enabled
+static K8S_EVENTS: OnceCell<K8sEvents> = OnceCell::new();
+
+/// Reason of a Kubernetes event emitted for a volume
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum VolumeEventReason {
+    /// The volume became degraded
+    VolumeDegraded,
+    /// The volume became faulted
+    VolumeFaulted,
+    /// The volume is back online
+    VolumeOnline,
+    /// A faulted replica was removed from the volume target
+    ReplicaFaulted,
+    /// Replicas were created to replace the missing volume replicas
+    ReplicaReplaced,
+    /// The missing volume target was recreated
+    NexusRecreated,
+    /// The missing volume target could not be recreated
+    NexusRecreateFailed,
+    /// The volume could not be published
+    PublishFailed,
+    /// The volume target was moved off its offline node
+    TargetFailedOver,
+    /// The volume target could not be moved off its offline node
+    TargetFailoverFailed,
+}
+impl VolumeEventReason {
+    /// The Kubernetes event type, ie: Normal or Warning
+    fn event_type(&self) -> &'static str {
+        match self {
+            Self::VolumeOnline
+            | Self::ReplicaReplaced
+            | Self::NexusRecreated
+            | Self::TargetFailedOver => "Normal",
+            Self::VolumeDegraded
+            | Self::VolumeFaulted
+            | Self::ReplicaFaulted
+            | Self::NexusRecreateFailed
+            | Self::PublishFailed
+            | Self::TargetFailoverFailed => "Warning",
+        }
+    }
+    /// Get the reason of the event emitted when the status of a volume changes from `previous`
+    /// to `current`, if any.
+    /// The first status of a volume is only reported when it's not online.
+    fn from_status_change(
+        previous: Option<&VolumeStatus>,
+        current: &VolumeStatus,
+    ) -> Option<(Self, &'static str)> {
+        if previous == Some(current) {
+            return None;
+        }
+        match (previous, current) {
+            (_, VolumeStatus::Degraded) => Some((
+                Self::VolumeDegraded,
+                "The volume is degraded: some of its replicas are not healthy",
+            )),
+            (_, VolumeStatus::Faulted) => Some((
+                Self::VolumeFaulted,
+                "The volume is faulted: none of its replicas are healthy",
+            )),
+            (Some(VolumeStatus::Degraded), VolumeStatus::Online)
+            | (Some(VolumeStatus::Faulted), VolumeStatus::Online) => {
+                Some((Self::VolumeOnline, "The volume is online again"))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for VolumeEventReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Emits Kubernetes events against the PersistentVolume and the PersistentVolumeClaim of the
+/// volumes
+#[derive(Clone)]
+struct K8sEvents {
+    client: Client,
+    /// last known status of each volume
+    statuses: Arc<Mutex<HashMap<VolumeId, VolumeStatus>>>,
+}
+
+/// Enable the Kubernetes events, using the in-cluster configuration or the kubeconfig
+pub(crate) async fn init() {
+    match Client::try_default().await {
+        Ok(client) => {
+            K8S_EVENTS
+                .set(K8sEvents {
+                    client,
+                    statuses: Default::default(),
+                })
+                .ok();
+        }
+        Err(error) => {
+            tracing::error!(error = %error, "Failed to create the Kubernetes client, Kubernetes events are disabled");
+        }
+    }
+}
+
+/// Emit a Kubernetes event for the given volume, in the background.
+/// Nothing is done if Kubernetes events are not enabled.
+pub(crate) fn volume_event(
+    volume: &VolumeId,
+    reason: VolumeEventReason,
+    message: impl Into<String>,
+) {
+    if let Some(events) = K8S_EVENTS.get() {
+        let events = events.clone();
+        let volume = volume.clone();
+        let message = message.into();
+        tokio::spawn(async move { events.emit(&volume, reason, &message).await });
+    }
+}
+
+/// Track the status of the given volume and emit a Kubernetes event when it becomes degraded,
+/// faulted or when it recovers.
+pub(crate) fn volume_status(volume: &VolumeId, status: &VolumeStatus) {
+    if let Some(events) = K8S_EVENTS.get() {
+        if status == &VolumeStatus::Unknown {
+            return;
+        }
+        let previous = events
+            .statuses
+            .lock()
+            .insert(volume.clone(), status.clone());
+        if let Some((reason, message)) =
+            VolumeEventReason::from_status_change(previous.as_ref(), status)
+        {
+            volume_event(volume, reason, message);
+        }
+    }
+}
+
+/// Forget the status of a deleted volume
+pub(crate) fn volume_deleted(volume: &VolumeId) {
+    if let Some(events) = K8S_EVENTS.get() {
+        events.statuses.lock().remove(volume);
+    }
+}
+
+impl K8sEvents {
+    /// Emit the event against the PV of the volume and against the PVC bound to it
+    async fn emit(&self, volume: &VolumeId, reason: VolumeEventReason, message: &str) {
+        // the CSI volumes are named after the uuid of the volume
+        let pv_name = format!("pvc-{}", volume);
+        let pvs: Api<PersistentVolume> = Api::all(self.client.clone());
+        let pv = match pvs.get(&pv_name).await {
+            Ok(pv) => pv,
+            Err(error) => {
+                tracing::debug!(volume.uuid = %volume, error = %error, "No PersistentVolume found for the volume");
+                return;
+            }
+        };
+
+        let mut involved = vec![ObjectReference {
+            api_version: Some("v1".to_string()),
+            kind: Some("PersistentVolume".to_string()),
+            name: Some(pv.name()),
+            resource_version: pv.resource_version(),
+            uid: pv.uid(),
+            ..Default::default()
+        }];
+        if let Some(claim) = pv.spec.and_then(|spec| spec.claim_ref) {
+            involved.push(claim);
+        }
+
+        for object in involved {
+            self.create_event(object, reason, message).await;
+        }
+    }
+
+    async fn create_event(
+        &self,
+        object: ObjectReference,
+        reason: VolumeEventReason,
+        message: &str,
+    ) {
+        // events of cluster scoped objects, such as the PV, live in the default namespace
+        let namespace = object
+            .namespace
+            .clone()
+            .unwrap_or_else(|| "default".to_string());
+        let name = object.name.clone().unwrap_or_default();
+        let events: Api<Event> = Api::namespaced(self.client.clone(), &namespace);
+        let now = Utc::now();
+        let event = Event {
+            metadata: ObjectMeta {
+                generate_name: Some(format!("{}.", name)),
+                namespace: Some(namespace),
+                ..Default::default()
+            },
+            involved_object: object,
+            action: Some(reason.to_string()),
+            reason: Some(reason.to_string()),
+            message: Some(message.to_string()),
+            type_: Some(reason.event_type().to_string()),
+            event_time: Some(MicroTime(now)),
+            first_timestamp: Some(Time(now)),
+            last_timestamp: Some(Time(now)),
+            count: Some(1),
+            reporting_component: Some(REPORTING_COMPONENT.to_string()),
+            reporting_instance: Some(
+                std::env::var("MY_POD_NAME").unwrap_or_else(|_| REPORTING_COMPONENT.to_string()),
+            ),
+            ..Default::default()
+        };
+        if let Err(error) = events.create(&PostParams::default(), &event).await {
+            tracing::error!(object = %name, error = %error, "Failed to create the Kubernetes event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VolumeEventReason;
+    use common_lib::types::v0::message_bus::VolumeStatus;
+
+    #[test]
+    fn volume_status_change() {
+        let reason = |previous: Option<VolumeStatus>, current: VolumeStatus| {
+            VolumeEventReason::from_status_change(previous.as_ref(), &current)
+                .map(|(reason, _)| reason)
+        };
+        assert_eq!(reason(None, VolumeStatus::Online), None);
+        assert_eq!(
+            reason(None, VolumeStatus::Degraded),
+            Some(VolumeEventReason::VolumeDegraded)
+        );
+        assert_eq!(
+            reason(Some(VolumeStatus::Degraded), VolumeStatus::Degraded),
+            None
+        );
+        assert_eq!(
+            reason(Some(VolumeStatus::Degraded), VolumeStatus::Faulted),
+            Some(VolumeEventReason::VolumeFaulted)
+        );
+        assert_eq!(
+            reason(Some(VolumeStatus::Faulted), VolumeStatus::Online),
+            Some(VolumeEventReason::VolumeOnline)
+        );
+        assert_eq!(
+            reason(Some(VolumeStatus::Online), VolumeStatus::Online),
+            None
+        );
+    }
+}
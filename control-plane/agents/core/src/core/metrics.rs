@@ -0,0 +1,415 @@
+use crate::core::{
+    registry::Registry,
+    task_poller::{PollResult, PollerState},
+};
+use common::errors::SvcError;
+use common_lib::{
+    metrics::NAMESPACE,
+    types::v0::message_bus::{NodeStatus, PoolStatus, VolumeStatus},
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    histogram_opts, opts, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    HistogramVec, IntCounterVec, IntGaugeVec,
+};
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+
+/// Number of volumes, by volume status
+/// The unit of the counts is the plural of what's counted, eg: volumes
+static VOLUMES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        opts!("volumes", "Number of volumes by status").namespace(NAMESPACE),
+        &["status"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Number of nodes, by node status
+static NODES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        opts!("nodes", "Number of nodes by status").namespace(NAMESPACE),
+        &["status"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Number of pools, by pool status
+static POOLS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        opts!("pools", "Number of pools by status").namespace(NAMESPACE),
+        &["status"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Capacity of each pool, in bytes
+static POOL_CAPACITY: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        opts!("pool_capacity_bytes", "Capacity of the pool in bytes").namespace(NAMESPACE),
+        &["pool", "node"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Used bytes of each pool
+static POOL_USED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        opts!("pool_used_bytes", "Used bytes from the pool").namespace(NAMESPACE),
+        &["pool", "node"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Duration of the reconciliation loops, by reconciler
+static RECONCILER_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        histogram_opts!(
+            "reconciler_poll_duration_seconds",
+            "Duration of the reconciler polls"
+        )
+        .namespace(NAMESPACE),
+        &["reconciler"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Outcome of the reconciliation loops, by reconciler and result
+static RECONCILER_RESULTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        opts!(
+            "reconciler_polls_total",
+            "Number of reconciler polls by result"
+        )
+        .namespace(NAMESPACE),
+        &["reconciler", "result"]
+    )
+    .expect("Should be able to register the metric")
+});
+
+/// Record the duration and the outcome of a reconciler poll
+pub(crate) fn observe_poll(reconciler: &str, result: &PollResult, duration: Duration) {
+    let outcome = match result {
+        Ok(PollerState::Idle) => "idle",
+        Ok(PollerState::Busy) => "busy",
+        Err(_) => "error",
+    };
+    RECONCILER_DURATION
+        .with_label_values(&[reconciler])
+        .observe(duration.as_secs_f64());
+    RECONCILER_RESULTS
+        .with_label_values(&[reconciler, outcome])
+        .inc();
+}
+
+/// Refresh the resource gauges from the current registry state
+pub(crate) async fn refresh(registry: &Registry) {
+    VOLUMES.reset();
+    for status in &[
+        VolumeStatus::Unknown,
+        VolumeStatus::Online,
+        VolumeStatus::Degraded,
+        VolumeStatus::Faulted,
+    ] {
+        VOLUMES.with_label_values(&[&status.to_string()]).set(0);
+    }
+    for volume in registry.get_volumes().await {
+        if let Some(status) = volume.status() {
+            VOLUMES.with_label_values(&[&status.to_string()]).inc();
+        }
+    }
+
+    NODES.reset();
+    for status in &[NodeStatus::Unknown, NodeStatus::Online, NodeStatus::Offline] {
+        NODES.with_label_values(&[&status.to_string()]).set(0);
+    }
+    for node in registry.get_node_wrappers().await {
+        let status = node.read().await.status();
+        NODES.with_label_values(&[&status.to_string()]).inc();
+    }
+    // the nodes which have not registered since the core agent started are also offline
+    for spec in registry.specs().get_nodes() {
+        if let Err(SvcError::NodeNotOnline { .. }) = registry.get_node_wrapper(spec.id()).await {
+            NODES
+                .with_label_values(&[&NodeStatus::Offline.to_string()])
+                .inc();
+        }
+    }
+
+    POOLS.reset();
+    for status in &[
+        PoolStatus::Unknown,
+        PoolStatus::Online,
+        PoolStatus::Degraded,
+        PoolStatus::Faulted,
+    ] {
+        POOLS.with_label_values(&[&status.to_string()]).set(0);
+    }
+    POOL_CAPACITY.reset();
+    POOL_USED.reset();
+    if let Ok(pools) = registry.get_pool_states_inner().await {
+        for pool in pools {
+            POOLS.with_label_values(&[&pool.status.to_string()]).inc();
+            let labels = [pool.id.as_str(), pool.node.as_str()];
+            POOL_CAPACITY
+                .with_label_values(&labels)
+                .set(pool.capacity as i64);
+            POOL_USED.with_label_values(&labels).set(pool.used as i64);
+        }
+    }
+}
+
+/// Serve the prometheus metrics on `/metrics` until the process exits
+pub(crate) async fn serve(addr: SocketAddr, registry: Registry) {
+    let make_service = make_service_fn(move |_| {
+        let registry = registry.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                metrics_handler(request, registry.clone())
+            }))
+        }
+    });
+    let server = match Server::try_bind(&addr) {
+        Ok(server) => server,
+        Err(error) => {
+            tracing::error!(%error, "Failed to bind the metrics server to '{}'", addr);
+            return;
+        }
+    };
+    tracing::info!("Serving the metrics on 'http://{}/metrics'", addr);
+    if let Err(error) = server.serve(make_service).await {
+        tracing::error!(%error, "Metrics server failed");
+    }
+}
+
+async fn metrics_handler(
+    request: Request<Body>,
+    registry: Registry,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        return Ok(response(StatusCode::NOT_FOUND, Body::empty()));
+    }
+    refresh(&registry).await;
+    Ok(match common_lib::metrics::encode() {
+        Ok(metrics) => {
+            let mut response = response(StatusCode::OK, Body::from(metrics));
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                common_lib::metrics::CONTENT_TYPE.parse().unwrap(),
+            );
+            response
+        }
+        Err(error) => response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Body::from(error.to_string()),
+        ),
+    })
+}
+
+fn response(status: StatusCode, body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use common_lib::{
+        mbus_api::{v0::Liveness, Message, TimeoutOptions},
+        types::v0::message_bus::{ChannelVs, CreateVolume},
+    };
+    use std::{convert::TryInto, net::SocketAddr, time::Duration};
+    use testlib::{Cluster, ClusterBuilder};
+
+    /// Get the prometheus metrics served by the core agent
+    async fn metrics(cluster: &Cluster) -> String {
+        let addr: SocketAddr = utils::DEFAULT_METRICS_ADDR.parse().unwrap();
+        let url = format!(
+            "http://{}:{}/metrics",
+            cluster.composer().container_ip("core"),
+            addr.port()
+        );
+        reqwest::get(url).await.unwrap().text().await.unwrap()
+    }
+
+    /// Get the value of the sample of the metric `name` which has all of the given `labels`
+    fn sample(metrics: &str, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        metrics
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| {
+                let (series, value) = line.rsplit_once(' ')?;
+                let (metric, series_labels) = series.split_once('{').unwrap_or((series, ""));
+                let matches = metric == name
+                    && labels
+                        .iter()
+                        .all(|(k, v)| series_labels.contains(&format!("{}=\"{}\"", k, v)));
+                matches.then(|| value.parse().ok()).flatten()
+            })
+    }
+
+    /// Wait until the metric `name` with the given `labels` has the `expected` value
+    async fn wait_sample(cluster: &Cluster, name: &str, labels: &[(&str, &str)], expected: f64) {
+        let mut last = None;
+        for _ in 0 .. 20 {
+            last = sample(&metrics(cluster).await, name, labels);
+            if last == Some(expected) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        panic!(
+            "Metric {}{:?} is {:?} rather than {}",
+            name, labels, last, expected
+        );
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint() {
+        let cluster = ClusterBuilder::builder()
+            .with_rest(false)
+            .with_agents(vec!["core"])
+            .with_mayastors(2)
+            .with_pools(1)
+            .with_node_deadline("2s")
+            .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+            .with_core_metrics()
+            .build()
+            .await
+            .unwrap();
+
+        CreateVolume {
+            uuid: "7a5d2c1e-3b4f-4e6a-9c8d-1f2e3a4b5c6d".try_into().unwrap(),
+            size: 5242880,
+            replicas: 1,
+            ..Default::default()
+        }
+        .request()
+        .await
+        .unwrap();
+
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_volumes",
+            &[("status", "Online")],
+            1.0,
+        )
+        .await;
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_nodes",
+            &[("status", "Online")],
+            2.0,
+        )
+        .await;
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_pools",
+            &[("status", "Online")],
+            2.0,
+        )
+        .await;
+
+        let metrics = metrics(&cluster).await;
+        assert_eq!(
+            sample(
+                &metrics,
+                "mayastor_control_plane_volumes",
+                &[("status", "Faulted")]
+            ),
+            Some(0.0)
+        );
+        assert_eq!(
+            sample(
+                &metrics,
+                "mayastor_control_plane_nodes",
+                &[("status", "Offline")]
+            ),
+            Some(0.0)
+        );
+        let pool = cluster.pool(0, 0);
+        let pool = [("pool", pool.as_str()), ("node", cluster.node(0).as_str())];
+        let capacity = sample(
+            &metrics,
+            "mayastor_control_plane_pool_capacity_bytes",
+            &pool,
+        )
+        .unwrap();
+        let used = sample(&metrics, "mayastor_control_plane_pool_used_bytes", &pool).unwrap();
+        assert!(capacity > 0.0 && used <= capacity);
+
+        let create_volume = CreateVolume::default().id().to_string();
+        let handled = sample(
+            &metrics,
+            "mayastor_control_plane_mbus_handler_duration_seconds_count",
+            &[("message_id", create_volume.as_str()), ("result", "ok")],
+        );
+        assert_eq!(handled, Some(1.0));
+        assert!(
+            metrics.contains("mayastor_control_plane_reconciler_polls_total{"),
+            "The reconcilers should have been polled"
+        );
+
+        CreateVolume {
+            uuid: "7a5d2c1e-3b4f-4e6a-9c8d-1f2e3a4b5c6e".try_into().unwrap(),
+            size: 5242880,
+            replicas: 3,
+            ..Default::default()
+        }
+        .request()
+        .await
+        .expect_err("Only 2 nodes for 3 replicas");
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_mbus_handler_duration_seconds_count",
+            &[("message_id", create_volume.as_str()), ("result", "error")],
+            1.0,
+        )
+        .await;
+
+        // an unreachable node is offline
+        cluster
+            .composer()
+            .kill(cluster.node(1).as_str())
+            .await
+            .unwrap();
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_nodes",
+            &[("status", "Offline")],
+            1.0,
+        )
+        .await;
+
+        // as is a node which hasn't registered since the core agent restarted
+        cluster.restart_core().await;
+        Liveness {}
+            .request_on_ext(
+                ChannelVs::Core,
+                TimeoutOptions::default()
+                    .with_timeout(Duration::from_secs(1))
+                    .with_timeout_backoff(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap();
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_nodes",
+            &[("status", "Online")],
+            1.0,
+        )
+        .await;
+        wait_sample(
+            &cluster,
+            "mayastor_control_plane_nodes",
+            &[("status", "Offline")],
+            1.0,
+        )
+        .await;
+    }
+}
@@ -0,0 +1,92 @@
+//! Migration of the objects in the persistent store to the current schema version.
+//! The objects are also upgraded in memory as they are read, though every object written by the
+//! core agent is written with the current schema version, which is why it exits after a dry-run.
+//!
+//! The migration can't be downgraded: once written within their versioned envelope, the objects
+//! can no longer be read by the core agents which predate the envelope, and an object migrated
+//! to a newer schema version is refused by older core agents. Going back to an older version
+//! requires restoring a backup of the store taken before the upgrade, eg: with `pstor-backup`.
+
+use common_lib::types::v0::store::{
+    definitions::{key_prefix, StorableObjectType, Store, StoreError},
+    registry::CoreRegistryConfig,
+    schema::{
+        migrate, pending_migrations, VersionedObject, STORE_SCHEMA_VERSION, VERSIONED_OBJECT_TYPES,
+    },
+};
+
+/// Migrate the versioned objects in the persistent store which are behind the current schema
+/// version and record the new schema version in the registry `config`.
+/// With `dry_run` the objects which need to be migrated are only logged.
+/// Returns the number of objects which were (or would be) migrated.
+pub(crate) async fn migrate_store<S: Store>(
+    store: &mut S,
+    config: &mut CoreRegistryConfig,
+    dry_run: bool,
+) -> Result<usize, StoreError> {
+    match config.schema_version() {
+        version if version == STORE_SCHEMA_VERSION => return Ok(0),
+        version if version > STORE_SCHEMA_VERSION => {
+            return Err(StoreError::SchemaVersion {
+                obj_type: StorableObjectType::CoreRegistryConfig,
+                version,
+                supported: STORE_SCHEMA_VERSION,
+            })
+        }
+        version => {
+            tracing::info!(
+                dry_run,
+                "Migrating the persistent store from schema version {} to {}",
+                version,
+                STORE_SCHEMA_VERSION
+            );
+        }
+    }
+
+    let mut migrated = 0;
+    for obj_type in VERSIONED_OBJECT_TYPES {
+        // the trailing slash keeps out the types which share the prefix, eg: Node and NodeSpec
+        let prefix = format!("{}/", key_prefix(obj_type));
+        for (key, value) in store.get_values_prefix(&prefix).await? {
+            let versioned = VersionedObject::from_value(value);
+            let version = versioned.schema_version();
+            if version == STORE_SCHEMA_VERSION {
+                continue;
+            }
+            let object = migrate(obj_type, version, versioned.into_object())?;
+            migrated += 1;
+
+            if dry_run {
+                let migrations = pending_migrations(obj_type, version)
+                    .map(|migration| migration.description)
+                    .collect::<Vec<_>>();
+                tracing::info!(
+                    key = %key,
+                    "Object needs migration from schema version {}: {:?}",
+                    version,
+                    migrations
+                );
+            } else {
+                store.put_kv(&key, &VersionedObject::new(object)).await?;
+                tracing::debug!(key = %key, "Migrated object from schema version {}", version);
+            }
+        }
+    }
+
+    if dry_run {
+        tracing::warn!(
+            "{} objects need to be migrated to schema version {}",
+            migrated,
+            STORE_SCHEMA_VERSION
+        );
+    } else {
+        config.set_schema_version(STORE_SCHEMA_VERSION);
+        store.put_obj(config).await?;
+        tracing::info!(
+            "Migrated {} objects to schema version {}",
+            migrated,
+            STORE_SCHEMA_VERSION
+        );
+    }
+    Ok(migrated)
+}
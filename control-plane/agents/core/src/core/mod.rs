@@ -1,7 +1,15 @@
 //! Common modules used by the different core services
 
+/// audit log of the spec operations
+pub(crate) mod audit;
 /// gRPC helpers
 pub mod grpc;
+/// kubernetes events emitted for the volumes
+pub(crate) mod k8s_events;
+/// prometheus metrics exported by the core agent
+pub(crate) mod metrics;
+/// migration of the persistent store objects to the current schema version
+pub(crate) mod migration;
 /// reconciliation logic
 pub mod reconciler;
 /// registry with node and all its resources
@@ -1,4 +1,5 @@
 mod nexus;
+mod node;
 mod persistent_store;
 pub mod poller;
 mod pool;
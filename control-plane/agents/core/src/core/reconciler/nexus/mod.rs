@@ -2,6 +2,7 @@ mod garbage_collector;
 
 use crate::{
     core::{
+        k8s_events::{self, VolumeEventReason},
         scheduling::resources::HealthyChildItems,
         specs::{OperationSequenceGuard, SpecOperations},
         task_poller::{
@@ -125,6 +126,16 @@ pub(super) async fn faulted_children_remover(
                 "Successfully removed faulted child '{}'",
                 child.uri,
             ));
+            if let Some(volume) = &nexus_spec_clone.owner {
+                k8s_events::volume_event(
+                    volume,
+                    VolumeEventReason::ReplicaFaulted,
+                    format!(
+                        "Removed faulted replica '{}' from the volume target",
+                        child.uri
+                    ),
+                );
+            }
         }
     }
 
@@ -295,10 +306,31 @@ pub(super) async fn missing_nexus_recreate(
     match node.create_nexus(&CreateNexus::from(&nexus)).await {
         Ok(_) => {
             nexus.info_span(|| tracing::info!("Nexus successfully recreated"));
+            if let Some(volume) = &nexus.owner {
+                k8s_events::volume_event(
+                    volume,
+                    VolumeEventReason::NexusRecreated,
+                    format!(
+                        "Recreated the missing volume target on node '{}'",
+                        nexus.node
+                    ),
+                );
+            }
             PollResult::Ok(PollerState::Idle)
         }
         Err(error) => {
             nexus.error_span(|| tracing::error!(error=%error, "Failed to recreate the nexus"));
+            if let Some(volume) = &nexus.owner {
+                k8s_events::volume_event(
+                    volume,
+                    VolumeEventReason::NexusRecreateFailed,
+                    format!(
+                        "Failed to recreate the missing volume target on node '{}': {}",
+                        nexus.node,
+                        error.full_string()
+                    ),
+                );
+            }
             Err(error)
         }
     }
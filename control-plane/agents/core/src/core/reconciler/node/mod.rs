@@ -0,0 +1,453 @@
+use crate::{
+    core::{
+        specs::OperationSequenceGuard,
+        task_poller::{
+            squash_results, PollContext, PollEvent, PollPeriods, PollResult, PollTimer,
+            PollTriggerEvent, PollerState, TaskPoller,
+        },
+    },
+    volume::specs::get_volume_replica_candidates,
+};
+use common::errors::{NexusNotFound, VolumeNotFound};
+use common_lib::{
+    mbus_api::ErrorChain,
+    types::v0::{
+        message_bus::{NodeId, PublishVolume, UnpublishVolume, VolumeState, VolumeStatus},
+        store::{
+            replica::ReplicaSpec,
+            volume::{VolumeSpec, VolumeTarget},
+            OperationMode, TraceSpan, TraceStrLog,
+        },
+    },
+};
+use parking_lot::Mutex;
+use snafu::OptionExt;
+use std::sync::Arc;
+
+/// Node Reconciler loop which:
+/// 1. moves the volume targets and replicas off the nodes which are being drained
+#[derive(Debug)]
+pub struct NodeReconciler {
+    counter: PollTimer,
+}
+impl NodeReconciler {
+    /// Return new `Self` with the provided period
+    pub fn from(period: PollPeriods) -> Self {
+        NodeReconciler {
+            counter: PollTimer::from(period),
+        }
+    }
+    /// Return new `Self` with the default period
+    pub fn new() -> Self {
+        Self::from(1)
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskPoller for NodeReconciler {
+    async fn poll(&mut self, context: &PollContext) -> PollResult {
+        let mut results = vec![];
+        for node in context.specs().get_draining_nodes() {
+            results.push(drain_node_reconciler(node.id(), context).await);
+        }
+        Self::squash_results(results)
+    }
+
+    async fn poll_timer(&mut self, _context: &PollContext) -> bool {
+        self.counter.poll()
+    }
+
+    async fn poll_event(&mut self, context: &PollContext) -> bool {
+        matches!(
+            context.event(),
+            PollEvent::TimedRun
+                | PollEvent::Triggered(PollTriggerEvent::NodeDrain)
+                | PollEvent::Triggered(PollTriggerEvent::Start)
+        )
+    }
+}
+
+/// How the target of a volume is moved off a draining node
+#[derive(Debug, Clone, PartialEq)]
+enum TargetDrain {
+    /// None of the targets of the volume are on the node
+    None,
+    /// The target is not shared, so it has no frontend consumer and the volume is republished
+    Republish,
+    /// A further path of a multipath volume is on the node, it's moved to another node while
+    /// the host keeps using the other paths
+    MovePath(VolumeTarget),
+    /// The target is shared and may be in use, so it's left on the node as moving it would cut
+    /// the frontend IO, and the volume blocks the drain
+    InUse,
+}
+
+/// Get how the target of the `volume` is moved off the draining `node`
+fn target_drain(node: &NodeId, volume: &VolumeSpec) -> TargetDrain {
+    match volume.targets().find(|target| target.node() == node) {
+        None => TargetDrain::None,
+        Some(target) if volume.paths.contains(target) => TargetDrain::MovePath(target.clone()),
+        Some(target) if target.protocol().is_none() && volume.paths.is_empty() => {
+            TargetDrain::Republish
+        }
+        Some(_) => TargetDrain::InUse,
+    }
+}
+
+/// Given a node which is being drained
+/// When volume targets or replicas are still placed on the node
+/// Then they should eventually be moved to other nodes
+/// And the node should eventually be marked as drained
+/// Unless a volume target which may be in use is on the node, in which case the node is left
+/// draining and the volume is reported as blocking the drain
+#[tracing::instrument(level = "debug", skip(context), fields(node.uuid = %node, request.reconcile = true))]
+async fn drain_node_reconciler(node: &NodeId, context: &PollContext) -> PollResult {
+    let mut results = vec![];
+    let mut blockers = vec![];
+    for volume in context.specs().get_locked_volumes() {
+        let (uuid, drain) = {
+            let volume = volume.lock();
+            (volume.uuid.clone(), target_drain(node, &volume))
+        };
+        if drain == TargetDrain::InUse {
+            blockers.push(uuid);
+        }
+        results.push(drain_volume_reconciler(node, &volume, context).await);
+    }
+    let node_spec = context.specs().get_node(node)?;
+    if !blockers.is_empty() && node_spec.drain_blockers() != blockers.as_slice() {
+        tracing::warn!(
+            node.uuid = %node,
+            "The drain is blocked by the targets of volumes {:?}, which may be in use",
+            blockers
+        );
+    }
+    context
+        .specs()
+        .set_node_drain_blockers(context.registry(), node, &blockers)
+        .await?;
+    let result = squash_results(results)?;
+
+    if result == PollerState::Idle && blockers.is_empty() {
+        context
+            .specs()
+            .set_node_drained(context.registry(), node)
+            .await?;
+        tracing::info!(node.uuid = %node, "Node successfully drained");
+    }
+    Ok(result)
+}
+
+/// Move the target and the replicas of the volume off the draining `node`, one step at a time:
+/// 1. the target is moved to another node, see `TargetDrain`
+/// 2. the replicas are moved to other nodes, see `move_draining_replica`
+async fn drain_volume_reconciler(
+    node: &NodeId,
+    volume_spec: &Arc<Mutex<VolumeSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return PollResult::Ok(PollerState::Busy),
+    };
+    let mode = OperationMode::ReconcileStep;
+    let volume = volume_spec.lock().clone();
+    if !volume.status.created() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+    let specs = context.specs();
+    let registry = context.registry();
+
+    match target_drain(node, &volume) {
+        TargetDrain::None | TargetDrain::InUse => {}
+        TargetDrain::MovePath(path) => {
+            volume.info(&format!(
+                "Moving the volume path off draining node '{}'",
+                node
+            ));
+            specs
+                .move_volume_path(registry, volume_spec, &path, mode)
+                .await?;
+            return PollResult::Ok(PollerState::Busy);
+        }
+        TargetDrain::Republish => {
+            return republish_draining_target(node, &volume, context).await;
+        }
+    }
+
+    let on_node = |replica: &ReplicaSpec| {
+        specs
+            .get_pool(&replica.pool)
+            .map(|pool| &pool.node == node)
+            .unwrap_or_default()
+    };
+    let location = format!("draining node '{}'", node);
+    move_draining_replica(&volume, on_node, &location, context).await
+}
+
+/// Republish the volume, whose target on the draining `node` has no frontend consumer, on
+/// another node, or publish it back where it was on failure.
+/// The caller must hold the operation guard of the volume.
+async fn republish_draining_target(
+    node: &NodeId,
+    volume: &VolumeSpec,
+    context: &PollContext,
+) -> PollResult {
+    let mode = OperationMode::ReconcileStep;
+    let specs = context.specs();
+    let registry = context.registry();
+
+    let publish =
+        |target_node: Option<NodeId>| PublishVolume::new(volume.uuid.clone(), target_node, None);
+    volume.info(&format!(
+        "Moving the unshared volume target off draining node '{}'",
+        node
+    ));
+    specs
+        .unpublish_volume(registry, &UnpublishVolume::new(&volume.uuid, false), mode)
+        .await?;
+    if let Err(error) = specs.publish_volume(registry, &publish(None), mode).await {
+        // publish the volume back where it was, so that it remains usable until the next try
+        volume.error_span(|| {
+            tracing::error!(
+                error = %error.full_string(),
+                "Failed to republish the volume off draining node '{}'",
+                node
+            )
+        });
+        if let Err(error) = specs
+            .publish_volume(registry, &publish(Some(node.clone())), mode)
+            .await
+        {
+            volume.error_span(|| {
+                tracing::error!(
+                    error = %error.full_string(),
+                    "Failed to publish the volume back on its previous target node"
+                )
+            });
+        }
+        return Err(error);
+    }
+    PollResult::Ok(PollerState::Busy)
+}
+
+/// Move the first replica of the volume selected by `select` off its `location`, which is either
+/// a draining node or a pool in maintenance, one step at a time:
+/// 1. an extra replica is created on another pool and added to the target nexus
+/// 2. once the extra replica is rebuilt, the selected replica is removed
+/// The replicas of a volume published through multiple paths are not moved.
+/// The replicas of a volume which is not published are seeded by the volume reconciler instead,
+/// see `move_unpublished_replica`, and the replicas of a volume being seeded are left alone until
+/// the seeding is complete.
+/// The caller must hold the operation guard of the volume.
+pub(super) async fn move_draining_replica(
+    volume: &VolumeSpec,
+    select: impl Fn(&ReplicaSpec) -> bool,
+    location: &str,
+    context: &PollContext,
+) -> PollResult {
+    let mode = OperationMode::ReconcileStep;
+    let specs = context.specs();
+    let registry = context.registry();
+
+    let replicas = specs
+        .get_volume_replicas(&volume.uuid)
+        .into_iter()
+        .map(|r| r.lock().clone())
+        .collect::<Vec<_>>();
+    let draining = match replicas.iter().find(|r| select(r)) {
+        None => return PollResult::Ok(PollerState::Idle),
+        Some(replica) => replica.clone(),
+    };
+    if volume.seeding.is_some() {
+        // wait for the seeding of the replicas to complete
+        return PollResult::Ok(PollerState::Busy);
+    }
+
+    let state = registry.get_volume_state(&volume.uuid).await?;
+    let nexus = match &state.target {
+        Some(nexus) => nexus.clone(),
+        None => {
+            return move_unpublished_replica(volume, &state, &draining, location, context).await
+        }
+    };
+    if !volume.paths.is_empty() {
+        // the replicas of a multipath volume cannot be changed, as every path nexus must have
+        // the same children, so the volume must be republished through a single path first
+        volume.warn_span(|| {
+            tracing::warn!(
+                replica.uuid = %draining.uuid,
+                "Cannot move the replica of the multipath volume off {}",
+                location
+            )
+        });
+        return PollResult::Ok(PollerState::Busy);
+    }
+    if state.status != VolumeStatus::Online {
+        // wait for the rebuild to complete
+        return PollResult::Ok(PollerState::Busy);
+    }
+
+    let nexus_spec = specs.get_nexus(&nexus.uuid).context(NexusNotFound {
+        nexus_id: nexus.uuid.to_string(),
+    })?;
+    let children = nexus_spec.lock().children.clone();
+    let draining_child = children
+        .iter()
+        .find(|c| c.as_replica().map(|r| r.uuid()) == Some(&draining.uuid));
+    let replacements = children
+        .iter()
+        .filter_map(|c| c.as_replica())
+        .filter_map(|r| specs.get_replica(r.uuid()))
+        .filter(|r| !specs.replica_draining(&r.lock()))
+        .count();
+
+    if replacements >= volume.num_replicas as usize {
+        // the volume is online so its replacement replicas are healthy, which means that the
+        // selected replica can now be removed
+        match draining_child {
+            Some(child) => {
+                specs
+                    .remove_nexus_child_by_uri(registry, &nexus, &child.uri(), true, mode)
+                    .await?
+            }
+            None => {
+                let node = specs.get_pool(&draining.pool)?.node;
+                specs
+                    .disown_and_destroy_replica(registry, &node, &draining.uuid)
+                    .await?
+            }
+        }
+        volume.info_span(|| {
+            tracing::info!(
+                replica.uuid = %draining.uuid,
+                "Successfully removed replica from {}",
+                location
+            )
+        });
+    } else {
+        let candidates = get_volume_replica_candidates(registry, &volume).await?;
+        let replica = specs
+            .create_volume_replica(registry, &state, &candidates, mode)
+            .await?;
+        if let Err(error) = specs
+            .attach_replica_to_nexus(registry, &volume.uuid, &nexus, &replica, mode)
+            .await
+        {
+            volume.error_span(|| {
+                tracing::error!(
+                    replica.uuid = %replica.uuid,
+                    error = %error.full_string(),
+                    "Failed to add the replacement replica to the volume target"
+                )
+            });
+            return Err(error);
+        }
+        volume.info_span(|| {
+            tracing::info!(
+                replica.uuid = %replica.uuid,
+                "Created replacement for replica '{}' on {}",
+                draining.uuid,
+                location
+            )
+        });
+    }
+    PollResult::Ok(PollerState::Busy)
+}
+
+/// Move the `draining` replica of a volume which is not published off its `location`: an extra
+/// replica is created on another pool and the volume is marked for seeding it with the volume
+/// content, after which the volume reconciler removes the draining replica.
+/// The caller must hold the operation guard of the volume.
+async fn move_unpublished_replica(
+    volume: &VolumeSpec,
+    state: &VolumeState,
+    draining: &ReplicaSpec,
+    location: &str,
+    context: &PollContext,
+) -> PollResult {
+    let mode = OperationMode::ReconcileStep;
+    let specs = context.specs();
+    let registry = context.registry();
+
+    let volume_spec = specs
+        .get_locked_volume(&volume.uuid)
+        .context(VolumeNotFound {
+            vol_id: volume.uuid.to_string(),
+        })?;
+    let candidates = get_volume_replica_candidates(registry, volume).await?;
+    let replica = specs
+        .create_volume_replica(registry, state, &candidates, mode)
+        .await?;
+    if let Err(error) = specs
+        .seed_unpublished_replica(registry, &volume_spec, &replica, &draining.uuid, mode)
+        .await
+    {
+        volume.error_span(|| {
+            tracing::error!(
+                replica.uuid = %replica.uuid,
+                error = %error.full_string(),
+                "Failed to start seeding the replacement replica of the unpublished volume"
+            )
+        });
+        if let Err(error) = specs
+            .disown_and_destroy_replica(registry, &replica.node, &replica.uuid)
+            .await
+        {
+            volume.warn_span(|| {
+                tracing::warn!(
+                    replica.uuid = %replica.uuid,
+                    error = %error.full_string(),
+                    "Failed to destroy the replacement replica, it will be garbage collected later"
+                )
+            });
+        }
+        return Err(error);
+    }
+
+    volume.info_span(|| {
+        tracing::info!(
+            replica.uuid = %draining.uuid,
+            "Seeding replacement replica '{}' for the replica of the unpublished volume on {}",
+            replica.uuid,
+            location
+        )
+    });
+    PollResult::Ok(PollerState::Busy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_lib::types::v0::message_bus::{NexusId, VolumeShareProtocol};
+
+    fn target(node: &str, protocol: Option<VolumeShareProtocol>) -> VolumeTarget {
+        VolumeTarget::new(node.into(), NexusId::new(), protocol)
+    }
+
+    #[test]
+    fn target_drain_of_volumes() {
+        let node = NodeId::from("node-1");
+        let nvmf = Some(VolumeShareProtocol::Nvmf);
+
+        let mut volume = VolumeSpec::default();
+        assert_eq!(target_drain(&node, &volume), TargetDrain::None);
+        volume.target = Some(target("node-2", nvmf));
+        assert_eq!(target_drain(&node, &volume), TargetDrain::None);
+
+        // a shared target may be in use, so it can't be moved
+        volume.target = Some(target("node-1", nvmf));
+        assert_eq!(target_drain(&node, &volume), TargetDrain::InUse);
+        volume.target = Some(target("node-1", None));
+        assert_eq!(target_drain(&node, &volume), TargetDrain::Republish);
+
+        // only the further paths of a multipath volume can be moved
+        volume.target = Some(target("node-1", nvmf));
+        volume.paths = vec![target("node-2", nvmf)];
+        assert_eq!(target_drain(&node, &volume), TargetDrain::InUse);
+        volume.target = Some(target("node-2", nvmf));
+        let path = target("node-1", nvmf);
+        volume.paths = vec![path.clone()];
+        assert_eq!(target_drain(&node, &volume), TargetDrain::MovePath(path));
+    }
+}
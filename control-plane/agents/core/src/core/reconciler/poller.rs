@@ -1,5 +1,5 @@
 use crate::core::{
-    reconciler::{nexus, persistent_store::PersistentStoreReconciler, pool, replica, volume},
+    reconciler::{nexus, node, persistent_store::PersistentStoreReconciler, pool, replica, volume},
     registry::Registry,
     task_poller::{
         squash_results, PollContext, PollEvent, PollResult, PollTriggerEvent, PollerState,
@@ -32,6 +32,7 @@ impl ReconcilerWorker {
             Box::new(volume::VolumeReconciler::new()),
             Box::new(PersistentStoreReconciler::new()),
             Box::new(replica::ReplicaReconciler::new()),
+            Box::new(node::NodeReconciler::new()),
         ];
 
         let event_channel = tokio::sync::mpsc::channel(poll_targets.len());
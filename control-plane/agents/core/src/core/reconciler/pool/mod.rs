@@ -1,17 +1,21 @@
 use crate::core::{
+    reconciler::node::move_draining_replica,
     specs::{OperationSequenceGuard, SpecOperations},
-    task_poller::{PollContext, PollPeriods, PollResult, PollTimer, PollerState, TaskPoller},
+    task_poller::{
+        squash_results, PollContext, PollPeriods, PollResult, PollTimer, PollerState, TaskPoller,
+    },
     wrapper::ClientOps,
 };
 use common_lib::types::v0::{
     message_bus::{CreatePool, DestroyPool, NodeStatus},
-    store::{pool::PoolSpec, OperationMode, TraceSpan},
+    store::{pool::PoolSpec, volume::VolumeSpec, OperationMode, TraceSpan},
 };
 use parking_lot::Mutex;
 use std::sync::Arc;
 
 /// Pool Reconciler loop which:
 /// 1. recreates pools which are not present following a mayastor restart
+/// 2. moves the volume replicas off the pools which are in maintenance
 #[derive(Debug)]
 pub struct PoolReconciler {
     counter: PollTimer,
@@ -36,6 +40,7 @@ impl TaskPoller for PoolReconciler {
         for pool in context.specs().get_locked_pools() {
             results.push(missing_pool_state_reconciler(pool.clone(), context).await);
             results.push(deleting_pool_spec_reconciler(pool.clone(), context).await);
+            results.push(maintenance_pool_reconciler(pool.clone(), context).await);
         }
         Self::squash_results(results)
     }
@@ -94,7 +99,8 @@ async fn missing_pool_state_reconciler(
 
         pool.warn_span(|| tracing::warn!("Attempting to recreate missing pool"));
 
-        let request = CreatePool::new(&pool.node, &pool.id, &pool.disks, &pool.labels);
+        let request = CreatePool::new(&pool.node, &pool.id, &pool.disks, &pool.labels)
+            .with_overcommit(pool.overcommit);
         match node.create_pool(&request).await {
             Ok(_) => {
                 pool.info_span(|| tracing::info!("Pool successfully recreated"));
@@ -155,3 +161,46 @@ async fn deleting_pool_spec_reconciler(
         }
     }
 }
+
+/// Given a pool which is in maintenance
+/// When volume replicas are still placed on the pool
+/// Then they should eventually be moved to other pools, after which the pool can be destroyed
+#[tracing::instrument(level = "debug", skip(pool_spec, context), fields(pool.uuid = %pool_spec.lock().id, request.reconcile = true))]
+async fn maintenance_pool_reconciler(
+    pool_spec: Arc<Mutex<PoolSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    let pool = pool_spec.lock().clone();
+    if !pool.maintenance || !pool.status().created() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+    let mut results = vec![];
+    for volume in context.specs().get_locked_volumes() {
+        results.push(maintenance_volume_reconciler(&pool, &volume, context).await);
+    }
+    squash_results(results)
+}
+
+/// Move the replicas of the volume off the `pool` in maintenance, one at a time
+async fn maintenance_volume_reconciler(
+    pool: &PoolSpec,
+    volume_spec: &Arc<Mutex<VolumeSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return PollResult::Ok(PollerState::Busy),
+    };
+    let volume = volume_spec.lock().clone();
+    if !volume.status.created() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+    let location = format!("pool '{}' in maintenance", pool.id);
+    move_draining_replica(
+        &volume,
+        |replica| replica.pool == pool.id,
+        &location,
+        context,
+    )
+    .await
+}
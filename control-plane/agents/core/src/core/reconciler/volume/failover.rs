@@ -0,0 +1,159 @@
+use crate::core::{
+    k8s_events::{self, VolumeEventReason},
+    reconciler::{PollContext, TaskPoller},
+    registry::Registry,
+    specs::OperationSequenceGuard,
+    task_poller::{PollResult, PollerState},
+};
+
+use common_lib::{
+    mbus_api::ErrorChain,
+    types::v0::{
+        message_bus::NodeId,
+        store::{volume::VolumeSpec, OperationMode, TraceSpan, TraceStrLog},
+    },
+};
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Volume target failover reconciler
+/// Moves the target of the volumes with the target failover policy off nodes which have not
+/// been online for longer than the failover grace period
+#[derive(Debug)]
+pub(super) struct TargetFailover {}
+impl TargetFailover {
+    /// Return a new `Self`
+    pub(super) fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskPoller for TargetFailover {
+    async fn poll(&mut self, context: &PollContext) -> PollResult {
+        let mut results = vec![];
+        for volume in context.specs().get_locked_volumes() {
+            results.push(volume_target_failover(&volume, context).await);
+        }
+        Self::squash_results(results)
+    }
+}
+
+/// Given a published volume with the target failover policy
+/// When the node of the volume target has not been online for longer than the grace period
+/// Then the volume target should be recreated on another online node which holds a healthy
+/// replica of the volume
+/// And the previous target should be fenced off from the replicas
+#[tracing::instrument(level = "debug", skip(context, volume_spec), fields(volume.uuid = %volume_spec.lock().uuid, request.reconcile = true))]
+async fn volume_target_failover(
+    volume_spec: &Arc<Mutex<VolumeSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return PollResult::Ok(PollerState::Busy),
+    };
+    let volume = volume_spec.lock().clone();
+
+    // the other paths of a multipath volume remain usable, so only single path volumes fail over
+    if !volume.policy.target_failover || !volume.status.created() || !volume.paths.is_empty() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+    let target_node = match &volume.target {
+        Some(target) => target.node().clone(),
+        None => return PollResult::Ok(PollerState::Idle),
+    };
+    let registry = context.registry();
+
+    match registry.node_offline_for(&target_node).await {
+        None => return PollResult::Ok(PollerState::Idle),
+        Some(offline_for) if offline_for < registry.failover_grace_period() => {
+            // check again once the grace period expires
+            return PollResult::Ok(PollerState::Busy);
+        }
+        Some(_) => {}
+    }
+
+    let node = match get_failover_node(registry, &volume, &target_node).await {
+        Some(node) => node,
+        None => {
+            volume.warn(&format!(
+                "No online node with a healthy replica to fail the volume target over to from node '{}'",
+                target_node
+            ));
+            return PollResult::Ok(PollerState::Busy);
+        }
+    };
+
+    volume.warn(&format!(
+        "Failing the volume target over from offline node '{}' to node '{}'",
+        target_node, node
+    ));
+    match context
+        .specs()
+        .failover_volume_target(registry, volume_spec, &node, OperationMode::ReconcileStep)
+        .await
+    {
+        Ok(_) => {
+            volume.info(&format!(
+                "Successfully failed the volume target over to node '{}'",
+                node
+            ));
+            k8s_events::volume_event(
+                &volume.uuid,
+                VolumeEventReason::TargetFailedOver,
+                format!(
+                    "Moved the volume target from offline node '{}' to node '{}'",
+                    target_node, node
+                ),
+            );
+            PollResult::Ok(PollerState::Idle)
+        }
+        Err(error) => {
+            volume.error_span(|| {
+                tracing::error!(
+                    error = %error.full_string(),
+                    "Failed to fail the volume target over to node '{}'",
+                    node
+                )
+            });
+            k8s_events::volume_event(
+                &volume.uuid,
+                VolumeEventReason::TargetFailoverFailed,
+                format!(
+                    "Failed to move the volume target from offline node '{}' to node '{}': {}",
+                    target_node,
+                    node,
+                    error.full_string()
+                ),
+            );
+            Err(error)
+        }
+    }
+}
+
+/// Get an online and uncordoned node, other than the `target_node`, which holds a healthy
+/// replica of the volume
+async fn get_failover_node(
+    registry: &Registry,
+    volume: &VolumeSpec,
+    target_node: &NodeId,
+) -> Option<NodeId> {
+    for replica in registry.specs().get_volume_replicas(&volume.uuid) {
+        let uuid = replica.lock().uuid.clone();
+        let replica = match registry.get_replica(&uuid).await {
+            Ok(replica) if replica.online() => replica,
+            _ => continue,
+        };
+        if &replica.node == target_node || registry.specs().node_cordoned(&replica.node) {
+            continue;
+        }
+        if let Ok(node) = registry.get_node_wrapper(&replica.node).await {
+            if node.read().await.is_online() {
+                return Some(replica.node);
+            }
+        }
+    }
+    None
+}
@@ -66,12 +66,15 @@ async fn disown_unused_nexuses(
     };
     let mut results = vec![];
     let volume_clone = volume.lock().clone();
+    if volume_clone.seeding.is_some() {
+        // the volume owns its seed nexus until the seeding is complete
+        return PollResult::Ok(PollerState::Idle);
+    }
 
     for nexus in context.specs().get_volume_nexuses(&volume_clone.uuid) {
-        match &volume_clone.target {
-            Some(target) if target.nexus() == &nexus.lock().uuid => continue,
-            _ => {}
-        };
+        if volume_clone.is_target_nexus(&nexus.lock().uuid) {
+            continue;
+        }
         let nexus_clone = nexus.lock().clone();
 
         nexus_clone.warn_span(|| tracing::warn!("Attempting to disown unused nexus"));
@@ -218,7 +221,12 @@ mod tests {
             .unwrap();
 
         let volume = volumes_api
-            .put_volume_target(&volume.spec.uuid, &node, models::VolumeShareProtocol::Nvmf)
+            .put_volume_target(
+                &volume.spec.uuid,
+                &node,
+                models::VolumeShareProtocol::Nvmf,
+                None,
+            )
             .await
             .unwrap();
 
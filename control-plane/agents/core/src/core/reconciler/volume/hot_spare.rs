@@ -1,4 +1,5 @@
 use crate::core::{
+    k8s_events::{self, VolumeEventReason},
     reconciler::{nexus, PollContext, TaskPoller},
     specs::OperationSequenceGuard,
     task_poller::{squash_results, PollResult, PollerState},
@@ -59,6 +60,15 @@ async fn hot_spare_reconcile(
     if !volume_spec.lock().status.created() {
         return PollResult::Ok(PollerState::Idle);
     }
+    if volume_spec.lock().seeding.is_some() {
+        // the replicas are being seeded by the seed reconciler
+        return PollResult::Ok(PollerState::Idle);
+    }
+    if !volume_spec.lock().paths.is_empty() {
+        // the replicas of a multipath volume cannot be changed, as every path nexus must have
+        // the same children
+        return PollResult::Ok(PollerState::Idle);
+    }
 
     match volume_state.status {
         VolumeStatus::Online => volume_replica_count_reconciler(volume_spec, context, mode).await,
@@ -201,6 +211,11 @@ async fn nexus_replica_count_reconciler(
                 )
                 .await?;
         }
+        Ordering::Greater if replacement_in_progress(&nexus_spec_clone, context) => {
+            // the extra replica is replacing a replica on a draining node or a replica which is
+            // being moved to another pool, so leave it to the drain or rebalance reconcilers to
+            // remove the replaced replica once the extra replica is rebuilt
+        }
         Ordering::Greater => {
             nexus_spec_clone.warn_span(|| {
                 tracing::warn!(
@@ -230,6 +245,21 @@ async fn nexus_replica_count_reconciler(
     })
 }
 
+/// Check if any of the nexus replicas is hosted on a node which is being drained or is being
+/// moved to another pool
+fn replacement_in_progress(nexus_spec: &NexusSpec, context: &PollContext) -> bool {
+    nexus_spec
+        .children
+        .iter()
+        .filter_map(|c| c.as_replica())
+        .filter_map(|r| context.specs().get_replica(r.uuid()))
+        .any(|r| {
+            let replica = r.lock().clone();
+            context.specs().replica_draining(&replica)
+                || context.specs().replica_moving(&replica.uuid)
+        })
+}
+
 /// Given a degraded volume
 /// When the number of created volume replicas is different to the required number of replicas
 /// Then the number of created volume replicas should eventually match the required number of
@@ -280,6 +310,15 @@ async fn volume_replica_count_reconciler(
                             result.len()
                         )
                     });
+                    k8s_events::volume_event(
+                        &volume_uuid,
+                        VolumeEventReason::ReplicaReplaced,
+                        format!(
+                            "Created '{}' replica(s) to replace the missing ones: {}",
+                            result.len(),
+                            replicas
+                        ),
+                    );
                 }
                 _ => {
                     volume_spec_clone.error("Failed to create replicas");
@@ -1,17 +1,23 @@
+mod failover;
 mod garbage_collector;
 mod hot_spare;
 mod nexus;
+mod rebalance;
+mod seed;
 
 use crate::core::task_poller::{PollContext, PollPeriods, PollResult, PollTimer, TaskPoller};
 
 use crate::core::reconciler::volume::{
-    garbage_collector::GarbageCollector, hot_spare::HotSpareReconciler,
-    nexus::VolumeNexusReconciler,
+    failover::TargetFailover, garbage_collector::GarbageCollector, hot_spare::HotSpareReconciler,
+    nexus::VolumeNexusReconciler, rebalance::ReplicaRebalancer, seed::VolumeSeedReconciler,
 };
 
 /// Volume Reconciler loop which:
 /// 1. does the replica replacement
 /// 2. volume garbage collection
+/// 3. moves replicas off overloaded pools
+/// 4. moves volume targets off nodes which have been offline for too long
+/// 5. seeds the new replicas of volumes with their content
 #[derive(Debug)]
 pub struct VolumeReconciler {
     counter: PollTimer,
@@ -26,6 +32,9 @@ impl VolumeReconciler {
                 Box::new(HotSpareReconciler::new()),
                 Box::new(GarbageCollector::new()),
                 Box::new(VolumeNexusReconciler::new()),
+                Box::new(ReplicaRebalancer::new()),
+                Box::new(TargetFailover::new()),
+                Box::new(VolumeSeedReconciler::new()),
             ],
         }
     }
@@ -0,0 +1,471 @@
+use crate::{
+    core::{
+        reconciler::{PollContext, TaskPoller},
+        registry::RebalanceConfig,
+        specs::OperationSequenceGuard,
+        task_poller::{PollResult, PollerState},
+    },
+    volume::specs::get_volume_replica_candidates,
+};
+
+use common::errors::{NexusNotFound, SvcError};
+use common_lib::{
+    mbus_api::ErrorChain,
+    types::v0::{
+        message_bus::{CreateReplica, PoolId, PoolStatus, VolumeStatus},
+        store::{replica::ReplicaSpec, volume::VolumeSpec, OperationMode, TraceSpan, TraceStrLog},
+    },
+};
+use parking_lot::Mutex;
+use snafu::OptionExt;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+/// Volume replica rebalancer which moves volume replicas off pools which are either above the
+/// configured usage threshold or which host too many replicas when compared to their peers.
+/// A replica is moved by adding a new replica on a better pool to the volume nexus and, once
+/// the new replica is rebuilt, by removing the old replica.
+/// Only one replica is moved at a time and new moves are started at most once per configured
+/// interval, and only when the rebalancing is enabled.
+/// The move is recorded in the volume spec before the new replica is created, so that a move
+/// which is interrupted by a restart of the core agent is carried on afterwards.
+#[derive(Debug)]
+pub(super) struct ReplicaRebalancer {
+    last_move: Option<Instant>,
+}
+impl ReplicaRebalancer {
+    /// Return a new `Self`
+    pub(super) fn new() -> Self {
+        Self { last_move: None }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskPoller for ReplicaRebalancer {
+    async fn poll(&mut self, context: &PollContext) -> PollResult {
+        let moves = context.specs().get_replica_moves();
+        if !moves.is_empty() {
+            let mut results = vec![];
+            for volume_spec in moves {
+                results.push(replica_move_reconciler(&volume_spec, context).await);
+            }
+            return Self::squash_results(results);
+        }
+
+        let config = context.registry().rebalance_config();
+        if !config.enabled {
+            return PollResult::Ok(PollerState::Idle);
+        }
+        let interval = config.interval;
+        if matches!(self.last_move, Some(last_move) if last_move.elapsed() < interval) {
+            return PollResult::Ok(PollerState::Idle);
+        }
+        if start_replica_move(context).await? {
+            self.last_move = Some(Instant::now());
+            PollResult::Ok(PollerState::Busy)
+        } else {
+            PollResult::Ok(PollerState::Idle)
+        }
+    }
+}
+
+/// Usage of a pool, used to decide if its replicas should be moved elsewhere
+#[derive(Debug, Clone)]
+struct PoolUsage {
+    id: PoolId,
+    capacity: u64,
+    used: u64,
+    replicas: usize,
+}
+impl PoolUsage {
+    /// Usage percentage of the pool after allocating `extra` bytes
+    fn usage_with(&self, extra: u64) -> u64 {
+        if self.capacity == 0 {
+            100
+        } else {
+            (self.used + extra) * 100 / self.capacity
+        }
+    }
+    /// Check if the pool usage is at or above the configured threshold
+    fn over_threshold(&self, config: &RebalanceConfig) -> bool {
+        self.usage_with(0) >= config.pool_usage_threshold as u64
+    }
+}
+
+/// Get the usage of all online pools, except for the pools in maintenance which are being emptied
+async fn get_pool_usages(context: &PollContext) -> Result<Vec<PoolUsage>, SvcError> {
+    let mut replicas = HashMap::<PoolId, usize>::new();
+    for replica in context.specs().get_replicas() {
+        *replicas.entry(replica.lock().pool.clone()).or_default() += 1;
+    }
+    let pools = context.registry().get_pool_states_inner().await?;
+    Ok(pools
+        .into_iter()
+        .filter(|pool| pool.status == PoolStatus::Online)
+        .filter(|pool| !context.specs().pool_in_maintenance(&pool.id))
+        .map(|pool| PoolUsage {
+            replicas: replicas.get(&pool.id).cloned().unwrap_or_default(),
+            id: pool.id,
+            capacity: pool.capacity,
+            used: pool.used,
+        })
+        .collect())
+}
+
+/// Get the pools which should have their replicas moved, in order of priority: pools above the
+/// usage threshold first, followed by the pools with the most replicas
+fn get_overloaded_pools(pools: &[PoolUsage], config: &RebalanceConfig) -> Vec<PoolUsage> {
+    let min_replicas = pools.iter().map(|p| p.replicas).min().unwrap_or_default();
+    let mut overloaded = pools
+        .iter()
+        .filter(|p| {
+            p.over_threshold(config) || p.replicas > min_replicas + config.replica_count_skew
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    overloaded.sort_by(|a, b| {
+        b.over_threshold(config)
+            .cmp(&a.over_threshold(config))
+            .then_with(|| b.replicas.cmp(&a.replicas))
+    });
+    overloaded
+}
+
+/// Check if moving a replica of `size` bytes from the `source` pool to the `target` pool would
+/// improve the balance of the pools, without overloading the target pool
+fn improves_balance(
+    source: &PoolUsage,
+    target: &PoolUsage,
+    size: u64,
+    config: &RebalanceConfig,
+) -> bool {
+    target.usage_with(size) < config.pool_usage_threshold as u64
+        && (source.over_threshold(config) || target.replicas + 1 < source.replicas)
+}
+
+/// Find a volume replica on the most overloaded pool which can be moved to a better pool and
+/// start moving it
+/// Returns true if a replica move was started
+async fn start_replica_move(context: &PollContext) -> Result<bool, SvcError> {
+    let config = context.registry().rebalance_config();
+    let pools = get_pool_usages(context).await?;
+
+    for source in get_overloaded_pools(&pools, config) {
+        let replicas = context
+            .specs()
+            .get_replicas()
+            .into_iter()
+            .map(|r| r.lock().clone())
+            .filter(|r| r.pool == source.id && r.owners.volume().is_some())
+            .collect::<Vec<_>>();
+        for replica in replicas {
+            if start_volume_replica_move(&replica, &source, &pools, context).await? {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Start moving the volume `replica` off the `source` pool, if the volume allows it and if
+/// there is a better pool for it
+/// Returns true if the replica move was started
+#[tracing::instrument(level = "debug", skip(replica, source, pools, context), fields(replica.uuid = %replica.uuid, request.reconcile = true))]
+async fn start_volume_replica_move(
+    replica: &ReplicaSpec,
+    source: &PoolUsage,
+    pools: &[PoolUsage],
+    context: &PollContext,
+) -> Result<bool, SvcError> {
+    let specs = context.specs();
+    let registry = context.registry();
+    let volume_spec = match replica
+        .owners
+        .volume()
+        .and_then(|v| specs.get_locked_volume(v))
+    {
+        Some(volume_spec) => volume_spec,
+        None => return Ok(false),
+    };
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return Ok(false),
+    };
+    let mode = OperationMode::ReconcileStep;
+    let volume = volume_spec.lock().clone();
+    // the replicas of a multipath volume cannot be changed, as every path nexus must have the
+    // same children
+    if !volume.policy.self_heal || !volume.status.created() || !volume.paths.is_empty() {
+        return Ok(false);
+    }
+    let volume_replicas = specs.get_volume_replicas(&volume.uuid);
+    if volume_replicas.len() != volume.num_replicas as usize
+        || volume.replica_move.is_some()
+        || volume_replicas.iter().any(|r| {
+            let replica = r.lock().clone();
+            specs.replica_draining(&replica) || specs.replica_moving(&replica.uuid)
+        })
+    {
+        // leave the volume alone until its replicas settle down
+        return Ok(false);
+    }
+
+    // the replicas can only be moved by rebuilding them through the volume target
+    let state = registry.get_volume_state(&volume.uuid).await?;
+    let nexus = match &state.target {
+        Some(nexus) if state.status == VolumeStatus::Online => nexus.clone(),
+        _ => return Ok(false),
+    };
+
+    let config = registry.rebalance_config();
+    let candidates = match get_volume_replica_candidates(registry, &volume).await {
+        Ok(candidates) => candidates
+            .into_iter()
+            .filter(|candidate: &CreateReplica| {
+                pools
+                    .iter()
+                    .find(|p| p.id == candidate.pool)
+                    .map(|target| improves_balance(source, target, volume.size, config))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => return Ok(false),
+    };
+    if candidates.is_empty() {
+        return Ok(false);
+    }
+
+    // record the move first, so that the new replica is not mistaken for an excess replica
+    specs
+        .start_replica_move(registry, &volume_spec, &replica.uuid, mode)
+        .await?;
+    let new_replica = match specs
+        .create_volume_replica(registry, &state, &candidates, mode)
+        .await
+    {
+        Ok(new_replica) => new_replica,
+        Err(error) => {
+            // otherwise the move is abandoned by the replica move reconciler
+            specs
+                .complete_replica_move(registry, &volume_spec, mode)
+                .await
+                .ok();
+            return Err(error);
+        }
+    };
+    if let Err(error) = specs
+        .attach_replica_to_nexus(registry, &volume.uuid, &nexus, &new_replica, mode)
+        .await
+    {
+        specs
+            .complete_replica_move(registry, &volume_spec, mode)
+            .await
+            .ok();
+        volume.error_span(|| {
+            tracing::error!(
+                replica.uuid = %new_replica.uuid,
+                error = %error.full_string(),
+                "Failed to add the rebalanced replica to the volume target"
+            )
+        });
+        return Err(error);
+    }
+
+    volume.info_span(|| {
+        tracing::info!(
+            replica.uuid = %replica.uuid,
+            "Moving replica from pool '{}' to pool '{}' (new replica '{}')",
+            replica.pool,
+            new_replica.pool,
+            new_replica.uuid
+        )
+    });
+    Ok(true)
+}
+
+/// Given a volume with one of its replicas being moved to another pool
+/// When the new replica is rebuilt
+/// Then the moved replica should be removed from the volume
+/// And the move should be abandoned if the new replica cannot be rebuilt
+#[tracing::instrument(level = "debug", skip(context, volume_spec), fields(volume.uuid = %volume_spec.lock().uuid, request.reconcile = true))]
+async fn replica_move_reconciler(
+    volume_spec: &Arc<Mutex<VolumeSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    let specs = context.specs();
+    let registry = context.registry();
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return PollResult::Ok(PollerState::Busy),
+    };
+    let mode = OperationMode::ReconcileStep;
+    let volume = volume_spec.lock().clone();
+    let replica_id = match &volume.replica_move {
+        Some(replica_id) => replica_id.clone(),
+        None => return PollResult::Ok(PollerState::Idle),
+    };
+    let replica = match specs.get_replica(&replica_id) {
+        Some(replica) if replica.lock().owners.owned_by(&volume.uuid) => replica.lock().clone(),
+        _ => {
+            // the moved replica was already removed, but the move was not yet cleared
+            specs
+                .complete_replica_move(registry, volume_spec, mode)
+                .await?;
+            return PollResult::Ok(PollerState::Idle);
+        }
+    };
+
+    let state = registry.get_volume_state(&volume.uuid).await?;
+    let nexus = match &state.target {
+        Some(nexus)
+            if volume.policy.self_heal
+                && volume.paths.is_empty()
+                && state.status != VolumeStatus::Faulted =>
+        {
+            nexus.clone()
+        }
+        _ => {
+            // the replica can no longer be rebuilt through the volume target alone, so give up
+            // and let the hot spare reconciler remove any excess replica
+            specs
+                .complete_replica_move(registry, volume_spec, mode)
+                .await?;
+            volume.warn_span(
+                || tracing::warn!(replica.uuid = %replica_id, "Abandoned the replica move"),
+            );
+            return PollResult::Ok(PollerState::Idle);
+        }
+    };
+    if state.status != VolumeStatus::Online {
+        // wait for the rebuild to complete
+        return PollResult::Ok(PollerState::Busy);
+    }
+
+    let nexus_spec = specs.get_nexus(&nexus.uuid).context(NexusNotFound {
+        nexus_id: nexus.uuid.to_string(),
+    })?;
+    let children = nexus_spec.lock().children.clone();
+    let moved_child = children
+        .iter()
+        .find(|c| c.as_replica().map(|r| r.uuid()) == Some(&replica_id));
+    let replacements = children
+        .iter()
+        .filter_map(|c| c.as_replica())
+        .filter(|r| r.uuid() != &replica_id)
+        .filter_map(|r| specs.get_replica(r.uuid()))
+        .filter(|r| !specs.replica_draining(&r.lock()))
+        .count();
+
+    if replacements < volume.num_replicas as usize {
+        // the new replica is gone, most likely because its rebuild failed
+        specs
+            .complete_replica_move(registry, volume_spec, mode)
+            .await?;
+        volume.warn_span(|| {
+            tracing::warn!(
+                replica.uuid = %replica_id,
+                "Abandoned the replica move as its new replica is not part of the volume target"
+            )
+        });
+        return PollResult::Ok(PollerState::Idle);
+    }
+
+    // the volume is online so the new replica is healthy, which means that the moved
+    // replica can now be removed
+    match moved_child {
+        Some(child) => {
+            specs
+                .remove_nexus_child_by_uri(registry, &nexus, &child.uri(), true, mode)
+                .await?
+        }
+        None => {
+            let pool = specs.get_pool(&replica.pool)?;
+            specs
+                .disown_and_destroy_replica(registry, &pool.node, &replica_id)
+                .await?
+        }
+    }
+    specs
+        .complete_replica_move(registry, volume_spec, mode)
+        .await?;
+    volume.info_span(|| {
+        tracing::info!(
+            replica.uuid = %replica_id,
+            "Successfully moved replica off pool '{}'",
+            replica.pool
+        )
+    });
+    PollResult::Ok(PollerState::Idle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(id: &str, used: u64, replicas: usize) -> PoolUsage {
+        PoolUsage {
+            id: id.into(),
+            capacity: 100,
+            used,
+            replicas,
+        }
+    }
+    fn config() -> RebalanceConfig {
+        RebalanceConfig {
+            enabled: true,
+            pool_usage_threshold: 80,
+            replica_count_skew: 2,
+            interval: std::time::Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn overloaded_pools() {
+        let config = config();
+        let pools = vec![
+            pool("p0", 10, 1),
+            pool("p1", 50, 3),
+            pool("p2", 60, 4),
+            pool("p3", 85, 2),
+        ];
+        let overloaded = get_overloaded_pools(&pools, &config)
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<Vec<_>>();
+        // the pool above the usage threshold comes first, followed by the skewed pools
+        assert_eq!(overloaded, vec![PoolId::from("p3"), PoolId::from("p2")]);
+
+        let balanced = vec![pool("p0", 10, 3), pool("p1", 20, 4), pool("p2", 30, 5)];
+        assert!(get_overloaded_pools(&balanced, &config).is_empty());
+    }
+
+    #[test]
+    fn balance_improvement() {
+        let config = config();
+        // skewed source: the target must end up with fewer replicas than the source
+        assert!(improves_balance(
+            &pool("s", 10, 5),
+            &pool("t", 10, 1),
+            10,
+            &config
+        ));
+        assert!(!improves_balance(
+            &pool("s", 10, 3),
+            &pool("t", 10, 2),
+            10,
+            &config
+        ));
+        // full source: any target which stays below the threshold will do
+        assert!(improves_balance(
+            &pool("s", 90, 1),
+            &pool("t", 60, 4),
+            10,
+            &config
+        ));
+        assert!(!improves_balance(
+            &pool("s", 90, 1),
+            &pool("t", 75, 0),
+            10,
+            &config
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::core::{
+    reconciler::{PollContext, TaskPoller},
+    specs::OperationSequenceGuard,
+    task_poller::{PollResult, PollerState},
+};
+use common_lib::types::v0::store::{volume::VolumeSpec, OperationMode};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Volume Seed reconciler, which seeds the new replicas of the volumes with their content
+#[derive(Debug)]
+pub(super) struct VolumeSeedReconciler {}
+impl VolumeSeedReconciler {
+    /// Return a new `Self`
+    pub(super) fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskPoller for VolumeSeedReconciler {
+    async fn poll(&mut self, context: &PollContext) -> PollResult {
+        let mut results = vec![];
+        for volume in context.specs().get_locked_volumes() {
+            results.push(volume_seed_reconcile(&volume, context).await);
+        }
+        Self::squash_results(results)
+    }
+}
+
+/// Given a volume whose replicas are being seeded
+/// When the seeding is started or its replicas are rebuilt
+/// Then the seeding should be carried on until it's complete
+#[tracing::instrument(level = "debug", skip(context, volume_spec), fields(volume.uuid = %volume_spec.lock().uuid, request.reconcile = true))]
+async fn volume_seed_reconcile(
+    volume_spec: &Arc<Mutex<VolumeSpec>>,
+    context: &PollContext,
+) -> PollResult {
+    if volume_spec.lock().seeding.is_none() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+    let _guard = match volume_spec.operation_guard(OperationMode::ReconcileStart) {
+        Ok(guard) => guard,
+        Err(_) => return PollResult::Ok(PollerState::Busy),
+    };
+    if !volume_spec.lock().status.created() {
+        return PollResult::Ok(PollerState::Idle);
+    }
+
+    let seeding = context
+        .specs()
+        .seed_volume(
+            context.registry(),
+            volume_spec,
+            OperationMode::ReconcileStep,
+        )
+        .await?;
+    if seeding {
+        PollResult::Ok(PollerState::Busy)
+    } else {
+        PollResult::Ok(PollerState::Idle)
+    }
+}
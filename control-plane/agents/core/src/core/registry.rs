@@ -15,18 +15,23 @@
 //! said instance.
 use super::{specs::*, wrapper::NodeWrapper};
 use crate::core::{
+    audit::EventLog,
+    migration::migrate_store,
     reconciler::ReconcilerControl,
     task_poller::{PollEvent, PollTriggerEvent},
     wrapper::InternalOps,
 };
 use common::errors::SvcError;
 use common_lib::{
-    store::etcd::Etcd,
+    store::persistent::PersistentStore,
     types::v0::{
-        message_bus::NodeId,
+        message_bus::{EventSource, NodeId},
         store::{
-            definitions::{StorableObject, Store, StoreError, StoreKey},
+            definitions::{
+                key_prefix, StorableObject, StorableObjectType, Store, StoreError, StoreKey,
+            },
             registry::{ControlPlaneService, CoreRegistryConfig, NodeRegistration},
+            schema::object_value,
         },
     },
 };
@@ -40,14 +45,17 @@ use tokio::sync::{Mutex, RwLock};
 /// Registry containing all mayastor instances (aka nodes)
 #[derive(Clone, Debug)]
 pub struct Registry {
-    inner: Arc<RegistryInner<Etcd>>,
+    inner: Arc<RegistryInner<PersistentStore>>,
+    /// the request or the reconciler on whose behalf the spec operations performed through this
+    /// handle are recorded in the audit log
+    source: EventSource,
 }
 
 /// Map that stores the actual state of the nodes
 pub(crate) type NodesMapLocked = Arc<RwLock<HashMap<NodeId, Arc<RwLock<NodeWrapper>>>>>;
 
 impl Deref for Registry {
-    type Target = Arc<RegistryInner<Etcd>>;
+    type Target = Arc<RegistryInner<PersistentStore>>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -72,12 +80,46 @@ pub struct RegistryInner<S: Store> {
     reconcile_period: std::time::Duration,
     reconciler: ReconcilerControl,
     config: CoreRegistryConfig,
+    /// configuration of the reconcilers
+    reconcilers: ReconcilerConfig,
+    /// audit log of the operations performed on the resource specs
+    events: EventLog,
+    /// when the registry was created, ie: when the core agent started
+    started: std::time::Instant,
+}
+
+/// Configuration of the reconcilers
+#[derive(Debug, Clone)]
+pub struct ReconcilerConfig {
+    /// configuration of the replica rebalancer
+    pub rebalance: RebalanceConfig,
+    /// the targets of the volumes with the target failover policy are moved to another node
+    /// once their node has not been online for this long
+    pub failover_grace_period: std::time::Duration,
+}
+
+/// Configuration of the replica rebalancing reconciler
+#[derive(Debug, Clone)]
+pub struct RebalanceConfig {
+    /// whether replicas are moved at all, which is off by default
+    pub enabled: bool,
+    /// pools with a usage percentage at or above this threshold have their replicas moved
+    pub pool_usage_threshold: u8,
+    /// pools with more replicas than the least used pool plus this skew have their replicas
+    /// moved
+    pub replica_count_skew: usize,
+    /// minimum interval between starting two replica moves
+    pub interval: std::time::Duration,
 }
 
 impl Registry {
     /// Create a new registry with the `cache_period` to reload the cache, the
     /// `store_url` to connect to, a `store_timeout` for store operations
-    /// and a `reconcile_period` for reconcile operations
+    /// a `reconcile_period` for reconcile operations and the `reconcilers` configuration.
+    /// The store objects are migrated to the current schema version, unless
+    /// `store_migration_dry_run` is set in which case they're only reported and the process
+    /// exits, as the objects written while running would be migrated anyway.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         cache_period: std::time::Duration,
         store_url: String,
@@ -85,17 +127,30 @@ impl Registry {
         store_lease_tll: std::time::Duration,
         reconcile_period: std::time::Duration,
         reconcile_idle_period: std::time::Duration,
+        reconcilers: ReconcilerConfig,
+        store_migration_dry_run: bool,
     ) -> Self {
         let store_endpoint = Self::format_store_endpoint(&store_url);
         tracing::info!("Connecting to persistent store at {}", store_endpoint);
-        let store = Etcd::new_leased(
-            [&store_endpoint],
+        let mut store = PersistentStore::new_leased(
+            &store_endpoint,
             ControlPlaneService::CoreAgent,
             store_lease_tll,
         )
         .await
         .expect("Should connect to the persistent store");
         tracing::info!("Connected to persistent store at {}", store_endpoint);
+        let mut config = Self::get_config_or_panic(store.clone()).await;
+        if let Err(error) = migrate_store(&mut store, &mut config, store_migration_dry_run).await {
+            panic!(
+                "Must be able to migrate the persistent store to the current schema version. Got error: '{:#?}'", error
+            );
+        }
+        if store_migration_dry_run {
+            tracing::info!("Persistent store migration dry-run complete, exiting");
+            store.revoke().await;
+            std::process::exit(0);
+        }
         let registry = Self {
             inner: Arc::new(RegistryInner {
                 nodes: Default::default(),
@@ -106,8 +161,12 @@ impl Registry {
                 reconcile_period,
                 reconcile_idle_period,
                 reconciler: ReconcilerControl::new(),
-                config: Self::get_config_or_panic(store).await,
+                config,
+                reconcilers,
+                events: EventLog::default(),
+                started: std::time::Instant::now(),
             }),
+            source: EventSource::Unknown,
         };
         registry.init().await;
         registry
@@ -146,6 +205,39 @@ impl Registry {
         self.reconcile_period
     }
 
+    /// configuration of the replica rebalancer
+    pub(crate) fn rebalance_config(&self) -> &RebalanceConfig {
+        &self.reconcilers.rebalance
+    }
+
+    /// how long the node of a volume target must not be online for before the target is failed
+    /// over to another node
+    pub(crate) fn failover_grace_period(&self) -> std::time::Duration {
+        self.reconcilers.failover_grace_period
+    }
+
+    /// how long ago the registry was created, ie: how long the core agent has been running for
+    pub(crate) fn uptime(&self) -> std::time::Duration {
+        self.started.elapsed()
+    }
+
+    /// Get a reference to the audit log of the spec operations
+    pub(crate) fn events(&self) -> &EventLog {
+        &self.events
+    }
+    /// Get a handle of the registry whose spec operations are recorded in the audit log on
+    /// behalf of the given `source`
+    pub(crate) fn with_event_source(&self, source: EventSource) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            source,
+        }
+    }
+    /// Get the request or the reconciler on whose behalf the spec operations are performed
+    pub(crate) fn event_source(&self) -> &EventSource {
+        &self.source
+    }
+
     /// Get a reference to the actual state of the nodes
     pub(crate) fn nodes(&self) -> &NodesMapLocked {
         &self.nodes
@@ -188,6 +280,39 @@ impl Registry {
         }
     }
 
+    /// Serialized read of all the objects of the given type from the persistent store
+    pub async fn load_objs<O: StorableObject>(
+        &self,
+        obj_type: StorableObjectType,
+    ) -> Result<Vec<O>, SvcError> {
+        let mut store = self.store.lock().await;
+        let prefix = key_prefix(obj_type);
+        match tokio::time::timeout(self.store_timeout, async move {
+            store.get_values_prefix(&prefix).await
+        })
+        .await
+        {
+            Ok(values) => values?
+                .into_iter()
+                .map(|(_, value)| -> Result<O, SvcError> {
+                    let value = object_value(obj_type, value)?;
+                    serde_json::from_value(value.clone()).map_err(|source| {
+                        StoreError::DeserialiseValue {
+                            value: value.to_string(),
+                            source,
+                        }
+                        .into()
+                    })
+                })
+                .collect(),
+            Err(_) => Err(StoreError::Timeout {
+                operation: "GetPrefix".to_string(),
+                timeout: self.store_timeout,
+            }
+            .into()),
+        }
+    }
+
     /// Serialized delete to the persistent store
     pub async fn delete_kv<K: StoreKey>(&self, key: &K) -> Result<(), SvcError> {
         let mut store = self.store.lock().await;
@@ -214,7 +339,7 @@ impl Registry {
     }
 
     /// Get a reference to the persistent store
-    pub(crate) fn store(&self) -> &Arc<Mutex<Etcd>> {
+    pub(crate) fn store(&self) -> &Arc<Mutex<PersistentStore>> {
         &self.store
     }
 
@@ -250,6 +375,7 @@ impl Registry {
     async fn init(&self) {
         let mut store = self.store.lock().await;
         self.specs.init(store.deref_mut()).await;
+        self.events.init(store.deref_mut()).await;
     }
 
     /// Send a triggered event signal to the reconciler module
@@ -7,7 +7,10 @@ use crate::core::scheduling::{
     resources::{ChildItem, PoolItem, ReplicaItem},
     volume::{GetSuitablePoolsContext, VolumeReplicasForNexusCtx},
 };
-use common_lib::types::v0::message_bus::{PoolStatus, PoolTopology};
+use common_lib::types::v0::{
+    message_bus::{PoolStatus, PoolTopology},
+    store::pool::DEFAULT_POOL_OVERCOMMIT,
+};
 use std::{cmp::Ordering, collections::HashMap, future::Future};
 
 #[async_trait::async_trait(?Send)]
@@ -53,6 +56,32 @@ impl NodeFilters {
     pub(crate) fn allowed(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
         request.allowed_nodes().is_empty() || request.allowed_nodes().contains(&item.pool.node)
     }
+    /// Should only attempt to use nodes which are not cordoned
+    pub(crate) fn uncordoned(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
+        !request.registry().specs().node_cordoned(item.node.id())
+    }
+    /// Should only attempt to use nodes in failure domains not currently used by the volume,
+    /// as defined by the topology spread labels
+    /// Replicas which are being replaced don't count as their failure domain is being vacated
+    pub(crate) fn spread(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
+        let labels = match &request.topology {
+            Some(topology) if !topology.spread_labels().is_empty() => topology.spread_labels(),
+            _ => return true,
+        };
+        let specs = request.registry().specs();
+        if !specs.node_labelled(&item.pool.node, labels) {
+            return false;
+        }
+        specs
+            .get_volume_replicas(&request.uuid)
+            .into_iter()
+            .map(|replica| replica.lock().clone())
+            .filter(|replica| {
+                !specs.replica_draining(replica) && !specs.replica_moving(&replica.uuid)
+            })
+            .filter_map(|replica| specs.get_pool(&replica.pool).ok())
+            .all(|pool| specs.nodes_spread_apart(&pool.node, &item.pool.node, labels))
+    }
     /// Should only attempt to use nodes not currently used by the volume
     pub(crate) fn unused(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
         let registry = request.registry();
@@ -65,43 +94,88 @@ impl NodeFilters {
 pub(crate) struct PoolFilters {}
 impl PoolFilters {
     /// Should only attempt to use pools with sufficient free space
+    /// The capacity already allocated to the pool replicas counts against the pool capacity,
+    /// which thin provisioned replicas may overcommit up to the pool's overcommit ratio
     pub(crate) fn free_space(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
-        item.pool.free_space() > request.size
+        let overcommit = request
+            .registry()
+            .specs()
+            .get_pool(&item.pool.id)
+            .map(|spec| spec.overcommit())
+            .unwrap_or(DEFAULT_POOL_OVERCOMMIT);
+        item.pool.allocatable(request.thin, overcommit) > request.size
     }
-    /// Should only attempt to use usable (not faulted) pools
-    pub(crate) fn usable(_: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
-        item.pool.status != PoolStatus::Faulted && item.pool.status != PoolStatus::Unknown
+    /// Should only attempt to use usable (not faulted) pools which are not in maintenance
+    pub(crate) fn usable(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
+        item.pool.status != PoolStatus::Faulted
+            && item.pool.status != PoolStatus::Unknown
+            && !request
+                .registry()
+                .specs()
+                .pool_in_maintenance(&item.pool.id)
     }
-    /// Should only attempt to use pools having specific creation label iff topology has it
+    /// Should only attempt to use pools which match the labelled pool topology, if any:
+    /// 1. the pool must have all the inclusive labels with the same value and match all the label
+    /// expressions
+    /// 2. the pool must not have any of the exclusive labels which have a value
+    /// 3. the pool must not have the same value for an exclusive label without a value as the
+    /// pools of the other volume replicas
     pub(crate) fn topology(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
-        let volume_pool_topology_labels: HashMap<String, String>;
-        match request.topology.clone() {
+        let labelled_topology = match request.topology.as_ref().and_then(|t| t.pool.as_ref()) {
+            Some(PoolTopology::Labelled(labelled_topology)) => labelled_topology,
             None => return true,
-            Some(topology) => match topology.pool {
-                None => return true,
-                Some(pool_topology) => match pool_topology {
-                    PoolTopology::Labelled(labelled_topology) => {
-                        // The labels in Volume Pool Topology should match the pool labels if
-                        // present, otherwise selection of any pool is allowed.
-                        if !labelled_topology.inclusion.is_empty() {
-                            volume_pool_topology_labels = labelled_topology.inclusion
-                        } else {
-                            return true;
-                        }
-                    }
-                },
-            },
         };
-        // We will reach this part of code only if the volume has pool topology labels.
-        return match request.registry().specs().get_pool(&item.pool.id) {
-            Ok(spec) => match spec.labels {
-                None => false,
-                Some(label) => volume_pool_topology_labels.keys().all(|k| {
-                    label.contains_key(k) && (volume_pool_topology_labels.get(k) == label.get(k))
-                }),
-            },
-            Err(_) => false,
+        let specs = request.registry().specs();
+        let pool_labels = specs
+            .get_pool(&item.pool.id)
+            .ok()
+            .and_then(|spec| spec.labels)
+            .unwrap_or_default();
+        if !labelled_topology.matches(&pool_labels) {
+            return false;
+        }
+
+        if labelled_topology.exclusion_keys().next().is_none() {
+            return true;
+        }
+        specs
+            .get_volume_replicas(&request.uuid)
+            .into_iter()
+            .map(|replica| replica.lock().clone())
+            .filter(|replica| {
+                !specs.replica_draining(replica) && !specs.replica_moving(&replica.uuid)
+            })
+            .filter_map(|replica| specs.get_pool(&replica.pool).ok())
+            .all(|pool| labelled_topology.apart(&pool.labels.unwrap_or_default(), &pool_labels))
+    }
+    /// Should not attempt to use pools which hold replicas of the volumes selected by the
+    /// volume anti-affinity, if any
+    pub(crate) fn anti_affinity(request: &GetSuitablePoolsContext, item: &PoolItem) -> bool {
+        let anti_affinity = match request
+            .topology
+            .as_ref()
+            .and_then(|t| t.anti_affinity.as_ref())
+        {
+            Some(anti_affinity) => anti_affinity,
+            None => return true,
         };
+        let specs = request.registry().specs();
+        specs
+            .get_replicas()
+            .into_iter()
+            .filter_map(|replica| {
+                let replica = replica.lock();
+                match replica.owners.volume() {
+                    Some(volume) if replica.pool == item.pool.id && volume != &request.uuid => {
+                        Some(volume.clone())
+                    }
+                    _ => None,
+                }
+            })
+            .all(|volume| match specs.get_volume(&volume) {
+                Ok(spec) => !anti_affinity.selects(&volume, spec.labels.as_ref()),
+                Err(_) => !anti_affinity.selects(&volume, None),
+            })
     }
 }
 
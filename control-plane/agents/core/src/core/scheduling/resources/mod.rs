@@ -33,7 +33,10 @@ impl PoolItemLister {
         }
         raw_nodes
     }
+    /// List the pools with a spec, without the replica snapshots which mayastor reports as
+    /// replicas of the pools
     pub(crate) async fn list(registry: &Registry) -> Vec<PoolItem> {
+        let snapshots = registry.specs().get_replica_snapshot_uuids();
         let pools = Self::nodes(registry)
             .await
             .iter()
@@ -41,7 +44,7 @@ impl PoolItemLister {
                 n.pool_wrappers()
                     .iter()
                     .filter(|p| registry.specs().get_pool(&p.id).is_ok())
-                    .map(|p| PoolItem::new(n.clone(), p.clone()))
+                    .map(|p| PoolItem::new(n.clone(), p.clone().without_snapshots(&snapshots)))
                     .collect::<Vec<_>>()
             })
             .collect();
@@ -91,16 +91,23 @@ impl AddVolumeReplica {
             // 1. if allowed_nodes were specified then only pools from those nodes
             // can be used.
             // 2. pools should have enough free space for the
-            // volume (do we need to take into account metadata?)
+            // volume (do we need to take into account metadata?), which thin provisioned
+            // volumes may overcommit
             // 3. ideally use only healthy(online) pools with degraded pools as a
             // fallback
             // 4. only one replica per node
+            // 5. cordoned nodes must not be used
+            // 6. only one replica per failure domain, if a topology spread is used
+            // 7. pools must match the labelled pool topology and volume anti-affinity
             .filter(NodeFilters::online)
+            .filter(NodeFilters::uncordoned)
             .filter(NodeFilters::allowed)
             .filter(NodeFilters::unused)
+            .filter(NodeFilters::spread)
             .filter(PoolFilters::usable)
             .filter(PoolFilters::free_space)
             .filter(PoolFilters::topology)
+            .filter(PoolFilters::anti_affinity)
             // sort pools in order of preference (from least to most number of replicas)
             .sort(PoolSorters::sort_by_replica_count)
     }
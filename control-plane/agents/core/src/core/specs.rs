@@ -1,9 +1,9 @@
 use crate::core::{registry::Registry, resource_map::ResourceMap};
 use common::errors::SvcError;
 use common_lib::{
-    mbus_api::ResourceKind,
+    mbus_api::{ErrorChain, ResourceKind},
     types::v0::{
-        message_bus::{NexusId, NodeId, PoolId, ReplicaId, VolumeId},
+        message_bus::{NexusId, NodeId, PoolId, ReplicaId, SnapshotId, VolumeId},
         openapi::apis::Uuid,
         store::{
             definitions::{
@@ -13,6 +13,8 @@ use common_lib::{
             node::NodeSpec,
             pool::PoolSpec,
             replica::ReplicaSpec,
+            schema::object_value,
+            snapshot::SnapshotSpec,
             volume::VolumeSpec,
             OperationGuard, OperationMode, OperationSequence, OperationSequencer, SpecStatus,
             SpecTransaction,
@@ -138,7 +140,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
     where
         Self: SpecTransaction<O>,
     {
-        match result {
+        let operation = locked_spec.lock().pending_op_name();
+        let result = match result {
             Ok(val) => {
                 let mut spec_clone = locked_spec.lock().clone();
                 spec_clone.commit_op();
@@ -160,7 +163,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
                 Self::delete_spec(registry, locked_spec).await.ok();
                 Err(error)
             }
-        }
+        };
+        Self::record_event(registry, locked_spec, operation, result).await
     }
 
     /// Validates the outcome of a create step.
@@ -178,8 +182,9 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
         match result {
             Ok(val) => Ok(val),
             Err(error) => {
+                let operation = locked_spec.lock().pending_op_name();
                 Self::delete_spec(registry, locked_spec).await.ok();
-                Err(error)
+                Self::record_event(registry, locked_spec, operation, Err(error)).await
             }
         }
     }
@@ -314,7 +319,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
         Self: StorableObject,
     {
         let key = locked_spec.lock().key();
-        match result {
+        let operation = locked_spec.lock().pending_op_name();
+        let result = match result {
             Ok(val) => {
                 let mut spec_clone = locked_spec.lock().clone();
                 spec_clone.commit_op();
@@ -349,7 +355,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
                     }
                 }
             }
-        }
+        };
+        Self::record_event(registry, locked_spec, operation, result).await
     }
 
     /// Start an update operation and attempt to log the transaction to the store.
@@ -422,7 +429,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
         Self: SpecTransaction<O>,
         Self: StorableObject,
     {
-        match result {
+        let operation = spec_clone.pending_op_name();
+        let result = match result {
             Ok(val) => {
                 spec_clone.commit_op();
                 let stored = registry.store_obj(&spec_clone).await;
@@ -453,7 +461,8 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
                     }
                 }
             }
-        }
+        };
+        Self::record_event(registry, &locked_spec, operation, result).await
     }
 
     /// Validates the outcome of an intermediate step, part of a transaction operation.
@@ -473,24 +482,50 @@ pub trait SpecOperations: Clone + Debug + Sized + StorableObject + OperationSequ
         match result {
             Ok(val) => Ok(val),
             Err(error) => {
+                let operation = spec_clone.pending_op_name();
                 let mut spec_clone = spec_clone.clone();
                 spec_clone.clear_op();
                 let stored = registry.store_obj(&spec_clone).await;
-                let mut spec = locked_spec.lock();
-                match stored {
-                    Ok(_) => {
-                        spec.clear_op();
-                        Err(error)
+                let result = {
+                    let mut spec = locked_spec.lock();
+                    match stored {
+                        Ok(_) => {
+                            spec.clear_op();
+                            Err(error)
+                        }
+                        Err(error) => {
+                            spec.set_op_result(false);
+                            Err(error)
+                        }
                     }
-                    Err(error) => {
-                        spec.set_op_result(false);
-                        Err(error)
-                    }
-                }
+                };
+                Self::record_event(registry, locked_spec, operation, result).await
             }
         }
     }
 
+    /// Record the outcome of the `operation` in the audit log and return the `result`.
+    /// Nothing is recorded if there is no operation.
+    async fn record_event<R: Send>(
+        registry: &Registry,
+        locked_spec: &Arc<Mutex<Self>>,
+        operation: Option<String>,
+        result: Result<R, SvcError>,
+    ) -> Result<R, SvcError> {
+        if let Some(operation) = operation {
+            let (kind, uuid) = {
+                let spec = locked_spec.lock();
+                (spec.kind(), spec.uuid())
+            };
+            let error = result.as_ref().err().map(|error| error.full_string());
+            registry
+                .events()
+                .record(registry, kind, uuid, operation, error)
+                .await;
+        }
+        result
+    }
+
     /// Check if the object is free to be modified or if it's still busy
     fn busy(&self) -> Result<(), SvcError> {
         if self.dirty() {
@@ -643,6 +678,7 @@ pub(crate) struct ResourceSpecs {
     pub(crate) nexuses: ResourceMap<NexusId, NexusSpec>,
     pub(crate) pools: ResourceMap<PoolId, PoolSpec>,
     pub(crate) replicas: ResourceMap<ReplicaId, ReplicaSpec>,
+    pub(crate) snapshots: ResourceMap<SnapshotId, SnapshotSpec>,
 }
 
 impl ResourceSpecsLocked {
@@ -658,6 +694,7 @@ impl ResourceSpecsLocked {
             StorableObjectType::NexusSpec,
             StorableObjectType::PoolSpec,
             StorableObjectType::ReplicaSpec,
+            StorableObjectType::SnapshotSpec,
         ];
         for spec in &spec_types {
             if let Err(e) = self.populate_specs(store, *spec).await {
@@ -705,7 +742,13 @@ impl ResourceSpecsLocked {
                 .map_err(|e| SpecError::StoreGet {
                     source: Box::new(e),
                 })?;
-        let store_values = store_entries.iter().map(|e| e.1.clone()).collect();
+        let store_values = store_entries
+            .into_iter()
+            .map(|(_, value)| object_value(spec_type, value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| SpecError::StoreGet {
+                source: Box::new(e),
+            })?;
 
         let mut resource_specs = self.0.write();
         match spec_type {
@@ -744,6 +787,13 @@ impl ResourceSpecsLocked {
                     })?;
                 resource_specs.replicas.populate(specs);
             }
+            StorableObjectType::SnapshotSpec => {
+                let specs =
+                    Self::deserialise_specs::<SnapshotSpec>(store_values).context(Deserialise {
+                        obj_type: StorableObjectType::SnapshotSpec,
+                    })?;
+                resource_specs.snapshots.populate(specs);
+            }
             _ => {
                 // Not all spec types are persisted in the store.
                 unimplemented!("{} not persisted in store", spec_type);
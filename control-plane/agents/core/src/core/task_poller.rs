@@ -1,5 +1,6 @@
-use crate::core::{registry::Registry, specs::ResourceSpecsLocked};
+use crate::core::{metrics, registry::Registry, specs::ResourceSpecsLocked};
 use common::errors::SvcError;
+use common_lib::types::v0::message_bus::EventSource;
 
 /// Poll Event that identifies why a poll is running
 #[derive(Debug, Clone)]
@@ -22,6 +23,10 @@ pub(crate) enum PollTriggerEvent {
     /// A volume has been published in a Degraded state
     /// eg: may need replicas to be carved and/or added
     VolumeDegraded,
+    /// A node has been marked for draining
+    NodeDrain,
+    /// A pool has been put in maintenance
+    PoolMaintenance,
     /// The Agent is starting up
     Start,
 }
@@ -95,6 +100,13 @@ impl PollContext {
             registry: registry.clone(),
         }
     }
+    /// Get a context whose spec operations are recorded in the audit log on behalf of `source`
+    pub(crate) fn with_event_source(&self, source: EventSource) -> Self {
+        Self {
+            event: self.event.clone(),
+            registry: self.registry.with_event_source(source),
+        }
+    }
     /// Get a reference to the core registry
     pub(crate) fn registry(&self) -> &Registry {
         &self.registry
@@ -119,7 +131,12 @@ pub(crate) trait TaskPoller: Send + Sync + std::fmt::Debug {
     async fn try_poll(&mut self, context: &PollContext) -> PollResult {
         tracing::trace!("Entering trace call");
         let result = if self.poll_ready(context).await {
-            self.poll(context).await
+            let started = std::time::Instant::now();
+            let context =
+                context.with_event_source(EventSource::Reconciler(self.name().to_string()));
+            let result = self.poll(&context).await;
+            metrics::observe_poll(self.name(), &result, started.elapsed());
+            result
         } else {
             PollResult::Ok(PollerState::Idle)
         };
@@ -127,6 +144,12 @@ pub(crate) trait TaskPoller: Send + Sync + std::fmt::Debug {
         result
     }
 
+    /// Name of the poller, used to label its metrics
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
     /// Force poll the poller
     async fn poll(&mut self, context: &PollContext) -> PollResult;
 
@@ -1,14 +1,19 @@
 #![cfg(test)]
 
+use crate::core::migration::migrate_store;
 use common_lib::{
     mbus_api::Message,
-    store::etcd::Etcd,
+    store::{etcd::Etcd, memory::MemoryStore},
     types::v0::{
         message_bus::{self, ChannelVs, Liveness},
         openapi::models,
         store::{
-            definitions::Store,
-            registry::{ControlPlaneService, StoreLeaseOwner, StoreLeaseOwnerKey},
+            definitions::{key_prefix, StorableObject, StorableObjectType, Store},
+            registry::{
+                ControlPlaneService, CoreRegistryConfig, NodeRegistration, StoreLeaseOwner,
+                StoreLeaseOwnerKey,
+            },
+            schema::{VersionedObject, STORE_SCHEMA_VERSION},
         },
     },
 };
@@ -203,3 +208,114 @@ async fn core_agent_lease_lock() {
     tracing::info!("core: {:?}", core.state);
     assert_eq!(Some(false), core.state.unwrap().running);
 }
+
+/// Test that the operations performed on the resource specs are recorded in the audit log, and
+/// that the log carries on from where it left off when the core agent restarts.
+#[tokio::test]
+async fn audit_log_events() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(true)
+        .with_pools(1)
+        .with_agents(vec!["core"])
+        .build()
+        .await
+        .unwrap();
+
+    let client = cluster.rest_v00();
+    let volume_id = message_bus::VolumeId::new();
+    client
+        .volumes_api()
+        .put_volume(
+            &volume_id,
+            models::CreateVolumeBody::new(models::VolumePolicy::default(), 1, 5242880u64),
+        )
+        .await
+        .unwrap();
+    client.volumes_api().del_volume(&volume_id).await.unwrap();
+
+    let events = client
+        .events_api()
+        .get_events(Some(volume_id.as_str()))
+        .await
+        .unwrap();
+    let summary = events
+        .iter()
+        .map(|e| (e.operation.as_str(), &e.result, e.source.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "Create",
+                &models::EventResult::Succeeded,
+                "request/createVolume"
+            ),
+            (
+                "Destroy",
+                &models::EventResult::Succeeded,
+                "request/destroyVolume"
+            ),
+        ]
+    );
+    assert!(events
+        .iter()
+        .all(|e| e.kind == "Volume" && e.error.is_none()));
+
+    let all_events = client.events_api().get_events(None).await.unwrap();
+    assert!(
+        all_events.len() > events.len(),
+        "the pool create is recorded"
+    );
+    let last_id = all_events.last().unwrap().id;
+
+    cluster.restart_core().await;
+    Liveness {}.request_on(ChannelVs::Core).await.unwrap();
+
+    let volume_id = message_bus::VolumeId::new();
+    client
+        .volumes_api()
+        .put_volume(
+            &volume_id,
+            models::CreateVolumeBody::new(models::VolumePolicy::default(), 1, 5242880u64),
+        )
+        .await
+        .unwrap();
+    let events = client
+        .events_api()
+        .get_events(Some(volume_id.as_str()))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].id > last_id);
+}
+
+/// Test that the objects written before the store was versioned are migrated, unless it's a
+/// dry-run, and that the objects which are not versioned are left untouched.
+#[tokio::test]
+async fn store_schema_migration() {
+    let mut store = MemoryStore::new();
+    let mut config = CoreRegistryConfig::new(NodeRegistration::Automatic);
+    let object = serde_json::json!({ "id": 1, "kind": "Volume" });
+    let event_key = format!("{}/1", key_prefix(StorableObjectType::Event));
+    let nexus_info_key = format!("{}/1", key_prefix(StorableObjectType::NexusInfo));
+    store.put_kv(&event_key, &object).await.unwrap();
+    store.put_kv(&nexus_info_key, &object).await.unwrap();
+
+    let migrated = migrate_store(&mut store, &mut config, true).await.unwrap();
+    assert_eq!(migrated, 1);
+    assert_eq!(config.schema_version(), 0);
+    assert_eq!(store.get_kv(&event_key).await.unwrap(), object);
+
+    let migrated = migrate_store(&mut store, &mut config, false).await.unwrap();
+    assert_eq!(migrated, 1);
+    assert_eq!(config.schema_version(), STORE_SCHEMA_VERSION);
+    let event = VersionedObject::from_value(store.get_kv(&event_key).await.unwrap());
+    assert_eq!(event, VersionedObject::new(object.clone()));
+    assert_eq!(store.get_kv(&nexus_info_key).await.unwrap(), object);
+    let stored: CoreRegistryConfig = store.get_obj(&config.key()).await.unwrap();
+    assert_eq!(stored.schema_version(), STORE_SCHEMA_VERSION);
+
+    // the store is now up to date
+    let migrated = migrate_store(&mut store, &mut config, false).await.unwrap();
+    assert_eq!(migrated, 0);
+}
@@ -15,10 +15,11 @@ use common_lib::{
     mbus_api::{Message, MessageId, MessageIdTimeout, ResourceKind},
     types::v0::{
         message_bus::{
-            AddNexusChild, Child, CreateNexus, CreatePool, CreateReplica, DestroyNexus,
-            DestroyPool, DestroyReplica, MessageIdVs, Nexus, NexusId, NodeId, NodeState,
-            NodeStatus, PoolId, PoolState, PoolStatus, Protocol, RemoveNexusChild, Replica,
-            ReplicaId, ShareNexus, ShareReplica, UnshareNexus, UnshareReplica,
+            AddNexusChild, Child, CreateNexus, CreateNexusSnapshot, CreatePool, CreateReplica,
+            DestroyNexus, DestroyPool, DestroyReplica, DestroyReplicaSnapshot, MessageIdVs, Nexus,
+            NexusId, NodeId, NodeState, NodeStatus, PoolId, PoolState, PoolStatus, Protocol,
+            RemoveNexusChild, Replica, ReplicaId, ShareNexus, ShareReplica, UnshareNexus,
+            UnshareReplica,
         },
         store,
         store::{nexus::NexusState, replica::ReplicaState},
@@ -30,6 +31,7 @@ use rpc::mayastor::Null;
 use snafu::ResultExt;
 use std::{
     cmp::Ordering,
+    collections::HashSet,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -59,6 +61,8 @@ pub(crate) struct NodeWrapper {
     comms_timeouts: NodeCommsTimeout,
     /// runtime state information
     states: ResourceStatesLocked,
+    /// when the node was last seen going from online to not online, cleared once it's online
+    offline_since: Option<std::time::Instant>,
 }
 
 impl NodeWrapper {
@@ -76,6 +80,7 @@ impl NodeWrapper {
             lock: Default::default(),
             comms_timeouts,
             states: ResourceStatesLocked::new(),
+            offline_since: None,
         }
     }
 
@@ -214,6 +219,11 @@ impl NodeWrapper {
             }
 
             self.node_state.status = next;
+            if self.node_state.status == NodeStatus::Online {
+                self.offline_since = None;
+            } else if self.offline_since.is_none() {
+                self.offline_since = Some(std::time::Instant::now());
+            }
             if self.node_state.status == NodeStatus::Unknown {
                 self.watchdog_mut().disarm()
             }
@@ -227,6 +237,11 @@ impl NodeWrapper {
         previous
     }
 
+    /// How long the node has not been online for, if it's not online
+    pub(crate) fn offline_for(&self) -> Option<std::time::Duration> {
+        self.offline_since.map(|since| since.elapsed())
+    }
+
     /// Clear all states from the node
     fn clear_states(&mut self) {
         self.resources_mut().clear_all();
@@ -564,6 +579,20 @@ pub(crate) trait ClientOps {
     async fn add_child(&self, request: &AddNexusChild) -> Result<Child, SvcError>;
     /// Remove a child from its parent nexus via gRPC
     async fn remove_child(&self, request: &RemoveNexusChild) -> Result<(), SvcError>;
+
+    /// Snapshot a nexus (and therefore all of its healthy children) on the node via gRPC
+    async fn create_nexus_snapshot(
+        &self,
+        request: &CreateNexusSnapshot,
+    ) -> Result<String, SvcError>;
+    /// Fetch the replicas of a pool from the node via gRPC, refreshing the cached replica states
+    /// Mayastor reports the replica snapshots as replicas of their pool as well
+    async fn fetch_pool_replicas(&self, pool: &PoolId) -> Result<Vec<Replica>, SvcError>;
+    /// Destroy a replica snapshot on the node via gRPC
+    async fn destroy_replica_snapshot(
+        &self,
+        request: &DestroyReplicaSnapshot,
+    ) -> Result<(), SvcError>;
 }
 
 /// Internal Operations on a mayastor locked `NodeWrapper` for the implementor
@@ -952,6 +981,80 @@ impl ClientOps for Arc<tokio::sync::RwLock<NodeWrapper>> {
             request: "remove_child_nexus",
         })
     }
+
+    /// Snapshot a nexus on the node via gRPC
+    async fn create_nexus_snapshot(
+        &self,
+        request: &CreateNexusSnapshot,
+    ) -> Result<String, SvcError> {
+        let mut ctx = self
+            .grpc_client_locked(MessageIdVs::CreateVolumeSnapshot)
+            .await?;
+        let snapshot = ctx
+            .mayastor
+            .create_snapshot(request.to_rpc())
+            .await
+            .context(GrpcRequestError {
+                resource: ResourceKind::Snapshot,
+                request: "create_snapshot",
+            })?;
+        let mut ctx = ctx.reconnect(GETS_TIMEOUT).await?;
+        self.update_replica_states(ctx.deref_mut()).await?;
+        self.update_pool_states(ctx.deref_mut()).await?;
+        Ok(snapshot.into_inner().name)
+    }
+
+    /// Fetch the replicas of a pool from the node via gRPC, refreshing the cached replica states
+    async fn fetch_pool_replicas(&self, pool: &PoolId) -> Result<Vec<Replica>, SvcError> {
+        let ctx = self.read().await.grpc_context_ext(GETS_TIMEOUT)?;
+        let mut client = ctx.connect().await?;
+        let node = self.read().await;
+        let replicas = node.fetch_replicas(&mut client).await?;
+        node.resources_mut().update_replicas(replicas.clone());
+        Ok(replicas.into_iter().filter(|r| &r.pool == pool).collect())
+    }
+
+    /// Destroy a replica snapshot on the node via gRPC
+    /// Mayastor has no api to destroy snapshots, which it reports as replicas, so the snapshot
+    /// lvol is destroyed as a replica once it's confirmed to be the recorded snapshot
+    async fn destroy_replica_snapshot(
+        &self,
+        request: &DestroyReplicaSnapshot,
+    ) -> Result<(), SvcError> {
+        let replicas = self.fetch_pool_replicas(&request.pool).await?;
+        match replicas.iter().find(|r| r.name.as_str() == request.name) {
+            Some(lvol) if lvol.uuid == request.uuid => {}
+            Some(lvol) => {
+                return Err(SvcError::Internal {
+                    details: format!(
+                        "Lvol '{}' of pool '{}' has uuid '{}' instead of the snapshot uuid '{}'",
+                        request.name, request.pool, lvol.uuid, request.uuid
+                    ),
+                })
+            }
+            None => {
+                return Err(SvcError::NotFound {
+                    kind: ResourceKind::Snapshot,
+                    id: request.name.clone(),
+                })
+            }
+        }
+        let mut ctx = self
+            .grpc_client_locked(MessageIdVs::DestroyVolumeSnapshot)
+            .await?;
+        let _ = ctx
+            .mayastor
+            .destroy_replica(request.to_rpc())
+            .await
+            .context(GrpcRequestError {
+                resource: ResourceKind::Snapshot,
+                request: "destroy_replica_snapshot",
+            })?;
+        let mut ctx = ctx.reconnect(GETS_TIMEOUT).await?;
+        self.update_replica_states(ctx.deref_mut()).await?;
+        self.update_pool_states(ctx.deref_mut()).await?;
+        Ok(())
+    }
 }
 
 /// convert rpc pool to a message bus pool
@@ -1035,6 +1138,36 @@ impl PoolWrapper {
         }
     }
 
+    /// Remove the replica `snapshots` from the replicas of the pool
+    /// Mayastor reports the snapshots of the replicas as replicas of their pool as well, but they
+    /// don't count against the capacity allocated to the replicas
+    #[must_use]
+    pub fn without_snapshots(mut self, snapshots: &HashSet<ReplicaId>) -> Self {
+        self.replicas
+            .retain(|replica| !snapshots.contains(&replica.uuid));
+        self
+    }
+
+    /// Get the capacity allocated to the replicas of the pool, whether thin provisioned or not
+    pub fn allocated(&self) -> u64 {
+        self.replicas.iter().map(|replica| replica.size).sum()
+    }
+
+    /// Get the capacity which can still be allocated to a replica, given the pool `overcommit`
+    /// as a percentage of its capacity
+    /// Thick provisioned replicas are also limited by the free space as they reserve their whole
+    /// capacity upfront
+    pub fn allocatable(&self, thin: bool, overcommit: u32) -> u64 {
+        let limit = self.state.capacity as u128 * overcommit as u128 / 100;
+        let limit = limit.min(u64::MAX as u128) as u64;
+        let allocatable = limit.saturating_sub(self.allocated());
+        if thin {
+            allocatable
+        } else {
+            allocatable.min(self.free_space())
+        }
+    }
+
     /// Set pool state as unknown
     pub fn set_unknown(&mut self) {
         self.state.status = PoolStatus::Unknown;
@@ -1122,3 +1255,34 @@ impl Ord for PoolWrapper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_allocated() {
+        let replica = |size: u64| Replica {
+            uuid: ReplicaId::new(),
+            size,
+            ..Default::default()
+        };
+        let pool = PoolState {
+            capacity: 100,
+            used: 50,
+            ..Default::default()
+        };
+        let replicas = vec![replica(40), replica(60), replica(60)];
+        let snapshot = replicas[2].uuid.clone();
+        let pool = PoolWrapper::new(&pool, &replicas);
+        assert_eq!(pool.allocated(), 160);
+        assert_eq!(pool.allocatable(true, 200), 40);
+
+        // the snapshots of the replicas are not allocated to the replicas
+        let pool = pool.without_snapshots(&vec![snapshot].into_iter().collect());
+        assert_eq!(pool.replicas().len(), 2);
+        assert_eq!(pool.allocated(), 100);
+        assert_eq!(pool.allocatable(true, 200), 100);
+        assert_eq!(pool.allocatable(false, 200), 50);
+    }
+}
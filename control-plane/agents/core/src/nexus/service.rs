@@ -4,8 +4,8 @@ use common_lib::{
     mbus_api::message_bus::v0::Nexuses,
     types::v0::{
         message_bus::{
-            AddNexusChild, Child, CreateNexus, DestroyNexus, Filter, GetNexuses, Nexus,
-            RemoveNexusChild, ShareNexus, UnshareNexus,
+            AddNexusChild, Child, CreateNexus, DestroyNexus, EventSource, Filter, GetNexuses,
+            Nexus, RemoveNexusChild, ShareNexus, UnshareNexus,
         },
         store::OperationMode,
     },
@@ -20,6 +20,10 @@ impl Service {
     pub(super) fn new(registry: Registry) -> Self {
         Self { registry }
     }
+    /// Get a handle of the service whose operations are performed on behalf of the `source`
+    pub(super) fn with_event_source(&self, source: EventSource) -> Self {
+        Self::new(self.registry.with_event_source(source))
+    }
     fn specs(&self) -> &ResourceSpecsLocked {
         self.registry.specs()
     }
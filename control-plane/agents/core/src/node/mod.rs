@@ -13,7 +13,8 @@ use common_lib::mbus_api::{v0::*, *};
 
 use async_trait::async_trait;
 use common_lib::types::v0::message_bus::{
-    ChannelVs, Deregister, GetBlockDevices, GetNodes, GetSpecs, GetStates, Register,
+    ChannelVs, CordonNode, Deregister, DrainNode, GetBlockDevices, GetEvents, GetNodes, GetSpecs,
+    GetStates, LabelNode, Register, UncordonNode, UnlabelNode,
 };
 use std::{convert::TryInto, marker::PhantomData};
 
@@ -26,9 +27,15 @@ pub(crate) async fn configure(builder: Service) -> Service {
         .with_subscription(handler_publish!(Deregister))
         .with_subscription(handler!(GetSpecs))
         .with_subscription(handler!(GetStates))
+        .with_subscription(handler!(GetEvents))
         .with_channel(ChannelVs::Node)
         .with_subscription(handler!(GetNodes))
         .with_subscription(handler!(GetBlockDevices))
+        .with_subscription(handler!(CordonNode))
+        .with_subscription(handler!(UncordonNode))
+        .with_subscription(handler!(DrainNode))
+        .with_subscription(handler!(LabelNode))
+        .with_subscription(handler!(UnlabelNode))
         .with_default_liveness()
 }
 
@@ -56,8 +63,12 @@ async fn create_node_service(builder: &Service) -> service::Service {
 mod tests {
     use super::*;
     use common_lib::types::v0::{
-        message_bus::{Liveness, Node, NodeId, NodeState, NodeStatus},
-        store::node::{NodeLabels, NodeSpec},
+        message_bus::{
+            CreateVolume, DestroyVolume, ExplicitNodeTopology, GetVolumes, Liveness,
+            NexusNvmfConfig, Node, NodeId, NodeState, NodeStatus, NodeTopology, PublishVolume,
+            SpreadTopology, Topology, UnpublishVolume, VolumeId, VolumeShareProtocol,
+        },
+        store::node::{NodeDrainState, NodeLabels, NodeSpec},
     };
     use std::time::Duration;
     use testlib::ClusterBuilder;
@@ -149,4 +160,311 @@ mod tests {
             &Node::new(maya_name.clone(), node.spec().cloned(), None)
         );
     }
+
+    #[tokio::test]
+    async fn node_cordon() {
+        let cluster = ClusterBuilder::builder()
+            .with_rest(false)
+            .with_agents(vec!["core"])
+            .with_pools(1)
+            .build()
+            .await
+            .unwrap();
+        let maya_name = cluster.node(0);
+
+        let node = CordonNode::new(&maya_name).request().await.unwrap();
+        assert!(node.spec().unwrap().cordoned());
+
+        let volume = CreateVolume {
+            uuid: "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+            size: 5242880,
+            replicas: 1,
+            ..Default::default()
+        };
+        volume
+            .request()
+            .await
+            .expect_err("Replicas cannot be placed on a cordoned node");
+
+        let node = UncordonNode::new(&maya_name).request().await.unwrap();
+        assert!(!node.spec().unwrap().cordoned());
+
+        let volume = volume.request().await.unwrap();
+        DestroyVolume {
+            uuid: volume.spec().uuid,
+        }
+        .request()
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn node_topology_spread() {
+        let cluster = ClusterBuilder::builder()
+            .with_rest(false)
+            .with_agents(vec!["core"])
+            .with_mayastors(3)
+            .with_pools(1)
+            .build()
+            .await
+            .unwrap();
+
+        LabelNode::new(&cluster.node(0), "zone", "a")
+            .request()
+            .await
+            .unwrap();
+        LabelNode::new(&cluster.node(1), "zone", "a")
+            .request()
+            .await
+            .unwrap();
+        let node = LabelNode::new(&cluster.node(2), "zone", "b")
+            .request()
+            .await
+            .unwrap();
+        assert_eq!(node.spec().unwrap().label("zone"), Some(&"b".to_string()));
+
+        let topology = Topology {
+            node: None,
+            pool: None,
+            spread: Some(SpreadTopology {
+                labels: vec!["zone".to_string()],
+            }),
+            anti_affinity: None,
+        };
+        let volume = CreateVolume {
+            uuid: "2e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+            size: 5242880,
+            replicas: 3,
+            topology: Some(topology.clone()),
+            ..Default::default()
+        };
+        volume
+            .request()
+            .await
+            .expect_err("Only 2 zones are available for the 3 replicas");
+
+        let volume = CreateVolume {
+            replicas: 2,
+            ..volume
+        }
+        .request()
+        .await
+        .unwrap();
+        let nodes = volume
+            .state()
+            .replica_topology
+            .values()
+            .filter_map(|r| r.node().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.contains(&cluster.node(2)));
+
+        DestroyVolume {
+            uuid: volume.spec().uuid,
+        }
+        .request()
+        .await
+        .unwrap();
+
+        let node = UnlabelNode::new(&cluster.node(2), "zone")
+            .request()
+            .await
+            .unwrap();
+        assert_eq!(node.spec().unwrap().label("zone"), None);
+    }
+
+    /// Wait until the spec of the `node` satisfies the given `condition`
+    async fn wait_for_node_spec(node: &NodeId, condition: impl Fn(&NodeSpec) -> bool) {
+        let start = std::time::Instant::now();
+        loop {
+            let nodes = GetNodes::default().request().await.unwrap();
+            let spec = nodes.0.iter().find(|n| n.id() == node).unwrap().spec();
+            if spec.map(|spec| condition(&spec)).unwrap_or_default() {
+                return;
+            }
+            if start.elapsed() > Duration::from_secs(30) {
+                panic!("Timeout waiting for the spec of node '{}'", node);
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Given a node with the shared target and the replica of a published volume, the unshared
+    /// target of another published volume and the replica of an unpublished volume
+    /// When the node is drained
+    /// Then the unshared target and the replicas are moved to the other node
+    /// And the shared target, which may be in use, is left on the node which remains draining
+    /// And the node is drained once the volume with the shared target is unpublished
+    #[tokio::test]
+    async fn node_drain() {
+        let cluster = ClusterBuilder::builder()
+            .with_rest(false)
+            .with_agents(vec!["core"])
+            .with_mayastors(2)
+            .with_pools(1)
+            .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+            .build()
+            .await
+            .unwrap();
+        let drained = cluster.node(0);
+        let other = cluster.node(1);
+
+        // place the replicas of the volumes on the node to be drained
+        CordonNode::new(&other).request().await.unwrap();
+        let shared: VolumeId = "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+        let unshared: VolumeId = "3e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+        let unpublished: VolumeId = "2e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+        for uuid in [&shared, &unshared, &unpublished] {
+            CreateVolume {
+                uuid: uuid.clone(),
+                size: 5242880,
+                replicas: 1,
+                ..Default::default()
+            }
+            .request()
+            .await
+            .unwrap();
+        }
+        for (uuid, share) in [
+            (&shared, Some(VolumeShareProtocol::Nvmf)),
+            (&unshared, None),
+        ] {
+            PublishVolume::new(uuid.clone(), Some(drained.clone()), share)
+                .request()
+                .await
+                .unwrap();
+        }
+        UncordonNode::new(&other).request().await.unwrap();
+
+        let node = DrainNode::new(&drained).request().await.unwrap();
+        assert!(node.spec().unwrap().cordoned());
+        let drained_pool = cluster.pool(0, 0);
+        wait_for_node_spec(&drained, |spec| spec.drain_blockers() == [shared.clone()]).await;
+        let start = std::time::Instant::now();
+        loop {
+            let specs = GetSpecs {}.request().await.unwrap();
+            if specs.replicas.iter().all(|r| r.pool != drained_pool) {
+                break;
+            }
+            if start.elapsed() > Duration::from_secs(30) {
+                panic!("Timeout waiting for the replicas to be moved off the node");
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
+        let volumes = GetVolumes::default().request().await.unwrap().entries;
+        let volume = |uuid: &VolumeId| volumes.iter().find(|v| v.uuid() == uuid).unwrap().spec();
+        let target = volume(&shared).target.expect("Should still be published");
+        assert_eq!(
+            target.node(),
+            &drained,
+            "The target in use should not be moved"
+        );
+        let target = volume(&unshared).target.expect("Should still be published");
+        assert_eq!(target.node(), &other);
+        assert_eq!(target.protocol(), None);
+        assert!(volume(&unpublished).target.is_none());
+        let nodes = GetNodes::default().request().await.unwrap();
+        let node = nodes.0.iter().find(|n| n.id() == &drained).unwrap();
+        assert_eq!(
+            node.spec().unwrap().drain_state(),
+            Some(NodeDrainState::Draining)
+        );
+
+        UnpublishVolume::new(&shared, false)
+            .request()
+            .await
+            .unwrap();
+        wait_for_node_spec(&drained, |spec| {
+            spec.drain_state() == Some(NodeDrainState::Drained) && spec.drain_blockers().is_empty()
+        })
+        .await;
+
+        let specs = GetSpecs {}.request().await.unwrap();
+        assert!(specs.nexuses.iter().all(|n| n.node != drained));
+        for uuid in [&shared, &unshared, &unpublished] {
+            assert_eq!(
+                specs
+                    .replicas
+                    .iter()
+                    .filter(|r| r.owners.owned_by(uuid))
+                    .count(),
+                1
+            );
+        }
+    }
+
+    /// Given a volume published through 2 paths, the further path being on the node
+    /// When the node is drained
+    /// Then the path is moved to another node, while the volume target is left where it is
+    #[tokio::test]
+    async fn node_drain_multipath() {
+        let cluster = ClusterBuilder::builder()
+            .with_rest(false)
+            .with_agents(vec!["core"])
+            .with_mayastors(3)
+            .with_pools(1)
+            .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+            .build()
+            .await
+            .unwrap();
+        let target_node = cluster.node(0);
+
+        let uuid: VolumeId = "4e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+        CreateVolume {
+            uuid: uuid.clone(),
+            size: 5242880,
+            replicas: 1,
+            // keep the replica off the drained node, as the replicas of a multipath volume
+            // cannot be moved
+            topology: Some(Topology {
+                node: Some(NodeTopology::Explicit(ExplicitNodeTopology {
+                    allowed_nodes: vec![target_node.clone()],
+                    preferred_nodes: vec![],
+                })),
+                pool: None,
+                spread: None,
+                anti_affinity: None,
+            }),
+            ..Default::default()
+        }
+        .request()
+        .await
+        .unwrap();
+        let volume = PublishVolume::new(
+            uuid.clone(),
+            Some(target_node.clone()),
+            Some(VolumeShareProtocol::Nvmf),
+        )
+        .with_num_paths(Some(2))
+        .request()
+        .await
+        .unwrap();
+        let path = volume.spec().paths[0].clone();
+
+        DrainNode::new(path.node()).request().await.unwrap();
+        wait_for_node_spec(path.node(), |spec| {
+            spec.drain_state() == Some(NodeDrainState::Drained)
+        })
+        .await;
+
+        let volumes = GetVolumes::default().request().await.unwrap().entries;
+        let spec = volumes.iter().find(|v| v.uuid() == &uuid).unwrap().spec();
+        assert_eq!(spec.num_paths(), 2);
+        assert_eq!(spec.target.unwrap().node(), &target_node);
+        assert_ne!(spec.paths[0].node(), path.node());
+        assert_ne!(spec.paths[0].node(), &target_node);
+        assert_ne!(spec.paths[0].nexus(), path.nexus());
+        assert_eq!(spec.paths[0].protocol(), Some(&VolumeShareProtocol::Nvmf));
+
+        let specs = GetSpecs {}.request().await.unwrap();
+        let nexus = specs
+            .nexuses
+            .iter()
+            .find(|n| &n.uuid == spec.paths[0].nexus())
+            .expect("The moved path should have a nexus");
+        // the new path keeps the nvmf configuration of the moved path
+        assert_eq!(nexus.config, Some(NexusNvmfConfig::multipath(1, 2)));
+        assert!(specs.nexuses.iter().all(|n| &n.node != path.node()));
+    }
 }
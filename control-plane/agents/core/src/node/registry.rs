@@ -43,6 +43,17 @@ impl Registry {
         }
     }
 
+    /// How long the node has not been online for, if it's not online.
+    /// The time a node goes offline is only kept in memory, so a node with a spec which has not
+    /// registered since the core agent started is considered offline since the startup.
+    pub(crate) async fn node_offline_for(&self, node_id: &NodeId) -> Option<std::time::Duration> {
+        match self.get_node_wrapper(node_id).await {
+            Ok(node) => node.read().await.offline_for(),
+            Err(SvcError::NodeNotOnline { .. }) => Some(self.uptime()),
+            Err(_) => None,
+        }
+    }
+
     /// Get node state by its `NodeId`
     pub(crate) async fn get_node_state(&self, node_id: &NodeId) -> Result<NodeState, SvcError> {
         match self.nodes().read().await.get(node_id).cloned() {
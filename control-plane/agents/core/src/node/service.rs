@@ -8,7 +8,8 @@ use common::{
     v0::msg_translation::RpcToMessageBus,
 };
 use common_lib::types::v0::message_bus::{
-    Filter, GetSpecs, Node, NodeId, NodeState, NodeStatus, Specs, States,
+    CordonNode, DrainNode, EventSource, Filter, GetEvents, GetSpecs, LabelNode, Node, NodeId,
+    NodeState, NodeStatus, Specs, States, UncordonNode, UnlabelNode,
 };
 
 use crate::core::wrapper::InternalOps;
@@ -66,6 +67,13 @@ impl Service {
             comms_timeouts: NodeCommsTimeout::new(connect, request),
         }
     }
+    /// Get a handle of the service whose operations are performed on behalf of the `source`
+    pub(super) fn with_event_source(&self, source: EventSource) -> Self {
+        Self {
+            registry: self.registry.with_event_source(source),
+            ..self.clone()
+        }
+    }
     fn specs(&self) -> &ResourceSpecsLocked {
         self.registry.specs()
     }
@@ -183,6 +191,55 @@ impl Service {
         }
     }
 
+    /// Cordon a node
+    pub(crate) async fn cordon_node(&self, request: &CordonNode) -> Result<Node, SvcError> {
+        self.specs()
+            .cordon_node(&self.registry, &request.id)
+            .await?;
+        self.get_node(&request.id).await
+    }
+
+    /// Uncordon a node
+    pub(crate) async fn uncordon_node(&self, request: &UncordonNode) -> Result<Node, SvcError> {
+        self.specs()
+            .uncordon_node(&self.registry, &request.id)
+            .await?;
+        self.get_node(&request.id).await
+    }
+
+    /// Drain a node
+    /// The node is cordoned straight away while its volume targets and replicas are moved off
+    /// it in the background
+    pub(crate) async fn drain_node(&self, request: &DrainNode) -> Result<Node, SvcError> {
+        self.specs().drain_node(&self.registry, &request.id).await?;
+        self.registry.notify(PollTriggerEvent::NodeDrain).await;
+        self.get_node(&request.id).await
+    }
+
+    /// Add or overwrite a node label
+    pub(crate) async fn label_node(&self, request: &LabelNode) -> Result<Node, SvcError> {
+        self.specs()
+            .label_node(&self.registry, &request.id, &request.key, &request.value)
+            .await?;
+        self.get_node(&request.id).await
+    }
+
+    /// Remove a node label
+    pub(crate) async fn unlabel_node(&self, request: &UnlabelNode) -> Result<Node, SvcError> {
+        self.specs()
+            .unlabel_node(&self.registry, &request.id, &request.key)
+            .await?;
+        self.get_node(&request.id).await
+    }
+
+    /// Get a node by its `NodeId`
+    async fn get_node(&self, node_id: &NodeId) -> Result<Node, SvcError> {
+        let nodes = self.get_nodes(&GetNodes::from(node_id.clone())).await?;
+        nodes.0.into_iter().next().ok_or(SvcError::NodeNotFound {
+            node_id: node_id.to_owned(),
+        })
+    }
+
     /// Get block devices from a node
     pub(crate) async fn get_block_devices(
         &self,
@@ -245,4 +302,10 @@ impl Service {
             replicas,
         })
     }
+
+    /// Get the audit log events, optionally only those of the requested resource
+    pub(crate) async fn get_events(&self, request: &GetEvents) -> Result<Events, SvcError> {
+        let events = self.registry.events().get(request.resource.as_deref());
+        Ok(Events(events))
+    }
 }
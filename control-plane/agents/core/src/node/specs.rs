@@ -1,8 +1,11 @@
 use crate::core::{registry::Registry, specs::ResourceSpecsLocked};
 use common::errors::{NodeNotFound, SvcError};
 use common_lib::types::v0::{
-    message_bus::{NodeId, Register},
-    store::node::{NodeLabels, NodeSpec},
+    message_bus::{NodeId, Register, VolumeId},
+    store::{
+        node::{NodeLabels, NodeSpec},
+        replica::ReplicaSpec,
+    },
 };
 use parking_lot::Mutex;
 use snafu::OptionExt;
@@ -42,6 +45,153 @@ impl ResourceSpecsLocked {
         Ok(node)
     }
 
+    /// Cordon the node, so that no new replicas or targets are placed on it
+    pub(crate) async fn cordon_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, NodeSpec::cordon).await
+    }
+
+    /// Uncordon the node, cancelling any drain in progress
+    pub(crate) async fn uncordon_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, NodeSpec::uncordon)
+            .await
+    }
+
+    /// Cordon the node and mark it for draining
+    /// The volume targets and replicas are moved off the node by the drain reconciler
+    pub(crate) async fn drain_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, NodeSpec::drain).await
+    }
+
+    /// Add or overwrite the node label `key` with `value`
+    pub(crate) async fn label_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+        key: &str,
+        value: &str,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, |node| node.set_label(key, value))
+            .await
+    }
+
+    /// Remove the node label `key`
+    pub(crate) async fn unlabel_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+        key: &str,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, |node| node.remove_label(key))
+            .await
+    }
+
+    /// Mark the drain of the node as complete
+    pub(crate) async fn set_node_drained(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+    ) -> Result<NodeSpec, SvcError> {
+        self.update_node(registry, node_id, NodeSpec::set_drained)
+            .await
+    }
+
+    /// Record the `volumes` which block the drain of the node, if they changed
+    pub(crate) async fn set_node_drain_blockers(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+        volumes: &[VolumeId],
+    ) -> Result<NodeSpec, SvcError> {
+        let node = self.get_node(node_id)?;
+        if node.drain_blockers() == volumes {
+            return Ok(node);
+        }
+        self.update_node(registry, node_id, |node| node.set_drain_blockers(volumes))
+            .await
+    }
+
+    /// Apply `update` to the node spec and persist it, leaving the spec untouched if the store
+    /// could not be updated
+    async fn update_node(
+        &self,
+        registry: &Registry,
+        node_id: &NodeId,
+        update: impl Fn(&mut NodeSpec),
+    ) -> Result<NodeSpec, SvcError> {
+        let node = self.get_locked_node(node_id)?;
+        let mut node_clone = node.lock().clone();
+        update(&mut node_clone);
+        registry.store_obj(&node_clone).await?;
+
+        let mut node = node.lock();
+        update(&mut node);
+        Ok(node.clone())
+    }
+
+    /// Check if the node is cordoned
+    pub(crate) fn node_cordoned(&self, node_id: &NodeId) -> bool {
+        self.get_node(node_id)
+            .map(|node| node.cordoned())
+            .unwrap_or_default()
+    }
+
+    /// Check if the node has all of the given topology spread `labels`
+    pub(crate) fn node_labelled(&self, node_id: &NodeId, labels: &[String]) -> bool {
+        match self.get_node(node_id) {
+            Ok(node) => labels.iter().all(|key| node.label(key).is_some()),
+            Err(_) => labels.is_empty(),
+        }
+    }
+
+    /// Check if the nodes are in different failure domains, ie: if they have different values
+    /// for all of the given topology spread `labels`
+    /// Nodes which do not have all of the labels are never deemed to be spread apart
+    pub(crate) fn nodes_spread_apart(&self, a: &NodeId, b: &NodeId, labels: &[String]) -> bool {
+        if labels.is_empty() {
+            return true;
+        }
+        match (self.get_node(a), self.get_node(b)) {
+            (Ok(a), Ok(b)) => labels.iter().all(|key| match (a.label(key), b.label(key)) {
+                (Some(a_value), Some(b_value)) => a_value != b_value,
+                _ => false,
+            }),
+            _ => false,
+        }
+    }
+
+    /// Check if the replica is hosted on a node which is being drained or on a pool which is in
+    /// maintenance
+    pub(crate) fn replica_draining(&self, replica: &ReplicaSpec) -> bool {
+        match self.get_pool(&replica.pool) {
+            Ok(pool) if pool.maintenance => true,
+            Ok(pool) => self
+                .get_node(&pool.node)
+                .map(|node| node.draining())
+                .unwrap_or_default(),
+            Err(_) => false,
+        }
+    }
+
+    /// Get the specs of the nodes which are being drained
+    pub(crate) fn get_draining_nodes(&self) -> Vec<NodeSpec> {
+        self.get_nodes()
+            .into_iter()
+            .filter(|node| node.draining())
+            .collect()
+    }
+
     /// Get node spec by its `NodeId`
     pub(crate) fn get_locked_node(
         &self,
@@ -1,21 +1,25 @@
-use crate::core::{registry::Registry, specs::ResourceSpecsLocked, wrapper::GetterOps};
+use crate::core::{
+    reconciler::PollTriggerEvent, registry::Registry, specs::ResourceSpecsLocked,
+    wrapper::GetterOps,
+};
 use common::errors::{PoolNotFound, ReplicaNotFound, SvcError};
 use common_lib::{
     mbus_api::{
         message_bus::v0::{Pools, Replicas},
-        ReplyError,
+        Message, ReplyError,
     },
     types::v0::{
         message_bus::{
-            CreatePool, CreateReplica, DestroyPool, DestroyReplica, Filter, GetPools, GetReplicas,
-            NodeId, Pool, PoolId, Replica, ShareReplica, UnshareReplica,
+            CreatePool, CreateReplica, DestroyPool, DestroyReplica, EventSource, Filter, GetPools,
+            GetReplicas, NodeId, Pool, PoolId, Replica, SetPoolMaintenance, ShareReplica,
+            UnshareReplica,
         },
         store::OperationMode,
     },
 };
 use grpc::{
     grpc_opts::Context,
-    pool::traits::{CreatePoolInfo, DestroyPoolInfo, PoolOperations},
+    pool::traits::{CreatePoolInfo, DestroyPoolInfo, PoolOperations, SetPoolMaintenanceInfo},
     replica::traits::{
         CreateReplicaInfo, DestroyReplicaInfo, ReplicaOperations, ShareReplicaInfo,
         UnshareReplicaInfo,
@@ -35,8 +39,8 @@ impl PoolOperations for Service {
         pool: &dyn CreatePoolInfo,
         _ctx: Option<Context>,
     ) -> Result<Pool, ReplyError> {
-        let req = pool.into();
-        let pool = self.create_pool(&req).await?;
+        let req: CreatePool = pool.into();
+        let pool = self.on_behalf_of(&req).create_pool(&req).await?;
         Ok(pool)
     }
 
@@ -45,11 +49,21 @@ impl PoolOperations for Service {
         pool: &dyn DestroyPoolInfo,
         _ctx: Option<Context>,
     ) -> Result<(), ReplyError> {
-        let req = pool.into();
-        self.destroy_pool(&req).await?;
+        let req: DestroyPool = pool.into();
+        self.on_behalf_of(&req).destroy_pool(&req).await?;
         Ok(())
     }
 
+    async fn set_maintenance(
+        &self,
+        pool: &dyn SetPoolMaintenanceInfo,
+        _ctx: Option<Context>,
+    ) -> Result<Pool, ReplyError> {
+        let req: SetPoolMaintenance = pool.into();
+        let pool = self.on_behalf_of(&req).set_pool_maintenance(&req).await?;
+        Ok(pool)
+    }
+
     async fn get(&self, filter: Filter, _ctx: Option<Context>) -> Result<Pools, ReplyError> {
         let req = GetPools { filter };
         let pools = self.get_pools(&req).await?;
@@ -64,8 +78,11 @@ impl ReplicaOperations for Service {
         req: &dyn CreateReplicaInfo,
         _ctx: Option<Context>,
     ) -> Result<Replica, ReplyError> {
-        let create_replica = req.into();
-        let replica = self.create_replica(&create_replica).await?;
+        let create_replica: CreateReplica = req.into();
+        let replica = self
+            .on_behalf_of(&create_replica)
+            .create_replica(&create_replica)
+            .await?;
         Ok(replica)
     }
 
@@ -80,8 +97,10 @@ impl ReplicaOperations for Service {
         req: &dyn DestroyReplicaInfo,
         _ctx: Option<Context>,
     ) -> Result<(), ReplyError> {
-        let destroy_replica = req.into();
-        self.destroy_replica(&destroy_replica).await?;
+        let destroy_replica: DestroyReplica = req.into();
+        self.on_behalf_of(&destroy_replica)
+            .destroy_replica(&destroy_replica)
+            .await?;
         Ok(())
     }
 
@@ -90,8 +109,11 @@ impl ReplicaOperations for Service {
         req: &dyn ShareReplicaInfo,
         _ctx: Option<Context>,
     ) -> Result<String, ReplyError> {
-        let share_replica = req.into();
-        let response = self.share_replica(&share_replica).await?;
+        let share_replica: ShareReplica = req.into();
+        let response = self
+            .on_behalf_of(&share_replica)
+            .share_replica(&share_replica)
+            .await?;
         Ok(response)
     }
 
@@ -100,8 +122,10 @@ impl ReplicaOperations for Service {
         req: &dyn UnshareReplicaInfo,
         _ctx: Option<Context>,
     ) -> Result<(), ReplyError> {
-        let unshare_replica = req.into();
-        self.unshare_replica(&unshare_replica).await?;
+        let unshare_replica: UnshareReplica = req.into();
+        self.on_behalf_of(&unshare_replica)
+            .unshare_replica(&unshare_replica)
+            .await?;
         Ok(())
     }
 }
@@ -110,6 +134,15 @@ impl Service {
     pub(super) fn new(registry: Registry) -> Self {
         Self { registry }
     }
+    /// Get a handle of the service whose operations are performed on behalf of the `source`
+    pub(super) fn with_event_source(&self, source: EventSource) -> Self {
+        Self::new(self.registry.with_event_source(source))
+    }
+    /// Get a handle of the service whose operations are performed on behalf of the gRPC
+    /// `request`
+    fn on_behalf_of(&self, request: &impl Message) -> Self {
+        self.with_event_source(EventSource::request(&request.id()))
+    }
     fn specs(&self) -> &ResourceSpecsLocked {
         self.registry.specs()
     }
@@ -241,6 +274,25 @@ impl Service {
             .await
     }
 
+    /// Start or stop the maintenance of a pool
+    /// The pool is no longer used for new replicas straight away while its replicas are moved
+    /// off it in the background
+    #[tracing::instrument(level = "info", skip(self), err, fields(pool.uuid = %request.id))]
+    pub(super) async fn set_pool_maintenance(
+        &self,
+        request: &SetPoolMaintenance,
+    ) -> Result<Pool, SvcError> {
+        self.specs()
+            .set_pool_maintenance(&self.registry, request, OperationMode::Exclusive)
+            .await?;
+        if request.maintenance {
+            self.registry
+                .notify(PollTriggerEvent::PoolMaintenance)
+                .await;
+        }
+        self.registry.get_pool(&request.id).await
+    }
+
     /// Create replica
     #[tracing::instrument(level = "info", skip(self), err)]
     pub(super) async fn create_replica(
@@ -10,12 +10,12 @@ use common_lib::{
     mbus_api::ResourceKind,
     types::v0::{
         message_bus::{
-            CreatePool, CreateReplica, DestroyPool, DestroyReplica, Pool, PoolId, PoolState,
-            PoolStatus, Replica, ReplicaId, ReplicaOwners, ReplicaStatus, ShareReplica,
-            UnshareReplica,
+            CreatePool, CreateReplica, DestroyPool, DestroyReplica, LabelledTopology, Pool, PoolId,
+            PoolState, PoolStatus, Replica, ReplicaId, ReplicaOwners, ReplicaStatus,
+            SetPoolMaintenance, ShareReplica, UnshareReplica,
         },
         store::{
-            pool::{PoolOperation, PoolSpec},
+            pool::{PoolOperation, PoolSpec, DEFAULT_POOL_OVERCOMMIT},
             replica::{ReplicaOperation, ReplicaSpec},
             OperationMode, SpecStatus, SpecTransaction,
         },
@@ -91,7 +91,7 @@ impl SpecOperations for ReplicaSpec {
         state: &Self::State,
         op: Self::UpdateOp,
     ) -> Result<(), SvcError> {
-        match op {
+        match &op {
             ReplicaOperation::Share(_) if self.share.shared() && state.share.shared() => {
                 Err(SvcError::AlreadyShared {
                     kind: self.kind(),
@@ -107,7 +107,12 @@ impl SpecOperations for ReplicaSpec {
                 })
             }
             ReplicaOperation::Unshare => Ok(()),
-            _ => unreachable!(),
+            ReplicaOperation::Create | ReplicaOperation::Destroy => Err(SvcError::Internal {
+                details: format!(
+                    "{:?} is not an update operation of replica '{}'",
+                    op, self.uuid
+                ),
+            }),
         }?;
         self.start_op(op);
         Ok(())
@@ -191,6 +196,10 @@ impl ResourceSpecsLocked {
         request: &CreatePool,
         mode: OperationMode,
     ) -> Result<Pool, SvcError> {
+        if matches!(request.overcommit, Some(overcommit) if overcommit < DEFAULT_POOL_OVERCOMMIT) {
+            // the pool capacity cannot be undercommitted
+            return Err(SvcError::InvalidArguments {});
+        }
         let node = registry.get_node_wrapper(&request.node).await?;
 
         let pool_spec = self.get_or_create_pool(request);
@@ -224,6 +233,31 @@ impl ResourceSpecsLocked {
         }
     }
 
+    /// Start or stop the maintenance of the pool
+    /// The replicas of a pool in maintenance are moved to other pools by the pool reconciler
+    pub(crate) async fn set_pool_maintenance(
+        &self,
+        registry: &Registry,
+        request: &SetPoolMaintenance,
+        mode: OperationMode,
+    ) -> Result<PoolSpec, SvcError> {
+        let pool_spec = self.get_locked_pool(&request.id).ok_or(PoolNotFound {
+            pool_id: request.id.clone(),
+        })?;
+        let _guard = pool_spec.operation_guard_wait(mode).await?;
+
+        let mut pool_clone = pool_spec.lock().clone();
+        if pool_clone.maintenance == request.maintenance {
+            return Ok(pool_clone);
+        }
+        pool_clone.maintenance = request.maintenance;
+        registry.store_obj(&pool_clone).await?;
+
+        let mut pool = pool_spec.lock();
+        pool.maintenance = request.maintenance;
+        Ok(pool.clone())
+    }
+
     pub(crate) async fn create_replica(
         &self,
         registry: &Registry,
@@ -392,6 +426,23 @@ impl ResourceSpecsLocked {
         let pools = self.get_locked_pools();
         pools.into_iter().map(|p| p.lock().clone()).collect()
     }
+    /// Check if the given pool `id` is in maintenance
+    pub(crate) fn pool_in_maintenance(&self, id: &PoolId) -> bool {
+        self.get_pool(id)
+            .map(|pool| pool.maintenance)
+            .unwrap_or_default()
+    }
+    /// Check if the pools `a` and `b` can be paired up as per the labelled pool `topology`, ie:
+    /// if they don't have the same value for any of its exclusive labels without a value
+    pub(crate) fn pools_apart(&self, a: &PoolId, b: &PoolId, topology: &LabelledTopology) -> bool {
+        let labels = |id: &PoolId| {
+            self.get_pool(id)
+                .ok()
+                .and_then(|pool| pool.labels)
+                .unwrap_or_default()
+        };
+        topology.apart(&labels(a), &labels(b))
+    }
     /// Check if the given pool `id` has any replicas
     fn pool_has_replicas(&self, id: &PoolId) -> bool {
         let specs = self.read();
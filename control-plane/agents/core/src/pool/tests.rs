@@ -2,12 +2,13 @@
 
 use super::*;
 use common_lib::{
-    mbus_api::{ReplyError, ReplyErrorKind, ResourceKind, TimeoutOptions},
+    mbus_api::{Message, ReplyError, ReplyErrorKind, ResourceKind, TimeoutOptions},
     types::v0::{
         message_bus::{
-            CreatePool, CreateReplica, DestroyPool, DestroyReplica, Filter, GetNodes, GetSpecs,
-            Protocol, Replica, ReplicaId, ReplicaName, ReplicaShareProtocol, ReplicaStatus,
-            ShareReplica, UnshareReplica, VolumeId,
+            CreatePool, CreateReplica, CreateVolume, DestroyPool, DestroyReplica, Filter, GetNodes,
+            GetSpecs, GetVolumes, PoolId, Protocol, PublishVolume, Replica, ReplicaId, ReplicaName,
+            ReplicaShareProtocol, ReplicaStatus, SetPoolMaintenance, ShareReplica, UnshareReplica,
+            VolumeId,
         },
         openapi::{
             apis::StatusCode,
@@ -19,7 +20,10 @@ use common_lib::{
 };
 use grpc::{grpc_opts::Context, pool::traits::PoolOperations, replica::traits::ReplicaOperations};
 use itertools::Itertools;
-use std::{convert::TryFrom, time::Duration};
+use std::{
+    convert::{TryFrom, TryInto},
+    time::Duration,
+};
 use testlib::{Cluster, ClusterBuilder};
 
 #[tokio::test]
@@ -45,6 +49,7 @@ async fn pool() {
                 id: "pooloop".into(),
                 disks: vec!["malloc:///disk0?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -616,3 +621,271 @@ async fn reconciler_deleting_pool_on_node_down() {
         .to_string();
     assert_eq!(pool_2_status_after_reconciler_action, "Created");
 }
+
+/// Given volumes with replicas on a pool in maintenance, both published and unpublished
+/// Then their replicas are moved to another pool, after which the pool can be destroyed
+#[tokio::test]
+async fn pool_maintenance_emptied() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(false)
+        .with_agents(vec!["core"])
+        .with_mayastors(2)
+        .with_pools(1)
+        .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+        .build()
+        .await
+        .unwrap();
+
+    let pool_client = cluster.grpc_client().pool();
+    let maintained = cluster.pool(0, 0);
+    let other = cluster.pool(1, 0);
+
+    // place the replicas of the volumes on the first pool
+    pool_client
+        .set_maintenance(&SetPoolMaintenance::new(&other, true), None)
+        .await
+        .unwrap();
+    let published: VolumeId = "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+    let unpublished: VolumeId = "2e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap();
+    for uuid in [&published, &unpublished] {
+        CreateVolume {
+            uuid: uuid.clone(),
+            size: 5 * 1024 * 1024,
+            replicas: 1,
+            ..Default::default()
+        }
+        .request()
+        .await
+        .unwrap();
+    }
+    PublishVolume::new(published.clone(), Some(cluster.node(0)), None)
+        .request()
+        .await
+        .unwrap();
+    pool_client
+        .set_maintenance(&SetPoolMaintenance::new(&other, false), None)
+        .await
+        .unwrap();
+
+    pool_client
+        .set_maintenance(&SetPoolMaintenance::new(&maintained, true), None)
+        .await
+        .unwrap();
+    let pool_replicas = |pool: PoolId| async move {
+        GetSpecs {}
+            .request()
+            .await
+            .unwrap()
+            .replicas
+            .into_iter()
+            .filter(|r| r.pool == pool)
+            .collect::<Vec<_>>()
+    };
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS * 3);
+    let start = std::time::Instant::now();
+    while !pool_replicas(maintained.clone()).await.is_empty() {
+        if start.elapsed() > timeout {
+            panic!("Timeout waiting for the pool in maintenance to be emptied");
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let moved = pool_replicas(other.clone()).await;
+    for uuid in [&published, &unpublished] {
+        assert_eq!(
+            moved.iter().filter(|r| r.owners.owned_by(uuid)).count(),
+            1,
+            "The replica of volume {} should have been moved to pool {}",
+            uuid,
+            other
+        );
+    }
+    let volume = GetVolumes::new(&unpublished)
+        .request()
+        .await
+        .unwrap()
+        .entries;
+    assert!(
+        volume[0].spec().target.is_none(),
+        "Should still be unpublished"
+    );
+
+    pool_client
+        .destroy(
+            &DestroyPool {
+                node: cluster.node(0),
+                id: maintained,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn thin_pool_overcommit() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(false)
+        .with_agents(vec!["core"])
+        .build()
+        .await
+        .unwrap();
+
+    let mayastor = cluster.node(0);
+    let pool_client = cluster.grpc_client().pool();
+    let size_mb = 1024 * 1024;
+
+    let error = pool_client
+        .create(
+            &CreatePool {
+                node: mayastor.clone(),
+                id: "undercommitted".into(),
+                disks: vec!["malloc:///disk0?size_mb=100".into()],
+                labels: None,
+                overcommit: Some(50),
+            },
+            None,
+        )
+        .await
+        .expect_err("The pool capacity cannot be undercommitted");
+    assert!(matches!(
+        error,
+        ReplyError {
+            kind: ReplyErrorKind::InvalidArgument,
+            ..
+        }
+    ));
+
+    // a pool of 100MiB which can allocate up to 300MiB to thin provisioned replicas
+    let pool = pool_client
+        .create(
+            &CreatePool {
+                node: mayastor.clone(),
+                id: "overcommitted".into(),
+                disks: vec!["malloc:///disk0?size_mb=100".into()],
+                labels: None,
+                overcommit: Some(300),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(pool.spec().unwrap().overcommit, Some(300));
+
+    CreateVolume {
+        uuid: "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+        size: 150 * size_mb,
+        replicas: 1,
+        thin: false,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .expect_err("Thick provisioned replicas cannot overcommit the pool");
+
+    let volume = CreateVolume {
+        uuid: "2e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+        size: 150 * size_mb,
+        replicas: 1,
+        thin: true,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    assert!(volume.spec().thin);
+
+    CreateVolume {
+        uuid: "3e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+        size: 150 * size_mb,
+        replicas: 1,
+        thin: true,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .expect_err("The thin provisioned replicas would exceed the pool overcommit");
+}
+
+#[tokio::test]
+async fn pool_maintenance() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(false)
+        .with_agents(vec!["core"])
+        .build()
+        .await
+        .unwrap();
+
+    let mayastor = cluster.node(0);
+    let pool_client = cluster.grpc_client().pool();
+
+    let pool = pool_client
+        .create(
+            &CreatePool {
+                node: mayastor.clone(),
+                id: "maintained".into(),
+                disks: vec!["malloc:///disk0?size_mb=100".into()],
+                labels: None,
+                overcommit: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(!pool.spec().unwrap().maintenance);
+
+    let pool = pool_client
+        .set_maintenance(&SetPoolMaintenance::new(pool.id(), true), None)
+        .await
+        .unwrap();
+    assert!(pool.spec().unwrap().maintenance);
+
+    CreateVolume {
+        uuid: "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+        size: 5 * 1024 * 1024,
+        replicas: 1,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .expect_err("Pools in maintenance are not used for new replicas");
+
+    let pool = pool_client
+        .set_maintenance(&SetPoolMaintenance::new(pool.id(), false), None)
+        .await
+        .unwrap();
+    assert!(!pool.spec().unwrap().maintenance);
+
+    CreateVolume {
+        uuid: "1e3cf927-80c2-47a8-adf0-95c486bdd7b7".try_into().unwrap(),
+        size: 5 * 1024 * 1024,
+        replicas: 1,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+
+    // with no other pool to move its replica to, the pool can't be emptied and destroyed
+    pool_client
+        .set_maintenance(&SetPoolMaintenance::new(pool.id(), true), None)
+        .await
+        .unwrap();
+    let error = pool_client
+        .destroy(
+            &DestroyPool {
+                node: mayastor.clone(),
+                id: pool.id().clone(),
+            },
+            None,
+        )
+        .await
+        .expect_err("The pool still has a replica");
+    assert!(matches!(
+        error,
+        ReplyError {
+            kind: ReplyErrorKind::InUse,
+            ..
+        }
+    ));
+}
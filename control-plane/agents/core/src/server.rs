@@ -4,6 +4,7 @@ pub mod lib;
 pub mod nexus;
 pub mod node;
 pub mod pool;
+pub mod snapshot;
 pub mod volume;
 pub mod watcher;
 
@@ -39,6 +40,30 @@ pub(crate) struct CliArgs {
     #[structopt(long, default_value = "10s")]
     pub(crate) reconcile_period: humantime::Duration,
 
+    /// Enable the rebalancing of the volume replicas across the pools, as per the rebalance
+    /// thresholds below. Replicas are not moved unless it's enabled
+    #[structopt(long)]
+    pub(crate) rebalance: bool,
+
+    /// Pools with a usage percentage at or above this threshold have their replicas moved to
+    /// other pools, when the rebalancing is enabled
+    #[structopt(long, default_value = "90")]
+    pub(crate) rebalance_pool_usage: u8,
+
+    /// Pools with more replicas than the least used pool plus this skew have their replicas
+    /// moved to other pools
+    #[structopt(long, default_value = "2")]
+    pub(crate) rebalance_replica_skew: usize,
+
+    /// The minimum interval between starting two replica moves when rebalancing the pools
+    #[structopt(long, default_value = "5m")]
+    pub(crate) rebalance_interval: humantime::Duration,
+
+    /// The targets of the volumes with the target failover policy are moved to another node once
+    /// their node has not been online for this long
+    #[structopt(long, default_value = "1m")]
+    pub(crate) target_failover_grace_period: humantime::Duration,
+
     /// Deadline for the mayastor instance keep alive registration
     /// Default: 10s
     #[structopt(long, short, default_value = "10s")]
@@ -46,15 +71,25 @@ pub(crate) struct CliArgs {
 
     /// The Persistent Store URLs to connect to
     /// (supports the http/https schema)
+    /// An in-memory store may be used instead, for tests and single-node deployments, with
+    /// memory:// or, backed by a file, with file:///path/to/store.json
     /// Default: http://localhost:2379
     #[structopt(long, short, default_value = "http://localhost:2379")]
     pub(crate) store: String,
 
+    /// Only report which persistent store objects need to be migrated to the current schema
+    /// version, rather than migrating them on startup, and then exit without writing to the
+    /// store. Note that the migration can't be undone: once migrated, the objects can no longer
+    /// be read by the core agents which predate the versioning of the store objects
+    #[structopt(long)]
+    pub(crate) store_migration_dry_run: bool,
+
     /// The timeout for store operations
     #[structopt(long, default_value = utils::STORE_OP_TIMEOUT)]
     pub(crate) store_timeout: humantime::Duration,
 
     /// The lease lock ttl for the persistent store after which we'll lose the exclusive access
+    /// Only applies to etcd, as the in-memory stores hold their lease until the process exits
     #[structopt(long, default_value = utils::STORE_LEASE_LOCK_TTL)]
     pub(crate) store_lease_ttl: humantime::Duration,
 
@@ -81,6 +116,17 @@ pub(crate) struct CliArgs {
     /// (supports the http/https schema)
     #[structopt(long, short, default_value = DEFAULT_GRPC_SERVER_ADDR)]
     pub(crate) grpc_server_addr: Uri,
+
+    /// Serve the prometheus metrics on this address, eg: 0.0.0.0:9502
+    /// The metrics are not served by default, so the core agent doesn't listen on another port
+    /// unless asked to, whereas the rest service serves its own on its existing http endpoint
+    #[structopt(long)]
+    pub(crate) metrics_addr: Option<std::net::SocketAddr>,
+
+    /// Emit Kubernetes events against the PersistentVolume and PersistentVolumeClaim of the
+    /// volumes, eg: when a volume becomes degraded or fails to be published
+    #[structopt(long)]
+    pub(crate) k8s_events: bool,
 }
 impl CliArgs {
     fn args() -> Self {
@@ -154,6 +200,16 @@ async fn server(cli_args: CliArgs) {
         cli_args.store_lease_ttl.into(),
         cli_args.reconcile_period.into(),
         cli_args.reconcile_idle_period.into(),
+        registry::ReconcilerConfig {
+            rebalance: registry::RebalanceConfig {
+                enabled: cli_args.rebalance,
+                pool_usage_threshold: cli_args.rebalance_pool_usage,
+                replica_count_skew: cli_args.rebalance_replica_skew,
+                interval: cli_args.rebalance_interval.into(),
+            },
+            failover_grace_period: cli_args.target_failover_grace_period.into(),
+        },
+        cli_args.store_migration_dry_run,
     )
     .await;
 
@@ -173,8 +229,17 @@ async fn server(cli_args: CliArgs) {
         .await
         .configure(nexus::configure)
         .configure(volume::configure)
+        .configure(snapshot::configure)
         .configure(watcher::configure);
 
+    if cli_args.k8s_events {
+        crate::core::k8s_events::init().await;
+    }
+
+    if let Some(addr) = cli_args.metrics_addr {
+        tokio::spawn(crate::core::metrics::serve(addr, registry.clone()));
+    }
+
     let service = lib::Service::new(base_service);
     registry.start().await;
     service.run().await;
@@ -202,6 +267,10 @@ macro_rules! impl_request_handler {
                 ) -> Result<<$RequestType as Message>::Reply, SvcError> {
                     let request: ReceivedMessage<$RequestType> = args.request.try_into()?;
                     let service: &service::Service = args.context.get_state()?;
+                    let source = common_lib::types::v0::message_bus::EventSource::request(
+                        &$RequestType::default().id(),
+                    );
+                    let service = service.with_event_source(source);
                     match service.$ServiceFnName(&request.inner()).await {
                         Ok(reply) => {
                             if let Ok(result_str) = serde_json::to_string(&reply) {
@@ -221,10 +290,16 @@ macro_rules! impl_request_handler {
                     }
                 }
                 use opentelemetry::trace::FutureExt;
-                match $ServiceFnName(args.clone())
+                let started = std::time::Instant::now();
+                let result = $ServiceFnName(args.clone())
                     .with_context(args.request.context())
-                    .await
-                {
+                    .await;
+                common_lib::metrics::observe_bus_handler(
+                    &$RequestType::default().id().to_string(),
+                    common_lib::metrics::result_label(&result),
+                    started.elapsed(),
+                );
+                match result {
                     Ok(reply) => Ok(args.request.respond(reply).await?),
                     Err(error) => Err(error),
                 }
@@ -253,6 +328,10 @@ macro_rules! impl_publish_handler {
                 let request: ReceivedMessage<$PublishType> = args.request.try_into()?;
 
                 let service: &service::Service = args.context.get_state()?;
+                let source = common_lib::types::v0::message_bus::EventSource::request(
+                    &$PublishType::default().id(),
+                );
+                let service = service.with_event_source(source);
                 service.$ServiceFnName(&request.inner()).await;
                 Ok(())
             }
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use std::{convert::TryInto, marker::PhantomData};
+
+use super::{core::registry::Registry, handler, impl_request_handler};
+use common::{errors::SvcError, handler::*};
+use common_lib::types::v0::message_bus::{
+    CreateVolumeSnapshot, DestroyVolumeSnapshot, GetVolumeSnapshots,
+};
+
+mod service;
+pub mod specs;
+
+/// Volume snapshots are served on the volume channel, alongside the volume service itself
+pub(crate) fn configure(builder: common::Service) -> common::Service {
+    let registry = builder.get_shared_state::<Registry>().clone();
+    builder
+        .with_channel(ChannelVs::Volume)
+        .with_shared_state(service::Service::new(registry))
+        .with_subscription(handler!(GetVolumeSnapshots))
+        .with_subscription(handler!(CreateVolumeSnapshot))
+        .with_subscription(handler!(DestroyVolumeSnapshot))
+}
+
+/// Snapshot Agent's Tests
+#[cfg(test)]
+mod tests;
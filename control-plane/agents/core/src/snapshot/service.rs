@@ -0,0 +1,62 @@
+use crate::core::{registry::Registry, specs::ResourceSpecsLocked};
+use common::errors::SvcError;
+use common_lib::{
+    mbus_api::message_bus::v0::VolumeSnapshots,
+    types::v0::{
+        message_bus::{
+            CreateVolumeSnapshot, DestroyVolumeSnapshot, EventSource, GetVolumeSnapshots,
+            VolumeSnapshot,
+        },
+        store::OperationMode,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub(super) struct Service {
+    registry: Registry,
+}
+
+impl Service {
+    pub(super) fn new(registry: Registry) -> Self {
+        Self { registry }
+    }
+    /// Get a handle of the service whose operations are performed on behalf of the `source`
+    pub(super) fn with_event_source(&self, source: EventSource) -> Self {
+        Self::new(self.registry.with_event_source(source))
+    }
+    fn specs(&self) -> &ResourceSpecsLocked {
+        self.registry.specs()
+    }
+
+    /// Get volume snapshots
+    #[tracing::instrument(level = "info", skip(self), err)]
+    pub(super) async fn get_volume_snapshots(
+        &self,
+        request: &GetVolumeSnapshots,
+    ) -> Result<VolumeSnapshots, SvcError> {
+        let snapshots = self.specs().get_volume_snapshots(&request.filter)?;
+        Ok(VolumeSnapshots(snapshots))
+    }
+
+    /// Create volume snapshot
+    #[tracing::instrument(level = "info", skip(self), err, fields(volume.uuid = %request.volume, snapshot.uuid = %request.uuid))]
+    pub(super) async fn create_volume_snapshot(
+        &self,
+        request: &CreateVolumeSnapshot,
+    ) -> Result<VolumeSnapshot, SvcError> {
+        self.specs()
+            .create_volume_snapshot(&self.registry, request, OperationMode::Exclusive)
+            .await
+    }
+
+    /// Destroy volume snapshot
+    #[tracing::instrument(level = "info", skip(self), err, fields(volume.uuid = %request.volume, snapshot.uuid = %request.uuid))]
+    pub(super) async fn destroy_volume_snapshot(
+        &self,
+        request: &DestroyVolumeSnapshot,
+    ) -> Result<(), SvcError> {
+        self.specs()
+            .destroy_volume_snapshot(&self.registry, request, OperationMode::Exclusive)
+            .await
+    }
+}
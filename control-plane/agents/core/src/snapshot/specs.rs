@@ -0,0 +1,336 @@
+use crate::{
+    core::{
+        specs::{OperationSequenceGuard, ResourceSpecsLocked, SpecOperations},
+        wrapper::ClientOps,
+    },
+    registry::Registry,
+};
+use common::errors::{NexusNotFound, SvcError, VolumeNotFound, VolumeNotPublished};
+use common_lib::{
+    mbus_api::ResourceKind,
+    types::v0::{
+        message_bus::{
+            ChildState, CreateNexusSnapshot, CreateVolumeSnapshot, DestroyReplicaSnapshot,
+            DestroyVolumeSnapshot, Filter, NodeId, ReplicaId, ReplicaSnapshot, SnapshotId,
+            VolumeContentSource, VolumeId, VolumeSnapshot,
+        },
+        store::{
+            replica::ReplicaSpec,
+            snapshot::{SnapshotOperation, SnapshotSpec},
+            volume::VolumeTarget,
+            OperationMode, SpecStatus, SpecTransaction,
+        },
+    },
+};
+
+use parking_lot::Mutex;
+use snafu::OptionExt;
+use std::{collections::HashSet, sync::Arc, time::SystemTime};
+
+/// Get the name of the snapshot of the `replica` which was taken as part of the nexus snapshot
+/// `nexus_snapshot`.
+/// Both are named after their source and the snapshot time, eg: `{nexus}-snap-{time}` and
+/// `{replica}-snap-{time}`.
+fn replica_snapshot_name(nexus_snapshot: &str, replica: &str) -> Option<String> {
+    let (_, time) = nexus_snapshot.rsplit_once("-snap-")?;
+    Some(format!("{}-snap-{}", replica, time))
+}
+
+#[async_trait::async_trait]
+impl SpecOperations for SnapshotSpec {
+    type Create = CreateVolumeSnapshot;
+    type Owners = ();
+    type Status = ();
+    type State = VolumeSnapshot;
+    type UpdateOp = ();
+
+    fn start_create_op(&mut self) {
+        self.start_op(SnapshotOperation::Create);
+    }
+    fn start_destroy_op(&mut self) {
+        self.start_op(SnapshotOperation::Destroy);
+    }
+    fn remove_spec(locked_spec: &Arc<Mutex<Self>>, registry: &Registry) {
+        let uuid = locked_spec.lock().uuid.clone();
+        registry.specs().remove_snapshot(&uuid);
+    }
+    fn dirty(&self) -> bool {
+        self.pending_op()
+    }
+    fn kind(&self) -> ResourceKind {
+        ResourceKind::Snapshot
+    }
+    fn uuid(&self) -> String {
+        self.uuid.to_string()
+    }
+    fn status(&self) -> SpecStatus<Self::Status> {
+        self.status.clone()
+    }
+    fn set_status(&mut self, status: SpecStatus<Self::Status>) {
+        self.status = status;
+    }
+}
+
+impl ResourceSpecsLocked {
+    /// Snapshot a published volume through its target nexus.
+    /// Only the replicas which are healthy children of the nexus are part of the snapshot.
+    /// The data plane can only snapshot the replicas through a nexus, so an unpublished volume
+    /// cannot be snapshotted and `VolumeNotPublished` is returned instead.
+    pub(crate) async fn create_volume_snapshot(
+        &self,
+        registry: &Registry,
+        request: &CreateVolumeSnapshot,
+        mode: OperationMode,
+    ) -> Result<VolumeSnapshot, SvcError> {
+        let volume_spec = self
+            .get_locked_volume(&request.volume)
+            .context(VolumeNotFound {
+                vol_id: request.volume.to_string(),
+            })?;
+        // the volume must not be republished or have its replicas changed while it's snapshotted
+        let _volume_guard = volume_spec.operation_guard_wait(mode).await?;
+
+        let volume = volume_spec.lock().clone();
+        let target = volume.target.clone().context(VolumeNotPublished {
+            vol_id: volume.uuid.to_string(),
+        })?;
+        let nexus_state = registry.get_nexus(target.nexus()).await?;
+
+        let nexus_spec = self.get_nexus(target.nexus()).context(NexusNotFound {
+            nexus_id: target.nexus().to_string(),
+        })?;
+        let children = nexus_spec.lock().children.clone();
+
+        let mut replicas = vec![];
+        for replica_uri in children.iter().filter_map(|c| c.as_replica()) {
+            // only healthy replicas can take part in the snapshot
+            let online = nexus_state
+                .children
+                .iter()
+                .any(|c| &c.uri == replica_uri.uri() && c.state == ChildState::Online);
+            if !online {
+                continue;
+            }
+            let replica = match self.get_replica(replica_uri.uuid()) {
+                Some(replica) => replica.lock().clone(),
+                None => continue,
+            };
+            if let Some(node) = Self::get_replica_node(registry, &replica).await {
+                replicas.push((replica, node));
+            }
+        }
+        if replicas.is_empty() {
+            return Err(SvcError::NoOnlineReplicas {
+                id: volume.uuid.to_string(),
+            });
+        }
+
+        let snapshot_spec = self.get_or_create_snapshot(request);
+        let (_, _guard) =
+            SpecOperations::start_create(&snapshot_spec, registry, request, mode).await?;
+
+        let result = Self::snapshot_replicas(registry, &volume.uuid, &target, &replicas)
+            .await
+            .map(|(name, replicas)| {
+                let mut spec = snapshot_spec.lock();
+                spec.size = volume.size;
+                spec.set_taken(&name, replicas, SystemTime::now());
+            });
+
+        SpecOperations::complete_create(result, &snapshot_spec, registry).await?;
+        let snapshot_spec = snapshot_spec.lock().clone();
+        Ok(VolumeSnapshot::new(snapshot_spec))
+    }
+
+    /// Snapshot the target nexus of the volume and find the snapshot of each of its `replicas`.
+    /// Mayastor reports the replica snapshots as replicas of the same pool, so the snapshot of a
+    /// replica is the lvol of its pool which is named after both the replica and the snapshot
+    /// returned by the nexus.
+    /// Only the replicas whose snapshot is reported by their node are part of the snapshot.
+    async fn snapshot_replicas(
+        registry: &Registry,
+        volume: &VolumeId,
+        target: &VolumeTarget,
+        replicas: &[(ReplicaSpec, NodeId)],
+    ) -> Result<(String, Vec<ReplicaSnapshot>), SvcError> {
+        let node = registry.get_node_wrapper(target.node()).await?;
+        let name = node
+            .create_nexus_snapshot(&CreateNexusSnapshot::new(target.node(), target.nexus()))
+            .await?;
+
+        let mut snapshots = vec![];
+        for (replica, node_id) in replicas {
+            let snapshot_name = match replica_snapshot_name(&name, replica.name.as_str()) {
+                Some(snapshot_name) => snapshot_name,
+                None => {
+                    return Err(SvcError::Internal {
+                        details: format!("Unexpected nexus snapshot name '{}'", name),
+                    })
+                }
+            };
+            let node = registry.get_node_wrapper(node_id).await?;
+            let snapshot = node
+                .fetch_pool_replicas(&replica.pool)
+                .await?
+                .into_iter()
+                .find(|lvol| lvol.name.as_str() == snapshot_name);
+            match snapshot {
+                Some(lvol) => snapshots.push(ReplicaSnapshot::new(
+                    &replica.uuid,
+                    node_id,
+                    &replica.pool,
+                    lvol.name.as_str(),
+                    &lvol.uuid,
+                )),
+                None => tracing::warn!(
+                    replica.uuid = %replica.uuid,
+                    "The snapshot '{}' of the replica is not reported by node '{}'",
+                    name,
+                    node_id
+                ),
+            }
+        }
+        if snapshots.is_empty() {
+            return Err(SvcError::NoOnlineReplicas {
+                id: volume.to_string(),
+            });
+        }
+        Ok((name, snapshots))
+    }
+
+    /// Destroy a volume snapshot by destroying the snapshot of each replica.
+    /// The snapshot can't be destroyed while a volume is being cloned from it.
+    pub(crate) async fn destroy_volume_snapshot(
+        &self,
+        registry: &Registry,
+        request: &DestroyVolumeSnapshot,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        let snapshot_spec = match self.get_locked_snapshot(&request.uuid) {
+            Some(spec) if spec.lock().volume == request.volume => spec,
+            _ => {
+                return Err(SvcError::NotFound {
+                    kind: ResourceKind::Snapshot,
+                    id: request.uuid.to_string(),
+                })
+            }
+        };
+        if self.seeding_source(&VolumeContentSource::Snapshot(request.uuid.clone())) {
+            return Err(SvcError::InUse {
+                kind: ResourceKind::Snapshot,
+                id: request.uuid.to_string(),
+            });
+        }
+        let _guard = SpecOperations::start_destroy(&snapshot_spec, registry, false, mode).await?;
+
+        let replicas = snapshot_spec.lock().replicas.clone();
+        let mut result = Ok(());
+        for replica in &replicas {
+            let node = match registry.get_node_wrapper(&replica.node).await {
+                Ok(node) => node,
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            };
+            match node
+                .destroy_replica_snapshot(&DestroyReplicaSnapshot::from(replica))
+                .await
+            {
+                Ok(_) | Err(SvcError::NotFound { .. }) => {}
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+
+        SpecOperations::complete_destroy(result, &snapshot_spec, registry).await
+    }
+
+    /// Get the volume snapshots which match the given `filter`
+    pub(crate) fn get_volume_snapshots(
+        &self,
+        filter: &Filter,
+    ) -> Result<Vec<VolumeSnapshot>, SvcError> {
+        let snapshots = self.get_snapshots();
+        let filtered = match filter {
+            Filter::None => snapshots,
+            Filter::Volume(volume_id) => {
+                self.get_volume(volume_id)?;
+                snapshots
+                    .into_iter()
+                    .filter(|s| &s.volume == volume_id)
+                    .collect()
+            }
+            Filter::VolumeSnapshot(volume_id, snapshot_id) => {
+                self.get_volume(volume_id)?;
+                snapshots
+                    .into_iter()
+                    .filter(|s| &s.volume == volume_id && &s.uuid == snapshot_id)
+                    .collect()
+            }
+            Filter::Snapshot(snapshot_id) => snapshots
+                .into_iter()
+                .filter(|s| &s.uuid == snapshot_id)
+                .collect(),
+            filter => {
+                return Err(SvcError::InvalidFilter {
+                    filter: filter.clone(),
+                })
+            }
+        };
+        Ok(filtered
+            .into_iter()
+            .filter(|s| s.status.created())
+            .map(VolumeSnapshot::new)
+            .collect())
+    }
+
+    /// Get or Create the protected SnapshotSpec for the given request
+    fn get_or_create_snapshot(&self, request: &CreateVolumeSnapshot) -> Arc<Mutex<SnapshotSpec>> {
+        let mut specs = self.write();
+        if let Some(snapshot) = specs.snapshots.get(&request.uuid) {
+            snapshot.clone()
+        } else {
+            specs.snapshots.insert(SnapshotSpec::from(request))
+        }
+    }
+    /// Get a protected SnapshotSpec for the given snapshot `id`, if it exists
+    pub(crate) fn get_locked_snapshot(&self, id: &SnapshotId) -> Option<Arc<Mutex<SnapshotSpec>>> {
+        let specs = self.read();
+        specs.snapshots.get(id).cloned()
+    }
+    /// Get a vector of SnapshotSpec's
+    pub(crate) fn get_snapshots(&self) -> Vec<SnapshotSpec> {
+        let specs = self.read();
+        specs
+            .snapshots
+            .to_vec()
+            .into_iter()
+            .map(|s| s.lock().clone())
+            .collect()
+    }
+    /// Get the uuids of the snapshot lvols of the replicas of all the snapshots
+    pub(crate) fn get_replica_snapshot_uuids(&self) -> HashSet<ReplicaId> {
+        let specs = self.read();
+        specs
+            .snapshots
+            .to_vec()
+            .into_iter()
+            .flat_map(|s| {
+                let snapshot = s.lock();
+                snapshot
+                    .replicas
+                    .iter()
+                    .map(|r| r.uuid.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+    /// Remove the snapshot `id` from the spec list
+    fn remove_snapshot(&self, id: &SnapshotId) {
+        let mut specs = self.write();
+        specs.snapshots.remove(id);
+    }
+}
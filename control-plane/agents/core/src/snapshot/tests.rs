@@ -0,0 +1,138 @@
+#![cfg(test)]
+
+use common_lib::{
+    mbus_api::{Message, ReplyErrorKind},
+    types::v0::{
+        message_bus::{
+            CreateVolume, CreateVolumeSnapshot, DestroyVolume, DestroyVolumeSnapshot, Filter,
+            GetReplicas, GetVolumeSnapshots, PublishVolume, ReplicaId, SnapshotId, UnpublishVolume,
+            VolumeId,
+        },
+        store::SpecStatus,
+    },
+};
+use testlib::ClusterBuilder;
+
+use std::{convert::TryFrom, time::Duration};
+
+#[tokio::test]
+async fn snapshot() {
+    let _cluster = ClusterBuilder::builder()
+        .with_rest(true)
+        .with_agents(vec!["core"])
+        .with_mayastors(2)
+        .with_pools(1)
+        .with_cache_period("1s")
+        // don't let the reconcile interfere with the tests
+        .with_reconcile_period(Duration::from_secs(1000), Duration::from_secs(1000))
+        .build()
+        .await
+        .unwrap();
+
+    let volume = CreateVolume {
+        uuid: VolumeId::try_from("1e3cf927-80c2-47a8-adf0-95c486bdd7b7").unwrap(),
+        size: 5242880,
+        replicas: 2,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    let volume_id = volume.spec().uuid;
+    let snapshot_id = SnapshotId::try_from("7a3b54d2-1d8d-4bc4-9e22-f1d6a4b5c8e2").unwrap();
+
+    let error = CreateVolumeSnapshot::new(&volume_id, &snapshot_id)
+        .request()
+        .await
+        .expect_err("Can't snapshot an unpublished volume");
+    assert_eq!(error.kind, ReplyErrorKind::NotPublished);
+
+    PublishVolume {
+        uuid: volume_id.clone(),
+        target_node: None,
+        share: None,
+        num_paths: None,
+    }
+    .request()
+    .await
+    .unwrap();
+
+    let snapshot = CreateVolumeSnapshot::new(&volume_id, &snapshot_id)
+        .request()
+        .await
+        .unwrap();
+    tracing::info!("Snapshot: {:?}", snapshot);
+    let spec = snapshot.spec();
+    assert_eq!(spec.status, SpecStatus::Created(()));
+    assert_eq!(spec.size, 5242880);
+    assert_eq!(spec.replicas.len(), 2);
+    assert!(spec.timestamp.is_some());
+    let name = spec
+        .name
+        .clone()
+        .expect("The nexus should name the snapshot");
+    let (_, time) = name.rsplit_once("-snap-").unwrap();
+
+    // the replica snapshots are reported by the nodes as replicas of the same pools
+    let replicas = GetReplicas::default().request().await.unwrap();
+    for snapshot in &spec.replicas {
+        assert_ne!(snapshot.uuid, ReplicaId::default());
+        let lvol = replicas
+            .0
+            .iter()
+            .find(|r| r.uuid == snapshot.uuid)
+            .expect("The replica snapshot should be reported");
+        assert_eq!(lvol.name.as_str(), snapshot.name);
+        assert!(snapshot.name.ends_with(&format!("-snap-{}", time)));
+        assert_eq!(lvol.pool, snapshot.pool);
+    }
+
+    CreateVolumeSnapshot::new(&volume_id, &snapshot_id)
+        .request()
+        .await
+        .expect_err("The snapshot already exists");
+
+    let snapshots = GetVolumeSnapshots {
+        filter: Filter::Volume(volume_id.clone()),
+    }
+    .request()
+    .await
+    .unwrap();
+    assert_eq!(snapshots.0, vec![snapshot]);
+
+    let error = DestroyVolume {
+        uuid: volume_id.clone(),
+    }
+    .request()
+    .await
+    .expect_err("The volume still has a snapshot");
+    assert_eq!(error.kind, ReplyErrorKind::InUse);
+
+    DestroyVolumeSnapshot::new(&volume_id, &snapshot_id)
+        .request()
+        .await
+        .unwrap();
+    let snapshots = GetVolumeSnapshots {
+        filter: Filter::Snapshot(snapshot_id.clone()),
+    }
+    .request()
+    .await
+    .unwrap();
+    assert!(snapshots.0.is_empty());
+    let replicas = GetReplicas::default().request().await.unwrap();
+    assert!(spec
+        .replicas
+        .iter()
+        .all(|snapshot| !replicas.0.iter().any(|r| r.uuid == snapshot.uuid)));
+
+    DestroyVolumeSnapshot::new(&volume_id, &snapshot_id)
+        .request()
+        .await
+        .expect_err("The snapshot no longer exists");
+
+    UnpublishVolume::new(&volume_id, false)
+        .request()
+        .await
+        .unwrap();
+    DestroyVolume { uuid: volume_id }.request().await.unwrap();
+}
@@ -1,7 +1,7 @@
-use crate::core::registry::Registry;
+use crate::core::{k8s_events, registry::Registry};
 use common::errors::{SvcError, VolumeNotFound};
 use common_lib::types::v0::message_bus::{
-    NexusStatus, ReplicaTopology, Volume, VolumeId, VolumeState, VolumeStatus,
+    NexusStatus, PoolUsageWarning, ReplicaTopology, Volume, VolumeId, VolumeState, VolumeStatus,
 };
 
 use crate::core::reconciler::PollTriggerEvent;
@@ -42,7 +42,21 @@ impl Registry {
             );
         }
 
-        Ok(if let Some(nexus_state) = nexus_state {
+        // Thin provisioned replicas can fail to allocate once their pools run out of space.
+        let mut usage_warnings = vec![];
+        if volume_spec.thin {
+            for spec in &replica_specs {
+                let pool = spec.lock().pool.clone();
+                match self.get_pool_state(&pool).await {
+                    Ok(state) if state.usage_warning() => {
+                        usage_warnings.push(PoolUsageWarning::from(&state))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let state = if let Some(nexus_state) = nexus_state {
             VolumeState {
                 uuid: volume_uuid.to_owned(),
                 size: nexus_state.size,
@@ -56,12 +70,16 @@ impl Registry {
                 },
                 target: Some(nexus_state),
                 replica_topology,
+                usage_warnings,
             }
         } else {
             VolumeState {
                 uuid: volume_uuid.to_owned(),
                 size: volume_spec.size,
-                status: if volume_spec.target.is_none() {
+                status: if volume_spec.seeding.is_some() {
+                    // the replicas are still being rebuilt with the volume content
+                    VolumeStatus::Degraded
+                } else if volume_spec.target.is_none() {
                     if replica_specs.len() >= volume_spec.num_replicas as usize {
                         VolumeStatus::Online
                     } else if replica_specs.is_empty() {
@@ -74,8 +92,11 @@ impl Registry {
                 },
                 target: None,
                 replica_topology,
+                usage_warnings,
             }
-        })
+        };
+        k8s_events::volume_status(volume_uuid, &state.status);
+        Ok(state)
     }
 
     /// Construct a replica topology from a replica spec.
@@ -91,7 +112,7 @@ impl Registry {
     }
 
     /// Get all volumes
-    pub(super) async fn get_volumes(&self) -> Vec<Volume> {
+    pub(crate) async fn get_volumes(&self) -> Vec<Volume> {
         let mut volumes = vec![];
         let volume_specs = self.specs().get_volumes();
         for spec in volume_specs {
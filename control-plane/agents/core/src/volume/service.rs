@@ -1,11 +1,15 @@
-use crate::core::{registry::Registry, specs::ResourceSpecsLocked};
+use crate::core::{
+    k8s_events::{self, VolumeEventReason},
+    registry::Registry,
+    specs::ResourceSpecsLocked,
+};
 use common::errors::SvcError;
 use common_lib::{
-    mbus_api::message_bus::v0::Volumes,
+    mbus_api::{message_bus::v0::Volumes, ErrorChain},
     types::v0::{
         message_bus::{
-            CreateVolume, DestroyVolume, Filter, GetVolumes, PublishVolume, SetVolumeReplica,
-            ShareVolume, UnpublishVolume, UnshareVolume, Volume,
+            CreateVolume, DestroyVolume, EventSource, Filter, GetVolumes, PublishVolume,
+            SetVolumeReplica, ShareVolume, UnpublishVolume, UnshareVolume, Volume,
         },
         store::OperationMode,
     },
@@ -20,6 +24,10 @@ impl Service {
     pub(super) fn new(registry: Registry) -> Self {
         Self { registry }
     }
+    /// Get a handle of the service whose operations are performed on behalf of the `source`
+    pub(super) fn with_event_source(&self, source: EventSource) -> Self {
+        Self::new(self.registry.with_event_source(source))
+    }
     fn specs(&self) -> &ResourceSpecsLocked {
         self.registry.specs()
     }
@@ -81,9 +89,18 @@ impl Service {
     /// Publish volume
     #[tracing::instrument(level = "info", skip(self), err, fields(volume.uuid = %request.uuid))]
     pub(super) async fn publish_volume(&self, request: &PublishVolume) -> Result<Volume, SvcError> {
-        self.specs()
+        let result = self
+            .specs()
             .publish_volume(&self.registry, request, OperationMode::Exclusive)
-            .await
+            .await;
+        if let Err(error) = &result {
+            k8s_events::volume_event(
+                &request.uuid,
+                VolumeEventReason::PublishFailed,
+                format!("Failed to publish the volume: {}", error.full_string()),
+            );
+        }
+        result
     }
 
     /// Unpublish volume
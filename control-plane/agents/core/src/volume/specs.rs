@@ -1,5 +1,6 @@
 use crate::{
     core::{
+        k8s_events,
         reconciler::PollTriggerEvent,
         scheduling::{
             nexus::GetPersistedNexusChildren,
@@ -11,6 +12,7 @@ use crate::{
             ResourceFilter,
         },
         specs::{OperationSequenceGuard, ResourceSpecs, ResourceSpecsLocked, SpecOperations},
+        wrapper::ClientOps,
     },
     registry::Registry,
     volume::scheduling,
@@ -26,17 +28,18 @@ use common_lib::{
     mbus_api::{ErrorChain, ResourceKind},
     types::v0::{
         message_bus::{
-            AddNexusReplica, ChildUri, CreateNexus, CreateReplica, CreateVolume, DestroyNexus,
-            DestroyReplica, DestroyVolume, Nexus, NexusId, NodeId, Protocol, PublishVolume,
-            RemoveNexusReplica, Replica, ReplicaId, ReplicaName, ReplicaOwners, SetVolumeReplica,
-            ShareNexus, ShareVolume, UnpublishVolume, UnshareNexus, UnshareVolume, Volume,
-            VolumeId, VolumeState, VolumeStatus,
+            AddNexusReplica, ChildState, ChildUri, CreateNexus, CreateReplica, CreateVolume,
+            DestroyNexus, DestroyReplica, DestroyVolume, Nexus, NexusId, NexusNvmfConfig, NodeId,
+            PoolTopology, Protocol, PublishVolume, RemoveNexusReplica, Replica, ReplicaId,
+            ReplicaName, ReplicaOwners, ReplicaSnapshot, SetVolumeReplica, ShareNexus, ShareVolume,
+            UnpublishVolume, UnshareNexus, UnshareVolume, Volume, VolumeContentSource, VolumeId,
+            VolumeState, VolumeStatus,
         },
         store::{
             nexus::{NexusSpec, ReplicaUri},
             nexus_child::NexusChild,
             replica::ReplicaSpec,
-            volume::{VolumeOperation, VolumeSpec},
+            volume::{VolumeOperation, VolumeSeeding, VolumeSpec, VolumeTarget},
             OperationMode, SpecStatus, SpecTransaction, TraceSpan, TraceStrLog,
         },
     },
@@ -151,7 +154,7 @@ pub(crate) async fn get_volume_replica_candidates(
                 uuid: replica_uuid,
                 pool: p.id.clone(),
                 size: request.size,
-                thin: false,
+                thin: request.thin,
                 share: Protocol::None,
                 managed: true,
                 owners: ReplicaOwners::from_volume(&request.uuid),
@@ -210,6 +213,77 @@ pub(crate) async fn get_healthy_volume_replicas(
     }
 }
 
+/// Content from which the replicas of a volume are seeded
+#[derive(Debug)]
+struct VolumeSeed {
+    /// size of the content in bytes
+    size: u64,
+    /// replicas or replica snapshots holding the content, in order of preference
+    sources: Vec<VolumeSeedSource>,
+}
+impl VolumeSeed {
+    /// Get the shared source replicas, which the seed nexus unshares
+    fn shared_replicas(&self) -> Vec<ReplicaId> {
+        self.sources
+            .iter()
+            .filter_map(|source| match source {
+                VolumeSeedSource::Replica(replica) if replica.share != Protocol::None => {
+                    Some(replica.uuid.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A copy of the content from which the replicas of a volume are seeded
+#[derive(Debug)]
+enum VolumeSeedSource {
+    /// healthy replica of the source volume, or of the volume itself
+    Replica(Replica),
+    /// snapshot of a replica of the source volume, taken by the source snapshot
+    Snapshot(ReplicaSnapshot),
+}
+impl VolumeSeedSource {
+    /// Get the node where the copy lives, which is where the seed nexus is created
+    fn node(&self) -> &NodeId {
+        match self {
+            Self::Replica(replica) => &replica.node,
+            Self::Snapshot(snapshot) => &snapshot.node,
+        }
+    }
+}
+
+/// Check whether the replicas being seeded through the seed `nexus`, whose current state is
+/// `state`, have been rebuilt, failing if any of them is no longer being rebuilt.
+fn seed_replicas_rebuilt(
+    volume: &VolumeSpec,
+    seeding: &VolumeSeeding,
+    nexus: &NexusSpec,
+    state: &Nexus,
+) -> Result<bool, SvcError> {
+    let mut rebuilt = true;
+    for replica in &seeding.replicas {
+        let child = nexus
+            .children
+            .iter()
+            .filter_map(|child| child.as_replica())
+            .find(|child| child.uuid() == replica)
+            .and_then(|child| state.children.iter().find(|c| &c.uri == child.uri()));
+        match child {
+            Some(child) if child.state == ChildState::Online => {}
+            Some(child) if !child.state.faulted() => rebuilt = false,
+            _ => {
+                return Err(SvcError::VolumeSeed {
+                    vol_id: volume.uuid.to_string(),
+                    replica: replica.to_string(),
+                })
+            }
+        }
+    }
+    Ok(rebuilt)
+}
+
 /// Implementation of the ResourceSpecs which is retrieved from the ResourceSpecsLocked
 /// During these calls, no other thread can add/remove elements from the list
 impl ResourceSpecs {
@@ -277,6 +351,65 @@ impl ResourceSpecsLocked {
             .collect()
     }
 
+    /// Record the move of the volume `replica` to another pool in the volume spec, so that the
+    /// move is carried on by the rebalancer, even if the core agent restarts
+    /// The caller must hold the operation guard of the volume.
+    pub(crate) async fn start_replica_move(
+        &self,
+        registry: &Registry,
+        volume_spec: &Arc<Mutex<VolumeSpec>>,
+        replica: &ReplicaId,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        let uuid = volume_spec.lock().uuid.clone();
+        let state = registry.get_volume_state(&uuid).await?;
+        let (spec_clone, _guard) = SpecOperations::start_update(
+            registry,
+            volume_spec,
+            &state,
+            VolumeOperation::MoveReplica(replica.clone()),
+            mode,
+        )
+        .await?;
+        SpecOperations::complete_update(registry, Ok(()), volume_spec.clone(), spec_clone).await
+    }
+    /// Clear the replica move of the volume, once the move is either complete or abandoned
+    /// The caller must hold the operation guard of the volume.
+    pub(crate) async fn complete_replica_move(
+        &self,
+        registry: &Registry,
+        volume_spec: &Arc<Mutex<VolumeSpec>>,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        let uuid = volume_spec.lock().uuid.clone();
+        let state = registry.get_volume_state(&uuid).await?;
+        let (spec_clone, _guard) = SpecOperations::start_update(
+            registry,
+            volume_spec,
+            &state,
+            VolumeOperation::ReplicaMoved,
+            mode,
+        )
+        .await?;
+        SpecOperations::complete_update(registry, Ok(()), volume_spec.clone(), spec_clone).await
+    }
+    /// Check if the replica is being moved to another pool
+    pub(crate) fn replica_moving(&self, replica: &ReplicaId) -> bool {
+        self.read()
+            .volumes
+            .values()
+            .any(|v| v.lock().replica_move.as_ref() == Some(replica))
+    }
+    /// Get the volumes which have one of their replicas being moved to another pool
+    pub(crate) fn get_replica_moves(&self) -> Vec<Arc<Mutex<VolumeSpec>>> {
+        self.read()
+            .volumes
+            .values()
+            .filter(|v| v.lock().replica_move.is_some())
+            .cloned()
+            .collect()
+    }
+
     /// Get the `NodeId` where `replica` lives
     pub(crate) async fn get_replica_node(
         registry: &Registry,
@@ -305,6 +438,15 @@ impl ResourceSpecsLocked {
             .cloned()
             .collect()
     }
+    /// Check if the content `source` is being seeded onto the replicas of a volume clone, in
+    /// which case it must be left unchanged
+    pub(crate) fn seeding_source(&self, source: &VolumeContentSource) -> bool {
+        self.read().volumes.values().any(|volume| {
+            let volume = volume.lock();
+            volume.source.as_ref() == Some(source)
+                && matches!(&volume.seeding, Some(seeding) if seeding.replaces.is_none())
+        })
+    }
     /// Get the protected volume nexus target for the given volume
     pub(crate) fn get_volume_target_nexus(
         &self,
@@ -342,12 +484,29 @@ impl ResourceSpecsLocked {
         let (volume_clone, _guard) =
             SpecOperations::start_create(&volume, registry, request, mode).await?;
 
+        let seed = match &request.source {
+            Some(source) => {
+                let result = self.get_volume_seed(registry, source, request.size).await;
+                Some(SpecOperations::validate_create_step(registry, result, &volume).await?)
+            }
+            None => None,
+        };
+
         // todo: pick nodes and pools using the Node&Pool Topology
         // todo: virtually increase the pool usage to avoid a race for space with concurrent calls
         let result = get_create_volume_replicas(registry, request).await;
         let create_replicas =
             SpecOperations::validate_create_step(registry, result, &volume).await?;
 
+        let spread_labels = request
+            .topology
+            .as_ref()
+            .map(|t| t.spread_labels().to_vec())
+            .unwrap_or_default();
+        let pool_topology = match request.topology.as_ref().and_then(|t| t.pool.as_ref()) {
+            Some(PoolTopology::Labelled(labelled_topology)) => Some(labelled_topology),
+            None => None,
+        };
         let mut replicas = Vec::<Replica>::new();
         for replica in &create_replicas {
             if replicas.len() >= request.replicas as usize {
@@ -355,6 +514,22 @@ impl ResourceSpecsLocked {
             } else if replicas.iter().any(|r| r.node == replica.node) {
                 // don't reuse the same node
                 continue;
+            } else if !replicas
+                .iter()
+                .all(|r| self.nodes_spread_apart(&r.node, &replica.node, &spread_labels))
+            {
+                // don't reuse the same failure domain
+                continue;
+            } else if !pool_topology
+                .map(|t| {
+                    replicas
+                        .iter()
+                        .all(|r| self.pools_apart(&r.pool, &replica.pool, t))
+                })
+                .unwrap_or(true)
+            {
+                // don't pair up pools with the same exclusive labels
+                continue;
             }
             let replica = if replicas.is_empty() {
                 let mut replica = replica.clone();
@@ -382,6 +557,19 @@ impl ResourceSpecsLocked {
         // we can't fulfil the required replication factor, so let the caller
         // decide what to do next
         let result = if replicas.len() < request.replicas as usize {
+            Err(SvcError::ReplicaCreateNumber {
+                id: request.uuid.to_string(),
+            })
+        } else {
+            // the replicas of a clone are seeded from its content source by the volume reconciler
+            volume.lock().seeding = seed.map(|seed| VolumeSeeding {
+                replicas: replicas.iter().map(|r| r.uuid.clone()).collect(),
+                replaces: None,
+                reshare: seed.shared_replicas(),
+            });
+            Ok(())
+        };
+        if result.is_err() {
             for replica in &replicas {
                 if let Err(error) = self
                     .destroy_replica(registry, &replica.clone().into(), true, mode)
@@ -394,21 +582,432 @@ impl ResourceSpecsLocked {
                     ));
                 }
             }
-            Err(SvcError::ReplicaCreateNumber {
-                id: request.uuid.to_string(),
-            })
-        } else {
-            Ok(())
-        };
+        }
 
         SpecOperations::complete_create(result, &volume, registry).await?;
         registry.get_volume(&request.uuid).await
     }
 
+    /// Get the content source from which the replicas of the volume clone are seeded.
+    /// The source must be created, no larger than the clone and, if it's a volume, neither
+    /// published nor being seeded itself so that its content doesn't change while it's cloned.
+    async fn get_volume_seed(
+        &self,
+        registry: &Registry,
+        source: &VolumeContentSource,
+        clone_size: u64,
+    ) -> Result<VolumeSeed, SvcError> {
+        let check_size = |kind: ResourceKind, id: String, size: u64| {
+            if size > clone_size {
+                Err(SvcError::CloneSourceSize {
+                    kind,
+                    id,
+                    size,
+                    clone_size,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        match source {
+            VolumeContentSource::Volume(id) => {
+                let source = self.get_volume(id)?;
+                if !source.status.created() || source.seeding.is_some() {
+                    return Err(SvcError::NotReady {
+                        kind: ResourceKind::Volume,
+                        id: id.to_string(),
+                    });
+                }
+                if source.target.is_some() {
+                    return Err(SvcError::InUse {
+                        kind: ResourceKind::Volume,
+                        id: id.to_string(),
+                    });
+                }
+                check_size(ResourceKind::Volume, id.to_string(), source.size)?;
+
+                let node = self
+                    .get_volume_data_nodes(id)
+                    .into_iter()
+                    .next()
+                    .context(errors::NoOnlineReplicas { id: id.to_string() })?;
+                let items = match get_healthy_volume_replicas(&source, &node, registry).await? {
+                    // only the first replica is guaranteed to hold the latest content
+                    HealthyChildItems::One(items) => items.into_iter().take(1).collect(),
+                    HealthyChildItems::All(items) => items,
+                };
+                Ok(VolumeSeed {
+                    size: source.size,
+                    sources: items
+                        .iter()
+                        .map(|item| VolumeSeedSource::Replica(item.state().clone()))
+                        .collect(),
+                })
+            }
+            VolumeContentSource::Snapshot(id) => {
+                let snapshot = self
+                    .get_locked_snapshot(id)
+                    .context(errors::NotFound {
+                        kind: ResourceKind::Snapshot,
+                        id: id.to_string(),
+                    })?
+                    .lock()
+                    .clone();
+                if !snapshot.status.created() {
+                    return Err(SvcError::NotReady {
+                        kind: ResourceKind::Snapshot,
+                        id: id.to_string(),
+                    });
+                }
+                check_size(ResourceKind::Snapshot, id.to_string(), snapshot.size)?;
+
+                Ok(VolumeSeed {
+                    size: snapshot.size,
+                    sources: snapshot
+                        .replicas
+                        .into_iter()
+                        .map(VolumeSeedSource::Snapshot)
+                        .collect(),
+                })
+            }
+        }
+    }
+
+    /// Get the content from which the replicas being seeded by `seeding` are seeded: the
+    /// content source of a volume clone, or otherwise the other healthy replicas of the volume.
+    async fn get_seeding_seed(
+        &self,
+        registry: &Registry,
+        volume: &VolumeSpec,
+        seeding: &VolumeSeeding,
+    ) -> Result<VolumeSeed, SvcError> {
+        match &volume.source {
+            Some(source) if seeding.replaces.is_none() => {
+                self.get_volume_seed(registry, source, volume.size).await
+            }
+            _ => {
+                let node = self
+                    .get_volume_data_nodes(&volume.uuid)
+                    .into_iter()
+                    .next()
+                    .context(errors::NoOnlineReplicas {
+                        id: volume.uuid.to_string(),
+                    })?;
+                // the replicas being seeded are deemed healthy if the volume was never published
+                let sources = |items: Vec<ChildItem>| {
+                    items
+                        .into_iter()
+                        .filter(|item| !seeding.replicas.contains(&item.state().uuid))
+                        .map(|item| VolumeSeedSource::Replica(item.state().clone()))
+                };
+                let sources = match get_healthy_volume_replicas(volume, &node, registry).await? {
+                    // only the first replica is guaranteed to hold the latest content
+                    HealthyChildItems::One(items) => sources(items).take(1).collect(),
+                    HealthyChildItems::All(items) => sources(items).collect(),
+                };
+                Ok(VolumeSeed {
+                    size: volume.size,
+                    sources,
+                })
+            }
+        }
+    }
+
+    /// Seed the replicas of the volume, as recorded by its seeding, one step at a time:
+    /// 1. a temporary nexus is created over the first usable copy of the content and the
+    ///    replicas are added to it, which rebuilds them from the copy
+    /// 2. once they're rebuilt, the nexus is destroyed, the replaced replica, if any, is removed
+    ///    and the seeding is completed
+    /// Should a replica fail to rebuild, the nexus is destroyed so that the seeding is started
+    /// over on the next step.
+    /// Returns whether the seeding is still in progress.
+    /// The caller must hold the operation guard of the volume.
+    pub(crate) async fn seed_volume(
+        &self,
+        registry: &Registry,
+        volume_spec: &Arc<Mutex<VolumeSpec>>,
+        mode: OperationMode,
+    ) -> Result<bool, SvcError> {
+        let volume = volume_spec.lock().clone();
+        let seeding = match &volume.seeding {
+            Some(seeding) => seeding.clone(),
+            None => return Ok(false),
+        };
+
+        // the volume can't be published while seeding, so any nexus it owns is the seed nexus
+        let nexus = self
+            .get_volume_nexuses(&volume.uuid)
+            .into_iter()
+            .next()
+            .map(|nexus| nexus.lock().clone());
+        let nexus = match nexus {
+            Some(nexus) => nexus,
+            None => {
+                self.start_volume_seeding(registry, &volume, &seeding, mode)
+                    .await?;
+                return Ok(true);
+            }
+        };
+
+        // the nexus may not be reachable for now, in which case it's checked again later
+        let state = registry.get_nexus(&nexus.uuid).await?;
+        match seed_replicas_rebuilt(&volume, &seeding, &nexus, &state) {
+            Ok(false) => return Ok(true),
+            Ok(true) => {}
+            Err(error) => {
+                self.stop_volume_seeding(registry, &volume, &seeding, nexus.into(), mode)
+                    .await?;
+                return Err(error);
+            }
+        }
+        self.stop_volume_seeding(registry, &volume, &seeding, nexus.into(), mode)
+            .await?;
+
+        if let Some(replaced) = &seeding.replaces {
+            if let Some(replica) = self.get_replica(replaced) {
+                let pool = replica.lock().pool.clone();
+                let node = self.get_pool(&pool)?.node;
+                self.disown_and_destroy_replica(registry, &node, replaced)
+                    .await?;
+            }
+        }
+
+        let state = registry.get_volume_state(&volume.uuid).await?;
+        let (spec_clone, _guard) = SpecOperations::start_update(
+            registry,
+            volume_spec,
+            &state,
+            VolumeOperation::Seeded,
+            mode,
+        )
+        .await?;
+        SpecOperations::complete_update(registry, Ok(()), volume_spec.clone(), spec_clone).await?;
+        volume.info(&format!(
+            "Seeded {} replicas from the volume content",
+            seeding.replicas.len()
+        ));
+        Ok(false)
+    }
+
+    /// Create the temporary nexus over the first usable copy of the content of the volume and
+    /// add the replicas being seeded to it, so that they're rebuilt from the copy.
+    async fn start_volume_seeding(
+        &self,
+        registry: &Registry,
+        volume: &VolumeSpec,
+        seeding: &VolumeSeeding,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        let seed = self.get_seeding_seed(registry, volume, seeding).await?;
+        let mut last_error = SvcError::NoOnlineReplicas {
+            id: volume.uuid.to_string(),
+        };
+        for source in &seed.sources {
+            let result = self
+                .create_seed_nexus(registry, volume, source, seed.size, mode)
+                .await;
+            let nexus = match result {
+                Ok(nexus) => nexus,
+                Err(error) => {
+                    volume.warn(&format!(
+                        "Failed to create a seed nexus over {:?}, error: {}",
+                        source,
+                        error.full_string()
+                    ));
+                    self.restore_seed_sources(registry, volume, seeding, mode)
+                        .await;
+                    last_error = error;
+                    continue;
+                }
+            };
+            let result = self
+                .add_seed_replicas(registry, &nexus, &seeding.replicas, mode)
+                .await;
+            if let Err(error) = result {
+                self.stop_volume_seeding(registry, volume, seeding, nexus.into(), mode)
+                    .await?;
+                return Err(error);
+            }
+            volume.info(&format!(
+                "Seeding {} replicas from {:?}",
+                seeding.replicas.len(),
+                source
+            ));
+            return Ok(());
+        }
+        Err(last_error)
+    }
+
+    /// Destroy the temporary seed `nexus` of the volume and share its source replicas again.
+    async fn stop_volume_seeding(
+        &self,
+        registry: &Registry,
+        volume: &VolumeSpec,
+        seeding: &VolumeSeeding,
+        nexus: DestroyNexus,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        self.destroy_nexus(registry, &nexus, true, mode).await?;
+        self.restore_seed_sources(registry, volume, seeding, mode)
+            .await;
+        Ok(())
+    }
+
+    /// Create a temporary nexus owned by the volume over a copy of its content.
+    /// The nexus is created on the node of the copy since snapshots can only be opened locally.
+    /// It reuses the id of the last target of the volume, if any, so that the seeded replicas are
+    /// recorded as healthy in the persisted information of that target.
+    async fn create_seed_nexus(
+        &self,
+        registry: &Registry,
+        volume: &VolumeSpec,
+        source: &VolumeSeedSource,
+        size: u64,
+        mode: OperationMode,
+    ) -> Result<Nexus, SvcError> {
+        let node = source.node();
+        let child = match source {
+            VolumeSeedSource::Replica(replica) => {
+                let uri = self
+                    .make_replica_accessible(registry, replica, node, mode)
+                    .await?;
+                NexusChild::Replica(ReplicaUri::new(&replica.uuid, &uri))
+            }
+            VolumeSeedSource::Snapshot(snapshot) => {
+                // open the snapshot lvol through the uri which its node reports for it
+                let lvol = registry
+                    .get_node_wrapper(node)
+                    .await?
+                    .fetch_pool_replicas(&snapshot.pool)
+                    .await?
+                    .into_iter()
+                    .find(|lvol| lvol.uuid == snapshot.uuid)
+                    .context(errors::NotFound {
+                        kind: ResourceKind::Snapshot,
+                        id: snapshot.name.clone(),
+                    })?;
+                NexusChild::Uri(ChildUri::from(lvol.uri))
+            }
+        };
+        self.create_nexus(
+            registry,
+            &CreateNexus::new(
+                node,
+                &volume.last_nexus_id.clone().unwrap_or_else(NexusId::new),
+                size,
+                &[child],
+                true,
+                Some(&volume.uuid),
+                None,
+            ),
+            mode,
+        )
+        .await
+    }
+
+    /// The source replicas of the seeding are opened locally by the seed nexus, which unshares
+    /// them, so share them again once the seed nexus is gone if they were shared before.
+    async fn restore_seed_sources(
+        &self,
+        registry: &Registry,
+        volume: &VolumeSpec,
+        seeding: &VolumeSeeding,
+        mode: OperationMode,
+    ) {
+        for id in &seeding.reshare {
+            let replica = match registry.get_replica(id).await {
+                Ok(replica) if replica.share == Protocol::None => replica,
+                _ => continue,
+            };
+            match self.share_replica(registry, &(&replica).into(), mode).await {
+                Ok(_) | Err(SvcError::AlreadyShared { .. }) => {}
+                Err(error) => volume.warn(&format!(
+                    "Failed to share the source replica '{}' again, error: {}",
+                    replica.uuid,
+                    error.full_string()
+                )),
+            }
+        }
+    }
+
+    /// Add the `replicas` being seeded to the seed `nexus`, which rebuilds them.
+    async fn add_seed_replicas(
+        &self,
+        registry: &Registry,
+        nexus: &Nexus,
+        replicas: &[ReplicaId],
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        for replica in replicas {
+            let replica = registry.get_replica(replica).await?;
+            let uri = self
+                .make_replica_accessible(registry, &replica, &nexus.node, mode)
+                .await?;
+            self.add_nexus_replica(
+                registry,
+                &AddNexusReplica {
+                    node: nexus.node.clone(),
+                    nexus: nexus.uuid.clone(),
+                    replica: ReplicaUri::new(&replica.uuid, &uri),
+                    auto_rebuild: true,
+                },
+                mode,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Seed the new `replica` of the unpublished `volume`, which replaces the `replaced` replica,
+    /// with the content of its other healthy replicas, since it can't be rebuilt by the volume
+    /// target.
+    /// The seeding is recorded in the volume spec and carried out by the volume reconciler, see
+    /// `seed_volume`, which removes the `replaced` replica once it's complete.
+    /// The caller must hold the operation guard of the volume.
+    pub(crate) async fn seed_unpublished_replica(
+        &self,
+        registry: &Registry,
+        volume_spec: &Arc<Mutex<VolumeSpec>>,
+        replica: &Replica,
+        replaced: &ReplicaId,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        let uuid = volume_spec.lock().uuid.clone();
+        let mut reshare = vec![];
+        for spec in self.get_volume_replicas(&uuid) {
+            let id = spec.lock().uuid.clone();
+            if id == replica.uuid {
+                continue;
+            }
+            if matches!(registry.get_replica(&id).await, Ok(state) if state.share != Protocol::None)
+            {
+                reshare.push(id);
+            }
+        }
+        let seeding = VolumeSeeding {
+            replicas: vec![replica.uuid.clone()],
+            replaces: Some(replaced.clone()),
+            reshare,
+        };
+
+        let state = registry.get_volume_state(&uuid).await?;
+        let (spec_clone, _guard) = SpecOperations::start_update(
+            registry,
+            volume_spec,
+            &state,
+            VolumeOperation::Seed(seeding),
+            mode,
+        )
+        .await?;
+        SpecOperations::complete_update(registry, Ok(()), volume_spec.clone(), spec_clone).await
+    }
+
     /// Destroy a volume based on the given `DestroyVolume` request.
     /// Volume destruction will succeed even if the nexus or replicas cannot be destroyed (i.e. due
     /// to an inaccessible node). In this case the resources will be destroyed by the garbage
     /// collector at a later time.
+    /// The volume can't be destroyed while it has snapshots, which must be destroyed first, nor
+    /// while it's being cloned.
     pub(crate) async fn destroy_volume(
         &self,
         registry: &Registry,
@@ -417,6 +1016,23 @@ impl ResourceSpecsLocked {
     ) -> Result<(), SvcError> {
         let volume = self.get_locked_volume(&request.uuid);
         if let Some(volume) = &volume {
+            let snapshots = self
+                .get_snapshots()
+                .iter()
+                .filter(|s| s.volume == request.uuid)
+                .count();
+            if snapshots > 0 {
+                return Err(SvcError::VolumeHasSnapshots {
+                    vol_id: request.uuid.to_string(),
+                    count: snapshots,
+                });
+            }
+            if self.seeding_source(&VolumeContentSource::Volume(request.uuid.clone())) {
+                return Err(SvcError::InUse {
+                    kind: ResourceKind::Volume,
+                    id: request.uuid.to_string(),
+                });
+            }
             SpecOperations::start_destroy(volume, registry, false, mode).await?;
 
             let nexuses = self.get_volume_nexuses(&request.uuid);
@@ -462,7 +1078,9 @@ impl ResourceSpecsLocked {
                 }
             }
 
-            SpecOperations::complete_destroy(Ok(()), volume, registry).await
+            SpecOperations::complete_destroy(Ok(()), volume, registry).await?;
+            k8s_events::volume_deleted(&request.uuid);
+            Ok(())
         } else {
             Err(SvcError::VolumeNotFound {
                 vol_id: request.uuid.to_string(),
@@ -538,6 +1156,10 @@ impl ResourceSpecsLocked {
     }
 
     /// Publish a volume based on the given `PublishVolume` request
+    /// When more than one path is requested, a nexus is created on each of the path nodes, the
+    /// first one being the volume target, and each path is then added to the volume.
+    /// All the paths are published under the same operation guard and the volume is unpublished
+    /// if any of them fails, so the volume is never left published through only some paths.
     pub(crate) async fn publish_volume(
         &self,
         registry: &Registry,
@@ -550,18 +1172,53 @@ impl ResourceSpecsLocked {
                 vol_id: request.uuid.to_string(),
             })?;
 
+        let num_paths = request.num_paths.unwrap_or(1);
+        if num_paths < 1 {
+            return Err(SvcError::InvalidArguments {});
+        }
         let state = registry.get_volume_state(&request.uuid).await?;
         let nexus_node = get_volume_target_node(registry, &state, request).await?;
+        let path_nodes = get_volume_path_nodes(
+            registry,
+            &request.uuid,
+            &[nexus_node.clone()],
+            num_paths - 1,
+        )
+        .await?;
+        // a single path volume keeps the default nvmf target configuration
+        let nexus_config = |path: u8| match num_paths {
+            1 => None,
+            _ => Some(NexusNvmfConfig::multipath(path, num_paths)),
+        };
         let nexus_id = NexusId::new();
 
+        // the target and the paths are compound steps of a single operation, unless the caller
+        // is already in the middle of one
+        let (_guard, mode) = match mode {
+            OperationMode::ReconcileStep => (None, mode),
+            _ => {
+                let guard = spec
+                    .operation_guard_wait(OperationMode::ReconcileStart)
+                    .await?;
+                (Some(guard), OperationMode::ReconcileStep)
+            }
+        };
+
         let operation =
             VolumeOperation::Publish((nexus_node.clone(), nexus_id.clone(), request.share));
-        let (spec_clone, _guard) =
+        let (spec_clone, guard) =
             SpecOperations::start_update(registry, &spec, &state, operation, mode).await?;
 
         // Create a Nexus on the requested or auto-selected node
         let result = self
-            .volume_create_nexus(registry, &nexus_node, &nexus_id, &spec_clone, mode)
+            .volume_create_nexus(
+                registry,
+                &nexus_node,
+                &nexus_id,
+                &spec_clone,
+                nexus_config(0),
+                mode,
+            )
             .await;
 
         let nexus =
@@ -576,7 +1233,32 @@ impl ResourceSpecsLocked {
                 .map(|_| nexus);
         }
 
-        SpecOperations::complete_update(registry, result, spec, spec_clone.clone()).await?;
+        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone.clone()).await?;
+        drop(guard);
+
+        for (path, node) in path_nodes.iter().enumerate() {
+            let config = nexus_config(path as u8 + 1);
+            if let Err(error) = self
+                .add_volume_path(registry, &spec, node, request, config, mode)
+                .await
+            {
+                // the volume is either published through all the requested paths or not at all
+                let unpublish = UnpublishVolume::new(&request.uuid, false);
+                if let Err(unpublish_error) =
+                    self.unpublish_volume(registry, &unpublish, mode).await
+                {
+                    spec_clone.error_span(|| {
+                        tracing::error!(
+                            error = %unpublish_error.full_string(),
+                            "Failed to unpublish the volume after failing to add a path on node '{}'",
+                            node
+                        )
+                    });
+                }
+                return Err(error);
+            }
+        }
+
         let volume = registry.get_volume(&request.uuid).await?;
         registry
             .notify_if_degraded(&volume, PollTriggerEvent::VolumeDegraded)
@@ -584,7 +1266,167 @@ impl ResourceSpecsLocked {
         Ok(volume)
     }
 
+    /// Add a path to the published volume, by creating another nexus on the given `node`
+    async fn add_volume_path(
+        &self,
+        registry: &Registry,
+        spec: &Arc<Mutex<VolumeSpec>>,
+        node: &NodeId,
+        request: &PublishVolume,
+        config: Option<NexusNvmfConfig>,
+        mode: OperationMode,
+    ) -> Result<Nexus, SvcError> {
+        let state = registry.get_volume_state(&request.uuid).await?;
+        let nexus_id = NexusId::new();
+
+        let operation = VolumeOperation::AddPath((node.clone(), nexus_id.clone(), request.share));
+        let (spec_clone, _guard) =
+            SpecOperations::start_update(registry, spec, &state, operation, mode).await?;
+
+        let result = self
+            .volume_create_nexus(registry, node, &nexus_id, &spec_clone, config, mode)
+            .await;
+
+        let nexus =
+            SpecOperations::validate_update_step(registry, result, spec, &spec_clone).await?;
+
+        let mut result = Ok(nexus.clone());
+        if let Some(share) = request.share {
+            result = self
+                .share_nexus(registry, &ShareNexus::from((&nexus, None, share)), mode)
+                .await
+                .map(|_| nexus);
+        }
+
+        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone).await
+    }
+
+    /// Fail the target of the published volume over to the given `node`, when the node of the
+    /// current target is no longer online.
+    /// The target nexus is recreated on the new node and shared as before. Its nvmf target
+    /// preempts the persistent reservation of the previous nexus, which fences the previous
+    /// nexus off from the replicas should its node come back.
+    /// The previous nexus is left for the garbage collector to disown and destroy.
+    pub(crate) async fn failover_volume_target(
+        &self,
+        registry: &Registry,
+        spec: &Arc<Mutex<VolumeSpec>>,
+        node: &NodeId,
+        mode: OperationMode,
+    ) -> Result<Volume, SvcError> {
+        let (uuid, target) = {
+            let spec = spec.lock();
+            (spec.uuid.clone(), spec.target.clone())
+        };
+        let target = target.context(errors::VolumeNotPublished {
+            vol_id: uuid.to_string(),
+        })?;
+        let state = registry.get_volume_state(&uuid).await?;
+        let previous_config = self
+            .get_nexus(target.nexus())
+            .and_then(|nexus| nexus.lock().config.clone())
+            .unwrap_or_default();
+        let nexus_id = NexusId::new();
+
+        let operation = VolumeOperation::Failover((node.clone(), nexus_id.clone()));
+        let (spec_clone, _guard) =
+            SpecOperations::start_update(registry, spec, &state, operation, mode).await?;
+
+        let result = self
+            .volume_create_nexus(
+                registry,
+                node,
+                &nexus_id,
+                &spec_clone,
+                Some(NexusNvmfConfig::failover(&previous_config)),
+                mode,
+            )
+            .await;
+
+        let nexus =
+            SpecOperations::validate_update_step(registry, result, spec, &spec_clone).await?;
+
+        let mut result = Ok(nexus.clone());
+        if let Some(share) = target.protocol() {
+            result = self
+                .share_nexus(registry, &ShareNexus::from((&nexus, None, *share)), mode)
+                .await
+                .map(|_| nexus);
+        }
+
+        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone).await?;
+        registry.get_volume(&uuid).await
+    }
+
+    /// Move the further `path` of the published multipath volume to another node, while the host
+    /// keeps using the other paths of the volume.
+    /// The nexus of the new path takes over the nvmf configuration of the moved path, ie: its
+    /// slice of the controller ids and its reservation key, after which the nexus of the moved
+    /// path is destroyed, or otherwise left for the garbage collector to disown and destroy.
+    pub(crate) async fn move_volume_path(
+        &self,
+        registry: &Registry,
+        spec: &Arc<Mutex<VolumeSpec>>,
+        path: &VolumeTarget,
+        mode: OperationMode,
+    ) -> Result<Volume, SvcError> {
+        let (uuid, used_nodes) = {
+            let spec = spec.lock();
+            let used_nodes = spec
+                .targets()
+                .map(|target| target.node().clone())
+                .collect::<Vec<_>>();
+            (spec.uuid.clone(), used_nodes)
+        };
+        let state = registry.get_volume_state(&uuid).await?;
+        let node = get_volume_path_nodes(registry, &uuid, &used_nodes, 1)
+            .await?
+            .remove(0);
+        let config = self
+            .get_nexus(path.nexus())
+            .and_then(|nexus| nexus.lock().config.clone());
+        let nexus_id = NexusId::new();
+
+        let operation =
+            VolumeOperation::MovePath((path.nexus().clone(), node.clone(), nexus_id.clone()));
+        let (spec_clone, _guard) =
+            SpecOperations::start_update(registry, spec, &state, operation, mode).await?;
+
+        let result = self
+            .volume_create_nexus(registry, &node, &nexus_id, &spec_clone, config, mode)
+            .await;
+
+        let nexus =
+            SpecOperations::validate_update_step(registry, result, spec, &spec_clone).await?;
+
+        let mut result = Ok(());
+        if let Some(share) = path.protocol() {
+            result = self
+                .share_nexus(registry, &ShareNexus::from((&nexus, None, *share)), mode)
+                .await
+                .map(|_| ());
+        }
+        if result.is_ok() {
+            if let Err(error) = self
+                .destroy_volume_target(registry, path, false, mode)
+                .await
+            {
+                spec_clone.warn_span(|| {
+                    tracing::warn!(
+                        nexus.uuid = %path.nexus(),
+                        error = %error.full_string(),
+                        "Failed to destroy the nexus of the moved path"
+                    )
+                });
+            }
+        }
+
+        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone).await?;
+        registry.get_volume(&uuid).await
+    }
+
     /// Unpublish a volume based on the given `UnpublishVolume` request
+    /// The nexuses of all the paths of the volume are destroyed.
     pub(crate) async fn unpublish_volume(
         &self,
         registry: &Registry,
@@ -602,8 +1444,30 @@ impl ResourceSpecsLocked {
             SpecOperations::start_update(registry, &spec, &state, VolumeOperation::Unpublish, mode)
                 .await?;
 
-        let volume_target = spec_clone.target.as_ref().expect("already validated");
-        let result = match self.get_nexus(volume_target.nexus()) {
+        let mut result = Ok(());
+        for volume_target in spec_clone.targets() {
+            if let Err(error) = self
+                .destroy_volume_target(registry, volume_target, request.force(), mode)
+                .await
+            {
+                result = Err(error);
+            }
+        }
+
+        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone.clone()).await?;
+        registry.get_volume(&request.uuid).await
+    }
+
+    /// Destroy the nexus of the given volume target
+    /// When `force`'d, the nexus is forgotten if its node is not online.
+    async fn destroy_volume_target(
+        &self,
+        registry: &Registry,
+        volume_target: &VolumeTarget,
+        force: bool,
+        mode: OperationMode,
+    ) -> Result<(), SvcError> {
+        match self.get_nexus(volume_target.nexus()) {
             None => Ok(()),
             Some(nexus_spec) => {
                 let nexus_clone = nexus_spec.lock().clone();
@@ -613,7 +1477,7 @@ impl ResourceSpecsLocked {
                     .await
                 {
                     Ok(_) => Ok(()),
-                    Err(error) if !request.force() => Err(error),
+                    Err(error) if !force => Err(error),
                     Err(error) => {
                         let node_online = match registry.get_node_wrapper(&nexus_clone.node).await {
                             Ok(node) => {
@@ -633,10 +1497,7 @@ impl ResourceSpecsLocked {
                     }
                 }
             }
-        };
-
-        SpecOperations::complete_update(registry, result, spec.clone(), spec_clone.clone()).await?;
-        registry.get_volume(&request.uuid).await
+        }
     }
 
     /// Create a replica for the given volume using the provided list of candidates in order
@@ -718,7 +1579,8 @@ impl ResourceSpecsLocked {
         Ok(created_replicas)
     }
 
-    /// Add the given replica to the nexus of the given volume
+    /// Add the given replica to the target nexus of the given volume
+    /// The replicas of a volume published through multiple paths cannot be changed.
     async fn add_replica_to_volume(
         &self,
         registry: &Registry,
@@ -766,8 +1628,8 @@ impl ResourceSpecsLocked {
         registry.get_volume(&state.uuid).await
     }
 
-    /// Remove a replica from all nexuses for the given volume
-    /// Only volumes with 1 nexus are currently supported
+    /// Remove a replica from the target nexus of the given volume
+    /// The replicas of a volume published through multiple paths cannot be changed.
     pub(crate) async fn remove_volume_child_candidate(
         &self,
         spec_clone: &VolumeSpec,
@@ -920,6 +1782,7 @@ impl ResourceSpecsLocked {
         target_node: &NodeId,
         nexus_id: &NexusId,
         vol_spec: &VolumeSpec,
+        config: Option<NexusNvmfConfig>,
         mode: OperationMode,
     ) -> Result<Nexus, SvcError> {
         let children = get_healthy_volume_replicas(vol_spec, target_node, registry).await?;
@@ -962,7 +1825,7 @@ impl ResourceSpecsLocked {
                     &nexus_replicas,
                     true,
                     Some(&vol_spec.uuid),
-                    None,
+                    config,
                 ),
                 mode,
             )
@@ -1418,7 +2281,7 @@ async fn get_volume_target_node(
             for locked_node in nodes {
                 let node = locked_node.read().await;
                 // todo: use other metrics in order to make the "best" choice
-                if node.is_online() {
+                if node.is_online() && !registry.specs().node_cordoned(node.id()) {
                     return Ok(node.id().clone());
                 }
             }
@@ -1440,6 +2303,50 @@ async fn get_volume_target_node(
     }
 }
 
+/// Select `count` online and uncordoned nodes, other than the `used_nodes` of the existing
+/// targets, for the additional paths of the `volume`
+/// The nodes which hold a replica of the volume are preferred, as their nexus has local access
+/// to it.
+async fn get_volume_path_nodes(
+    registry: &Registry,
+    volume: &VolumeId,
+    used_nodes: &[NodeId],
+    count: u8,
+) -> Result<Vec<NodeId>, SvcError> {
+    let mut path_nodes = vec![];
+    if count == 0 {
+        return Ok(path_nodes);
+    }
+    let mut replica_nodes = vec![];
+    for replica in registry.specs().get_volume_replicas(volume) {
+        let uuid = replica.lock().uuid.clone();
+        if let Ok(replica) = registry.get_replica(&uuid).await {
+            replica_nodes.push(replica.node);
+        }
+    }
+    for locked_node in registry.get_node_wrappers().await {
+        let node = locked_node.read().await;
+        if !used_nodes.contains(node.id())
+            && node.is_online()
+            && !registry.specs().node_cordoned(node.id())
+        {
+            path_nodes.push(node.id().clone());
+        }
+    }
+    if path_nodes.len() < count as usize {
+        return Err(SvcError::NotEnoughResources {
+            source: NotEnough::OfNexuses {
+                have: path_nodes.len() as u64 + 1,
+                need: count as u64 + 1,
+            },
+        });
+    }
+    // the sort is stable so the nodes otherwise keep the registry order
+    path_nodes.sort_by_key(|node| !replica_nodes.contains(node));
+    path_nodes.truncate(count as usize);
+    Ok(path_nodes)
+}
+
 #[async_trait::async_trait]
 impl SpecOperations for VolumeSpec {
     type Create = CreateVolume;
@@ -1454,9 +2361,26 @@ impl SpecOperations for VolumeSpec {
         state: &Self::State,
         operation: Self::UpdateOp,
     ) -> Result<(), SvcError> {
+        if self.seeding.is_some()
+            && !matches!(
+                operation,
+                VolumeOperation::Seeded | VolumeOperation::ReplicaMoved
+            )
+        {
+            // the volume can't be used until its content is seeded onto its replicas
+            return Err(SvcError::NotReady {
+                kind: self.kind(),
+                id: self.uuid(),
+            });
+        }
         if !matches!(
             &operation,
-            VolumeOperation::Publish(..) | VolumeOperation::Unpublish
+            VolumeOperation::Publish(..)
+                | VolumeOperation::AddPath(..)
+                | VolumeOperation::MovePath(..)
+                | VolumeOperation::Failover(..)
+                | VolumeOperation::Unpublish
+                | VolumeOperation::ReplicaMoved
         ) {
             // don't attempt to modify the volume parameters if the nexus target is not "stable"
             if self.target.is_some() != state.target.is_some() {
@@ -1468,6 +2392,19 @@ impl SpecOperations for VolumeSpec {
         }
 
         match &operation {
+            // the share protocol of the paths of a multipath volume cannot be changed and its
+            // target cannot be failed over, the volume must be republished instead
+            // nor can its replicas be changed, as every path nexus must have the same children
+            VolumeOperation::Share(_)
+            | VolumeOperation::Unshare
+            | VolumeOperation::Failover(_)
+            | VolumeOperation::SetReplica(_)
+            | VolumeOperation::RemoveUnusedReplica(_)
+            | VolumeOperation::MoveReplica(_)
+                if !self.paths.is_empty() =>
+            {
+                Err(SvcError::MultipleNexuses {})
+            }
             VolumeOperation::Share(_) => match &self.target {
                 None => Err(SvcError::VolumeNotPublished {
                     vol_id: self.uuid(),
@@ -1499,10 +2436,44 @@ impl SpecOperations for VolumeSpec {
                         node: target.node().to_string(),
                         protocol: format!("{:?}", target.protocol()),
                     })
+                } else if registry
+                    .specs()
+                    .seeding_source(&VolumeContentSource::Volume(self.uuid.clone()))
+                {
+                    // the content of the volume must not change while it's being cloned
+                    Err(SvcError::InUse {
+                        kind: self.kind(),
+                        id: self.uuid(),
+                    })
                 } else {
                     Ok(())
                 }
             }
+            VolumeOperation::AddPath((node, _, _)) => match &self.target {
+                None => Err(SvcError::VolumeNotPublished {
+                    vol_id: self.uuid(),
+                }),
+                Some(_) if self.targets().any(|target| target.node() == node) => {
+                    Err(SvcError::InvalidArguments {})
+                }
+                Some(_) => Ok(()),
+            },
+            VolumeOperation::MovePath((previous, node, _)) => {
+                if !self.paths.iter().any(|path| path.nexus() == previous)
+                    || self.targets().any(|target| target.node() == node)
+                {
+                    Err(SvcError::InvalidArguments {})
+                } else {
+                    Ok(())
+                }
+            }
+            VolumeOperation::Failover((node, _)) => match &self.target {
+                None => Err(SvcError::VolumeNotPublished {
+                    vol_id: self.uuid(),
+                }),
+                Some(target) if target.node() == node => Err(SvcError::InvalidArguments {}),
+                Some(_) => Ok(()),
+            },
             VolumeOperation::Unpublish if self.target.is_none() => {
                 Err(SvcError::VolumeNotPublished {
                     vol_id: self.uuid(),
@@ -1585,6 +2556,29 @@ impl SpecOperations for VolumeSpec {
                 }
             }
 
+            VolumeOperation::Seed(_) => match &self.target {
+                Some(target) => Err(SvcError::VolumeAlreadyPublished {
+                    vol_id: self.uuid(),
+                    node: target.node().to_string(),
+                    protocol: format!("{:?}", target.protocol()),
+                }),
+                None => Ok(()),
+            },
+            VolumeOperation::Seeded if self.seeding.is_none() => Err(SvcError::InvalidArguments {}),
+            VolumeOperation::Seeded => Ok(()),
+
+            VolumeOperation::MoveReplica(_) if self.replica_move.is_some() => {
+                Err(SvcError::NotReady {
+                    kind: self.kind(),
+                    id: self.uuid(),
+                })
+            }
+            VolumeOperation::MoveReplica(_) => Ok(()),
+            VolumeOperation::ReplicaMoved if self.replica_move.is_none() => {
+                Err(SvcError::InvalidArguments {})
+            }
+            VolumeOperation::ReplicaMoved => Ok(()),
+
             VolumeOperation::Create => unreachable!(),
             VolumeOperation::Destroy => unreachable!(),
         }?;
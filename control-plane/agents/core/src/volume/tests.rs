@@ -6,9 +6,10 @@ use common_lib::{
     store::etcd::Etcd,
     types::v0::{
         message_bus::{
-            Child, ChildState, CreateReplica, CreateVolume, DestroyVolume, Filter, GetNexuses,
-            GetNodes, GetReplicas, GetVolumes, Nexus, NodeId, PublishVolume, SetVolumeReplica,
-            ShareVolume, Topology, UnpublishVolume, UnshareVolume, Volume, VolumeShareProtocol,
+            Child, ChildState, CreateReplica, CreateVolume, CreateVolumeSnapshot, DestroyVolume,
+            DestroyVolumeSnapshot, Filter, GetNexuses, GetNodes, GetReplicas, GetVolumes, Nexus,
+            NodeId, PublishVolume, SetVolumeReplica, ShareVolume, Topology, UnpublishVolume,
+            UnshareVolume, Volume, VolumeContentSource, VolumePolicy, VolumeShareProtocol,
             VolumeState, VolumeStatus,
         },
         openapi::apis::{StatusCode, Uuid},
@@ -26,8 +27,9 @@ use common_lib::{
     mbus_api::TimeoutOptions,
     types::v0::{
         message_bus::{
-            ChannelVs, ChildUri, CreateNexus, DestroyReplica, GetSpecs, Liveness, NexusId,
-            ReplicaId, ReplicaOwners, VolumeId,
+            ChannelVs, ChildUri, CordonNode, CreateNexus, DestroyReplica, GetSpecs, Liveness,
+            NexusId, NexusNvmfConfig, NexusStatus, ReplicaId, ReplicaOwners, SnapshotId,
+            UncordonNode, VolumeId,
         },
         openapi::{models, models::NodeStatus, tower::client::Error},
         store::{definitions::StorableObject, volume::VolumeSpec},
@@ -65,12 +67,29 @@ async fn volume() {
 async fn test_volume(cluster: &Cluster) {
     smoke_test(cluster).await;
     publishing_test(cluster).await;
+    multipath_test(cluster).await;
     replica_count_test(cluster).await;
     nexus_persistence_test(cluster).await;
 }
 
 const RECONCILE_TIMEOUT_SECS: u64 = 7;
 
+#[tokio::test]
+async fn volume_clone() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(false)
+        .with_agents(vec!["core"])
+        .with_mayastors(3)
+        .with_pools(1)
+        .with_cache_period("1s")
+        .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+        .build()
+        .await
+        .unwrap();
+
+    clone_test(&cluster).await;
+}
+
 #[tokio::test]
 async fn hotspare() {
     let cluster = ClusterBuilder::builder()
@@ -113,6 +132,44 @@ async fn volume_nexus_reconcile() {
     missing_nexus_reconcile(&cluster).await;
 }
 
+#[tokio::test]
+async fn target_failover() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(true)
+        .with_agents(vec!["core"])
+        .with_mayastors(3)
+        .with_tmpfs_pool(POOL_SIZE_BYTES)
+        .with_cache_period("1s")
+        .with_node_deadline("2s")
+        .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+        .with_target_failover_grace_period(Duration::from_secs(2))
+        .build()
+        .await
+        .unwrap();
+    let nodes = GetNodes::default().request().await.unwrap();
+    tracing::info!("Nodes: {:?}", nodes);
+
+    offline_target_failover(&cluster).await;
+    restarted_target_failover(&cluster).await;
+}
+
+#[tokio::test]
+async fn replica_rebalance() {
+    let cluster = ClusterBuilder::builder()
+        .with_rest(false)
+        .with_agents(vec!["core"])
+        .with_mayastors(3)
+        .with_pools(1)
+        .with_cache_period("1s")
+        .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+        .with_rebalance(0, Duration::from_secs(1))
+        .build()
+        .await
+        .unwrap();
+
+    restarted_replica_rebalance(&cluster).await;
+}
+
 #[tokio::test]
 async fn garbage_collection() {
     let cluster = ClusterBuilder::builder()
@@ -149,6 +206,7 @@ async fn unused_nexus_reconcile(cluster: &Cluster) {
             &volume.spec.uuid,
             cluster.node(0).as_str(),
             models::VolumeShareProtocol::Nvmf,
+            None,
         )
         .await
         .unwrap();
@@ -230,6 +288,7 @@ async fn unused_reconcile(cluster: &Cluster) {
             &volume.spec.uuid,
             nexus_node.id.as_str(),
             models::VolumeShareProtocol::Nvmf,
+            None,
         )
         .await
         .unwrap();
@@ -248,6 +307,7 @@ async fn unused_reconcile(cluster: &Cluster) {
             &volume.spec.uuid,
             unused_node.id.as_str(),
             models::VolumeShareProtocol::Nvmf,
+            None,
         )
         .await
         .unwrap();
@@ -329,6 +389,7 @@ async fn missing_nexus_reconcile(cluster: &Cluster) {
             &volume.spec().uuid,
             cluster.node(0).as_str(),
             models::VolumeShareProtocol::Nvmf,
+            None,
         )
         .await
         .unwrap();
@@ -352,6 +413,246 @@ async fn missing_nexus_reconcile(cluster: &Cluster) {
     volumes_api.del_volume(&volume_id).await.unwrap();
 }
 
+/// Publishes a volume with the target failover policy and kills the node of its target.
+/// Once the grace period expires the target is recreated on another node holding a replica.
+async fn offline_target_failover(cluster: &Cluster) {
+    let volume = CreateVolume {
+        uuid: "6f5d2c1e-8a4b-4e3f-9b2a-1c7d3e5f7a90".try_into().unwrap(),
+        size: 5242880,
+        replicas: 2,
+        policy: VolumePolicy {
+            self_heal: true,
+            target_failover: true,
+        },
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    let volume_id = volume.spec().uuid;
+
+    let volume = PublishVolume::new(
+        volume_id.clone(),
+        Some(cluster.node(0)),
+        Some(VolumeShareProtocol::Nvmf),
+    )
+    .request()
+    .await
+    .unwrap();
+    let target = volume.spec().target.unwrap();
+    tracing::info!("Published Volume: {:?}", volume);
+
+    cluster
+        .composer()
+        .kill(target.node().as_str())
+        .await
+        .unwrap();
+
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS * 2);
+    let start = std::time::Instant::now();
+    let target = loop {
+        let volume = get_volume(&volume.state()).await;
+        match volume.spec().target {
+            Some(new_target) if new_target.node() != target.node() => break new_target,
+            _ => {}
+        }
+        if std::time::Instant::now() > (start + timeout) {
+            panic!("Timeout waiting for the volume target to fail over");
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    };
+    assert_eq!(target.protocol(), Some(&VolumeShareProtocol::Nvmf));
+
+    let nexuses = GetNexuses::default().request().await.unwrap().0;
+    assert!(nexuses
+        .iter()
+        .any(|n| &n.uuid == target.nexus() && &n.node == target.node()));
+
+    cluster
+        .composer()
+        .start(cluster.node(0).as_str())
+        .await
+        .unwrap();
+    wait_for_node_online(cluster, &cluster.node(0)).await;
+
+    DestroyVolume { uuid: volume_id }
+        .request()
+        .await
+        .expect("Should be able to destroy the volume");
+}
+
+/// The target of a volume should fail over even when the core agent restarts while the node of
+/// the target is offline, as the node never registers with the restarted core agent.
+async fn restarted_target_failover(cluster: &Cluster) {
+    let volume = CreateVolume {
+        uuid: "0c8a3b7e-2f61-4d9a-8e5c-7b4f1a2d6e93".try_into().unwrap(),
+        size: 5242880,
+        replicas: 2,
+        policy: VolumePolicy {
+            self_heal: true,
+            target_failover: true,
+        },
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    let volume_id = volume.spec().uuid;
+
+    let volume = PublishVolume::new(
+        volume_id.clone(),
+        Some(cluster.node(1)),
+        Some(VolumeShareProtocol::Nvmf),
+    )
+    .request()
+    .await
+    .unwrap();
+    let target = volume.spec().target.unwrap();
+    tracing::info!("Published Volume: {:?}", volume);
+
+    cluster
+        .composer()
+        .kill(target.node().as_str())
+        .await
+        .unwrap();
+    cluster.restart_core().await;
+    Liveness::default()
+        .request_on_ext(
+            ChannelVs::Volume,
+            TimeoutOptions::default()
+                .with_max_retries(10)
+                .with_timeout(Duration::from_millis(200))
+                .with_timeout_backoff(Duration::from_millis(50)),
+        )
+        .await
+        .expect("Should have restarted by now");
+
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS * 2);
+    let start = std::time::Instant::now();
+    let target = loop {
+        let volume = get_volume(&volume.state()).await;
+        match volume.spec().target {
+            Some(new_target) if new_target.node() != target.node() => break new_target,
+            _ => {}
+        }
+        if std::time::Instant::now() > (start + timeout) {
+            panic!("Timeout waiting for the volume target to fail over after the restart");
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    };
+    assert_eq!(target.protocol(), Some(&VolumeShareProtocol::Nvmf));
+
+    cluster
+        .composer()
+        .start(cluster.node(1).as_str())
+        .await
+        .unwrap();
+    wait_for_node_online(cluster, &cluster.node(1)).await;
+
+    DestroyVolume { uuid: volume_id }
+        .request()
+        .await
+        .expect("Should be able to destroy the volume");
+}
+
+/// Given published volumes with all their replicas on the same pool
+/// When the core agent is restarted while a replica is being moved to an empty pool
+/// Then the move should be completed after the restart, without leaving an extra replica
+async fn restarted_replica_rebalance(cluster: &Cluster) {
+    let overloaded = cluster.node(0);
+    let others = [cluster.node(1), cluster.node(2)];
+
+    // place the replicas of the volumes on the same pool
+    for node in &others {
+        CordonNode::new(node).request().await.unwrap();
+    }
+    let volumes: Vec<VolumeId> = vec![
+        "5a1d7c3e-9b24-4f6e-8d1a-3c7e2b9f4a61".try_into().unwrap(),
+        "6b2e8d4f-0c35-4a7f-9e2b-4d8f3c0a5b72".try_into().unwrap(),
+    ];
+    for uuid in &volumes {
+        CreateVolume {
+            uuid: uuid.clone(),
+            size: 5242880,
+            replicas: 1,
+            ..Default::default()
+        }
+        .request()
+        .await
+        .unwrap();
+        PublishVolume::new(uuid.clone(), Some(overloaded.clone()), None)
+            .request()
+            .await
+            .unwrap();
+    }
+    for node in &others {
+        UncordonNode::new(node).request().await.unwrap();
+    }
+
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS);
+    let start = std::time::Instant::now();
+    let moved = loop {
+        let specs = GetSpecs::default().request().await.unwrap();
+        if let Some(replica) = specs.volumes.iter().find_map(|v| v.replica_move.clone()) {
+            break replica;
+        }
+        if std::time::Instant::now() > (start + timeout) {
+            panic!("Timeout waiting for a replica move to start");
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    cluster.restart_core().await;
+    Liveness::default()
+        .request_on_ext(
+            ChannelVs::Volume,
+            TimeoutOptions::default()
+                .with_max_retries(10)
+                .with_timeout(Duration::from_millis(200))
+                .with_timeout_backoff(Duration::from_millis(50)),
+        )
+        .await
+        .expect("Should have restarted by now");
+
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS * 2);
+    let start = std::time::Instant::now();
+    let specs = loop {
+        let specs = GetSpecs::default().request().await.unwrap();
+        if specs.volumes.iter().all(|v| v.replica_move.is_none())
+            && specs.replicas.iter().all(|r| r.uuid != moved)
+        {
+            break specs;
+        }
+        if std::time::Instant::now() > (start + timeout) {
+            panic!("Timeout waiting for the replica move to complete after the restart");
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    };
+
+    let overloaded_pool = cluster.pool(0, 0);
+    assert_eq!(
+        specs
+            .replicas
+            .iter()
+            .filter(|r| r.pool == overloaded_pool)
+            .count(),
+        1,
+        "Only one replica should be left on the overloaded pool"
+    );
+    for uuid in &volumes {
+        assert_eq!(
+            specs
+                .replicas
+                .iter()
+                .filter(|r| r.owners.owned_by(uuid))
+                .count(),
+            1,
+            "The volume should be left with a single replica"
+        );
+        DestroyVolume::new(uuid).request().await.unwrap();
+    }
+}
+
 /// Wait until the specified nexus state option matches the requested `state`
 async fn wait_till_nexus_state(
     cluster: &Cluster,
@@ -842,6 +1143,27 @@ async fn existing_replicas(volume_id: &VolumeId) -> usize {
         .count()
 }
 
+/// Wait for the replicas of the volume to be seeded with its content
+async fn wait_till_volume_seeded(volume: &VolumeId) {
+    let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS);
+    let start = std::time::Instant::now();
+    loop {
+        let volumes = GetVolumes::new(volume).request().await.unwrap();
+        let spec = volumes.into_inner().first().unwrap().spec();
+        if spec.seeding.is_none() {
+            return;
+        }
+
+        if std::time::Instant::now() > (start + timeout) {
+            panic!(
+                "Timeout waiting for the volume to be seeded, current: '{:?}'",
+                spec.seeding
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
 /// Wait for a volume to reach the provided status
 async fn wait_till_volume_status(cluster: &Cluster, volume: &Uuid, status: models::VolumeStatus) {
     let timeout = Duration::from_secs(RECONCILE_TIMEOUT_SECS);
@@ -900,6 +1222,8 @@ async fn nexus_persistence_test_iteration(
                 models::ExplicitNodeTopology::new(allowed_nodes, preferred_nodes),
             )),
             None,
+            None,
+            None,
         ))),
         ..Default::default()
     }
@@ -913,6 +1237,7 @@ async fn nexus_persistence_test_iteration(
         // publish it on the remote first, to complicate things
         target_node: Some(remote.clone()),
         share: None,
+        num_paths: None,
     }
     .request()
     .await
@@ -984,6 +1309,7 @@ async fn nexus_persistence_test_iteration(
         uuid: volume_state.uuid.clone(),
         target_node: Some(local.clone()),
         share: None,
+        num_paths: None,
     }
     .request()
     .await
@@ -1053,6 +1379,7 @@ async fn publishing_test(cluster: &Cluster) {
         uuid: volume.spec().uuid.clone(),
         target_node: None,
         share: None,
+        num_paths: None,
     }
     .request()
     .await
@@ -1101,6 +1428,7 @@ async fn publishing_test(cluster: &Cluster) {
         uuid: volume_state.uuid.clone(),
         target_node: None,
         share: None,
+        num_paths: None,
     }
     .request()
     .await
@@ -1115,6 +1443,7 @@ async fn publishing_test(cluster: &Cluster) {
         uuid: volume_state.uuid.clone(),
         target_node: Some(cluster.node(0)),
         share: Some(VolumeShareProtocol::Iscsi),
+        num_paths: None,
     }
     .request()
     .await
@@ -1145,6 +1474,7 @@ async fn publishing_test(cluster: &Cluster) {
         uuid: volume_state.uuid.clone(),
         target_node: None,
         share: Some(VolumeShareProtocol::Iscsi),
+        num_paths: None,
     }
     .request()
     .await
@@ -1159,6 +1489,7 @@ async fn publishing_test(cluster: &Cluster) {
         uuid: volume_state.uuid.clone(),
         target_node: Some(cluster.node(1)),
         share: None,
+        num_paths: None,
     }
     .request()
     .await
@@ -1225,6 +1556,122 @@ async fn publishing_test(cluster: &Cluster) {
         .is_empty());
 }
 
+async fn multipath_test(cluster: &Cluster) {
+    let volume = CreateVolume {
+        uuid: "a5e1b6a4-4d0c-4c46-9f0e-7d7b4d4b2a37".try_into().unwrap(),
+        size: 5242880,
+        replicas: 2,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    let volume_id = volume.spec().uuid;
+
+    let error = PublishVolume::new(
+        volume_id.clone(),
+        Some(cluster.node(0)),
+        Some(VolumeShareProtocol::Nvmf),
+    )
+    .with_num_paths(Some(4))
+    .request()
+    .await
+    .expect_err("Only 3 nodes are available for the paths");
+    assert!(matches!(error.kind, ReplyErrorKind::ResourceExhausted));
+    assert!(GetNexuses::default().request().await.unwrap().0.is_empty());
+
+    let volume = PublishVolume::new(
+        volume_id.clone(),
+        Some(cluster.node(0)),
+        Some(VolumeShareProtocol::Nvmf),
+    )
+    .with_num_paths(Some(2))
+    .request()
+    .await
+    .unwrap();
+    tracing::info!("Published Volume: {:?}", volume);
+
+    let spec = volume.spec();
+    assert_eq!(spec.num_paths(), 2);
+    assert_eq!(spec.target.as_ref().unwrap().node(), &cluster.node(0));
+    assert_ne!(spec.paths[0].node(), &cluster.node(0));
+    assert_eq!(spec.paths[0].protocol(), Some(&VolumeShareProtocol::Nvmf));
+
+    let nexuses = GetNexuses::default().request().await.unwrap().0;
+    assert_eq!(nexuses.len(), 2);
+    assert!(spec.targets().all(|target| nexuses
+        .iter()
+        .any(|n| &n.uuid == target.nexus() && &n.node == target.node())));
+
+    let specs = GetSpecs::default().request().await.unwrap();
+    // the path is added on a node which holds a replica, if there's one besides the target node
+    let replica_nodes = specs
+        .replicas
+        .iter()
+        .filter(|r| r.owners.owned_by(&volume_id))
+        .filter_map(|r| specs.pools.iter().find(|p| p.id == r.pool))
+        .map(|p| p.node.clone())
+        .filter(|node| node != &cluster.node(0))
+        .collect::<Vec<_>>();
+    if !replica_nodes.is_empty() {
+        assert!(replica_nodes.contains(spec.paths[0].node()));
+    }
+
+    // each path has its own slice of the controller ids and its own reservation key, and none
+    // of them preempts another
+    let configs = spec
+        .targets()
+        .map(|target| {
+            let nexus = specs
+                .nexuses
+                .iter()
+                .find(|n| &n.uuid == target.nexus())
+                .expect("Each path should have a nexus spec");
+            nexus
+                .config
+                .clone()
+                .expect("The nexus of a path should have an nvmf configuration")
+        })
+        .collect::<Vec<_>>();
+    for (path, config) in configs.iter().enumerate() {
+        assert_eq!(config, &NexusNvmfConfig::multipath(path as u8, 2));
+        assert_eq!(config.resv_key(), path as u64 + 1);
+        assert_eq!(config.preempt_key(), 0);
+    }
+    assert!(configs[0].max_cntl_id() < configs[1].min_cntl_id());
+
+    // every path nexus must have the same children, so the replicas can't be changed
+    let error = SetVolumeReplica {
+        uuid: volume_id.clone(),
+        replicas: 3,
+    }
+    .request()
+    .await
+    .expect_err("The replicas of a multipath volume cannot be changed");
+    assert_eq!(error.kind, ReplyErrorKind::InvalidArgument);
+    let children = GetNexuses::default()
+        .request()
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .map(|n| n.children.len())
+        .collect::<Vec<_>>();
+    assert_eq!(children, vec![2, 2]);
+
+    let volume = UnpublishVolume::new(&volume_id, false)
+        .request()
+        .await
+        .unwrap();
+    assert_eq!(volume.spec().num_paths(), 0);
+    assert!(GetNexuses::default().request().await.unwrap().0.is_empty());
+
+    DestroyVolume { uuid: volume_id }
+        .request()
+        .await
+        .expect("Should be able to destroy the volume");
+}
+
 async fn get_volume(volume: &VolumeState) -> Volume {
     let request = GetVolumes {
         filter: Filter::Volume(volume.uuid.clone()),
@@ -1463,3 +1910,190 @@ async fn smoke_test(cluster: &Cluster) {
         .0
         .is_empty());
 }
+
+async fn clone_test(cluster: &Cluster) {
+    let source = CreateVolume {
+        uuid: VolumeId::try_from("4d2e6f1a-3b5c-4a7d-8e9f-0a1b2c3d4e5f").unwrap(),
+        size: 5242880,
+        replicas: 1,
+        ..Default::default()
+    }
+    .request()
+    .await
+    .unwrap();
+    let clone = |size: u64, source: VolumeContentSource| CreateVolume {
+        uuid: VolumeId::try_from("5e3f7a2b-4c6d-4b8e-9fa0-1b2c3d4e5f60").unwrap(),
+        size,
+        replicas: 2,
+        source: Some(source),
+        ..Default::default()
+    };
+    let from_source = VolumeContentSource::Volume(source.spec().uuid.clone());
+
+    let error = clone(5242880, VolumeContentSource::Volume(VolumeId::new()))
+        .request()
+        .await
+        .expect_err("The source volume does not exist");
+    assert!(matches!(
+        error,
+        mbus_api::Error::ReplyWithError {
+            source: ReplyError {
+                kind: ReplyErrorKind::NotFound,
+                ..
+            },
+        }
+    ));
+
+    let error = clone(4194304, from_source.clone())
+        .request()
+        .await
+        .expect_err("The clone cannot be smaller than its source");
+    assert!(matches!(
+        error,
+        mbus_api::Error::ReplyWithError {
+            source: ReplyError {
+                kind: ReplyErrorKind::InvalidArgument,
+                resource: ResourceKind::Volume,
+                ..
+            },
+        }
+    ));
+
+    PublishVolume::new(source.spec().uuid.clone(), Some(cluster.node(0)), None)
+        .request()
+        .await
+        .unwrap();
+    let error = clone(5242880, from_source.clone())
+        .request()
+        .await
+        .expect_err("A published volume cannot be cloned");
+    assert!(matches!(
+        error,
+        mbus_api::Error::ReplyWithError {
+            source: ReplyError {
+                kind: ReplyErrorKind::InUse,
+                resource: ResourceKind::Volume,
+                ..
+            },
+        }
+    ));
+    UnpublishVolume::new(&source.spec().uuid, false)
+        .request()
+        .await
+        .unwrap();
+
+    let source_replicas = |source: &Volume| GetReplicas {
+        filter: Filter::Volume(source.spec().uuid),
+    };
+    let shared = source_replicas(&source).request().await.unwrap().0;
+
+    let cloned = clone(8388608, from_source.clone())
+        .request()
+        .await
+        .expect("An unpublished volume can be cloned into a larger volume");
+    assert_eq!(cloned.spec().source, Some(from_source));
+    assert!(
+        cloned.spec().seeding.is_some(),
+        "The clone should be seeded after its creation"
+    );
+    wait_till_volume_seeded(cloned.uuid()).await;
+    let replicas = cluster
+        .grpc_client()
+        .replica()
+        .get(Filter::Volume(cloned.spec().uuid.clone()), None)
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(replicas.len(), 2);
+
+    let nexuses = GetNexuses::default().request().await.unwrap().0;
+    assert!(
+        nexuses.is_empty(),
+        "The seed nexus should have been destroyed"
+    );
+
+    let replicas = source_replicas(&source).request().await.unwrap().0;
+    assert_eq!(
+        replicas.iter().map(|r| &r.share).collect::<Vec<_>>(),
+        shared.iter().map(|r| &r.share).collect::<Vec<_>>(),
+        "The source replicas should be shared as before"
+    );
+    DestroyVolume {
+        uuid: cloned.spec().uuid,
+    }
+    .request()
+    .await
+    .unwrap();
+
+    PublishVolume::new(source.spec().uuid.clone(), Some(cluster.node(0)), None)
+        .request()
+        .await
+        .unwrap();
+    let snapshot_id = SnapshotId::try_from("6f4a8b3c-5d7e-4c9f-a0b1-2c3d4e5f6071").unwrap();
+    CreateVolumeSnapshot::new(&source.spec().uuid, &snapshot_id)
+        .request()
+        .await
+        .unwrap();
+    UnpublishVolume::new(&source.spec().uuid, false)
+        .request()
+        .await
+        .unwrap();
+    let from_snapshot = VolumeContentSource::Snapshot(snapshot_id.clone());
+
+    let error = clone(4194304, from_snapshot.clone())
+        .request()
+        .await
+        .expect_err("The clone cannot be smaller than its source snapshot");
+    assert!(matches!(
+        error,
+        mbus_api::Error::ReplyWithError {
+            source: ReplyError {
+                kind: ReplyErrorKind::InvalidArgument,
+                resource: ResourceKind::Snapshot,
+                ..
+            },
+        }
+    ));
+
+    let cloned = clone(8388608, from_snapshot.clone())
+        .request()
+        .await
+        .expect("A snapshot can be cloned into a larger volume");
+    assert_eq!(cloned.spec().source, Some(from_snapshot));
+    assert!(
+        cloned.spec().seeding.is_some(),
+        "The clone should be seeded after its creation"
+    );
+    wait_till_volume_seeded(cloned.uuid()).await;
+    let replicas = GetReplicas {
+        filter: Filter::Volume(cloned.spec().uuid.clone()),
+    }
+    .request()
+    .await
+    .unwrap()
+    .0;
+    assert_eq!(replicas.len(), 2);
+
+    let nexuses = GetNexuses::default().request().await.unwrap().0;
+    assert!(
+        nexuses.is_empty(),
+        "The seed nexus should have been destroyed"
+    );
+
+    DestroyVolume {
+        uuid: cloned.spec().uuid,
+    }
+    .request()
+    .await
+    .unwrap();
+    DestroyVolumeSnapshot::new(&source.spec().uuid, &snapshot_id)
+        .request()
+        .await
+        .unwrap();
+    DestroyVolume {
+        uuid: source.spec().uuid,
+    }
+    .request()
+    .await
+    .unwrap();
+}
@@ -25,22 +25,38 @@ pub(crate) fn configure(builder: common::Service) -> common::Service {
 #[cfg(test)]
 mod tests {
     use common_lib::{
-        mbus_api::Message,
+        mbus_api::{bus, Message},
         store::etcd::Etcd,
         types::v0::{
-            message_bus::{CreateVolume, Volume, VolumeId, WatchResourceId},
+            message_bus::{
+                CreateVolume, CreateWatch, DeleteWatch, EventResult, GetEvents, Volume, VolumeId,
+                WatchCallback, WatchNotification, WatchOptions, WatchResourceId, WatchType,
+            },
             openapi::models,
             store::definitions::{ObjectKey, Store},
         },
     };
+    use grpc::watch_grpc::{
+        watch_callback_server::{WatchCallback as GrpcWatchCallback, WatchCallbackServer},
+        WatchNotification as GrpcWatchNotification,
+    };
     use once_cell::sync::OnceCell;
     use std::{net::SocketAddr, str::FromStr, time::Duration};
     use testlib::*;
-    use tokio::net::TcpStream;
+    use tokio::{net::TcpStream, sync::mpsc};
+
+    static CALLBACK: OnceCell<mpsc::Sender<WatchNotification>> = OnceCell::new();
+    static FAILED_CALLBACK: OnceCell<mpsc::Sender<()>> = OnceCell::new();
 
-    static CALLBACK: OnceCell<tokio::sync::mpsc::Sender<()>> = OnceCell::new();
+    const CALLBACK_URI: &str = "http://10.1.0.1:8082/test";
+    const FAILED_CALLBACK_URI: &str = "http://10.1.0.1:8082/fail";
+    const GRPC_CALLBACK: &str = "grpc://10.1.0.1:50051";
 
-    async fn setup_watcher() -> (Volume, tokio::sync::mpsc::Receiver<()>) {
+    async fn setup_watcher() -> (
+        Volume,
+        mpsc::Receiver<WatchNotification>,
+        mpsc::Receiver<()>,
+    ) {
         let volume = CreateVolume {
             uuid: VolumeId::new(),
             size: 10 * 1024 * 1024,
@@ -51,19 +67,44 @@ mod tests {
         .await
         .unwrap();
 
-        let (s, r) = tokio::sync::mpsc::channel(1);
+        let (s, r) = mpsc::channel(10);
         CALLBACK.set(s).unwrap();
+        let (failed_s, failed_r) = mpsc::channel(10);
+        FAILED_CALLBACK.set(failed_s).unwrap();
 
-        async fn notify() -> actix_web::HttpResponse {
-            CALLBACK.get().cloned().unwrap().send(()).await.unwrap();
+        async fn notify(
+            notification: actix_web::web::Json<WatchNotification>,
+        ) -> actix_web::HttpResponse {
+            CALLBACK
+                .get()
+                .cloned()
+                .unwrap()
+                .send(notification.into_inner())
+                .await
+                .unwrap();
             actix_web::HttpResponse::Ok().finish()
         }
+        // counts the delivery attempts, none of which succeeds
+        async fn fail() -> actix_web::HttpResponse {
+            FAILED_CALLBACK
+                .get()
+                .cloned()
+                .unwrap()
+                .send(())
+                .await
+                .unwrap();
+            actix_web::HttpResponse::InternalServerError().finish()
+        }
 
         actix_rt::spawn(async move {
             let _ = actix_web::HttpServer::new(|| {
-                actix_web::App::new().service(
-                    actix_web::web::resource("/test").route(actix_web::web::put().to(notify)),
-                )
+                actix_web::App::new()
+                    .service(
+                        actix_web::web::resource("/test").route(actix_web::web::put().to(notify)),
+                    )
+                    .service(
+                        actix_web::web::resource("/fail").route(actix_web::web::put().to(fail)),
+                    )
             })
             .bind("10.1.0.1:8082")
             .unwrap()
@@ -75,7 +116,7 @@ mod tests {
         // wait until the "callback" server is running
         callback_server_liveness("10.1.0.1:8082").await;
 
-        (volume, r)
+        (volume, r, failed_r)
     }
 
     async fn callback_server_liveness(uri: &str) {
@@ -89,6 +130,54 @@ mod tests {
         TcpStream::connect(&sa).await.unwrap();
     }
 
+    /// gRPC watch callback service which forwards the notifications to the test
+    struct GrpcCallback(mpsc::Sender<GrpcWatchNotification>);
+    #[tonic::async_trait]
+    impl GrpcWatchCallback for GrpcCallback {
+        async fn notify(
+            &self,
+            request: tonic::Request<GrpcWatchNotification>,
+        ) -> Result<tonic::Response<()>, tonic::Status> {
+            self.0.send(request.into_inner()).await.unwrap();
+            Ok(tonic::Response::new(()))
+        }
+    }
+
+    /// Create a resource directly in the store, so that it can be watched
+    async fn new_resource(store: &mut Etcd, value: serde_json::Value) -> WatchResourceId {
+        let resource = WatchResourceId::Volume(VolumeId::new());
+        store.put_kv(&resource.key(), &value).await.unwrap();
+        resource
+    }
+
+    /// Watch the actual state of the `resource` through the `callback`
+    async fn create_watch(resource: &WatchResourceId, callback: &str, options: WatchOptions) {
+        CreateWatch {
+            id: resource.clone(),
+            callback: WatchCallback::from(callback),
+            watch_type: WatchType::Actual,
+            options,
+        }
+        .request()
+        .await
+        .unwrap();
+    }
+
+    /// Wait for the next notification received through the `channel`
+    async fn next_notification<T>(channel: &mut mpsc::Receiver<T>) -> T {
+        tokio::time::timeout(Duration::from_secs(1), channel.recv())
+            .await
+            .expect("Timeout waiting for a notification")
+            .unwrap()
+    }
+
+    /// Check that no notification is received through the `channel`
+    async fn no_notification<T: std::fmt::Debug>(channel: &mut mpsc::Receiver<T>) {
+        tokio::time::timeout(Duration::from_millis(250), channel.recv())
+            .await
+            .expect_err("The change should not be notified");
+    }
+
     #[actix_rt::test]
     async fn watcher() {
         let cluster = ClusterBuilder::builder().with_pools(1).build().await;
@@ -96,10 +185,10 @@ mod tests {
         let client = cluster.rest_v00();
         let client = client.watches_api();
 
-        let (volume, mut callback_ch) = setup_watcher().await;
+        let (volume, mut callback_ch, mut failed_ch) = setup_watcher().await;
 
         let watch_volume = WatchResourceId::Volume(volume.spec().uuid);
-        let callback = url::Url::parse("http://10.1.0.1:8082/test").unwrap();
+        let callback = url::Url::parse(CALLBACK_URI).unwrap();
 
         let watchers = client.get_watch_volume(&volume.spec().uuid).await.unwrap();
         assert!(watchers.is_empty());
@@ -109,7 +198,7 @@ mod tests {
             .expect("Failed to connect to etcd.");
 
         client
-            .put_watch_volume(&volume.spec().uuid, callback.as_str())
+            .put_watch_volume(&volume.spec().uuid, callback.as_str(), None, None, None)
             .await
             .expect_err("volume does not exist in the store");
 
@@ -119,7 +208,7 @@ mod tests {
             .unwrap();
 
         client
-            .put_watch_volume(&volume.spec().uuid, callback.as_str())
+            .put_watch_volume(&volume.spec().uuid, callback.as_str(), None, None, None)
             .await
             .unwrap();
 
@@ -158,5 +247,192 @@ mod tests {
 
         let watchers = client.get_watch_volume(&volume.spec().uuid).await.unwrap();
         assert!(watchers.is_empty());
+
+        payload_test(&mut store, &mut callback_ch).await;
+        fields_test(&mut store, &mut callback_ch).await;
+        nats_test(&mut store).await;
+        grpc_test(&mut store).await;
+        dead_letter_test(&mut store, &mut failed_ch).await;
+    }
+
+    /// The notifications carry the resource, the store revision and the value of the change, and
+    /// an `Actual` watch is only notified of the changes of the state of the resource
+    async fn payload_test(store: &mut Etcd, callback_ch: &mut mpsc::Receiver<WatchNotification>) {
+        let value = |size: u64, status: &str| serde_json::json!({ "spec": { "size": size }, "state": { "status": status } });
+        let resource = new_resource(store, value(1, "Online")).await;
+        create_watch(&resource, CALLBACK_URI, WatchOptions::default()).await;
+
+        // the first change is always notified
+        store
+            .put_kv(&resource.key(), &value(2, "Online"))
+            .await
+            .unwrap();
+        let first = next_notification(callback_ch).await;
+        assert_eq!(first.resource, resource.to_string());
+        assert_eq!(first.value, Some(value(2, "Online")));
+
+        store
+            .put_kv(&resource.key(), &value(3, "Online"))
+            .await
+            .unwrap();
+        no_notification(callback_ch).await;
+
+        store
+            .put_kv(&resource.key(), &value(3, "Degraded"))
+            .await
+            .unwrap();
+        let notification = next_notification(callback_ch).await;
+        assert_eq!(notification.value, Some(value(3, "Degraded")));
+        // the unnotified change also has its own revision
+        assert!(notification.revision > first.revision + 1);
+
+        store.delete_kv(&resource.key()).await.unwrap();
+        let notification = next_notification(callback_ch).await;
+        assert_eq!(notification.resource, resource.to_string());
+        assert_eq!(notification.value, None);
+    }
+
+    /// A watch with fields is only notified of the changes of those fields
+    async fn fields_test(store: &mut Etcd, callback_ch: &mut mpsc::Receiver<WatchNotification>) {
+        let resource = new_resource(store, serde_json::json!({ "a": 1, "b": 1 })).await;
+        let options = WatchOptions {
+            fields: vec!["/a".to_string()],
+            ..Default::default()
+        };
+        create_watch(&resource, CALLBACK_URI, options).await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!({ "a": 1, "b": 1 }))
+            .await
+            .unwrap();
+        next_notification(callback_ch).await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!({ "a": 1, "b": 2 }))
+            .await
+            .unwrap();
+        no_notification(callback_ch).await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!({ "a": 2, "b": 2 }))
+            .await
+            .unwrap();
+        let notification = next_notification(callback_ch).await;
+        assert_eq!(
+            notification.value,
+            Some(serde_json::json!({ "a": 2, "b": 2 }))
+        );
+
+        DeleteWatch {
+            id: resource.clone(),
+            callback: WatchCallback::from(CALLBACK_URI),
+            watch_type: WatchType::Actual,
+        }
+        .request()
+        .await
+        .unwrap();
+        store
+            .put_kv(&resource.key(), &serde_json::json!({ "a": 3, "b": 2 }))
+            .await
+            .unwrap();
+        no_notification(callback_ch).await;
+    }
+
+    /// The notifications of a NATS watch are published as JSON on its subject
+    async fn nats_test(store: &mut Etcd) {
+        let subject = "watches.test";
+        let subscription = bus().subscribe_subject(subject).await.unwrap();
+        let resource = new_resource(store, serde_json::json!("aaa")).await;
+        create_watch(
+            &resource,
+            &format!("nats:{}", subject),
+            WatchOptions::default(),
+        )
+        .await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!("bbb"))
+            .await
+            .unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(1), subscription.next())
+            .await
+            .expect("Timeout waiting for the NATS notification")
+            .unwrap();
+        let notification: WatchNotification = serde_json::from_slice(&message.data).unwrap();
+        assert_eq!(notification.resource, resource.to_string());
+        assert_eq!(notification.value, Some(serde_json::json!("bbb")));
+    }
+
+    /// The notifications of a gRPC watch are sent to its `WatchCallback` service
+    async fn grpc_test(store: &mut Etcd) {
+        let (sender, mut grpc_ch) = mpsc::channel(10);
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(WatchCallbackServer::new(GrpcCallback(sender)))
+                .serve("10.1.0.1:50051".parse().unwrap())
+                .await
+                .unwrap();
+        });
+        callback_server_liveness("10.1.0.1:50051").await;
+
+        let resource = new_resource(store, serde_json::json!("aaa")).await;
+        create_watch(&resource, GRPC_CALLBACK, WatchOptions::default()).await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!({ "a": 1 }))
+            .await
+            .unwrap();
+        let notification = next_notification(&mut grpc_ch).await;
+        assert_eq!(notification.resource, resource.to_string());
+        let value: serde_json::Value =
+            serde_json::from_str(&notification.value.expect("The resource was not deleted"))
+                .unwrap();
+        assert_eq!(value, serde_json::json!({ "a": 1 }));
+
+        store.delete_kv(&resource.key()).await.unwrap();
+        let notification = next_notification(&mut grpc_ch).await;
+        assert_eq!(notification.value, None);
+    }
+
+    /// A notification which can't be delivered within the maximum number of attempts is
+    /// dead-lettered, ie: dropped and recorded as a failed event in the audit log
+    async fn dead_letter_test(store: &mut Etcd, failed_ch: &mut mpsc::Receiver<()>) {
+        let resource = new_resource(store, serde_json::json!("aaa")).await;
+        let options = WatchOptions {
+            max_attempts: Some(2),
+            timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+        create_watch(&resource, FAILED_CALLBACK_URI, options).await;
+
+        store
+            .put_kv(&resource.key(), &serde_json::json!("bbb"))
+            .await
+            .unwrap();
+        next_notification(failed_ch).await;
+        next_notification(failed_ch).await;
+        no_notification(failed_ch).await;
+
+        let operation = format!("WatchNotify({})", FAILED_CALLBACK_URI);
+        let mut event = None;
+        for _ in 0 .. 10 {
+            let events = GetEvents::new(Some(resource.to_string()))
+                .request()
+                .await
+                .unwrap()
+                .0;
+            event = events.into_iter().find(|e| e.operation == operation);
+            if event.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let event = event.expect("The dead-lettered notification should be in the audit log");
+        assert_eq!(event.result, EventResult::Failed);
+        assert!(event
+            .error
+            .as_ref()
+            .unwrap()
+            .starts_with("Dead-lettered the notification"));
     }
 }
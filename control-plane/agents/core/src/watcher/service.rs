@@ -6,7 +6,7 @@ pub use common::errors::SvcError;
 pub use common_lib::mbus_api::{Message, MessageId, ReceivedMessage};
 use common_lib::{
     mbus_api::message_bus::v0::Watches,
-    types::v0::message_bus::{CreateWatch, DeleteWatch, GetWatchers},
+    types::v0::message_bus::{CreateWatch, DeleteWatch, EventSource, GetWatchers},
 };
 pub use std::convert::TryInto;
 use std::sync::Arc;
@@ -24,6 +24,11 @@ impl Service {
             watcher: Arc::new(Mutex::new(StoreWatcher::new(registry))),
         }
     }
+    /// The watches are not spec operations, and their workers record the notifications which
+    /// they fail to deliver on behalf of the watch itself
+    pub(super) fn with_event_source(&self, _source: EventSource) -> Self {
+        self.clone()
+    }
 
     /// Create new resource watch
     #[tracing::instrument(level = "debug", skip(self), err)]
@@ -35,6 +40,7 @@ impl Service {
                 &WatchCfgId::from(request),
                 &request.callback,
                 &request.watch_type,
+                &request.options,
             )
             .await?;
         Ok(())
@@ -1,17 +1,22 @@
 use crate::core::registry::Registry;
 use common::errors::{Store as SvcStoreError, SvcError};
 use common_lib::{
-    mbus_api::{message_bus::v0::Watches, ResourceKind},
+    mbus_api::{bus, message_bus::v0::Watches, ErrorChain, ResourceKind},
     types::v0::{
         message_bus::{
-            CreateWatch, DeleteWatch, GetWatchers, Watch, WatchCallback, WatchResourceId, WatchType,
+            CreateWatch, DeleteWatch, EventSource, GetWatchers, Watch, WatchCallback,
+            WatchNotification, WatchOptions, WatchResourceId, WatchType,
         },
-        store::definitions::{
-            ObjectKey, StorableObject, StorableObjectType, Store, StoreError, StoreWatchReceiver,
-            WatchEvent,
+        store::{
+            definitions::{
+                ObjectKey, RevisionWatchEvent, StorableObject, StorableObjectType, Store,
+                StoreError, StoreRevisionWatchReceiver, WatchEvent,
+            },
+            schema::object_value,
         },
     },
 };
+use grpc::watch::client::WatchClient;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use std::{
@@ -57,6 +62,50 @@ struct WatchParams {
     /// Type of event to watch
     #[serde(rename = "type")]
     type_: WatchType,
+    /// Filter and delivery options
+    #[serde(default)]
+    options: WatchOptions,
+}
+
+impl WatchParams {
+    /// Watches are uniquely identified by their callback and type, regardless of their options
+    fn matches(&self, other: &WatchParams) -> bool {
+        self.callback == other.callback && self.type_ == other.type_
+    }
+
+    /// Check if the change of the watched resource from `previous` to `value` should be
+    /// notified to the watcher:
+    /// 1. the fields of the options are compared, if any
+    /// 2. otherwise the spec is compared for the `Desired` watches and the state for the `Actual`
+    /// watches, for resources which have both
+    /// 3. otherwise the whole resource is compared
+    /// The first seen value and the deletion of the resource are always notified.
+    fn changed(
+        &self,
+        previous: Option<&serde_json::Value>,
+        value: Option<&serde_json::Value>,
+    ) -> bool {
+        let (previous, value) = match (previous, value) {
+            (Some(previous), Some(value)) => (previous, value),
+            _ => return true,
+        };
+        let pointers = if !self.options.fields.is_empty() {
+            self.options
+                .fields
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+        } else {
+            match self.type_ {
+                WatchType::Desired if value.get("spec").is_some() => vec!["/spec"],
+                WatchType::Actual if value.get("state").is_some() => vec!["/state"],
+                _ => vec![""],
+            }
+        };
+        pointers
+            .iter()
+            .any(|pointer| previous.pointer(pointer) != value.pointer(pointer))
+    }
 }
 
 /// Watch parameters with handle to the watcher worker thread
@@ -136,18 +185,14 @@ impl WatchCfg {
     }
 
     /// Add a new watch element to this watcher
-    async fn add(
-        &mut self,
-        watch: &WatchParams,
-        store: Arc<Mutex<impl Store + 'static>>,
-    ) -> Result<(), SvcError> {
-        if self.watchers.iter().any(|item| &item.params == watch) {
+    async fn add(&mut self, watch: &WatchParams, registry: &Registry) -> Result<(), SvcError> {
+        if self.watchers.iter().any(|item| item.params.matches(watch)) {
             return Err(SvcError::WatchAlreadyExists {});
         }
 
         {
             // make sure the target resource exists
-            let mut store = store.lock().await;
+            let mut store = registry.store().lock().await;
             match store.get_kv(&self.watch_id.id.key()).await {
                 Ok(_) => Ok(()),
                 Err(StoreError::MissingEntry { .. }) => Err(SvcError::WatchResourceNotFound {
@@ -157,7 +202,7 @@ impl WatchCfg {
             }?;
         }
 
-        let handle = self.watch(watch, store).await?;
+        let handle = self.watch(watch, registry).await?;
 
         let watch = WatchParamsCfg {
             params: watch.clone(),
@@ -182,10 +227,10 @@ impl WatchCfg {
 
     /// Delete a watcher using its parameters
     fn del(&mut self, watch: &WatchParams) -> Result<(), SvcError> {
-        if !self.watchers.iter().any(|item| &item.params == watch) {
+        if !self.watchers.iter().any(|item| item.params.matches(watch)) {
             Err(SvcError::WatchNotFound {})
         } else {
-            self.watchers.retain(|item| &item.params != watch);
+            self.watchers.retain(|item| !item.params.matches(watch));
             Ok(())
         }
     }
@@ -194,18 +239,20 @@ impl WatchCfg {
     async fn watch(
         &self,
         watch: &WatchParams,
-        store_arc: Arc<Mutex<impl Store + 'static>>,
+        registry: &Registry,
     ) -> Result<WatchHandle, SvcError> {
-        let mut store = store_arc.lock().await;
+        let mut store = registry.store().lock().await;
         let handle = {
             // start watching before writing to the store
-            let channel = store.watch_obj(&self.watch_id.id).await?;
+            let channel = store.watch_kv_from(&self.watch_id.id.key(), None).await?;
             let watch = watch.clone();
             let id = self.watch_id.id.clone();
-            let store = store_arc.clone();
+            // the worker records the notifications it fails to deliver on behalf of the watch
+            let registry =
+                registry.with_event_source(EventSource::Watcher(watch.callback.to_string()));
             let (cancel_sender, cancel) = tokio::sync::broadcast::channel(1);
             let thread = tokio::spawn(async move {
-                Self::watcher_worker(cancel, channel, watch, id, store).await;
+                Self::watcher_worker(cancel, channel, watch, id, registry).await;
             });
             Arc::new((cancel_sender, thread))
         };
@@ -219,12 +266,14 @@ impl WatchCfg {
     /// specific watcher which is created through `create_watcher`.
     async fn watcher_worker(
         mut cancel: tokio::sync::broadcast::Receiver<()>,
-        mut channel: StoreWatchReceiver,
+        mut channel: StoreRevisionWatchReceiver,
         params: WatchParams,
         id: WatchResourceId,
-        store: Arc<Mutex<impl Store + 'static>>,
+        registry: Registry,
     ) {
         let mut last_seen: Option<serde_json::Value> = None;
+        let mut last_revision: Option<i64> = None;
+        let mut grpc_client: Option<WatchClient> = None;
         loop {
             tokio::select! {
                 _cancel = cancel.recv() => {
@@ -234,36 +283,78 @@ impl WatchCfg {
                 watch_event = channel.recv() => {
                     match watch_event {
                         None => {
-                            if let Some(chan) =
-                                Self::reconnect_watch(&mut cancel, &id, &store).await
-                            {
-                                if Some(&chan.0) != last_seen.as_ref() {
-                                    // we can't know if we missed any event so just
-                                    // compare the latest with last seen
-                                    Self::notify(&mut cancel, &params.callback).await;
-                                }
-                                last_seen = Some(chan.0);
-                                channel = chan.1;
-                            } else {
-                                break;
+                            // resume from the revision following the last seen one so that the
+                            // changes made while the watch was lost are not missed
+                            let revision = last_revision.map(|revision| revision + 1);
+                            match Self::reconnect_watch(&mut cancel, &id, revision, &registry).await {
+                                Some(chan) => channel = chan,
+                                None => break,
                             }
                         }
+                        Some(Err(StoreError::WatchCompacted { compacted, .. })) => {
+                            // the missed changes are no longer available so resume from the
+                            // current revision when the watch is reissued
+                            tracing::warn!(
+                                "Changes of {} up to revision {} were compacted before being notified",
+                                id.to_string(),
+                                compacted
+                            );
+                            last_revision = None;
+                        }
+                        Some(Err(StoreError::WatchLagged { missed, .. })) => {
+                            // the store ends the watch, which is then resumed from the revision
+                            // following the last seen one
+                            tracing::warn!(
+                                "Fell behind {} changes of {}, resuming the watch",
+                                missed,
+                                id.to_string()
+                            );
+                        }
                         Some(Err(error)) => {
                             // Should not happen, most likely a deserialize error?
                             tracing::error!("Error watching: {:?}", error);
                         }
 
-                        Some(Ok(result)) => {
-                            match &result {
-                                WatchEvent::Put(_, v) => {
-                                    last_seen = Some(v.clone());
-                                }
-                                WatchEvent::Delete => {
-                                    // resource deleted so we don't need to keep on watching
-                                    return;
+                        Some(Ok(RevisionWatchEvent { event, revision })) => {
+                            last_revision = Some(revision);
+                            let value = match event {
+                                WatchEvent::Put(_, value) => {
+                                    match object_value(id.key_type(), value) {
+                                        Ok(value) => Some(value),
+                                        Err(error) => {
+                                            tracing::error!(
+                                                "Failed to read the change of {} at revision {}: {}",
+                                                id.to_string(),
+                                                revision,
+                                                error
+                                            );
+                                            continue;
+                                        }
+                                    }
                                 }
+                                WatchEvent::Delete => None,
+                            };
+                            if params.changed(last_seen.as_ref(), value.as_ref()) {
+                                let notification = WatchNotification {
+                                    resource: id.to_string(),
+                                    revision,
+                                    value: value.clone(),
+                                };
+                                Self::notify(
+                                    &mut cancel,
+                                    &params,
+                                    &mut grpc_client,
+                                    &notification,
+                                    &id,
+                                    &registry,
+                                )
+                                .await;
+                            }
+                            match value {
+                                Some(value) => last_seen = Some(value),
+                                // resource deleted so we don't need to keep on watching
+                                None => return,
                             }
-                            Self::notify(&mut cancel, &params.callback).await;
                         }
                     }
                 }
@@ -271,75 +362,137 @@ impl WatchCfg {
         }
     }
 
-    /// Notify the watcher using its callback
-    async fn notify(cancel: &mut tokio::sync::broadcast::Receiver<()>, callback: &WatchCallback) {
+    /// Notify the watcher using its callback, retrying up to the maximum number of attempts of
+    /// the watch after which the notification is dead-lettered, ie: dropped and recorded in the
+    /// audit log.
+    async fn notify(
+        cancel: &mut tokio::sync::broadcast::Receiver<()>,
+        params: &WatchParams,
+        grpc_client: &mut Option<WatchClient>,
+        notification: &WatchNotification,
+        id: &WatchResourceId,
+        registry: &Registry,
+    ) {
+        let callback = params.callback.to_string();
+        let max_attempts = params.options.max_attempts();
         let mut tries = 0;
-        let mut log_failure = true;
-        loop {
+        let mut last_error = String::new();
+        for attempt in 1 ..= max_attempts {
             match cancel.try_recv() {
                 Err(TryRecvError::Empty) => {}
                 // dropped or received the cancel signal so bail out
                 _ => return,
             };
 
-            match &callback {
-                WatchCallback::Uri(uri) => {
-                    let request = reqwest::Client::new()
-                        .put(uri)
-                        .timeout(std::time::Duration::from_secs(1))
-                        .send();
-                    match request.await {
-                        Ok(resp) if resp.status().is_success() => {
-                            // notification complete
-                            if !log_failure {
-                                tracing::info!("Completed notification for url {}", uri);
-                            }
-                            return;
-                        }
-                        Ok(resp) => {
-                            if log_failure {
-                                tracing::error!(
-                                    "Notify response for url {} completed with error: {}. Quietly retrying...",
-                                    uri,
-                                    resp.status().to_string()
-                                );
-                                log_failure = false;
-                            }
-                        }
-                        Err(error) => {
-                            if log_failure {
-                                tracing::error!(
-                                    "Failed to send notify for url {}, {}. Quietly retrying...",
-                                    uri,
-                                    error.to_string()
-                                );
-                                log_failure = false;
-                            }
-                        }
+            match Self::deliver(params, grpc_client, notification).await {
+                Ok(()) => {
+                    // notification complete
+                    if attempt > 1 {
+                        tracing::info!("Completed notification for {}", callback);
                     }
+                    return;
+                }
+                Err(error) => {
+                    if attempt == 1 {
+                        tracing::error!(
+                            "Failed to notify {}, {}. Quietly retrying...",
+                            callback,
+                            error
+                        );
+                    }
+                    last_error = error;
                 }
             }
 
-            backoff(&mut tries, Duration::from_secs(5)).await;
+            if attempt < max_attempts {
+                backoff(&mut tries, Duration::from_secs(5)).await;
+            }
+        }
+
+        let error = format!(
+            "Dead-lettered the notification of revision {} after {} attempts: {}",
+            notification.revision, max_attempts, last_error
+        );
+        tracing::error!("Failed to notify {}. {}", callback, error);
+        registry
+            .events()
+            .record(
+                registry,
+                Self::resource_to_kind(id),
+                id.to_string(),
+                format!("WatchNotify({})", callback),
+                Some(error),
+            )
+            .await;
+    }
+
+    /// Deliver the notification to the watcher through its callback, once
+    async fn deliver(
+        params: &WatchParams,
+        grpc_client: &mut Option<WatchClient>,
+        notification: &WatchNotification,
+    ) -> Result<(), String> {
+        let timeout = params.options.timeout();
+        match &params.callback {
+            WatchCallback::Uri(uri) => {
+                let body = serde_json::to_vec(notification).map_err(|e| e.to_string())?;
+                let response = reqwest::Client::new()
+                    .put(uri)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .timeout(timeout)
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "response completed with error: {}",
+                        response.status()
+                    ))
+                }
+            }
+            WatchCallback::Nats(subject) => {
+                let payload = serde_json::to_vec(notification).map_err(|e| e.to_string())?;
+                bus()
+                    .publish_subject(subject, &payload)
+                    .await
+                    .map_err(|e| e.full_string())
+            }
+            WatchCallback::Grpc(endpoint) => {
+                let mut client = match grpc_client.take() {
+                    Some(client) => client,
+                    None => WatchClient::connect(endpoint, timeout)
+                        .await
+                        .map_err(|e| e.full_string())?,
+                };
+                client
+                    .notify(notification)
+                    .await
+                    .map_err(|e| e.full_string())?;
+                // keep the connection for the next notifications
+                *grpc_client = Some(client);
+                Ok(())
+            }
         }
     }
 
-    /// Reissue a watch for the given resource id.
-    /// The actual value is returned as its useful to crudely verify if any
-    /// update was missed.
+    /// Reissue a watch for the given resource id, from the given revision if any.
     async fn rewatch(
         id: &WatchResourceId,
+        revision: Option<i64>,
         store: &mut impl Store,
-    ) -> Option<(serde_json::Value, StoreWatchReceiver)> {
-        match store.watch_obj(id).await {
+    ) -> Option<StoreRevisionWatchReceiver> {
+        match store.watch_kv_from(&id.key(), revision).await {
             Ok(channel) => {
-                // get the current value
-                match store.get_kv(&id.key()).await {
-                    Ok(obj) => Some((obj, channel)),
-                    // deleted, so bail out
-                    Err(StoreError::MissingEntry { .. }) => None,
-                    Err(_) => Some((serde_json::Value::default(), channel)),
+                if revision.is_none() {
+                    // the deletion would not be replayed, so check the resource still exists
+                    if let Err(StoreError::MissingEntry { .. }) = store.get_kv(&id.key()).await {
+                        return None;
+                    }
                 }
+                Some(channel)
             }
             Err(_) => {
                 // lost connection? we'll just retry again...
@@ -354,8 +507,9 @@ impl WatchCfg {
     async fn reconnect_watch(
         cancel: &mut tokio::sync::broadcast::Receiver<()>,
         id: &WatchResourceId,
-        store: &Arc<Mutex<impl Store + 'static>>,
-    ) -> Option<(serde_json::Value, StoreWatchReceiver)> {
+        revision: Option<i64>,
+        registry: &Registry,
+    ) -> Option<StoreRevisionWatchReceiver> {
         // we're still here so let's try to reconnect
         let mut tries = 0;
         loop {
@@ -365,9 +519,9 @@ impl WatchCfg {
                 _ => return None,
             };
 
-            let mut store = store.lock().await;
+            let mut store = registry.store().lock().await;
             if store.online().await {
-                return Self::rewatch(id, store.deref_mut()).await;
+                return Self::rewatch(id, revision, store.deref_mut()).await;
             }
 
             backoff(&mut tries, Duration::from_secs(5)).await;
@@ -399,6 +553,7 @@ impl StoreWatcher {
                         id: watch_id.id.clone(),
                         callback: e.callback.clone(),
                         watch_type: e.type_.clone(),
+                        options: e.options.clone(),
                     })
                     .collect()
             }
@@ -440,15 +595,17 @@ impl StoreWatcher {
         watch_id: &WatchCfgId,
         callback: &WatchCallback,
         type_: &WatchType,
+        options: &WatchOptions,
     ) -> Result<(), SvcError> {
         let watch_cfg = self.get_or_create_watch_cfg(watch_id).await;
         let watch = WatchParams {
             callback: callback.clone(),
             type_: type_.clone(),
+            options: options.clone(),
         };
 
         let mut watch_cfg = watch_cfg.lock().await;
-        watch_cfg.add(&watch, self.registry.store().clone()).await?;
+        watch_cfg.add(&watch, &self.registry).await?;
         Ok(())
     }
 
@@ -464,6 +621,7 @@ impl StoreWatcher {
         let watch = WatchParams {
             callback: callback.clone(),
             type_: type_.clone(),
+            options: WatchOptions::default(),
         };
         watch_cfg.del(&watch)?;
         Ok(())
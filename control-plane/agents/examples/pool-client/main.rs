@@ -49,6 +49,7 @@ async fn create_pool(node: &str, pool: &str) {
         id: pool.into(),
         disks: vec!["malloc:///disk0?size_mb=100".into()],
         labels: None,
+        overcommit: None,
     }
     .request()
     .await
@@ -2,8 +2,9 @@ use common_lib::types::v0::openapi::{
     clients,
     clients::tower::StatusCode,
     models::{
-        CreateVolumeBody, ExplicitNodeTopology, LabelledTopology, Node, NodeTopology, Pool,
-        PoolTopology, RestJsonError, Topology, Volume, VolumePolicy, VolumeShareProtocol,
+        CreateVolumeBody, ExplicitNodeTopology, LabelExpression, LabelledTopology, Node,
+        NodeTopology, Pool, PoolTopology, RestJsonError, Topology, Volume, VolumeAntiAffinity,
+        VolumeContentSource, VolumePolicy, VolumeShareProtocol, VolumeSnapshot,
     },
 };
 
@@ -23,6 +24,8 @@ pub enum ApiClientError {
     ResourceAlreadyExists(String),
     // No resource instance exists.
     ResourceNotExists(String),
+    // The resource is not in the state required by the operation.
+    PreconditionFailed(String),
     // Generic operation errors.
     GenericOperation(String),
     // Problems with parsing response body.
@@ -35,6 +38,9 @@ pub enum ApiClientError {
 #[derive(Debug)]
 pub struct CreateVolumeTopology {
     inclusive_label_topology: HashMap<String, String>,
+    exclusive_label_topology: HashMap<String, String>,
+    label_expressions: Vec<LabelExpression>,
+    anti_affinity_labels: HashMap<String, String>,
     allowed_nodes: Vec<String>,
     preferred_nodes: Vec<String>,
 }
@@ -49,8 +55,29 @@ impl CreateVolumeTopology {
             allowed_nodes,
             preferred_nodes,
             inclusive_label_topology,
+            exclusive_label_topology: HashMap::new(),
+            label_expressions: vec![],
+            anti_affinity_labels: HashMap::new(),
         }
     }
+    /// Add the exclusive pool labels and the pool label expressions
+    pub fn with_pool_labels(
+        mut self,
+        exclusive_label_topology: HashMap<String, String>,
+        label_expressions: Vec<LabelExpression>,
+    ) -> Self {
+        self.exclusive_label_topology = exclusive_label_topology;
+        self.label_expressions = label_expressions;
+        self
+    }
+    /// Keep the volume replicas away from the pools of the volumes with the given labels
+    pub fn with_volume_anti_affinity(
+        mut self,
+        anti_affinity_labels: HashMap<String, String>,
+    ) -> Self {
+        self.anti_affinity_labels = anti_affinity_labels;
+        self
+    }
 }
 
 impl From<clients::tower::Error<RestJsonError>> for ApiClientError {
@@ -67,6 +94,8 @@ impl From<clients::tower::Error<RestJsonError>> for ApiClientError {
                         Self::ResourceNotExists(response.to_string())
                     } else if response.status() == StatusCode::UNPROCESSABLE_ENTITY {
                         Self::ResourceAlreadyExists(response.to_string())
+                    } else if response.status() == StatusCode::PRECONDITION_FAILED {
+                        Self::PreconditionFailed(response.to_string())
                     } else {
                         Self::GenericOperation(response.to_string())
                     }
@@ -160,8 +189,10 @@ impl MayastorApiClient {
     }
 
     /// Create a volume of target size and provision storage resources for it.
+    /// The volume is a clone of the given source volume or snapshot, if any.
     /// This operation is not idempotent, so the caller is responsible for taking
     /// all actions with regards to idempotency.
+    #[allow(clippy::too_many_arguments)]
     #[instrument(fields(volume.uuid = %volume_id), skip(volume_id))]
     pub async fn create_volume(
         &self,
@@ -169,33 +200,51 @@ impl MayastorApiClient {
         replicas: u8,
         size: u64,
         volume_topology: CreateVolumeTopology,
-        pinned_volume: bool,
+        volume_labels: HashMap<String, String>,
+        thin: bool,
+        source: Option<VolumeContentSource>,
     ) -> Result<Volume, ApiClientError> {
+        let label_expressions = if volume_topology.label_expressions.is_empty() {
+            None
+        } else {
+            Some(volume_topology.label_expressions)
+        };
+        let anti_affinity = if volume_topology.anti_affinity_labels.is_empty() {
+            None
+        } else {
+            Some(VolumeAntiAffinity::new(
+                volume_topology.anti_affinity_labels,
+                vec![],
+            ))
+        };
         let topology = Topology::new_all(
             Some(NodeTopology::explicit(ExplicitNodeTopology::new(
                 volume_topology.allowed_nodes,
                 volume_topology.preferred_nodes,
             ))),
-            Some(PoolTopology::labelled(LabelledTopology::new(
-                HashMap::new(),
+            Some(PoolTopology::labelled(LabelledTopology::new_all(
+                volume_topology.exclusive_label_topology,
+                label_expressions,
                 volume_topology.inclusive_label_topology,
             ))),
+            None,
+            anti_affinity,
         );
 
-        let labels = if pinned_volume {
-            let mut labels = HashMap::new();
-            labels.insert("local".to_string(), "true".to_string());
-            Some(labels)
-        } else {
+        let labels = if volume_labels.is_empty() {
             None
+        } else {
+            Some(volume_labels)
         };
 
         let req = CreateVolumeBody {
             replicas,
             size,
             topology: Some(topology),
-            policy: VolumePolicy::new_all(true),
+            policy: VolumePolicy::new_all(true, None),
             labels,
+            thin: Some(thin),
+            source,
         };
 
         let result = self
@@ -281,8 +330,75 @@ impl MayastorApiClient {
         let volume = self
             .rest_client
             .volumes_api()
-            .put_volume_target(volume_id, node, protocol)
+            .put_volume_target(volume_id, node, protocol, None)
             .await?;
         Ok(volume.into_body())
     }
+
+    /// Create a snapshot of the volume through its target.
+    #[instrument(fields(volume.uuid = %volume_id, snapshot.uuid = %snapshot_id), skip(volume_id, snapshot_id))]
+    pub async fn create_snapshot(
+        &self,
+        volume_id: &uuid::Uuid,
+        snapshot_id: &uuid::Uuid,
+    ) -> Result<VolumeSnapshot, ApiClientError> {
+        let snapshot = self
+            .rest_client
+            .snapshots_api()
+            .put_volume_snapshot(volume_id, snapshot_id)
+            .await?;
+        Ok(snapshot.into_body())
+    }
+
+    /// Delete a volume snapshot.
+    /// This operation is idempotent, so the caller does not see errors indicating
+    /// absence of the resource.
+    #[instrument(fields(volume.uuid = %volume_id, snapshot.uuid = %snapshot_id), skip(volume_id, snapshot_id))]
+    pub async fn delete_snapshot(
+        &self,
+        volume_id: &uuid::Uuid,
+        snapshot_id: &uuid::Uuid,
+    ) -> Result<(), ApiClientError> {
+        Self::delete_idempotent(
+            self.rest_client
+                .snapshots_api()
+                .del_volume_snapshot(volume_id, snapshot_id)
+                .await,
+            true,
+        )?;
+        debug!(snapshot.uuid=%snapshot_id, "Snapshot successfully deleted");
+        Ok(())
+    }
+
+    /// Get specific snapshot.
+    #[instrument(fields(snapshot.uuid = %snapshot_id), skip(snapshot_id))]
+    pub async fn get_snapshot(
+        &self,
+        snapshot_id: &uuid::Uuid,
+    ) -> Result<VolumeSnapshot, ApiClientError> {
+        let snapshot = self
+            .rest_client
+            .snapshots_api()
+            .get_snapshot(snapshot_id)
+            .await?;
+        Ok(snapshot.into_body())
+    }
+
+    /// List all snapshots, or only the snapshots of the given volume.
+    #[instrument(skip(volume_id))]
+    pub async fn list_snapshots(
+        &self,
+        volume_id: Option<&uuid::Uuid>,
+    ) -> Result<Vec<VolumeSnapshot>, ApiClientError> {
+        let snapshots = match volume_id {
+            Some(volume_id) => {
+                self.rest_client
+                    .snapshots_api()
+                    .get_volume_snapshots(volume_id)
+                    .await?
+            }
+            None => self.rest_client.snapshots_api().get_snapshots().await?,
+        };
+        Ok(snapshots.into_body())
+    }
 }
@@ -6,8 +6,12 @@ use tonic::{Response, Status};
 use tracing::{debug, error, instrument, warn};
 use uuid::Uuid;
 
-use common_lib::types::v0::openapi::models::{
-    Node, Pool, PoolStatus, SpecStatus, Volume, VolumeShareProtocol,
+use common_lib::types::v0::{
+    openapi::models::{
+        Node, Pool, PoolStatus, SpecStatus, Volume, VolumeContentSource, VolumeShareProtocol,
+        VolumeSnapshot,
+    },
+    store::pool::DEFAULT_POOL_OVERCOMMIT,
 };
 use utils::{MSP_OPERATOR, OPENEBS_CREATED_BY_KEY};
 
@@ -16,6 +20,8 @@ use rpc::csi::Topology as CsiTopology;
 const K8S_HOSTNAME: &str = "kubernetes.io/hostname";
 const VOLUME_NAME_PATTERN: &str =
     r"pvc-([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})";
+const SNAPSHOT_NAME_PATTERN: &str =
+    r"snapshot-([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})";
 const SUPPORTED_FS_TYPES: [&str; 2] = ["ext4", "xfs"];
 const MAYASTOR_NODE_PREFIX: &str = "mayastor://";
 const MAX_VOLUMES_TO_LIST: usize = 1024 * 1024;
@@ -25,20 +31,135 @@ pub struct CsiControllerSvc {}
 
 // TODO: Implement VolumeOpts
 mod volume_opts {
+    use common_lib::types::v0::openapi::models::{LabelExpression, LabelOperator};
+    use std::collections::HashMap;
+
     pub const IO_TIMEOUT: &str = "ioTimeout";
     pub const LOCAL_VOLUME: &str = "local";
+    pub const POOL_TOPOLOGY_INCLUSION: &str = "poolTopologyInclusion";
+    pub const POOL_TOPOLOGY_EXCLUSION: &str = "poolTopologyExclusion";
+    pub const POOL_TOPOLOGY_SELECTOR: &str = "poolTopologySelector";
+    pub const VOLUME_LABELS: &str = "volumeLabels";
+    pub const VOLUME_ANTI_AFFINITY: &str = "volumeAntiAffinity";
+    pub const THIN_PROVISIONING: &str = "thin";
 
     const YAML_TRUE_VALUE: [&str; 11] = [
         "y", "Y", "yes", "Yes", "YES", "true", "True", "TRUE", "on", "On", "ON",
     ];
 
-    // Decode 'local' volume attribute into a boolean flag.
-    pub fn decode_local_volume_flag(encoded: Option<&String>) -> bool {
+    fn decode_flag(encoded: Option<&String>) -> bool {
         match encoded {
             Some(v) => YAML_TRUE_VALUE.iter().any(|p| p == v),
             None => false,
         }
     }
+
+    // Decode 'local' volume attribute into a boolean flag.
+    pub fn decode_local_volume_flag(encoded: Option<&String>) -> bool {
+        decode_flag(encoded)
+    }
+
+    // Decode 'thin' volume attribute into a boolean flag.
+    pub fn decode_thin_flag(encoded: Option<&String>) -> bool {
+        decode_flag(encoded)
+    }
+
+    // Decode a comma separated list of labels, eg: "zone=a,rack", into a map.
+    // Labels without a value are mapped to an empty value.
+    pub fn decode_labels(encoded: Option<&String>) -> Result<HashMap<String, String>, String> {
+        let mut labels = HashMap::new();
+        for label in encoded.iter().flat_map(|e| e.split(',')) {
+            let label = label.trim();
+            if label.is_empty() {
+                continue;
+            }
+            let (key, value) = match label.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (label, ""),
+            };
+            if key.is_empty() {
+                return Err(format!("Invalid label '{}'", label));
+            }
+            labels.insert(key.to_string(), value.to_string());
+        }
+        Ok(labels)
+    }
+
+    // Decode a comma separated list of inclusive labels, eg: "zone=a,ssd".
+    // The labels without a value only require the label to be present, with any value, so they
+    // are decoded into label expressions.
+    pub fn decode_inclusive_labels(
+        encoded: Option<&String>,
+    ) -> Result<(HashMap<String, String>, Vec<LabelExpression>), String> {
+        let (keys, labels): (HashMap<_, _>, HashMap<_, _>) = decode_labels(encoded)?
+            .into_iter()
+            .partition(|(_, value)| value.is_empty());
+        let expressions = keys
+            .into_iter()
+            .map(|(key, _)| LabelExpression::new(key, LabelOperator::Exists))
+            .collect();
+        Ok((labels, expressions))
+    }
+
+    // Decode a kubernetes style set-based label selector into label expressions, eg:
+    // "zone in (a, b), tier notin (slow), ssd, !hdd".
+    pub fn decode_label_selector(encoded: Option<&String>) -> Result<Vec<LabelExpression>, String> {
+        let encoded = match encoded {
+            Some(encoded) => encoded,
+            None => return Ok(vec![]),
+        };
+        // split the requirements on the commas which are not part of a value set
+        let mut requirements = vec![];
+        let mut requirement = String::new();
+        let mut depth = 0;
+        for c in encoded.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    requirements.push(std::mem::take(&mut requirement));
+                    continue;
+                }
+                _ => {}
+            }
+            requirement.push(c);
+        }
+        requirements.push(requirement);
+
+        let mut expressions = vec![];
+        for requirement in requirements
+            .iter()
+            .map(|r| r.trim())
+            .filter(|r| !r.is_empty())
+        {
+            let invalid = || format!("Invalid label selector requirement '{}'", requirement);
+            let expression = if let Some(key) = requirement.strip_prefix('!') {
+                LabelExpression::new(key.trim(), LabelOperator::DoesNotExist)
+            } else if let Some((key_op, values)) = requirement.split_once('(') {
+                let values = values.strip_suffix(')').ok_or_else(invalid)?;
+                let mut key_op = key_op.split_whitespace();
+                let key = key_op.next().ok_or_else(invalid)?;
+                let operator = match key_op.next() {
+                    Some("in") => LabelOperator::In,
+                    Some("notin") => LabelOperator::NotIn,
+                    _ => return Err(invalid()),
+                };
+                let values = values
+                    .split(',')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect();
+                LabelExpression::new_all(key, operator, Some(values))
+            } else {
+                LabelExpression::new(requirement, LabelOperator::Exists)
+            };
+            if expression.key.is_empty() || expression.key.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            expressions.push(expression);
+        }
+        Ok(expressions)
+    }
 }
 
 /// Check whether the passed fs type is supported or not,
@@ -77,6 +198,26 @@ fn parse_protocol(proto: Option<&String>) -> Result<VolumeShareProtocol, Status>
     }
 }
 
+/// Parse the CSI volume content source into the volume or snapshot which the volume is cloned from.
+fn parse_content_source(
+    source: &Option<rpc::csi::VolumeContentSource>,
+) -> Result<Option<VolumeContentSource>, Status> {
+    let parse_uuid = |id: &str| {
+        Uuid::parse_str(id).map_err(|_e| {
+            Status::invalid_argument(format!("Malformed volume content source UUID: {}", id))
+        })
+    };
+    match source.as_ref().and_then(|source| source.r#type.as_ref()) {
+        None => Ok(None),
+        Some(volume_content_source::Type::Volume(volume)) => Ok(Some(
+            VolumeContentSource::new_all(Some(parse_uuid(&volume.volume_id)?), None),
+        )),
+        Some(volume_content_source::Type::Snapshot(snapshot)) => Ok(Some(
+            VolumeContentSource::new_all(None, Some(parse_uuid(&snapshot.snapshot_id)?)),
+        )),
+    }
+}
+
 /// Transform Kubernetes Mayastor node ID into its real hostname.
 fn normalize_hostname(name: &str) -> String {
     if let Some(hostname) = name.strip_prefix(MAYASTOR_NODE_PREFIX) {
@@ -99,6 +240,7 @@ impl From<ApiClientError> for Status {
     fn from(error: ApiClientError) -> Self {
         match error {
             ApiClientError::ResourceNotExists(reason) => Status::not_found(reason),
+            ApiClientError::PreconditionFailed(reason) => Status::failed_precondition(reason),
             error => Status::internal(format!("Operation failed: {:?}", error)),
         }
     }
@@ -113,10 +255,12 @@ fn check_existing_volume(
     replica_count: u8,
     size: u64,
     pinned_volume: bool,
+    thin: bool,
 ) -> Result<(), Status> {
     // Check if the existing volume is compatible, which means
     //  - number of replicas is equal or greater
     //  - size is equal or greater
+    //  - thin provisioning is the same
     //  - volume is fully created
     let spec = &volume.spec;
 
@@ -141,9 +285,31 @@ fn check_existing_volume(
         )));
     }
 
+    if spec.thin != thin {
+        return Err(Status::already_exists(format!(
+            "Existing volume {} has a different thin provisioning: {} ({} requested)",
+            spec.uuid, spec.thin, thin
+        )));
+    }
+
     Ok(())
 }
 
+/// Convert a volume snapshot into its CSI representation.
+fn csi_snapshot(snapshot: &VolumeSnapshot) -> rpc::csi::Snapshot {
+    rpc::csi::Snapshot {
+        size_bytes: snapshot.size as i64,
+        snapshot_id: snapshot.uuid.to_string(),
+        source_volume_id: snapshot.volume.to_string(),
+        creation_time: snapshot
+            .timestamp
+            .as_deref()
+            .and_then(|timestamp| humantime::parse_rfc3339(timestamp).ok())
+            .map(prost_types::Timestamp::from),
+        ready_to_use: snapshot.status == SpecStatus::Created,
+    }
+}
+
 struct VolumeTopologyMapper {
     nodes: Vec<Node>,
 }
@@ -202,12 +368,6 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
     ) -> Result<tonic::Response<CreateVolumeResponse>, tonic::Status> {
         let args = request.into_inner();
 
-        if args.volume_content_source.is_some() {
-            return Err(Status::invalid_argument(
-                "Source for create volume is not supported",
-            ));
-        }
-
         // k8s uses names pvc-{uuid} and we use uuid further as ID in SPDK so we
         // must require it.
         let re = Regex::new(VOLUME_NAME_PATTERN).unwrap();
@@ -224,12 +384,15 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
 
         check_volume_capabilities(&args.volume_capabilities)?;
 
+        // The volume is cloned from the content source, if any.
+        let source = parse_content_source(&args.volume_content_source)?;
+
         // Check volume size.
         let size = match args.capacity_range {
             Some(range) => {
                 if range.required_bytes <= 0 {
                     return Err(Status::invalid_argument(
-                        "Volume size must be a non-negative number",
+                        "Volume size must be a positive number",
                     ));
                 }
                 range.required_bytes as u64
@@ -278,6 +441,31 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
 
         let pinned_volume =
             volume_opts::decode_local_volume_flag(args.parameters.get(volume_opts::LOCAL_VOLUME));
+        let thin =
+            volume_opts::decode_thin_flag(args.parameters.get(volume_opts::THIN_PROVISIONING));
+
+        // Check the pool topology and the volume anti-affinity.
+        let (inclusive_labels, inclusive_expressions) = volume_opts::decode_inclusive_labels(
+            args.parameters.get(volume_opts::POOL_TOPOLOGY_INCLUSION),
+        )
+        .map_err(Status::invalid_argument)?;
+        let exclusive_labels =
+            volume_opts::decode_labels(args.parameters.get(volume_opts::POOL_TOPOLOGY_EXCLUSION))
+                .map_err(Status::invalid_argument)?;
+        let mut label_expressions = volume_opts::decode_label_selector(
+            args.parameters.get(volume_opts::POOL_TOPOLOGY_SELECTOR),
+        )
+        .map_err(Status::invalid_argument)?;
+        label_expressions.extend(inclusive_expressions);
+        let mut volume_labels =
+            volume_opts::decode_labels(args.parameters.get(volume_opts::VOLUME_LABELS))
+                .map_err(Status::invalid_argument)?;
+        if pinned_volume {
+            volume_labels.insert("local".to_string(), "true".to_string());
+        }
+        let anti_affinity_labels =
+            volume_opts::decode_labels(args.parameters.get(volume_opts::VOLUME_ANTI_AFFINITY))
+                .map_err(Status::invalid_argument)?;
 
         // For explanation of accessibilityRequirements refer to a table at
         // https://github.com/kubernetes-csi/external-provisioner.
@@ -297,6 +485,7 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
             String::from(OPENEBS_CREATED_BY_KEY),
             String::from(MSP_OPERATOR),
         );
+        inclusive_label_topology.extend(inclusive_labels);
 
         if let Some(reqs) = args.accessibility_requirements {
             for r in reqs.requisite.iter() {
@@ -335,17 +524,27 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
             .into_iter()
             .find(|v| v.spec.uuid == u)
         {
-            check_existing_volume(&existing_volume, replica_count, size, pinned_volume)?;
+            check_existing_volume(&existing_volume, replica_count, size, pinned_volume, thin)?;
             debug!(
                 "Volume {} already exists and is compatible with requested config",
                 volume_uuid
             );
         } else {
             let volume_topology =
-                CreateVolumeTopology::new(allowed_nodes, preferred_nodes, inclusive_label_topology);
+                CreateVolumeTopology::new(allowed_nodes, preferred_nodes, inclusive_label_topology)
+                    .with_pool_labels(exclusive_labels, label_expressions)
+                    .with_volume_anti_affinity(anti_affinity_labels);
 
             MayastorApiClient::get_client()
-                .create_volume(&u, replica_count, size, volume_topology, pinned_volume)
+                .create_volume(
+                    &u,
+                    replica_count,
+                    size,
+                    volume_topology,
+                    volume_labels,
+                    thin,
+                    source,
+                )
                 .await?;
 
             debug!(
@@ -358,7 +557,7 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
             capacity_bytes: size as i64,
             volume_id: volume_uuid,
             volume_context: args.parameters.clone(),
-            content_source: None,
+            content_source: args.volume_content_source.clone(),
             accessible_topology: vt_mapper.volume_accessible_topology(pinned_volume),
         };
 
@@ -432,6 +631,14 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
             .get_volume(&volume_id)
             .await?;
 
+        // A cloned volume can't be published until its content has been copied over.
+        if volume.spec.seeding {
+            return Err(Status::unavailable(format!(
+                "Volume {} is still being seeded from its content source",
+                volume_id
+            )));
+        }
+
         let uri =
             // Volume is already published, make sure the protocol matches and get URI.
             match &volume.spec.target {
@@ -670,6 +877,10 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
         // Check capabilities.
         check_volume_capabilities(&args.volume_capabilities)?;
 
+        // Thin provisioned volumes can make use of the pools overcommit.
+        let thin =
+            volume_opts::decode_thin_flag(args.parameters.get(volume_opts::THIN_PROVISIONING));
+
         // Determine target node, if requested.
         let node: Option<&String> = if let Some(topology) = args.accessible_topology.as_ref() {
             topology.segments.get(K8S_HOSTNAME)
@@ -700,7 +911,13 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
 
         let available_capacity: i64 = pools.into_iter().fold(0, |acc, p| match p.state {
             Some(state) => match state.status {
-                PoolStatus::Online | PoolStatus::Degraded => acc + state.capacity as i64,
+                PoolStatus::Online | PoolStatus::Degraded => {
+                    let overcommit = match p.spec {
+                        Some(spec) if thin => spec.overcommit.unwrap_or(DEFAULT_POOL_OVERCOMMIT),
+                        _ => DEFAULT_POOL_OVERCOMMIT,
+                    };
+                    acc + (state.capacity as u128 * overcommit as u128 / 100) as i64
+                }
                 _ => {
                     warn!(
                         "Pool {} on node {} is in '{:?}' state, not accounting it for capacity",
@@ -729,6 +946,9 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
             controller_service_capability::rpc::Type::PublishUnpublishVolume,
             controller_service_capability::rpc::Type::ListVolumes,
             controller_service_capability::rpc::Type::GetCapacity,
+            controller_service_capability::rpc::Type::CreateDeleteSnapshot,
+            controller_service_capability::rpc::Type::ListSnapshots,
+            controller_service_capability::rpc::Type::CloneVolume,
         ];
 
         Ok(Response::new(ControllerGetCapabilitiesResponse {
@@ -743,28 +963,197 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
         }))
     }
 
-    #[instrument(error)]
+    #[instrument(error, fields(volume.uuid = %request.get_ref().source_volume_id, snapshot.uuid = tracing::field::Empty))]
     async fn create_snapshot(
         &self,
-        _request: tonic::Request<CreateSnapshotRequest>,
+        request: tonic::Request<CreateSnapshotRequest>,
     ) -> Result<tonic::Response<CreateSnapshotResponse>, tonic::Status> {
-        Err(Status::unimplemented("Not implemented"))
+        let args = request.into_inner();
+
+        let volume_uuid = Uuid::parse_str(&args.source_volume_id).map_err(|_e| {
+            Status::invalid_argument(format!("Malformed volume UUID: {}", args.source_volume_id))
+        })?;
+
+        // k8s uses names snapshot-{uuid} and we use the uuid as the snapshot ID.
+        let re = Regex::new(SNAPSHOT_NAME_PATTERN).unwrap();
+        let snapshot_uuid = match re.captures(&args.name) {
+            Some(captures) => Uuid::parse_str(captures.get(1).unwrap().as_str()).unwrap(),
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "Expected the snapshot name in snapshot-<UUID> format: {}",
+                    args.name
+                )))
+            }
+        };
+        tracing::Span::current().record("snapshot.uuid", &snapshot_uuid.to_string().as_str());
+
+        // Check for an existing snapshot with the same name.
+        let snapshot = match MayastorApiClient::get_client()
+            .get_snapshot(&snapshot_uuid)
+            .await
+        {
+            Ok(snapshot) => {
+                if snapshot.volume != volume_uuid {
+                    return Err(Status::already_exists(format!(
+                        "Snapshot {} already exists for volume {}",
+                        snapshot_uuid, snapshot.volume
+                    )));
+                }
+                debug!("Snapshot {} already exists", snapshot_uuid);
+                snapshot
+            }
+            Err(ApiClientError::ResourceNotExists(_)) => MayastorApiClient::get_client()
+                .create_snapshot(&volume_uuid, &snapshot_uuid)
+                .await
+                .map_err(|e| match e {
+                    ApiClientError::ResourceNotExists(_) => Status::not_found(format!(
+                        "Volume {} not found, error = {:?}",
+                        volume_uuid, e
+                    )),
+                    // the volume can only be snapshotted while it's published
+                    ApiClientError::PreconditionFailed(_) => Status::failed_precondition(format!(
+                        "Volume {} can't be snapshotted, error = {:?}",
+                        volume_uuid, e
+                    )),
+                    _ => Status::internal(format!(
+                        "Failed to create snapshot {}, error = {:?}",
+                        snapshot_uuid, e
+                    )),
+                })?,
+            Err(e) => {
+                return Err(Status::internal(format!(
+                    "Failed to get snapshot {}, error = {:?}",
+                    snapshot_uuid, e
+                )))
+            }
+        };
+
+        debug!(
+            "Snapshot {} of volume {} successfully created",
+            snapshot_uuid, volume_uuid
+        );
+        Ok(Response::new(CreateSnapshotResponse {
+            snapshot: Some(csi_snapshot(&snapshot)),
+        }))
     }
 
-    #[instrument(error)]
+    #[instrument(error, fields(snapshot.uuid = %request.get_ref().snapshot_id))]
     async fn delete_snapshot(
         &self,
-        _request: tonic::Request<DeleteSnapshotRequest>,
+        request: tonic::Request<DeleteSnapshotRequest>,
     ) -> Result<tonic::Response<DeleteSnapshotResponse>, tonic::Status> {
-        Err(Status::unimplemented("Not implemented"))
+        let args = request.into_inner();
+
+        let snapshot_uuid = Uuid::parse_str(&args.snapshot_id).map_err(|_e| {
+            Status::invalid_argument(format!("Malformed snapshot UUID: {}", args.snapshot_id))
+        })?;
+
+        let snapshot = match MayastorApiClient::get_client()
+            .get_snapshot(&snapshot_uuid)
+            .await
+        {
+            Ok(snapshot) => snapshot,
+            Err(ApiClientError::ResourceNotExists(_)) => {
+                debug!("Snapshot {} does not exist", snapshot_uuid);
+                return Ok(Response::new(DeleteSnapshotResponse {}));
+            }
+            Err(e) => {
+                return Err(Status::internal(format!(
+                    "Failed to get snapshot {}, error = {:?}",
+                    snapshot_uuid, e
+                )))
+            }
+        };
+
+        MayastorApiClient::get_client()
+            .delete_snapshot(&snapshot.volume, &snapshot_uuid)
+            .await
+            .map_err(|e| {
+                Status::internal(format!(
+                    "Failed to delete snapshot {}, error = {:?}",
+                    snapshot_uuid, e
+                ))
+            })?;
+
+        debug!("Snapshot {} successfully deleted", snapshot_uuid);
+        Ok(Response::new(DeleteSnapshotResponse {}))
     }
 
     #[instrument(error)]
     async fn list_snapshots(
         &self,
-        _request: tonic::Request<ListSnapshotsRequest>,
+        request: tonic::Request<ListSnapshotsRequest>,
     ) -> Result<tonic::Response<ListSnapshotsResponse>, tonic::Status> {
-        Err(Status::unimplemented("Not implemented"))
+        let args = request.into_inner();
+
+        let max_entries = args.max_entries;
+        if max_entries < 0 {
+            return Err(Status::invalid_argument("max_entries can't be negative"));
+        }
+
+        let snapshots = if !args.snapshot_id.is_empty() {
+            let snapshot_uuid = Uuid::parse_str(&args.snapshot_id).map_err(|_e| {
+                Status::invalid_argument(format!("Malformed snapshot UUID: {}", args.snapshot_id))
+            })?;
+            match MayastorApiClient::get_client()
+                .get_snapshot(&snapshot_uuid)
+                .await
+            {
+                Ok(snapshot) => vec![snapshot],
+                // An unknown snapshot yields an empty list, not an error.
+                Err(ApiClientError::ResourceNotExists(_)) => vec![],
+                Err(e) => {
+                    return Err(Status::internal(format!(
+                        "Failed to get snapshot {}, error = {:?}",
+                        snapshot_uuid, e
+                    )))
+                }
+            }
+        } else if !args.source_volume_id.is_empty() {
+            let volume_uuid = Uuid::parse_str(&args.source_volume_id).map_err(|_e| {
+                Status::invalid_argument(format!(
+                    "Malformed volume UUID: {}",
+                    args.source_volume_id
+                ))
+            })?;
+            match MayastorApiClient::get_client()
+                .list_snapshots(Some(&volume_uuid))
+                .await
+            {
+                Ok(snapshots) => snapshots,
+                Err(ApiClientError::ResourceNotExists(_)) => vec![],
+                Err(e) => {
+                    return Err(Status::internal(format!(
+                        "Failed to list snapshots of volume {}, error = {:?}",
+                        volume_uuid, e
+                    )))
+                }
+            }
+        } else {
+            MayastorApiClient::get_client()
+                .list_snapshots(None)
+                .await
+                .map_err(|e| {
+                    Status::internal(format!("Failed to list snapshots, error = {:?}", e))
+                })?
+        };
+
+        let entries = snapshots
+            .iter()
+            .take(if max_entries > 0 {
+                max_entries as usize
+            } else {
+                MAX_VOLUMES_TO_LIST
+            })
+            .map(|s| list_snapshots_response::Entry {
+                snapshot: Some(csi_snapshot(s)),
+            })
+            .collect();
+
+        Ok(Response::new(ListSnapshotsResponse {
+            entries,
+            next_token: "".to_string(),
+        }))
     }
 
     #[instrument(error)]
@@ -783,3 +1172,114 @@ impl rpc::csi::controller_server::Controller for CsiControllerSvc {
         Err(Status::unimplemented("Not implemented"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_lib::types::v0::openapi::models::{LabelExpression, LabelOperator};
+
+    fn content_source(
+        source: volume_content_source::Type,
+    ) -> Option<rpc::csi::VolumeContentSource> {
+        Some(rpc::csi::VolumeContentSource {
+            r#type: Some(source),
+        })
+    }
+
+    #[test]
+    fn parse_content_source_test() {
+        let uuid = Uuid::parse_str("4d2e6f1a-3b5c-4a7d-8e9f-0a1b2c3d4e5f").unwrap();
+
+        assert_eq!(parse_content_source(&None).unwrap(), None);
+        assert_eq!(
+            parse_content_source(&Some(rpc::csi::VolumeContentSource { r#type: None })).unwrap(),
+            None
+        );
+
+        let volume = content_source(volume_content_source::Type::Volume(
+            volume_content_source::VolumeSource {
+                volume_id: uuid.to_string(),
+            },
+        ));
+        assert_eq!(
+            parse_content_source(&volume).unwrap(),
+            Some(VolumeContentSource::new_all(Some(uuid), None))
+        );
+
+        let snapshot = content_source(volume_content_source::Type::Snapshot(
+            volume_content_source::SnapshotSource {
+                snapshot_id: uuid.to_string(),
+            },
+        ));
+        assert_eq!(
+            parse_content_source(&snapshot).unwrap(),
+            Some(VolumeContentSource::new_all(None, Some(uuid)))
+        );
+
+        let malformed = content_source(volume_content_source::Type::Snapshot(
+            volume_content_source::SnapshotSource {
+                snapshot_id: "snapshot-1".to_string(),
+            },
+        ));
+        let error = parse_content_source(&malformed).unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn decode_labels_test() {
+        let encoded = |e: &str| Some(e.to_string());
+        let labels = |labels: &[(&str, &str)]| {
+            labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        assert_eq!(volume_opts::decode_labels(None).unwrap(), labels(&[]));
+        assert_eq!(
+            volume_opts::decode_labels(encoded(" zone = a, rack,, ").as_ref()).unwrap(),
+            labels(&[("zone", "a"), ("rack", "")])
+        );
+        assert!(volume_opts::decode_labels(encoded("=a").as_ref()).is_err());
+
+        let (inclusive, expressions) =
+            volume_opts::decode_inclusive_labels(encoded("zone=a,ssd").as_ref()).unwrap();
+        assert_eq!(inclusive, labels(&[("zone", "a")]));
+        assert_eq!(
+            expressions,
+            vec![LabelExpression::new("ssd", LabelOperator::Exists)]
+        );
+    }
+
+    #[test]
+    fn decode_label_selector_test() {
+        let decode = |e: &str| volume_opts::decode_label_selector(Some(&e.to_string()));
+        let values =
+            |values: &[&str]| Some(values.iter().map(|v| v.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(volume_opts::decode_label_selector(None).unwrap(), vec![]);
+        assert_eq!(decode(" ").unwrap(), vec![]);
+        assert_eq!(
+            decode("zone in (a, b), tier notin (slow), ssd, !hdd").unwrap(),
+            vec![
+                LabelExpression::new_all("zone", LabelOperator::In, values(&["a", "b"])),
+                LabelExpression::new_all("tier", LabelOperator::NotIn, values(&["slow"])),
+                LabelExpression::new("ssd", LabelOperator::Exists),
+                LabelExpression::new("hdd", LabelOperator::DoesNotExist),
+            ]
+        );
+
+        for invalid in [
+            "zone in (a, b",
+            "zone (a)",
+            "zone is (a)",
+            "in (a)",
+            "zone a",
+            "!",
+        ]
+        .iter()
+        {
+            assert!(decode(invalid).is_err(), "'{}' should be invalid", invalid);
+        }
+    }
+}
@@ -7,6 +7,7 @@ fn main() {
                 "proto/v1/pool/pool.proto",
                 "proto/v1/replica/replica.proto",
                 "proto/v1/misc/common.proto",
+                "proto/v1/watch/watch.proto",
             ],
             &["proto/"],
         )
@@ -3,6 +3,7 @@ pub mod grpc_opts;
 pub mod misc;
 pub mod pool;
 pub mod replica;
+pub mod watch;
 
 // Common module for all the misc operations
 // TODO: move this to its respective directory structure
@@ -19,3 +20,9 @@ pub(crate) mod pool_grpc {
 pub(crate) mod replica_grpc {
     tonic::include_proto!("v1.replica");
 }
+
+// Watch GRPC module for the autogenerated watch callback code
+// Public so that the watchers can implement the generated server
+pub mod watch_grpc {
+    tonic::include_proto!("v1.watch");
+}
@@ -16,6 +16,7 @@ impl From<ResourceKind> for common::ResourceKind {
             ResourceKind::JsonGrpc => Self::JsonGrpc,
             ResourceKind::Block => Self::Block,
             ResourceKind::Watch => Self::Watch,
+            ResourceKind::Snapshot => Self::Snapshot,
         }
     }
 }
@@ -35,6 +36,7 @@ impl From<common::ResourceKind> for ResourceKind {
             common::ResourceKind::JsonGrpc => Self::JsonGrpc,
             common::ResourceKind::Block => Self::Block,
             common::ResourceKind::Watch => Self::Watch,
+            common::ResourceKind::Snapshot => Self::Snapshot,
         }
     }
 }
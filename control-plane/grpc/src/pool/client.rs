@@ -1,10 +1,11 @@
 use crate::{
     common::{NodeFilter, NodePoolFilter, PoolFilter},
     grpc_opts::{timeout_grpc, Context},
-    pool::traits::{CreatePoolInfo, DestroyPoolInfo, PoolOperations},
+    pool::traits::{CreatePoolInfo, DestroyPoolInfo, PoolOperations, SetPoolMaintenanceInfo},
     pool_grpc::{
         create_pool_reply, get_pools_reply, get_pools_request, pool_grpc_client::PoolGrpcClient,
-        CreatePoolRequest, DestroyPoolRequest, GetPoolsRequest,
+        set_pool_maintenance_reply, CreatePoolRequest, DestroyPoolRequest, GetPoolsRequest,
+        SetPoolMaintenanceRequest,
     },
 };
 use common_lib::{
@@ -136,4 +137,21 @@ impl PoolOperations for PoolClient {
             None => Err(ReplyError::invalid_response(ResourceKind::Pool)),
         }
     }
+
+    async fn set_maintenance(
+        &self,
+        set_maintenance_req: &dyn SetPoolMaintenanceInfo,
+        ctx: Option<Context>,
+    ) -> Result<Pool, ReplyError> {
+        let client = self.reconnect(ctx, MessageIdVs::SetPoolMaintenance).await?;
+        let req: SetPoolMaintenanceRequest = set_maintenance_req.into();
+        let response = client.clone().set_pool_maintenance(req).await?.into_inner();
+        match response.reply {
+            Some(set_pool_maintenance_reply) => match set_pool_maintenance_reply {
+                set_pool_maintenance_reply::Reply::Pool(pool) => Ok(Pool::try_from(pool)?),
+                set_pool_maintenance_reply::Reply::Error(err) => Err(err.into()),
+            },
+            None => Err(ReplyError::invalid_response(ResourceKind::Pool)),
+        }
+    }
 }
@@ -4,8 +4,9 @@ use crate::{
     pool_grpc::{
         create_pool_reply, get_pools_reply,
         pool_grpc_server::{PoolGrpc, PoolGrpcServer},
-        CreatePoolReply, CreatePoolRequest, DestroyPoolReply, DestroyPoolRequest, GetPoolsReply,
-        GetPoolsRequest,
+        set_pool_maintenance_reply, CreatePoolReply, CreatePoolRequest, DestroyPoolReply,
+        DestroyPoolRequest, GetPoolsReply, GetPoolsRequest, SetPoolMaintenanceReply,
+        SetPoolMaintenanceRequest,
     },
 };
 use common_lib::mbus_api::{ErrorChain, ReplyError};
@@ -108,4 +109,30 @@ impl PoolGrpc for PoolServer {
             }))
         })
     }
+
+    async fn set_pool_maintenance(
+        &self,
+        request: Request<SetPoolMaintenanceRequest>,
+    ) -> Result<tonic::Response<SetPoolMaintenanceReply>, tonic::Status> {
+        let req: SetPoolMaintenanceRequest = request.into_inner();
+        let service = self.service.clone();
+        tokio::spawn(async move {
+            match service.set_maintenance(&req, None).await {
+                Ok(pool) => Ok(Response::new(SetPoolMaintenanceReply {
+                    reply: Some(set_pool_maintenance_reply::Reply::Pool(pool.into())),
+                })),
+                Err(err) => Ok(Response::new(SetPoolMaintenanceReply {
+                    reply: Some(set_pool_maintenance_reply::Reply::Error(err.into())),
+                })),
+            }
+        })
+        .await
+        .unwrap_or_else(|e| {
+            Ok(Response::new(SetPoolMaintenanceReply {
+                reply: Some(set_pool_maintenance_reply::Reply::Error(
+                    ReplyError::tonic_reply_error(e.to_string(), e.full_string()).into(),
+                )),
+            }))
+        })
+    }
 }
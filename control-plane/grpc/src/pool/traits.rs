@@ -2,7 +2,9 @@ use crate::{
     common,
     grpc_opts::Context,
     pool_grpc,
-    pool_grpc::{get_pools_request, CreatePoolRequest, DestroyPoolRequest},
+    pool_grpc::{
+        get_pools_request, CreatePoolRequest, DestroyPoolRequest, SetPoolMaintenanceRequest,
+    },
 };
 use common_lib::{
     mbus_api::{v0::Pools, ReplyError, ResourceKind},
@@ -10,6 +12,7 @@ use common_lib::{
         message_bus,
         message_bus::{
             CreatePool, DestroyPool, Filter, NodeId, Pool, PoolDeviceUri, PoolId, PoolState,
+            SetPoolMaintenance,
         },
         store::pool::{PoolLabel, PoolSpec, PoolSpecStatus},
     },
@@ -30,6 +33,11 @@ pub trait PoolOperations: Send + Sync {
         ctx: Option<Context>,
     ) -> Result<(), ReplyError>;
     async fn get(&self, filter: Filter, ctx: Option<Context>) -> Result<Pools, ReplyError>;
+    async fn set_maintenance(
+        &self,
+        pool: &dyn SetPoolMaintenanceInfo,
+        ctx: Option<Context>,
+    ) -> Result<Pool, ReplyError>;
 }
 
 impl TryFrom<pool_grpc::Pool> for Pool {
@@ -82,6 +90,8 @@ impl TryFrom<pool_grpc::Pool> for Pool {
                         Some(labels) => Some(labels.value),
                         None => None,
                     },
+                    overcommit: pool_spec.overcommit,
+                    maintenance: pool_spec.maintenance,
                     sequencer: Default::default(),
                     operation: None,
                 })
@@ -108,6 +118,8 @@ impl From<Pool> for pool_grpc::Pool {
                         labels: pool_spec
                             .labels
                             .map(|labels| crate::common::StringMapValue { value: labels }),
+                        overcommit: pool_spec.overcommit,
+                        maintenance: pool_spec.maintenance,
                     }),
                     metadata: Some(pool_grpc::Metadata {
                         uuid: None,
@@ -181,6 +193,7 @@ pub trait CreatePoolInfo: Send + Sync {
     fn node_id(&self) -> NodeId;
     fn disks(&self) -> Vec<PoolDeviceUri>;
     fn labels(&self) -> Option<PoolLabel>;
+    fn overcommit(&self) -> Option<u32>;
 }
 
 /// DestroyPoolInfo trait for the pool deletion to be implemented by entities which want to avail
@@ -206,6 +219,10 @@ impl CreatePoolInfo for CreatePool {
     fn labels(&self) -> Option<PoolLabel> {
         self.labels.clone()
     }
+
+    fn overcommit(&self) -> Option<u32> {
+        self.overcommit
+    }
 }
 
 impl CreatePoolInfo for CreatePoolRequest {
@@ -227,6 +244,10 @@ impl CreatePoolInfo for CreatePoolRequest {
             Some(labels) => Some(labels.value),
         }
     }
+
+    fn overcommit(&self) -> Option<u32> {
+        self.overcommit
+    }
 }
 
 impl From<&dyn CreatePoolInfo> for CreatePoolRequest {
@@ -238,6 +259,7 @@ impl From<&dyn CreatePoolInfo> for CreatePoolRequest {
             labels: data
                 .labels()
                 .map(|labels| crate::common::StringMapValue { value: labels }),
+            overcommit: data.overcommit(),
         }
     }
 }
@@ -249,6 +271,7 @@ impl From<&dyn CreatePoolInfo> for CreatePool {
             id: data.pool_id(),
             disks: data.disks(),
             labels: data.labels(),
+            overcommit: data.overcommit(),
         }
     }
 }
@@ -291,6 +314,51 @@ impl From<&dyn DestroyPoolInfo> for DestroyPool {
     }
 }
 
+/// SetPoolMaintenanceInfo trait for starting or stopping the maintenance of a pool to be
+/// implemented by entities which want to avail this operation
+pub trait SetPoolMaintenanceInfo: Send + Sync {
+    fn pool_id(&self) -> PoolId;
+    fn maintenance(&self) -> bool;
+}
+
+impl SetPoolMaintenanceInfo for SetPoolMaintenance {
+    fn pool_id(&self) -> PoolId {
+        self.id.clone()
+    }
+
+    fn maintenance(&self) -> bool {
+        self.maintenance
+    }
+}
+
+impl SetPoolMaintenanceInfo for SetPoolMaintenanceRequest {
+    fn pool_id(&self) -> PoolId {
+        self.pool_id.clone().into()
+    }
+
+    fn maintenance(&self) -> bool {
+        self.maintenance
+    }
+}
+
+impl From<&dyn SetPoolMaintenanceInfo> for SetPoolMaintenanceRequest {
+    fn from(data: &dyn SetPoolMaintenanceInfo) -> Self {
+        Self {
+            pool_id: data.pool_id().to_string(),
+            maintenance: data.maintenance(),
+        }
+    }
+}
+
+impl From<&dyn SetPoolMaintenanceInfo> for SetPoolMaintenance {
+    fn from(data: &dyn SetPoolMaintenanceInfo) -> Self {
+        Self {
+            id: data.pool_id(),
+            maintenance: data.maintenance(),
+        }
+    }
+}
+
 impl From<pool_grpc::PoolStatus> for message_bus::PoolStatus {
     fn from(src: pool_grpc::PoolStatus) -> Self {
         match src {
@@ -0,0 +1,59 @@
+use crate::watch_grpc::{watch_callback_client::WatchCallbackClient, WatchNotification};
+use common_lib::{
+    mbus_api::{ReplyError, ReplyErrorKind, ResourceKind},
+    types::v0::message_bus,
+};
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+
+/// Prefix of the watch callback gRPC endpoints, which is replaced by the http scheme
+const GRPC_SCHEME: &str = "grpc://";
+
+/// RPC Watch Callback Client, which notifies a watcher of the changes of its watched resource
+#[derive(Clone)]
+pub struct WatchClient {
+    client: WatchCallbackClient<Channel>,
+}
+
+impl WatchClient {
+    /// connects to the `WatchCallback` service of the watcher at the given endpoint,
+    /// eg: "grpc://10.1.0.1:50051", with the timeout used for the connection and each request
+    pub async fn connect(endpoint: &str, timeout: Duration) -> Result<Self, ReplyError> {
+        let uri = match endpoint.strip_prefix(GRPC_SCHEME) {
+            Some(address) => format!("http://{}", address),
+            None => endpoint.to_string(),
+        };
+        let endpoint = Endpoint::from_shared(uri)
+            .map_err(|error| ReplyError {
+                kind: ReplyErrorKind::InvalidArgument,
+                resource: ResourceKind::Unknown,
+                source: endpoint.to_string(),
+                extra: error.to_string(),
+            })?
+            .connect_timeout(timeout)
+            .timeout(timeout);
+        let client = WatchCallbackClient::connect(endpoint).await?;
+        Ok(Self { client })
+    }
+
+    /// notify the watcher of a change of its watched resource
+    pub async fn notify(
+        &mut self,
+        notification: &message_bus::WatchNotification,
+    ) -> Result<(), ReplyError> {
+        self.client
+            .notify(WatchNotification::from(notification))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<&message_bus::WatchNotification> for WatchNotification {
+    fn from(notification: &message_bus::WatchNotification) -> Self {
+        Self {
+            resource: notification.resource.clone(),
+            revision: notification.revision,
+            value: notification.value.as_ref().map(|value| value.to_string()),
+        }
+    }
+}
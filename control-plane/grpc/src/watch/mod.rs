@@ -0,0 +1,2 @@
+// Watch callback grpc Client related code
+pub mod client;
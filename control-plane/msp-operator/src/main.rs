@@ -32,9 +32,14 @@ use std::{collections::HashMap, io::Write, ops::Deref, sync::Arc, time::Duration
 use tracing::{debug, error, info, trace, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 
+mod volume;
+
 const WHO_AM_I: &str = "Mayastor pool operator";
 const WHO_AM_I_SHORT: &str = "msp-operator";
 const CRD_FILE_NAME: &str = "mayastorpoolcrd.yaml";
+const VOLUME_CRD_FILE_NAME: &str = "mayastorvolumecrd.yaml";
+/// Annotation which puts the pool in maintenance when set to "true"
+const MAINTENANCE_ANNOTATION: &str = "openebs.io/maintenance";
 
 /// Various common constants used by the control plane
 pub mod constants {
@@ -64,7 +69,8 @@ pub mod constants {
 pub struct MayastorPoolSpec {
     /// The node the pool is placed on
     node: String,
-    /// The disk device the pool is located on
+    /// The disk devices the pool is located on. Once the pool is created, a change of the disks
+    /// is reported as a drift of the spec but it's not applied to the pool.
     disks: Vec<String>,
 }
 
@@ -102,6 +108,38 @@ pub struct MayastorPoolStatus {
     used: u64,
     /// Available number of bytes
     available: u64,
+    /// Conditions of the pool, eg: the resource spec drifted from the pool
+    #[serde(default)]
+    conditions: Vec<MayastorPoolCondition>,
+}
+
+/// Type of the condition set when the resource spec drifted from the pool
+const SPEC_DRIFT: &str = "SpecDrift";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+/// Condition of the pool, as per the Kubernetes API conventions
+pub struct MayastorPoolCondition {
+    /// Type of the condition
+    #[serde(rename = "type")]
+    type_: String,
+    /// Status of the condition, one of True, False or Unknown
+    status: String,
+    /// Machine readable reason of the condition
+    reason: String,
+    /// Human readable details of the condition
+    message: String,
+}
+
+impl MayastorPoolCondition {
+    /// The resource spec drifted from the pool
+    fn spec_drift(reason: &str, message: &str) -> Self {
+        Self {
+            type_: SPEC_DRIFT.to_string(),
+            status: "True".to_string(),
+            reason: reason.to_string(),
+            message: message.to_string(),
+        }
+    }
 }
 
 impl Default for MayastorPoolStatus {
@@ -111,6 +149,7 @@ impl Default for MayastorPoolStatus {
             capacity: 0,
             used: 0,
             available: 0,
+            conditions: vec![],
         }
     }
 }
@@ -122,6 +161,7 @@ impl MayastorPoolStatus {
             capacity: 0,
             used: 0,
             available: 0,
+            conditions: vec![],
         }
     }
     fn created() -> Self {
@@ -130,6 +170,7 @@ impl MayastorPoolStatus {
             capacity: 0,
             used: 0,
             available: 0,
+            conditions: vec![],
         }
     }
     fn unknown() -> Self {
@@ -138,6 +179,7 @@ impl MayastorPoolStatus {
             capacity: 0,
             used: 0,
             available: 0,
+            conditions: vec![],
         }
     }
 }
@@ -156,6 +198,7 @@ impl From<Pool> for MayastorPoolStatus {
             capacity: state.capacity,
             used: state.used,
             available: free,
+            conditions: vec![],
         }
     }
 }
@@ -513,7 +556,7 @@ impl ResourceContext {
                 Ok(response) => {
                     let pool = response.into_body();
                     // As pool exists, set the status based on the presence of pool state.
-                    self.set_status_or_unknown(pool).await
+                    self.set_status_or_unknown(pool, None).await
                 }
                 Err(_) => {
                     // If we don't find the pool, i.e. its not present or not yet created
@@ -601,13 +644,11 @@ impl ResourceContext {
     /// accordingly. If the control plane returns a pool state, set the CRD to 'Online'. If the
     /// control plane does not return a pool state (occurs when a node is missing), set the CRD to
     /// 'Unknown' and let the reconciler retry later.
+    /// Any drift of the CRD spec from the pool is reported as a condition of the CRD.
     #[tracing::instrument(fields(name = ?self.name(), status = ?self.status) skip(self))]
     async fn pool_check(&self) -> Result<ReconcilerAction, Error> {
-        let pool = match self
-            .pools_api()
-            .get_node_pool(&self.spec.node, &self.name())
-            .await
-        {
+        // the pool is looked up by name only so that a change of node is reported as a drift
+        let pool = match self.pools_api().get_pool(&self.name()).await {
             Ok(response) => Ok(response),
             Err(clients::tower::Error::Response(response)) => {
                 if response.status() == clients::tower::StatusCode::NOT_FOUND {
@@ -643,16 +684,103 @@ impl ResourceContext {
             }
             error => error,
         }?.into_body();
+        let drift = self.detect_drift(&pool).await;
+        let pool = self.converge_maintenance(pool).await;
         // As pool exists, set the status based on the presence of pool state.
-        self.set_status_or_unknown(pool).await
+        self.set_status_or_unknown(pool, drift).await
+    }
+
+    /// Detect the drift of the CRD spec from the pool, the node and the disks of a pool can't be
+    /// changed once it's created so any drift is reported as a condition of the CRD instead.
+    async fn detect_drift(&self, pool: &Pool) -> Option<MayastorPoolCondition> {
+        let spec = pool.spec.as_ref()?;
+        if spec.node != self.spec.node {
+            let message = format!(
+                "The pool can not be moved from node '{}' to node '{}'",
+                spec.node, self.spec.node
+            );
+            return Some(self.drift_condition("NodeChanged", &message).await);
+        }
+        match disk_drift(&self.spec.disks, &spec.disks) {
+            Some(message) => Some(self.drift_condition("DisksChanged", &message).await),
+            None => None,
+        }
+    }
+
+    /// Check if the pool maintenance is requested through the maintenance annotation
+    fn maintenance(&self) -> bool {
+        self.annotations()
+            .get(MAINTENANCE_ANNOTATION)
+            .map(|value| value == "true")
+            .unwrap_or_default()
+    }
+
+    /// Start or stop the maintenance of the pool as requested through the maintenance
+    /// annotation. Pools in maintenance are not used for new replicas and their replicas are
+    /// moved to other pools by the control plane.
+    async fn converge_maintenance(&self, pool: Pool) -> Pool {
+        let maintenance = self.maintenance();
+        match &pool.spec {
+            Some(spec) if spec.maintenance != maintenance => {}
+            _ => return pool,
+        }
+
+        let result = if maintenance {
+            self.pools_api().put_pool_maintenance(&self.name()).await
+        } else {
+            self.pools_api().del_pool_maintenance(&self.name()).await
+        };
+        match result {
+            Ok(response) => {
+                let message = if maintenance {
+                    "The pool is in maintenance, its replicas are being moved to other pools"
+                } else {
+                    "The pool is no longer in maintenance"
+                };
+                self.k8s_notify("Update pool", "Maintenance", message, "Normal")
+                    .await;
+                response.into_body()
+            }
+            Err(error) => {
+                let message = format!(
+                    "Failed to update the maintenance of the pool: {}",
+                    Error::from(error)
+                );
+                self.k8s_notify("Update pool", "Maintenance", &message, "Warning")
+                    .await;
+                pool
+            }
+        }
+    }
+
+    /// Get the drift condition with the given reason, posting an event when the condition is new
+    async fn drift_condition(&self, reason: &str, message: &str) -> MayastorPoolCondition {
+        let condition = MayastorPoolCondition::spec_drift(reason, message);
+        let known = self
+            .status
+            .as_ref()
+            .map(|status| status.conditions.contains(&condition))
+            .unwrap_or(false);
+        if !known {
+            warn!(name = ?self.name(), message, "pool spec drift");
+            self.k8s_notify("Update pool", reason, message, "Warning")
+                .await;
+        }
+        condition
     }
 
     /// If the pool, has a state we set that status to the CR and if it does not have a state
     /// we set the status as unknown so that we can try again later.
-    async fn set_status_or_unknown(&self, pool: Pool) -> Result<ReconcilerAction, Error> {
+    /// The drift condition, if any, is added to the status.
+    async fn set_status_or_unknown(
+        &self,
+        pool: Pool,
+        drift: Option<MayastorPoolCondition>,
+    ) -> Result<ReconcilerAction, Error> {
         if pool.state.is_some() {
             if let Some(status) = &self.status {
-                let new_status = MayastorPoolStatus::from(pool);
+                let mut new_status = MayastorPoolStatus::from(pool);
+                new_status.conditions = drift.into_iter().collect();
                 if status != &new_status {
                     // update the usage state such that users can see the values changes
                     // as replica's are added and/or removed.
@@ -699,50 +827,7 @@ impl ResourceContext {
     ///     the  future
 
     async fn k8s_notify(&self, action: &str, reason: &str, message: &str, type_: &str) {
-        let client = self.ctx.k8s.clone();
-        let ns = self.namespace().expect("must be namespaced");
-        let e: Api<k8Event> = Api::namespaced(client, &ns);
-        let pp = PostParams::default();
-        let time = Utc::now();
-
-        let metadata = ObjectMeta {
-            // the name must be unique for all events we post
-            generate_name: Some(format!("{}.{:x}", self.name(), time.timestamp())),
-            namespace: Some(ns),
-            ..Default::default()
-        };
-
-        let _ = e
-            .create(
-                &pp,
-                &k8Event {
-                    //last_timestamp: Some(time2),
-                    event_time: Some(MicroTime(time)),
-                    involved_object: ObjectReference {
-                        api_version: Some(self.api_version.clone()),
-                        field_path: None,
-                        kind: Some(self.kind.clone()),
-                        name: Some(self.name()),
-                        namespace: self.namespace(),
-                        resource_version: self.resource_version(),
-                        uid: self.uid(),
-                    },
-                    action: Some(action.into()),
-                    reason: Some(reason.into()),
-                    type_: Some(type_.into()),
-                    metadata,
-                    reporting_component: Some(WHO_AM_I_SHORT.into()),
-                    reporting_instance: Some(
-                        std::env::var("MY_POD_NAME")
-                            .ok()
-                            .unwrap_or_else(|| WHO_AM_I_SHORT.into()),
-                    ),
-                    message: Some(message.into()),
-                    ..Default::default()
-                },
-            )
-            .await
-            .map_err(|e| error!(?e));
+        k8s_notify(&self.ctx.k8s, &self.inner, action, reason, message, type_).await
     }
 
     /// Callback hooks for the finalizers
@@ -767,23 +852,78 @@ impl ResourceContext {
     }
 }
 
+/// Post an event against the given custom `resource`, see `ResourceContext::k8s_notify`.
+async fn k8s_notify<K: kube::Resource<DynamicType = ()>>(
+    client: &Client,
+    resource: &K,
+    action: &str,
+    reason: &str,
+    message: &str,
+    type_: &str,
+) {
+    let ns = resource.namespace().expect("must be namespaced");
+    let e: Api<k8Event> = Api::namespaced(client.clone(), &ns);
+    let pp = PostParams::default();
+    let time = Utc::now();
+
+    let metadata = ObjectMeta {
+        // the name must be unique for all events we post
+        generate_name: Some(format!("{}.{:x}", resource.name(), time.timestamp())),
+        namespace: Some(ns),
+        ..Default::default()
+    };
+
+    let _ = e
+        .create(
+            &pp,
+            &k8Event {
+                //last_timestamp: Some(time2),
+                event_time: Some(MicroTime(time)),
+                involved_object: ObjectReference {
+                    api_version: Some(K::api_version(&()).to_string()),
+                    field_path: None,
+                    kind: Some(K::kind(&()).to_string()),
+                    name: Some(resource.name()),
+                    namespace: resource.namespace(),
+                    resource_version: resource.resource_version(),
+                    uid: resource.uid(),
+                },
+                action: Some(action.into()),
+                reason: Some(reason.into()),
+                type_: Some(type_.into()),
+                metadata,
+                reporting_component: Some(WHO_AM_I_SHORT.into()),
+                reporting_instance: Some(
+                    std::env::var("MY_POD_NAME")
+                        .ok()
+                        .unwrap_or_else(|| WHO_AM_I_SHORT.into()),
+                ),
+                message: Some(message.into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| error!(?e));
+}
+
 /// ensure the CRD is installed. This creates a chicken and egg problem. When the CRD is removed,
 /// the operator will fail to list the CRD going into a error loop.
 ///
 /// To prevent that, we will simply panic, and hope we can make progress after restart. Keep
 /// running is not an option as the operator would be "running" and the only way to know something
 /// is wrong would be to consult the logs.
-async fn ensure_crd(k8s: Client) {
+async fn ensure_crd<K: CustomResourceExt>(k8s: Client) {
     let msp: Api<k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition> = Api::all(k8s);
-    let lp = ListParams::default().fields(&format!("metadata.name={}", "mayastorpools.openebs.io"));
+    let crd_name = K::crd().metadata.name.unwrap_or_default();
+    let lp = ListParams::default().fields(&format!("metadata.name={}", crd_name));
     let crds = msp.list(&lp).await.expect("failed to list CRDS");
 
     // the CRD has not been installed yet, to avoid overwriting (and create upgrade issues) only
     // install it when there is no crd with the given name
     if crds.iter().count() == 0 {
-        let crd = MayastorPool::crd();
+        let crd = K::crd();
         info!(
-            "Creating Foo CRD: {}",
+            "Creating CRD: {}",
             serde_json::to_string_pretty(&crd).unwrap()
         );
 
@@ -809,7 +949,7 @@ async fn ensure_crd(k8s: Client) {
 
 /// Determine what we want to do when dealing with errors from the
 /// reconciliation loop
-fn error_policy(error: &Error, _ctx: Context<OperatorContext>) -> ReconcilerAction {
+fn error_policy<T>(error: &Error, _ctx: Context<T>) -> ReconcilerAction {
     let duration = Duration::from_secs(match error {
         Error::Duplicate { timeout } | Error::SpecError { timeout, .. } => (*timeout).into(),
 
@@ -889,29 +1029,16 @@ async fn reconcile(
 async fn pool_controller(args: ArgMatches<'_>) -> anyhow::Result<()> {
     let k8s = Client::try_default().await?;
     let namespace = args.value_of("namespace").unwrap();
-    ensure_crd(k8s.clone()).await;
+    ensure_crd::<MayastorPool>(k8s.clone()).await;
 
     let msp: Api<MayastorPool> = Api::namespaced(k8s.clone(), namespace);
     let lp = ListParams::default();
-    let url = Url::parse(args.value_of("endpoint").unwrap()).expect("endpoint is not a valid URL");
-    let cfg = clients::tower::Configuration::new(url, Duration::from_secs(5), None, None, true)
-        .map_err(|error| {
-            anyhow::anyhow!(
-                "Failed to create openapi configuration, Error: '{:?}'",
-                error
-            )
-        })?;
 
     let context = Context::new(OperatorContext {
         k8s,
         inventory: tokio::sync::RwLock::new(HashMap::new()),
-        http: clients::tower::ApiClient::new(cfg),
-        interval: args
-            .value_of("interval")
-            .unwrap()
-            .parse::<humantime::Duration>()
-            .expect("interval value is invalid")
-            .as_secs(),
+        http: rest_client(&args)?,
+        interval: interval(&args),
         retries: args
             .value_of("retries")
             .unwrap()
@@ -941,14 +1068,36 @@ async fn pool_controller(args: ArgMatches<'_>) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Generate the mayastor pool CRD file allowing users to register them with kubernetes
-/// before the pool operator starts running.
+/// Create the client of the control plane REST API at the given endpoint
+fn rest_client(args: &ArgMatches<'_>) -> anyhow::Result<clients::tower::ApiClient> {
+    let url = Url::parse(args.value_of("endpoint").unwrap()).expect("endpoint is not a valid URL");
+    let cfg = clients::tower::Configuration::new(url, Duration::from_secs(5), None, None, true)
+        .map_err(|error| {
+            anyhow::anyhow!(
+                "Failed to create openapi configuration, Error: '{:?}'",
+                error
+            )
+        })?;
+    Ok(clients::tower::ApiClient::new(cfg))
+}
+
+/// The timer based reconciliation interval, in seconds
+fn interval(args: &ArgMatches<'_>) -> u64 {
+    args.value_of("interval")
+        .unwrap()
+        .parse::<humantime::Duration>()
+        .expect("interval value is invalid")
+        .as_secs()
+}
+
+/// Generate the CRD file of the mayastor pools or volumes allowing users to register them with
+/// kubernetes before the operator starts running.
 /// Can also be used to unregister the CRDs on uninstall.
-fn write_msp_crd(name: Option<&str>) -> anyhow::Result<()> {
-    let file = std::path::Path::new(name.unwrap_or(CRD_FILE_NAME));
+fn write_crd<K: CustomResourceExt>(name: &str) -> anyhow::Result<()> {
+    let file = std::path::Path::new(name);
     let mut file = std::fs::File::create(file)?;
 
-    let crd = MayastorPool::crd();
+    let crd = K::crd();
     let str = serde_json::to_string_pretty(&crd)?;
     file.write_all(str.as_ref())?;
 
@@ -957,7 +1106,7 @@ fn write_msp_crd(name: Option<&str>) -> anyhow::Result<()> {
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
-    let matches = App::new("Mayastor k8s pool operator")
+    let matches = App::new("Mayastor k8s pool and volume operator")
         .author(clap::crate_authors!())
         .version(utils::package_info!())
         .settings(&[
@@ -1012,6 +1161,36 @@ async fn main() -> anyhow::Result<()> {
                     "writes out the CRD file to current directory with the optional name and exits",
                 ),
         )
+        .arg(
+            Arg::with_name("write_volume_crd")
+                .long("write-volume-crd")
+                .default_value(VOLUME_CRD_FILE_NAME)
+                .takes_value(true)
+                .help(
+                    "writes out the volume CRD file to current directory with the optional name and exits",
+                ),
+        )
+        .arg(
+            Arg::with_name("volumes")
+                .long("volumes")
+                .env("VOLUMES")
+                .takes_value(false)
+                .help("also manage the volumes declared through MayastorVolume resources"),
+        )
+        .arg(
+            Arg::with_name("watch_addr")
+                .long("watch-addr")
+                .env("WATCH_ADDR")
+                .takes_value(true)
+                .help("serve the volume watch notifications of the control plane on this address, eg: 0.0.0.0:8090"),
+        )
+        .arg(
+            Arg::with_name("watch_callback")
+                .long("watch-callback")
+                .env("WATCH_CALLBACK")
+                .takes_value(true)
+                .help("an URL through which the control plane reaches the watch address, eg: http://msp-operator:8090"),
+        )
         .get_matches();
 
     utils::print_package_info!();
@@ -1040,14 +1219,63 @@ async fn main() -> anyhow::Result<()> {
     }
 
     if matches.occurrences_of("write_crd") > 0 {
-        return write_msp_crd(matches.value_of("write_crd"));
+        return write_crd::<MayastorPool>(matches.value_of("write_crd").unwrap_or(CRD_FILE_NAME));
+    }
+    if matches.occurrences_of("write_volume_crd") > 0 {
+        return write_crd::<volume::MayastorVolume>(
+            matches
+                .value_of("write_volume_crd")
+                .unwrap_or(VOLUME_CRD_FILE_NAME),
+        );
     }
 
-    pool_controller(matches).await?;
+    if matches.is_present("volumes") {
+        futures::try_join!(
+            pool_controller(matches.clone()),
+            volume::volume_controller(matches)
+        )?;
+    } else {
+        pool_controller(matches).await?;
+    }
     global::shutdown_tracer_provider();
     Ok(())
 }
 
+/// Get the difference between the disks of the CRD `spec` and the disks of the `pool`, if any
+fn disk_drift(spec: &[String], pool: &[String]) -> Option<String> {
+    let contains = |disks: &[String], disk: &String| {
+        disks
+            .iter()
+            .any(|d| normalize_disk(d) == normalize_disk(disk))
+    };
+    let added = spec
+        .iter()
+        .filter(|disk| !contains(pool, disk))
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed = pool
+        .iter()
+        .filter(|disk| !contains(spec, disk))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut changes = vec![];
+    if !added.is_empty() {
+        changes.push(format!("added {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        changes.push(format!("removed {}", removed.join(", ")));
+    }
+    if changes.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "The disks of the pool can not be changed, {}",
+            changes.join(" and ")
+        ))
+    }
+}
+
 /// Normalize the disks if they have a schema, we dont want to change anything
 /// or do any error checking -- the loop will converge to the error state eventually
 fn normalize_disk(disk: &str) -> String {
@@ -1076,4 +1304,28 @@ mod test {
         assert_eq!(normalize_disk(disks[1]), "/dev/null");
         assert_eq!(normalize_disk(disks[2]), "uring://dev/null");
     }
+
+    #[test]
+    fn disk_drift() {
+        use super::*;
+        let disks = |disks: &[&str]| disks.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        let pool = disks(&["/dev/sda", "/dev/sdb"]);
+
+        assert_eq!(
+            disk_drift(&disks(&["aio:///dev/sda", "/dev/sdb"]), &pool),
+            None
+        );
+        assert_eq!(
+            disk_drift(&disks(&["/dev/sda", "/dev/sdb", "/dev/sdc"]), &pool).as_deref(),
+            Some("The disks of the pool can not be changed, added /dev/sdc")
+        );
+        assert_eq!(
+            disk_drift(&disks(&["/dev/sda"]), &pool).as_deref(),
+            Some("The disks of the pool can not be changed, removed /dev/sdb")
+        );
+        assert_eq!(
+            disk_drift(&disks(&["/dev/sda", "/dev/sdc"]), &pool).as_deref(),
+            Some("The disks of the pool can not be changed, added /dev/sdc and removed /dev/sdb")
+        );
+    }
 }
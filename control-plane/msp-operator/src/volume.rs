@@ -0,0 +1,743 @@
+//! Mayastor volume operator which watches for volume CRDs and creates the volume with the given
+//! size and number of replicas. The replica count of the volume can then be changed
+//! declaratively and, when a target node is given, the volume is published on that node. A volume
+//! which is already published is never moved to another node, as its target may be in use.
+//!
+//! The status of the CRD mirrors the state of the volume. It is refreshed on a timer and whenever
+//! the control plane notifies the operator of a change, through the volume watch API.
+use crate::{ensure_crd, error_policy, interval, k8s_notify, rest_client, Error, WHO_AM_I};
+use clap::ArgMatches;
+use futures::StreamExt;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use kube::{
+    api::{Api, DeleteParams, ListParams, Patch, PatchParams},
+    Client, CustomResource, ResourceExt,
+};
+use kube_runtime::{
+    controller::{Context, Controller, ReconcilerAction},
+    finalizer::{finalizer, Error as FinalizerError, Event},
+};
+use openapi::{
+    apis::Uuid,
+    clients::{self, tower::Url},
+    models::{CreateVolumeBody, Volume, VolumePolicy, VolumeShareProtocol},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{debug, error, info, trace, warn};
+
+/// Finalizer which destroys the volumes created by the operator when their CRD is deleted
+const FINALIZER: &str = "io.mayastor.volume/cleanup";
+/// Path prefix of the volume watch notifications
+const WATCH_PATH: &str = "/volumes/";
+
+#[derive(CustomResource, Serialize, Deserialize, Default, Debug, PartialEq, Clone, JsonSchema)]
+#[kube(
+    group = "openebs.io",
+    version = "v1alpha1",
+    kind = "MayastorVolume",
+    plural = "mayastorvolumes",
+    // The name of the struct that gets created that represents a resource
+    namespaced,
+    status = "MayastorVolumeStatus",
+    derive = "PartialEq",
+    derive = "Default",
+    shortname = "msv",
+    printcolumn = r#"{ "name":"replicas", "type":"integer", "description":"number of replicas", "jsonPath":".spec.replicas"}"#,
+    printcolumn = r#"{ "name":"size", "type":"integer", "format": "int64", "minimum" : "0", "description":"size in bytes", "jsonPath":".spec.size"}"#,
+    printcolumn = r#"{ "name":"status", "type":"string", "description":"volume status", "jsonPath":".status.state"}"#,
+    printcolumn = r#"{ "name":"target", "type":"string", "description":"node the volume is published on", "jsonPath":".status.target.node"}"#
+)]
+#[serde(rename_all = "camelCase")]
+/// The volume spec which contains the parameters we use when creating and updating the volume.
+/// The name of the resource is the uuid of the volume.
+pub struct MayastorVolumeSpec {
+    /// The size of the volume in bytes, only used when the volume is created
+    size: u64,
+    /// The number of replicas of the volume
+    replicas: u8,
+    /// Thin provisioning of the replicas, only used when the volume is created
+    #[serde(default)]
+    thin: bool,
+    /// The node on which the volume is published over nvmf. When not set, the volume is left
+    /// as it is so that it can still be published by other means, eg: by the CSI driver.
+    /// The volume is only published when it's not published already: changing the node of a
+    /// published volume does not move its target, which may be in use
+    #[serde(default)]
+    target_node: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[non_exhaustive]
+pub enum VolumeState {
+    /// The resource is new and the volume is getting created.
+    Creating,
+    /// The volume is healthy.
+    Online,
+    /// Some of the replicas of the volume are not healthy.
+    Degraded,
+    /// None of the replicas of the volume are healthy.
+    Faulted,
+    /// The control plane did not return the volume state.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// Status of the volume which is driven by the state of the volume in the control plane
+pub struct MayastorVolumeStatus {
+    /// The state of the volume
+    state: VolumeState,
+    /// The size of the volume in bytes
+    size: u64,
+    /// The target through which the volume is published, if any
+    target: Option<MayastorVolumeTarget>,
+    /// The location and the state of the replicas, by replica uuid
+    replica_topology: BTreeMap<String, MayastorVolumeReplica>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// Target of a published volume
+pub struct MayastorVolumeTarget {
+    /// The node the target is on
+    node: String,
+    /// The protocol the target is shared with
+    protocol: String,
+    /// The URI through which the volume is connected to
+    device_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+/// Location and state of a replica of a volume
+pub struct MayastorVolumeReplica {
+    /// The node the replica is on
+    node: Option<String>,
+    /// The pool the replica is on
+    pool: Option<String>,
+    /// The state of the replica
+    state: String,
+}
+
+impl MayastorVolumeStatus {
+    fn creating() -> Self {
+        Self {
+            state: VolumeState::Creating,
+            size: 0,
+            target: None,
+            replica_topology: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<Volume> for MayastorVolumeStatus {
+    fn from(volume: Volume) -> Self {
+        let state = volume.state;
+        Self {
+            state: match state.status {
+                openapi::models::VolumeStatus::Online => VolumeState::Online,
+                openapi::models::VolumeStatus::Degraded => VolumeState::Degraded,
+                openapi::models::VolumeStatus::Faulted => VolumeState::Faulted,
+                openapi::models::VolumeStatus::Unknown => VolumeState::Unknown,
+            },
+            size: state.size,
+            target: state.target.map(|nexus| MayastorVolumeTarget {
+                node: nexus.node,
+                protocol: nexus.protocol.to_string(),
+                device_uri: nexus.device_uri,
+            }),
+            replica_topology: state
+                .replica_topology
+                .into_iter()
+                .map(|(uuid, replica)| {
+                    (
+                        uuid,
+                        MayastorVolumeReplica {
+                            node: replica.node,
+                            pool: replica.pool,
+                            state: replica.state.to_string(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// How the target of a volume converges towards the target node of the spec
+#[derive(Debug, PartialEq)]
+enum TargetConvergence {
+    /// The volume is published as per the spec, or the spec has no target node.
+    None,
+    /// The volume is not published, so it's published on the given node.
+    Publish(String),
+    /// The volume is published on another node and left there, as its target may be in use.
+    InUse { node: String },
+}
+
+/// Get how the volume `target` node converges towards the `target_node` of the spec
+fn target_convergence(target_node: Option<&String>, target: Option<&String>) -> TargetConvergence {
+    match (target_node, target) {
+        (Some(node), None) => TargetConvergence::Publish(node.clone()),
+        (Some(node), Some(target)) if node != target => TargetConvergence::InUse {
+            node: target.clone(),
+        },
+        _ => TargetConvergence::None,
+    }
+}
+
+/// Check whether the volume was created by the operator, which is then responsible for
+/// destroying it, given the `labels` of the volume
+fn created_by_operator(labels: Option<&HashMap<String, String>>) -> bool {
+    labels
+        .and_then(|labels| labels.get(utils::OPENEBS_CREATED_BY_KEY))
+        .map(|created_by| created_by == utils::MSP_OPERATOR)
+        .unwrap_or(false)
+}
+
+/// Data we want access to in error/reconcile calls
+pub struct VolumeOperatorContext {
+    /// Reference to our k8s client
+    k8s: Client,
+    /// The namespace of the volume resources
+    namespace: String,
+    /// HTTP client
+    http: clients::tower::ApiClient,
+    /// Interval
+    interval: u64,
+    /// URL through which the control plane notifies the operator of volume changes, if any
+    callback: Option<Url>,
+}
+
+impl VolumeOperatorContext {
+    /// Construct an API handle for the volume resources
+    fn api(&self) -> Api<MayastorVolume> {
+        Api::namespaced(self.k8s.clone(), &self.namespace)
+    }
+
+    fn volumes_api(&self) -> &dyn openapi::apis::volumes_api::tower::client::Volumes {
+        self.http.volumes_api()
+    }
+
+    /// The URL through which the control plane notifies the operator of changes to the volume
+    fn callback(&self, uuid: &Uuid) -> Option<Url> {
+        self.callback
+            .as_ref()
+            .and_then(|callback| callback.join(&format!("{}{}", WATCH_PATH, uuid)).ok())
+    }
+}
+
+/// Volume resource along with the operator context
+#[derive(Clone)]
+pub struct VolumeResource {
+    /// The latest CRD known to us
+    inner: MayastorVolume,
+    /// Reference to the operator context
+    ctx: Arc<VolumeOperatorContext>,
+}
+
+impl std::ops::Deref for VolumeResource {
+    type Target = MayastorVolume;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl VolumeResource {
+    fn volumes_api(&self) -> &dyn openapi::apis::volumes_api::tower::client::Volumes {
+        self.ctx.volumes_api()
+    }
+
+    fn watches_api(&self) -> &dyn openapi::apis::watches_api::tower::client::Watches {
+        self.ctx.http.watches_api()
+    }
+
+    async fn k8s_notify(&self, action: &str, reason: &str, message: &str, type_: &str) {
+        k8s_notify(&self.ctx.k8s, &self.inner, action, reason, message, type_).await
+    }
+
+    /// The uuid of the volume, which is the name of the resource
+    async fn uuid(&self) -> Result<Uuid, Error> {
+        match Uuid::parse_str(&self.name()) {
+            Ok(uuid) => Ok(uuid),
+            Err(_) => {
+                self.k8s_notify(
+                    "Create or import",
+                    "Invalid",
+                    "The name of the resource must be the uuid of the volume",
+                    "Warning",
+                )
+                .await;
+                Err(Error::ReconcileError { name: self.name() })
+            }
+        }
+    }
+
+    /// Patch the status of the resource, if it changed
+    async fn patch_status(&self, status: MayastorVolumeStatus) -> Result<(), Error> {
+        if self.status.as_ref() == Some(&status) {
+            return Ok(());
+        }
+        let status = json!({ "status": status });
+        let ps = PatchParams::apply(WHO_AM_I);
+
+        let o = self
+            .ctx
+            .api()
+            .patch_status(&self.name(), &ps, &Patch::Merge(&status))
+            .await
+            .map_err(|source| Error::Kube { source })?;
+
+        debug!(name = ?o.name(), old = ?self.status, new =?o.status, "status changed");
+        Ok(())
+    }
+
+    /// Mirror the state of the volume in the status of the resource
+    async fn set_status(&self, volume: Volume) -> Result<(), Error> {
+        self.patch_status(MayastorVolumeStatus::from(volume)).await
+    }
+
+    /// Create the volume, or adopt it when it already exists, and converge it towards the spec
+    #[tracing::instrument(fields(name = ?self.name(), status = ?self.status) skip(self))]
+    async fn apply(&self) -> Result<ReconcilerAction, Error> {
+        let uuid = self.uuid().await?;
+        let volume = match self.volumes_api().get_volume(&uuid).await {
+            Ok(response) => response.into_body(),
+            Err(clients::tower::Error::Response(response))
+                if response.status() == clients::tower::StatusCode::NOT_FOUND =>
+            {
+                if self.status.is_some() {
+                    return self.deleted_externally().await;
+                }
+                self.create_volume(&uuid).await?
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        self.watch(&uuid).await;
+        let volume = self.converge(&uuid, volume).await?;
+        self.set_status(volume).await?;
+
+        // always reschedule though
+        Ok(ReconcilerAction {
+            requeue_after: Some(Duration::from_secs(self.ctx.interval)),
+        })
+    }
+
+    /// Create the volume with the parameters of the spec
+    async fn create_volume(&self, uuid: &Uuid) -> Result<Volume, Error> {
+        self.patch_status(MayastorVolumeStatus::creating()).await?;
+
+        let mut labels: HashMap<String, String> = HashMap::new();
+        labels.insert(
+            String::from(utils::OPENEBS_CREATED_BY_KEY),
+            String::from(utils::MSP_OPERATOR),
+        );
+        let body = CreateVolumeBody::new_all(
+            VolumePolicy::new(true),
+            self.spec.replicas,
+            self.spec.size,
+            None,
+            labels,
+            self.spec.thin,
+            None,
+        );
+        let volume = self.volumes_api().put_volume(uuid, body).await?.into_body();
+
+        self.k8s_notify("Create", "Created", "Created the volume", "Normal")
+            .await;
+        Ok(volume)
+    }
+
+    /// Update the replica count and the target of the volume to match the spec
+    async fn converge(&self, uuid: &Uuid, mut volume: Volume) -> Result<Volume, Error> {
+        if volume.spec.num_replicas != self.spec.replicas {
+            volume = self
+                .volumes_api()
+                .put_volume_replica_count(uuid, self.spec.replicas)
+                .await?
+                .into_body();
+            self.k8s_notify(
+                "Update",
+                "ReplicaCount",
+                &format!("Set the replica count to {}", self.spec.replicas),
+                "Normal",
+            )
+            .await;
+        }
+
+        if volume.spec.size != self.spec.size {
+            warn!(name = ?self.name(), size = volume.spec.size, "volumes can't be resized");
+        }
+
+        let target = volume.spec.target.as_ref().map(|target| &target.node);
+        match target_convergence(self.spec.target_node.as_ref(), target) {
+            TargetConvergence::None => {}
+            TargetConvergence::Publish(node) => {
+                volume = self
+                    .volumes_api()
+                    .put_volume_target(uuid, &node, VolumeShareProtocol::Nvmf, None)
+                    .await?
+                    .into_body();
+                self.k8s_notify(
+                    "Publish",
+                    "Published",
+                    &format!("Published the volume on node '{}'", node),
+                    "Normal",
+                )
+                .await;
+            }
+            TargetConvergence::InUse { node } => {
+                warn!(name = ?self.name(), target = %node, "volume target not moved");
+                self.k8s_notify(
+                    "Publish",
+                    "InUse",
+                    &format!(
+                        "The volume is already published on node '{}' so it's not moved",
+                        node
+                    ),
+                    "Warning",
+                )
+                .await;
+            }
+        }
+        Ok(volume)
+    }
+
+    /// Ask the control plane to notify us of the changes to the volume, if not already done
+    async fn watch(&self, uuid: &Uuid) {
+        let callback = match self.ctx.callback(uuid) {
+            Some(callback) => callback,
+            None => return,
+        };
+        match self.watches_api().get_watch_volume(uuid).await {
+            Ok(watches)
+                if watches
+                    .into_body()
+                    .iter()
+                    .any(|w| w.callback == callback.as_str()) => {}
+            _ => {
+                if let Err(error) = self
+                    .watches_api()
+                    .put_watch_volume(uuid, callback.as_str(), None, None, None)
+                    .await
+                {
+                    error!(name = ?self.name(), %error, "failed to watch the volume");
+                }
+            }
+        }
+    }
+
+    /// The volume was deleted through an external API request, so delete its resource as well
+    async fn deleted_externally(&self) -> Result<ReconcilerAction, Error> {
+        warn!(volume = ?self.name(), "deleted by external event NOT recreating");
+        self.k8s_notify(
+            "Deleted",
+            "Check",
+            "The volume has been deleted through an external API request",
+            "Warning",
+        )
+        .await;
+        self.ctx
+            .api()
+            .delete(&self.name(), &DeleteParams::default())
+            .await
+            .map_err(|source| Error::Kube { source })?;
+        Ok(ReconcilerAction {
+            requeue_after: None,
+        })
+    }
+
+    /// Stop watching the volume and destroy it, if it was created by the operator.
+    /// Volumes which were adopted are left alone.
+    #[tracing::instrument(fields(name = ?self.name(), status = ?self.status) skip(self))]
+    async fn cleanup(&self) -> Result<ReconcilerAction, Error> {
+        let uuid = match Uuid::parse_str(&self.name()) {
+            Ok(uuid) => uuid,
+            Err(_) => {
+                return Ok(ReconcilerAction {
+                    requeue_after: None,
+                })
+            }
+        };
+        if let Some(callback) = self.ctx.callback(&uuid) {
+            let _ = self
+                .watches_api()
+                .del_watch_volume(&uuid, callback.as_str())
+                .await;
+        }
+
+        let volume = match self.volumes_api().get_volume(&uuid).await {
+            Ok(response) => response.into_body(),
+            Err(clients::tower::Error::Response(response))
+                if response.status() == clients::tower::StatusCode::NOT_FOUND =>
+            {
+                return Ok(ReconcilerAction {
+                    requeue_after: None,
+                })
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if created_by_operator(volume.spec.labels.as_ref()) {
+            self.volumes_api().del_volume(&uuid).await?;
+            self.k8s_notify(
+                "Destroyed volume",
+                "Destroy",
+                "The volume has been destroyed",
+                "Normal",
+            )
+            .await;
+        } else {
+            info!(name = ?self.name(), "volume not created by the operator, NOT destroying");
+        }
+        Ok(ReconcilerAction {
+            requeue_after: None,
+        })
+    }
+}
+
+/// The main work horse
+#[tracing::instrument(fields(name = %msv.name(), status = ?msv.status) skip(msv, ctx))]
+async fn reconcile(
+    msv: MayastorVolume,
+    ctx: Context<VolumeOperatorContext>,
+) -> Result<ReconcilerAction, Error> {
+    let resource = VolumeResource {
+        inner: msv,
+        ctx: ctx.into_inner(),
+    };
+
+    let resource = &resource;
+    finalizer(
+        &resource.ctx.api(),
+        FINALIZER,
+        resource.inner.clone(),
+        |event| async move {
+            match event {
+                Event::Apply(_) => resource.apply().await,
+                Event::Cleanup(_) => resource.cleanup().await,
+            }
+        },
+    )
+    .await
+    .map_err(|error| match error {
+        FinalizerError::ApplyFailed(error) | FinalizerError::CleanupFailed(error) => error,
+        FinalizerError::AddFinalizer(source) | FinalizerError::RemoveFinalizer(source) => {
+            Error::Kube { source }
+        }
+        FinalizerError::UnnamedObject => Error::Noun {},
+    })
+}
+
+/// Refresh the status of the resource of the volume, following a notification from the control
+/// plane
+async fn refresh(ctx: Arc<VolumeOperatorContext>, uuid: &Uuid) -> Result<(), Error> {
+    let msv = match ctx.api().get(&uuid.to_string()).await {
+        Ok(msv) => msv,
+        Err(kube::Error::Api(response)) if response.code == 404 => return Ok(()),
+        Err(source) => return Err(Error::Kube { source }),
+    };
+    let resource = VolumeResource { inner: msv, ctx };
+    let volume = resource.volumes_api().get_volume(uuid).await?.into_body();
+    resource.set_status(volume).await
+}
+
+/// Serve the volume watch notifications of the control plane on `addr` until the process exits
+async fn serve(addr: SocketAddr, ctx: Arc<VolumeOperatorContext>) {
+    let make_service = make_service_fn(move |_| {
+        let ctx = ctx.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                notify_handler(request, ctx.clone())
+            }))
+        }
+    });
+    let server = match Server::try_bind(&addr) {
+        Ok(server) => server,
+        Err(error) => {
+            error!(%error, "failed to bind the watch server to '{}'", addr);
+            return;
+        }
+    };
+    info!(
+        "Serving the volume watch notifications on 'http://{}'",
+        addr
+    );
+    if let Err(error) = server.serve(make_service).await {
+        error!(%error, "watch server failed");
+    }
+}
+
+/// Handle a notification of the control plane that the volume has changed
+async fn notify_handler(
+    request: Request<Body>,
+    ctx: Arc<VolumeOperatorContext>,
+) -> Result<Response<Body>, Infallible> {
+    let uuid = request
+        .uri()
+        .path()
+        .strip_prefix(WATCH_PATH)
+        .and_then(|uuid| Uuid::parse_str(uuid).ok());
+    let uuid = match uuid {
+        Some(uuid) if request.method() == Method::PUT => uuid,
+        _ => return Ok(response(StatusCode::NOT_FOUND)),
+    };
+    trace!(volume = %uuid, "volume change notification");
+
+    // failing to refresh the status is not reported back, the status is refreshed on a timer
+    // anyway
+    if let Err(error) = refresh(ctx, &uuid).await {
+        error!(volume = %uuid, %error, "failed to refresh the volume status");
+    }
+    Ok(response(StatusCode::NO_CONTENT))
+}
+
+fn response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+pub(crate) async fn volume_controller(args: ArgMatches<'_>) -> anyhow::Result<()> {
+    let k8s = Client::try_default().await?;
+    let namespace = args.value_of("namespace").unwrap();
+    ensure_crd::<MayastorVolume>(k8s.clone()).await;
+
+    let msv: Api<MayastorVolume> = Api::namespaced(k8s.clone(), namespace);
+    let lp = ListParams::default();
+
+    let context = Context::new(VolumeOperatorContext {
+        k8s,
+        namespace: namespace.to_string(),
+        http: rest_client(&args)?,
+        interval: interval(&args),
+        callback: args
+            .value_of("watch_callback")
+            .map(|callback| Url::parse(callback).expect("watch callback is not a valid URL")),
+    });
+
+    if let Some(addr) = args.value_of("watch_addr") {
+        let addr = addr.parse::<SocketAddr>()?;
+        tokio::spawn(serve(addr, context.clone().into_inner()));
+    }
+
+    info!(
+        "Starting Mayastor Volume Operator (MSV) in namespace {}",
+        namespace
+    );
+
+    Controller::new(msv, lp)
+        .run(reconcile, error_policy, context)
+        .for_each(|res| async move {
+            match res {
+                Ok(o) => {
+                    trace!(?o);
+                }
+                Err(e) => {
+                    trace!(?e);
+                }
+            }
+        })
+        .await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kube::CustomResourceExt;
+
+    #[test]
+    fn crd() {
+        let crd = MayastorVolume::crd();
+        assert_eq!(
+            crd.metadata.name.as_deref(),
+            Some("mayastorvolumes.openebs.io")
+        );
+        assert_eq!(crd.spec.scope, "Namespaced");
+        assert_eq!(crd.spec.names.kind, "MayastorVolume");
+        assert_eq!(crd.spec.names.short_names, Some(vec!["msv".to_string()]));
+        let version = &crd.spec.versions[0];
+        assert_eq!(version.name, "v1alpha1");
+        assert!(version.subresources.as_ref().unwrap().status.is_some());
+
+        // thin provisioning and the target node are optional
+        let msv: MayastorVolume = serde_yaml::from_str(
+            r#"
+apiVersion: openebs.io/v1alpha1
+kind: MayastorVolume
+metadata:
+  name: 1e3cf927-80c2-47a8-adf0-95c486bdd7b7
+spec:
+  size: 5242880
+  replicas: 2
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            msv.spec,
+            MayastorVolumeSpec {
+                size: 5242880,
+                replicas: 2,
+                thin: false,
+                target_node: None,
+            }
+        );
+        assert!(msv.status.is_none());
+    }
+
+    #[test]
+    fn target() {
+        let node = |name: &str| Some(name.to_string());
+        assert_eq!(target_convergence(None, None), TargetConvergence::None);
+        assert_eq!(
+            target_convergence(None, node("a").as_ref()),
+            TargetConvergence::None
+        );
+        assert_eq!(
+            target_convergence(node("a").as_ref(), node("a").as_ref()),
+            TargetConvergence::None
+        );
+        assert_eq!(
+            target_convergence(node("a").as_ref(), None),
+            TargetConvergence::Publish("a".to_string())
+        );
+        // a published volume is never moved
+        assert_eq!(
+            target_convergence(node("a").as_ref(), node("b").as_ref()),
+            TargetConvergence::InUse {
+                node: "b".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn finalizer_cleanup() {
+        let labels = |created_by: &str| {
+            let mut labels = HashMap::new();
+            labels.insert(
+                utils::OPENEBS_CREATED_BY_KEY.to_string(),
+                created_by.to_string(),
+            );
+            labels
+        };
+        // only the volumes created by the operator are destroyed with their resource
+        assert!(created_by_operator(Some(&labels(utils::MSP_OPERATOR))));
+        assert!(!created_by_operator(Some(&labels("csi"))));
+        assert!(!created_by_operator(Some(&HashMap::new())));
+        assert!(!created_by_operator(None));
+    }
+}
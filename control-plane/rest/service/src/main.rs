@@ -34,6 +34,11 @@ pub(crate) struct CliArgs {
     #[structopt(long, short = "z", default_value = DEFAULT_GRPC_CLIENT_ADDR)]
     core_grpc: Uri,
 
+    /// The Persistent Store URL to connect to, used to stream the changes of the resources
+    /// Default: http://localhost:2379
+    #[structopt(long, default_value = "http://localhost:2379")]
+    store: String,
+
     /// Path to the certificate file
     #[structopt(long, short, required_unless = "dummy-certificates")]
     cert_file: Option<String>,
@@ -0,0 +1,13 @@
+use super::*;
+use common_lib::types::v0::message_bus::GetEvents;
+use mbus_api::message_bus::v0::{MessageBus, MessageBusTrait};
+
+#[async_trait::async_trait]
+impl apis::actix_server::Events for RestApi {
+    async fn get_events(
+        Query(resource): Query<Option<String>>,
+    ) -> Result<Vec<models::Event>, RestError<RestJsonError>> {
+        let events = MessageBus::get_events(GetEvents::new(resource)).await?;
+        Ok(events.into_inner().into_vec())
+    }
+}
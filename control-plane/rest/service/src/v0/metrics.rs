@@ -0,0 +1,17 @@
+use actix_web::{web, HttpResponse};
+
+/// Serve the prometheus metrics of the rest service, which include the latencies of the
+/// message bus requests issued on behalf of the rest clients
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/metrics").route(web::get().to(get_metrics)));
+}
+
+async fn get_metrics() -> HttpResponse {
+    match common_lib::metrics::encode() {
+        Ok(metrics) => HttpResponse::Ok()
+            .content_type(common_lib::metrics::CONTENT_TYPE)
+            .body(metrics),
+        Err(error) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({ "error": error.to_string() })),
+    }
+}
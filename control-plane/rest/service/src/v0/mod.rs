@@ -4,11 +4,14 @@
 
 pub mod block_devices;
 pub mod children;
+pub mod events;
 pub mod jsongrpc;
+pub mod metrics;
 pub mod nexuses;
 pub mod nodes;
 pub mod pools;
 pub mod replicas;
+pub mod snapshots;
 pub mod specs;
 pub mod states;
 pub mod swagger_ui;
@@ -53,6 +56,7 @@ fn configure(cfg: &mut actix_web::web::ServiceConfig) {
     apis::actix_server::configure::<RestApi, BearerToken>(cfg);
     // todo: remove when the /states is added to the spec
     states::configure(cfg);
+    watches::configure(cfg);
 }
 
 fn json_error(err: impl std::fmt::Display, _req: &actix_web::HttpRequest) -> actix_web::Error {
@@ -76,15 +80,17 @@ where
         InitError = (),
     >,
 {
-    api.configure(swagger_ui::configure).service(
-        // any /v0 services must either live within this scope or be
-        // declared beforehand
-        web::scope("/v0")
-            .app_data(web::PathConfig::default().error_handler(|e, r| json_error(e, r)))
-            .app_data(web::JsonConfig::default().error_handler(|e, r| json_error(e, r)))
-            .app_data(web::QueryConfig::default().error_handler(|e, r| json_error(e, r)))
-            .configure(configure),
-    )
+    api.configure(swagger_ui::configure)
+        .configure(metrics::configure)
+        .service(
+            // any /v0 services must either live within this scope or be
+            // declared beforehand
+            web::scope("/v0")
+                .app_data(web::PathConfig::default().error_handler(|e, r| json_error(e, r)))
+                .app_data(web::JsonConfig::default().error_handler(|e, r| json_error(e, r)))
+                .app_data(web::QueryConfig::default().error_handler(|e, r| json_error(e, r)))
+                .configure(configure),
+        )
 }
 
 #[derive(Deserialize)]
@@ -12,4 +12,41 @@ impl apis::actix_server::Nodes for RestApi {
         let nodes = MessageBus::get_nodes().await?;
         Ok(nodes.into_vec())
     }
+
+    async fn put_node_cordon(
+        Path(id): Path<String>,
+    ) -> Result<models::Node, RestError<RestJsonError>> {
+        let node = MessageBus::cordon_node(&id.into()).await?;
+        Ok(node.into())
+    }
+
+    async fn del_node_cordon(
+        Path(id): Path<String>,
+    ) -> Result<models::Node, RestError<RestJsonError>> {
+        let node = MessageBus::uncordon_node(&id.into()).await?;
+        Ok(node.into())
+    }
+
+    async fn put_node_label(
+        Path(id): Path<String>,
+        Query((key, value)): Query<(String, String)>,
+    ) -> Result<models::Node, RestError<RestJsonError>> {
+        let node = MessageBus::label_node(&id.into(), &key, &value).await?;
+        Ok(node.into())
+    }
+
+    async fn del_node_label(
+        Path(id): Path<String>,
+        Query(key): Query<String>,
+    ) -> Result<models::Node, RestError<RestJsonError>> {
+        let node = MessageBus::unlabel_node(&id.into(), &key).await?;
+        Ok(node.into())
+    }
+
+    async fn put_node_drain(
+        Path(id): Path<String>,
+    ) -> Result<models::Node, RestError<RestJsonError>> {
+        let node = MessageBus::drain_node(&id.into()).await?;
+        Ok(node.into())
+    }
 }
@@ -1,5 +1,5 @@
 use super::*;
-use common_lib::types::v0::message_bus::{DestroyPool, Filter};
+use common_lib::types::v0::message_bus::{DestroyPool, Filter, SetPoolMaintenance};
 use grpc::pool::traits::PoolOperations;
 use mbus_api::{message_bus::v0::BusError, ReplyErrorKind, ResourceKind};
 
@@ -45,6 +45,16 @@ impl apis::actix_server::Pools for RestApi {
         destroy_pool(Filter::Pool(pool_id.into())).await
     }
 
+    async fn del_pool_maintenance(
+        Path(pool_id): Path<String>,
+    ) -> Result<models::Pool, RestError<RestJsonError>> {
+        let client = CORE_CLIENT.get().unwrap().pool();
+        let pool = client
+            .set_maintenance(&SetPoolMaintenance::new(&pool_id.into(), false), None)
+            .await?;
+        Ok(pool.into())
+    }
+
     async fn get_node_pool(
         Path((node_id, pool_id)): Path<(String, String)>,
     ) -> Result<models::Pool, RestError<RestJsonError>> {
@@ -99,6 +109,16 @@ impl apis::actix_server::Pools for RestApi {
         let pool = client.create(&create, None).await?;
         Ok(pool.into())
     }
+
+    async fn put_pool_maintenance(
+        Path(pool_id): Path<String>,
+    ) -> Result<models::Pool, RestError<RestJsonError>> {
+        let client = CORE_CLIENT.get().unwrap().pool();
+        let pool = client
+            .set_maintenance(&SetPoolMaintenance::new(&pool_id.into(), true), None)
+            .await?;
+        Ok(pool.into())
+    }
 }
 
 /// returns pool from pool option and returns an error on non existence
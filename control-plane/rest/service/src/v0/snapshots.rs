@@ -0,0 +1,49 @@
+use super::*;
+use common_lib::types::v0::{message_bus::Filter, openapi::apis::Uuid};
+use mbus_api::message_bus::v0::{MessageBus, MessageBusTrait};
+
+#[async_trait::async_trait]
+impl apis::actix_server::Snapshots for RestApi {
+    async fn del_volume_snapshot(
+        Path((volume_id, snapshot_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<(), RestError<RestJsonError>> {
+        MessageBus::delete_volume_snapshot(volume_id.into(), snapshot_id.into()).await?;
+        Ok(())
+    }
+
+    async fn get_snapshot(
+        Path(snapshot_id): Path<Uuid>,
+    ) -> Result<models::VolumeSnapshot, RestError<RestJsonError>> {
+        let snapshot =
+            MessageBus::get_volume_snapshot(Filter::Snapshot(snapshot_id.into())).await?;
+        Ok(snapshot.into())
+    }
+
+    async fn get_snapshots() -> Result<Vec<models::VolumeSnapshot>, RestError<RestJsonError>> {
+        let snapshots = MessageBus::get_volume_snapshots(Filter::None).await?;
+        Ok(snapshots.into_iter().map(From::from).collect())
+    }
+
+    async fn get_volume_snapshot(
+        Path((volume_id, snapshot_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<models::VolumeSnapshot, RestError<RestJsonError>> {
+        let filter = Filter::VolumeSnapshot(volume_id.into(), snapshot_id.into());
+        let snapshot = MessageBus::get_volume_snapshot(filter).await?;
+        Ok(snapshot.into())
+    }
+
+    async fn get_volume_snapshots(
+        Path(volume_id): Path<Uuid>,
+    ) -> Result<Vec<models::VolumeSnapshot>, RestError<RestJsonError>> {
+        let snapshots = MessageBus::get_volume_snapshots(Filter::Volume(volume_id.into())).await?;
+        Ok(snapshots.into_iter().map(From::from).collect())
+    }
+
+    async fn put_volume_snapshot(
+        Path((volume_id, snapshot_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<models::VolumeSnapshot, RestError<RestJsonError>> {
+        let snapshot =
+            MessageBus::create_volume_snapshot(volume_id.into(), snapshot_id.into()).await?;
+        Ok(snapshot.into())
+    }
+}
@@ -4,6 +4,7 @@ use common_lib::types::v0::{
     openapi::{apis::Uuid, models::VolumeShareProtocol},
 };
 use mbus_api::message_bus::v0::{MessageBus, MessageBusTrait};
+use std::convert::TryFrom;
 
 #[async_trait::async_trait]
 impl apis::actix_server::Volumes for RestApi {
@@ -51,7 +52,7 @@ impl apis::actix_server::Volumes for RestApi {
         Path(volume_id): Path<Uuid>,
         Body(create_volume_body): Body<models::CreateVolumeBody>,
     ) -> Result<models::Volume, RestError<RestJsonError>> {
-        let create = CreateVolumeBody::from(create_volume_body).bus_request(volume_id.into());
+        let create = CreateVolumeBody::try_from(create_volume_body)?.bus_request(volume_id.into());
         let volume = MessageBus::create_volume(create).await?;
         Ok(volume.into())
     }
@@ -72,11 +73,15 @@ impl apis::actix_server::Volumes for RestApi {
 
     async fn put_volume_target(
         Path(volume_id): Path<Uuid>,
-        Query((node, protocol)): Query<(String, VolumeShareProtocol)>,
+        Query((node, protocol, num_paths)): Query<(String, VolumeShareProtocol, Option<u8>)>,
     ) -> Result<models::Volume, RestError<RestJsonError>> {
-        let volume =
-            MessageBus::publish_volume(volume_id.into(), Some(node.into()), Some(protocol.into()))
-                .await?;
+        let volume = MessageBus::publish_volume(
+            volume_id.into(),
+            Some(node.into()),
+            Some(protocol.into()),
+            num_paths,
+        )
+        .await?;
         Ok(volume.into())
     }
 }
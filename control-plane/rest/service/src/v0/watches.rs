@@ -1,12 +1,139 @@
 use super::*;
-use common_lib::types::v0::{
-    message_bus::{
-        CreateWatch, DeleteWatch, GetWatchers, WatchCallback, WatchResourceId, WatchType,
+use actix_web::{web::Bytes, HttpResponse};
+use common_lib::{
+    store::etcd::Etcd,
+    types::v0::{
+        message_bus::{
+            CreateWatch, DeleteWatch, GetWatchers, WatchCallback, WatchOptions, WatchResourceId,
+            WatchType,
+        },
+        openapi::apis::Uuid,
+        store::{
+            definitions::{ObjectKey, RevisionWatchEvent, Store, StoreError, WatchEvent},
+            schema::object_value,
+        },
     },
-    openapi::apis::Uuid,
 };
 use mbus_api::Message;
-use std::convert::TryFrom;
+use std::{convert::TryFrom, str::FromStr};
+
+/// The openapi spec can't describe server-sent events, so the watch stream is configured here
+/// rather than generated from the spec.
+pub(super) fn configure(cfg: &mut actix_web::web::ServiceConfig) {
+    cfg.service(
+        actix_web::web::resource("/watch")
+            .name("get_watch_stream")
+            .guard(actix_web::guard::Get())
+            .route(actix_web::web::get().to(get_watch_stream)),
+    );
+}
+
+/// Client of the persistent store shared by all the watch streams, connected on first use
+static STORE: OnceCell<Etcd> = OnceCell::new();
+
+/// Get the shared client of the persistent store, connecting to the store on first use.
+async fn store() -> Result<Etcd, StoreError> {
+    if let Some(store) = STORE.get() {
+        return Ok(store.clone());
+    }
+    let store = Etcd::new(&crate::CliArgs::args().store).await?;
+    // should another request have connected in the meantime, its client is kept instead
+    Ok(STORE.get_or_init(|| store).clone())
+}
+
+/// Query parameters of the watch stream
+#[derive(Deserialize)]
+struct WatchStreamQuery {
+    /// type of the watched resource, eg: volumes
+    resource: String,
+    /// id of the watched resource
+    id: String,
+    /// store revision from which to start watching, so that the changes made since that
+    /// revision (inclusive) are replayed
+    revision: Option<i64>,
+}
+
+/// Stream the changes of a resource in the persistent store as server-sent events.
+/// Each event carries the store revision as its id, its type (put or delete) and the full JSON
+/// value of the resource. Clients may resume the stream by reconnecting with the id of the last
+/// event they received in the `Last-Event-ID` header, or with the next revision as a query
+/// parameter.
+async fn get_watch_stream(
+    _token: BearerToken,
+    request: HttpRequest,
+    query: actix_web::web::Query<WatchStreamQuery>,
+) -> Result<HttpResponse, RestError<RestJsonError>> {
+    let resource = WatchResourceId::from_str(&format!("{}/{}", query.resource, query.id)).map_err(
+        |error| ReplyError {
+            kind: ReplyErrorKind::InvalidArgument,
+            resource: ResourceKind::Unknown,
+            source: request.uri().to_string(),
+            extra: error,
+        },
+    )?;
+    let revision = match request
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        Some(last_revision) => Some(last_revision + 1),
+        None => query.revision,
+    };
+
+    let store_error = |error: StoreError| ReplyError {
+        kind: ReplyErrorKind::Unavailable,
+        resource: ResourceKind::Unknown,
+        source: "Persistent Store".to_string(),
+        extra: error.to_string(),
+    };
+    let mut store = store().await.map_err(store_error)?;
+    let channel = store
+        .watch_kv_from(&resource.key(), revision)
+        .await
+        .map_err(store_error)?;
+
+    // the stream ends once the resource is deleted or the watch fails
+    let obj_type = resource.key_type();
+    let stream = futures::stream::unfold(channel, move |mut channel| {
+        let resource = resource.to_string();
+        async move {
+            let error_event = |error: StoreError| {
+                let data = serde_json::json!({
+                    "resource": resource,
+                    "error": error.to_string(),
+                });
+                format!("event: error\ndata: {}\n\n", data)
+            };
+            let event = match channel.recv().await? {
+                Ok(RevisionWatchEvent { event, revision }) => {
+                    let (name, value) = match event {
+                        WatchEvent::Put(_, value) => ("put", object_value(obj_type, value)),
+                        WatchEvent::Delete => ("delete", Ok(serde_json::Value::Null)),
+                    };
+                    match value {
+                        Ok(value) => {
+                            let data = serde_json::json!({
+                                "resource": resource,
+                                "revision": revision,
+                                "value": value,
+                            });
+                            format!("id: {}\nevent: {}\ndata: {}\n\n", revision, name, data)
+                        }
+                        Err(error) => error_event(error),
+                    }
+                }
+                Err(error) => error_event(error),
+            };
+            Some((Ok::<_, actix_web::Error>(Bytes::from(event)), channel))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream))
+}
 
 #[async_trait::async_trait]
 impl apis::actix_server::Watches for RestApi {
@@ -16,7 +143,7 @@ impl apis::actix_server::Watches for RestApi {
     ) -> Result<(), RestError<RestJsonError>> {
         DeleteWatch {
             id: WatchResourceId::Volume(volume_id.into()),
-            callback: WatchCallback::Uri(callback.to_string()),
+            callback: WatchCallback::from(callback.as_str()),
             watch_type: WatchType::Actual,
         }
         .request()
@@ -42,12 +169,44 @@ impl apis::actix_server::Watches for RestApi {
 
     async fn put_watch_volume(
         Path(volume_id): Path<Uuid>,
-        Query(callback): Query<url::Url>,
+        Query((callback, fields, max_attempts, timeout)): Query<(
+            url::Url,
+            Option<String>,
+            Option<u32>,
+            Option<String>,
+        )>,
     ) -> Result<(), RestError<RestJsonError>> {
+        let timeout = match timeout {
+            Some(timeout) => {
+                Some(
+                    humantime::parse_duration(&timeout).map_err(|error| ReplyError {
+                        kind: ReplyErrorKind::InvalidArgument,
+                        resource: ResourceKind::Watch,
+                        source: timeout.clone(),
+                        extra: error.to_string(),
+                    })?,
+                )
+            }
+            None => None,
+        };
+        let fields = fields
+            .map(|fields| {
+                fields
+                    .split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
         CreateWatch {
             id: WatchResourceId::Volume(volume_id.into()),
-            callback: WatchCallback::Uri(callback.to_string()),
+            callback: WatchCallback::from(callback.as_str()),
             watch_type: WatchType::Actual,
+            options: WatchOptions {
+                fields,
+                max_attempts,
+                timeout,
+            },
         }
         .request()
         .await?;
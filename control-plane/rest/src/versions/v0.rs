@@ -9,19 +9,20 @@ pub use common_lib::{
             CreateVolume, DestroyNexus, DestroyPool, DestroyReplica, DestroyVolume, Filter,
             GetBlockDevices, JsonGrpcRequest, Nexus, NexusId, Node, NodeId, Pool, PoolDeviceUri,
             PoolId, Protocol, RemoveNexusChild, Replica, ReplicaId, ReplicaShareProtocol,
-            ShareNexus, ShareReplica, Specs, Topology, UnshareNexus, UnshareReplica, VolumeId,
-            VolumeLabels, VolumePolicy, Watch, WatchCallback, WatchResourceId,
+            ShareNexus, ShareReplica, Specs, Topology, UnshareNexus, UnshareReplica,
+            VolumeContentSource, VolumeId, VolumeLabels, VolumePolicy, Watch, WatchCallback,
+            WatchResourceId,
         },
         openapi::{apis, apis::actix_server::RestError, models, tower::client},
         store::pool::PoolLabel,
     },
 };
 
-use common_lib::{IntoOption, IntoVec};
+use common_lib::{mbus_api::ReplyError, IntoOption, IntoVec};
 pub use models::rest_json_error::Kind as RestJsonErrorKind;
 
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::{convert::TryFrom, fmt::Debug};
 
 /// Create Replica Body JSON
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -53,12 +54,15 @@ pub struct CreatePoolBody {
     pub disks: Vec<PoolDeviceUri>,
     /// labels to be set on the pool
     pub labels: Option<PoolLabel>,
+    /// overcommit ratio of the pool, as a percentage of its capacity
+    pub overcommit: Option<u32>,
 }
 impl From<models::CreatePoolBody> for CreatePoolBody {
     fn from(src: models::CreatePoolBody) -> Self {
         Self {
             disks: src.disks.iter().cloned().map(From::from).collect(),
             labels: src.labels,
+            overcommit: src.overcommit,
         }
     }
 }
@@ -67,6 +71,7 @@ impl From<CreatePool> for CreatePoolBody {
         CreatePoolBody {
             disks: create.disks,
             labels: create.labels,
+            overcommit: create.overcommit,
         }
     }
 }
@@ -78,6 +83,7 @@ impl CreatePoolBody {
             id: pool_id,
             disks: self.disks.clone(),
             labels: self.labels.clone(),
+            overcommit: self.overcommit,
         }
     }
 }
@@ -162,16 +168,23 @@ pub struct CreateVolumeBody {
     pub topology: Option<Topology>,
     /// Volume labels, used ot store custom volume information
     pub labels: Option<VolumeLabels>,
+    /// Thin provisioning of the volume replicas
+    pub thin: bool,
+    /// Existing volume or snapshot whose content is cloned into the volume
+    pub source: Option<VolumeContentSource>,
 }
-impl From<models::CreateVolumeBody> for CreateVolumeBody {
-    fn from(src: models::CreateVolumeBody) -> Self {
-        Self {
+impl TryFrom<models::CreateVolumeBody> for CreateVolumeBody {
+    type Error = ReplyError;
+    fn try_from(src: models::CreateVolumeBody) -> Result<Self, Self::Error> {
+        Ok(Self {
             size: src.size as u64,
             replicas: src.replicas as u64,
             policy: src.policy.into(),
             topology: src.topology.into_opt(),
             labels: src.labels,
-        }
+            thin: src.thin.unwrap_or_default(),
+            source: src.source.map(TryFrom::try_from).transpose()?,
+        })
     }
 }
 impl From<CreateVolume> for CreateVolumeBody {
@@ -182,6 +195,8 @@ impl From<CreateVolume> for CreateVolumeBody {
             policy: create.policy,
             topology: create.topology,
             labels: create.labels,
+            thin: create.thin,
+            source: create.source,
         }
     }
 }
@@ -195,6 +210,8 @@ impl CreateVolumeBody {
             policy: self.policy.clone(),
             topology: self.topology.clone(),
             labels: self.labels.clone(),
+            thin: self.thin,
+            source: self.source.clone(),
         }
     }
 }
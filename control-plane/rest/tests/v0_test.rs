@@ -1,6 +1,10 @@
-use common_lib::types::v0::{
-    message_bus::WatchResourceId,
-    openapi::{apis, models},
+use common_lib::{
+    store::etcd::Etcd,
+    types::v0::{
+        message_bus::WatchResourceId,
+        openapi::{apis, models},
+        store::definitions::{ObjectKey, Store},
+    },
 };
 
 use rest_client::RestClient;
@@ -91,8 +95,12 @@ async fn client_test(cluster: &Cluster, auth: &bool) {
     let mut node = models::Node {
         id: mayastor1.to_string(),
         spec: Some(models::NodeSpec {
+            cordoned: false,
+            drain_blockers: None,
+            drain_state: None,
             id: mayastor1.to_string(),
             grpc_endpoint: "10.1.0.7:10124".to_string(),
+            labels: None,
         }),
         state: Some(models::NodeState {
             id: mayastor1.to_string(),
@@ -120,7 +128,7 @@ async fn client_test(cluster: &Cluster, auth: &bool) {
         pool,
         models::Pool::new_all(
             "pooloop",
-            models::PoolSpec::new(vec!["malloc:///malloc0?blk_size=512&size_mb=100&uuid=b940f4f2-d45d-4404-8167-3b0366f9e2b0"], "pooloop", &mayastor1, models::SpecStatus::Created),
+            models::PoolSpec::new(vec!["malloc:///malloc0?blk_size=512&size_mb=100&uuid=b940f4f2-d45d-4404-8167-3b0366f9e2b0"], "pooloop", false, &mayastor1, models::SpecStatus::Created),
             models::PoolState::new(100663296u64, vec!["malloc:///malloc0?blk_size=512&size_mb=100&uuid=b940f4f2-d45d-4404-8167-3b0366f9e2b0"], "pooloop", &mayastor1, models::PoolStatus::Online, 0u64)
         )
     );
@@ -280,6 +288,7 @@ async fn client_test(cluster: &Cluster, auth: &bool) {
             &volume.state.uuid,
             mayastor1.as_str(),
             models::VolumeShareProtocol::Nvmf,
+            None,
         )
         .await
         .unwrap();
@@ -330,7 +339,7 @@ async fn client_test(cluster: &Cluster, auth: &bool) {
 
     client
         .watches_api()
-        .put_watch_volume(&volume_uuid, &callback.to_string())
+        .put_watch_volume(&volume_uuid, &callback.to_string(), None, None, None)
         .await
         .expect_err("volume does not exist in the store");
 
@@ -436,3 +445,159 @@ async fn client_invalid_token() {
     };
     assert!(unauthorized);
 }
+
+/// Server-sent events of the watch stream of the rest server
+struct WatchStream {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl WatchStream {
+    /// Start streaming the changes of the `resource`, from the given `revision` query parameter
+    /// or following the `last_event_id`
+    async fn connect(
+        resource: &WatchResourceId,
+        revision: Option<i64>,
+        last_event_id: Option<i64>,
+    ) -> Self {
+        let resource = resource.to_string();
+        let (kind, id) = resource
+            .split_once('/')
+            .expect("The resource should be formatted as type/id");
+        let mut query = vec![("resource", kind.to_string()), ("id", id.to_string())];
+        if let Some(revision) = revision {
+            query.push(("revision", revision.to_string()));
+        }
+        let mut request = reqwest::Client::new()
+            .get("http://localhost:8081/v0/watch")
+            .query(&query);
+        if let Some(id) = last_event_id {
+            request = request.header("Last-Event-ID", id.to_string());
+        }
+        let response = request.send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// Wait for the next event, as its id, type and data
+    async fn next(&mut self) -> (Option<i64>, String, serde_json::Value) {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let event = self.buffer[.. end].to_string();
+                self.buffer.drain(.. end + 2);
+                let (mut id, mut name, mut data) = (None, String::new(), serde_json::Value::Null);
+                for line in event.lines() {
+                    match line.split_once(": ") {
+                        Some(("id", value)) => id = value.parse().ok(),
+                        Some(("event", value)) => name = value.to_string(),
+                        Some(("data", value)) => data = serde_json::from_str(value).unwrap(),
+                        _ => {}
+                    }
+                }
+                return (id, name, data);
+            }
+            let chunk = tokio::time::timeout(Duration::from_secs(5), self.response.chunk())
+                .await
+                .expect("Timeout waiting for a watch event")
+                .unwrap()
+                .expect("The watch stream should not end");
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
+
+#[tokio::test]
+async fn watch_stream() {
+    let _cluster = test_setup(&false).await;
+    let mut store = Etcd::new("0.0.0.0:2379")
+        .await
+        .expect("Failed to connect to etcd.");
+
+    let resource = WatchResourceId::Volume(VolumeId::new());
+    store
+        .put_kv(&resource.key(), &serde_json::json!("aaa"))
+        .await
+        .unwrap();
+    store
+        .put_kv(&resource.key(), &serde_json::json!("bbb"))
+        .await
+        .unwrap();
+
+    // replay the changes from the first revision of the store
+    let mut stream = WatchStream::connect(&resource, Some(1), None).await;
+    let (first, name, data) = stream.next().await;
+    let first = first.expect("Each change should have the revision as its id");
+    assert_eq!(name, "put");
+    assert_eq!(data["resource"], resource.to_string());
+    assert_eq!(data["revision"], first);
+    assert_eq!(data["value"], "aaa");
+    let (second, _, data) = stream.next().await;
+    let second = second.unwrap();
+    assert!(second > first);
+    assert_eq!(data["value"], "bbb");
+
+    // the live changes follow the replayed ones
+    store
+        .put_kv(&resource.key(), &serde_json::json!("ccc"))
+        .await
+        .unwrap();
+    let (third, _, data) = stream.next().await;
+    assert!(third.unwrap() > second);
+    assert_eq!(data["value"], "ccc");
+
+    // resume after the first change, with the id of the last event received
+    let mut stream = WatchStream::connect(&resource, None, Some(first)).await;
+    let (id, _, data) = stream.next().await;
+    assert_eq!(id, Some(second));
+    assert_eq!(data["value"], "bbb");
+
+    // or from a given revision, inclusive
+    let mut stream = WatchStream::connect(&resource, Some(second), None).await;
+    let (id, _, data) = stream.next().await;
+    assert_eq!(id, Some(second));
+    assert_eq!(data["value"], "bbb");
+
+    let (_, _, data) = stream.next().await;
+    assert_eq!(data["value"], "ccc");
+
+    // the deletion of the resource is streamed too
+    store.delete_kv(&resource.key()).await.unwrap();
+    let (_, name, data) = stream.next().await;
+    assert_eq!(name, "delete");
+    assert_eq!(data["value"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn metrics() {
+    let cluster = test_setup(&false).await;
+    let nodes_api = cluster.rest_v00().nodes_api();
+    assert_eq!(nodes_api.get_nodes().await.unwrap().len(), 2);
+    nodes_api
+        .get_node("unknown-node")
+        .await
+        .expect_err("The node does not exist");
+
+    let response = reqwest::get("http://localhost:8081/metrics").await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        common_lib::metrics::CONTENT_TYPE
+    );
+    let metrics = response.text().await.unwrap();
+    // the requests sent by the rest service on behalf of its clients are timed by their result
+    for result in &["ok", "error"] {
+        let series = format!(
+            "mayastor_control_plane_mbus_request_duration_seconds_count{{message_id=\"v0/getNodes\",result=\"{}\"}}",
+            result
+        );
+        assert!(
+            metrics.lines().any(|line| line.starts_with(&series)),
+            "Missing {} in the metrics:\n{}",
+            series,
+            metrics
+        );
+    }
+}
@@ -36,6 +36,18 @@ resource "kubernetes_cluster_role" "mayastor" {
     resources  = ["mayastorpools/status"]
   }
 
+  rule {
+    verbs      = ["get", "list", "watch", "update", "patch", "replace", "delete"]
+    api_groups = ["openebs.io"]
+    resources  = ["mayastorvolumes"]
+  }
+
+  rule {
+    verbs      = ["update", "patch"]
+    api_groups = ["openebs.io"]
+    resources  = ["mayastorvolumes/status"]
+  }
+
   rule {
     verbs      = ["get", "list", "watch", "update", "create", "delete", "patch"]
     api_groups = [""]
@@ -5,6 +5,9 @@ use std::net::{IpAddr, SocketAddr};
 #[async_trait]
 impl ComponentAction for Mayastor {
     fn configure(&self, options: &StartOptions, cfg: Builder) -> Result<Builder, Error> {
+        if options.fake_mayastor {
+            return Self::configure_fake(options, cfg);
+        }
         let mut cfg = cfg;
         for i in 0 .. options.mayastors {
             let mayastor_socket =
@@ -74,6 +77,31 @@ impl Mayastor {
     pub fn name(i: u32, _options: &StartOptions) -> String {
         format!("mayastor-{}", i + 1)
     }
+    /// Configure the simulated mayastor instances, which are named like the real ones
+    fn configure_fake(options: &StartOptions, cfg: Builder) -> Result<Builder, Error> {
+        if options.build {
+            let status = std::process::Command::new("cargo")
+                .args(&["build", "-p", "fake-mayastor", "--bin", "fake-mayastor"])
+                .status()?;
+            build_error("fake-mayastor", status.code())?;
+        }
+        let mut cfg = cfg;
+        for i in 0 .. options.mayastors {
+            let name = Self::name(i, options);
+            let mayastor_socket = format!("{}:10124", cfg.next_ip_for_name(&name)?);
+            let mut binary = Binary::from_dbg("fake-mayastor")
+                .with_nats("-n")
+                .with_args(vec!["-N", &name])
+                .with_args(vec!["-g", &mayastor_socket]);
+            if let Some(env) = &options.mayastor_env {
+                for kv in env {
+                    binary = binary.with_env(kv.key.as_str(), kv.value.as_str().as_ref());
+                }
+            }
+            cfg = cfg.add_container_spec(ContainerSpec::from_binary(&name, binary));
+        }
+        Ok(cfg)
+    }
     fn binary(path: &str) -> Option<String> {
         match std::env::var_os(&path) {
             None => None,
@@ -137,6 +137,18 @@ macro_rules! impl_ctrlp_agents {
                     if let Some(period) = &options.reconcile_idle_period {
                         binary = binary.with_args(vec!["--reconcile-idle-period", &period.to_string()]);
                     }
+                    if let Some(period) = &options.target_failover_grace_period {
+                        binary = binary.with_args(vec!["--target-failover-grace-period", &period.to_string()]);
+                    }
+                    if let Some(skew) = &options.rebalance_replica_skew {
+                        binary = binary.with_args(vec!["--rebalance", "--rebalance-replica-skew", &skew.to_string()]);
+                    }
+                    if let Some(interval) = &options.rebalance_interval {
+                        binary = binary.with_args(vec!["--rebalance-interval", &interval.to_string()]);
+                    }
+                    if options.core_metrics {
+                        binary = binary.with_args(vec!["--metrics-addr", utils::DEFAULT_METRICS_ADDR]);
+                    }
                     if cfg.container_exists("jaeger") {
                         let jaeger_config = format!("jaeger.{}:6831", cfg.get_name());
                         binary = binary.with_args(vec!["--jaeger", &jaeger_config]);
@@ -15,7 +15,11 @@ impl ComponentAction for Rest {
                 .with_nats("-n")
                 .with_arg("--dummy-certificates")
                 .with_args(vec!["--https", "rest:8080"])
-                .with_args(vec!["--http", "rest:8081"]);
+                .with_args(vec!["--http", "rest:8081"])
+                .with_args(vec![
+                    "--store",
+                    &format!("etcd.{}:2379", options.cluster_label.name()),
+                ]);
 
             let binary = if let Some(jwk) = &options.rest_jwk {
                 binary.with_arg("--jwk").with_arg(jwk)
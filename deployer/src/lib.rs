@@ -134,6 +134,11 @@ pub struct StartOptions {
     #[structopt(long, env = "MAYASTOR_BIN", conflicts_with = "mayastor_image")]
     pub mayastor_bin: Option<String>,
 
+    /// Use simulated mayastor instances which serve the gRPC service from memory
+    /// This allows for dozens of instances, without hugepages or block devices
+    #[structopt(long, conflicts_with_all = &["mayastor_image", "mayastor_bin"])]
+    pub fake_mayastor: bool,
+
     /// Add host block devices to the mayastor containers as a docker bind mount
     /// A raw block device: --mayastor-devices /dev/sda /dev/sdb
     /// An lvm volume group: --mayastor-devices /dev/sdavg
@@ -222,6 +227,23 @@ pub struct StartOptions {
     #[structopt(long)]
     pub reconcile_idle_period: Option<humantime::Duration>,
 
+    /// Override the core agent's volume target failover grace period
+    #[structopt(long)]
+    pub target_failover_grace_period: Option<humantime::Duration>,
+
+    /// Enable the core agent's rebalancing of the volume replicas, moving the replicas off the
+    /// pools with more replicas than the least used pool plus this skew
+    #[structopt(long)]
+    pub rebalance_replica_skew: Option<usize>,
+
+    /// Override the core agent's minimum interval between two replica moves
+    #[structopt(long)]
+    pub rebalance_interval: Option<humantime::Duration>,
+
+    /// Serve the core agent's prometheus metrics on its default metrics address
+    #[structopt(long)]
+    pub core_metrics: bool,
+
     /// Amount of time to wait for all containers to start.
     #[structopt(short, long)]
     pub wait_timeout: Option<humantime::Duration>,
@@ -314,6 +336,22 @@ impl StartOptions {
         self
     }
     #[must_use]
+    pub fn with_target_failover_grace_period(mut self, period: Duration) -> Self {
+        self.target_failover_grace_period = Some(period.into());
+        self
+    }
+    #[must_use]
+    pub fn with_rebalance(mut self, replica_skew: usize, interval: Duration) -> Self {
+        self.rebalance_replica_skew = Some(replica_skew);
+        self.rebalance_interval = Some(interval.into());
+        self
+    }
+    #[must_use]
+    pub fn with_core_metrics(mut self, enabled: bool) -> Self {
+        self.core_metrics = enabled;
+        self
+    }
+    #[must_use]
     pub fn with_req_timeouts(mut self, no_min: bool, connect: Duration, request: Duration) -> Self {
         self.no_min_timeouts = no_min;
         self.node_conn_timeout = Some(connect.into());
@@ -357,6 +395,11 @@ impl StartOptions {
         self
     }
     #[must_use]
+    pub fn with_fake_mayastor(mut self, fake: bool) -> Self {
+        self.fake_mayastor = fake;
+        self
+    }
+    #[must_use]
     pub fn with_show_info(mut self, show_info: bool) -> Self {
         self.show_info = show_info;
         self
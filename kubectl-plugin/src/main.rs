@@ -8,8 +8,11 @@ mod resources;
 mod rest_wrapper;
 
 use crate::{
-    operations::{Get, List, ReplicaTopology, Scale},
-    resources::{node, pool, volume, GetResources, ScaleResources},
+    operations::{Cordoning, Drain, Get, List, Maintenance, ReplicaTopology, Scale},
+    resources::{
+        event, node, pool, volume, CordonResources, DrainResources, GetResources,
+        MaintenanceResources, ScaleResources,
+    },
     rest_wrapper::RestClient,
 };
 use anyhow::Result;
@@ -123,12 +126,34 @@ async fn execute(cli_args: CliArgs) {
             GetResources::Pool { id } => pool::Pool::get(id, &cli_args.output).await,
             GetResources::Nodes => node::Nodes::list(&cli_args.output).await,
             GetResources::Node { id } => node::Node::get(id, &cli_args.output).await,
+            GetResources::Events { resource } => {
+                event::Events::list_by_resource(resource, &cli_args.output).await
+            }
         },
         Operations::Scale(resource) => match resource {
             ScaleResources::Volume { id, replica_count } => {
                 volume::Volume::scale(id, *replica_count, &cli_args.output).await
             }
         },
+        Operations::Cordon(resource) => match resource {
+            CordonResources::Node { id } => node::Node::cordon(id, &cli_args.output).await,
+        },
+        Operations::Uncordon(resource) => match resource {
+            CordonResources::Node { id } => node::Node::uncordon(id, &cli_args.output).await,
+        },
+        Operations::Drain(resource) => match resource {
+            DrainResources::Node { id } => node::Node::drain(id, &cli_args.output).await,
+        },
+        Operations::StartMaintenance(resource) => match resource {
+            MaintenanceResources::Pool { id } => {
+                pool::Pool::start_maintenance(id, &cli_args.output).await
+            }
+        },
+        Operations::StopMaintenance(resource) => match resource {
+            MaintenanceResources::Pool { id } => {
+                pool::Pool::stop_maintenance(id, &cli_args.output).await
+            }
+        },
     };
 }
 
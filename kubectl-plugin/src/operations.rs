@@ -1,4 +1,6 @@
-use crate::resources::{utils, GetResources, ScaleResources};
+use crate::resources::{
+    utils, CordonResources, DrainResources, GetResources, MaintenanceResources, ScaleResources,
+};
 use async_trait::async_trait;
 use structopt::StructOpt;
 
@@ -9,6 +11,16 @@ pub(crate) enum Operations {
     Get(GetResources),
     /// 'Scale' resources.
     Scale(ScaleResources),
+    /// 'Cordon' resources.
+    Cordon(CordonResources),
+    /// 'Uncordon' resources.
+    Uncordon(CordonResources),
+    /// 'Drain' resources.
+    Drain(DrainResources),
+    /// 'StartMaintenance' of resources.
+    StartMaintenance(MaintenanceResources),
+    /// 'StopMaintenance' of resources.
+    StopMaintenance(MaintenanceResources),
 }
 
 /// List trait.
@@ -34,6 +46,33 @@ pub trait Scale {
     async fn scale(id: &Self::ID, replica_count: u8, output: &utils::OutputFormat);
 }
 
+/// Cordoning trait.
+/// To be implemented by resources which support the 'cordon' and 'uncordon' operations.
+#[async_trait(?Send)]
+pub trait Cordoning {
+    type ID;
+    async fn cordon(id: &Self::ID, output: &utils::OutputFormat);
+    async fn uncordon(id: &Self::ID, output: &utils::OutputFormat);
+}
+
+/// Drain trait.
+/// To be implemented by resources which support the 'drain' operation.
+#[async_trait(?Send)]
+pub trait Drain {
+    type ID;
+    async fn drain(id: &Self::ID, output: &utils::OutputFormat);
+}
+
+/// Maintenance trait.
+/// To be implemented by resources which support the 'start-maintenance' and 'stop-maintenance'
+/// operations.
+#[async_trait(?Send)]
+pub trait Maintenance {
+    type ID;
+    async fn start_maintenance(id: &Self::ID, output: &utils::OutputFormat);
+    async fn stop_maintenance(id: &Self::ID, output: &utils::OutputFormat);
+}
+
 /// Replica topology trait.
 /// To be implemented by resources which support the 'replica-topology' operation
 #[async_trait(?Send)]
@@ -0,0 +1,58 @@
+use crate::{
+    resources::{
+        utils,
+        utils::{CreateRows, GetHeaderRow},
+    },
+    rest_wrapper::RestClient,
+};
+use prettytable::Row;
+use structopt::StructOpt;
+
+/// Audit log events resource.
+#[derive(StructOpt, Debug)]
+pub(crate) struct Events {}
+
+// CreateRows being trait for Event would create the rows from the list of
+// Events returned from REST call.
+impl CreateRows for openapi::models::Event {
+    fn create_rows(&self) -> Vec<Row> {
+        let rows = vec![row![
+            self.id,
+            self.timestamp,
+            self.kind,
+            self.resource,
+            self.operation,
+            self.result,
+            self.source,
+            self.error.clone().unwrap_or_default()
+        ]];
+        rows
+    }
+}
+
+// GetHeaderRow being trait for Event would return the Header Row for
+// Event.
+impl GetHeaderRow for openapi::models::Event {
+    fn get_header_row(&self) -> Row {
+        (&*utils::EVENT_HEADERS).clone()
+    }
+}
+
+impl Events {
+    /// List the audit log events, optionally only those of the given resource.
+    pub(crate) async fn list_by_resource(resource: &Option<String>, output: &utils::OutputFormat) {
+        match RestClient::client()
+            .events_api()
+            .get_events(resource.as_deref())
+            .await
+        {
+            Ok(events) => {
+                // Print table, json or yaml based on output format.
+                utils::print_table(output, events.into_body());
+            }
+            Err(e) => {
+                println!("Failed to list events. Error {}", e)
+            }
+        }
+    }
+}
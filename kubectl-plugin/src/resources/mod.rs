@@ -1,3 +1,4 @@
+pub mod event;
 pub mod node;
 pub mod pool;
 pub mod utils;
@@ -27,6 +28,12 @@ pub(crate) enum GetResources {
     Nodes,
     /// Get node with the given ID.
     Node { id: NodeId },
+    /// Get the audit log of the operations performed on the resources.
+    Events {
+        /// Only get the events of the resource with this ID.
+        #[structopt(long)]
+        resource: Option<String>,
+    },
 }
 
 /// The types of resources that support the 'scale' operation.
@@ -41,6 +48,28 @@ pub(crate) enum ScaleResources {
     },
 }
 
+/// The types of resources that support the 'cordon' and 'uncordon' operations.
+#[derive(StructOpt, Debug)]
+pub(crate) enum CordonResources {
+    /// Cordon or uncordon node with the given ID.
+    Node { id: NodeId },
+}
+
+/// The types of resources that support the 'drain' operation.
+#[derive(StructOpt, Debug)]
+pub(crate) enum DrainResources {
+    /// Drain node with the given ID.
+    Node { id: NodeId },
+}
+
+/// The types of resources that support the 'start-maintenance' and 'stop-maintenance'
+/// operations.
+#[derive(StructOpt, Debug)]
+pub(crate) enum MaintenanceResources {
+    /// Start or stop the maintenance of pool with the given ID.
+    Pool { id: PoolId },
+}
+
 /// Tabular Output Tests
 #[cfg(test)]
 mod tests;
@@ -1,5 +1,5 @@
 use crate::{
-    operations::{Get, List},
+    operations::{Cordoning, Drain, Get, List},
     resources::{
         utils,
         utils::{CreateRows, GetHeaderRow},
@@ -23,11 +23,16 @@ impl CreateRows for openapi::models::Node {
         // In case the state is not coming as filled, either due to node offline, fill in
         // spec data and mark the status as Unknown.
         let state = self.state.clone().unwrap_or(openapi::models::NodeState {
-            id: spec.id,
-            grpc_endpoint: spec.grpc_endpoint,
+            id: spec.id.clone(),
+            grpc_endpoint: spec.grpc_endpoint.clone(),
             status: openapi::models::NodeStatus::Unknown,
         });
-        let rows = vec![row![self.id, state.grpc_endpoint, state.status,]];
+        let rows = vec![row![
+            self.id,
+            state.grpc_endpoint,
+            state.status,
+            spec.cordoned
+        ]];
         rows
     }
 }
@@ -74,3 +79,64 @@ impl Get for Node {
         }
     }
 }
+
+#[async_trait(?Send)]
+impl Cordoning for Node {
+    type ID = NodeId;
+    async fn cordon(id: &Self::ID, output: &utils::OutputFormat) {
+        match RestClient::client().nodes_api().put_node_cordon(id).await {
+            Ok(node) => match output {
+                utils::OutputFormat::Yaml | utils::OutputFormat::Json => {
+                    // Print json or yaml based on output format.
+                    utils::print_table(output, node.into_body());
+                }
+                utils::OutputFormat::NoFormat => {
+                    // In case the output format is not specified, show a success message.
+                    println!("Node {} cordoned successfully", id)
+                }
+            },
+            Err(e) => {
+                println!("Failed to cordon node {}. Error {}", id, e)
+            }
+        }
+    }
+    async fn uncordon(id: &Self::ID, output: &utils::OutputFormat) {
+        match RestClient::client().nodes_api().del_node_cordon(id).await {
+            Ok(node) => match output {
+                utils::OutputFormat::Yaml | utils::OutputFormat::Json => {
+                    // Print json or yaml based on output format.
+                    utils::print_table(output, node.into_body());
+                }
+                utils::OutputFormat::NoFormat => {
+                    // In case the output format is not specified, show a success message.
+                    println!("Node {} uncordoned successfully", id)
+                }
+            },
+            Err(e) => {
+                println!("Failed to uncordon node {}. Error {}", id, e)
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Drain for Node {
+    type ID = NodeId;
+    async fn drain(id: &Self::ID, output: &utils::OutputFormat) {
+        match RestClient::client().nodes_api().put_node_drain(id).await {
+            Ok(node) => match output {
+                utils::OutputFormat::Yaml | utils::OutputFormat::Json => {
+                    // Print json or yaml based on output format.
+                    utils::print_table(output, node.into_body());
+                }
+                utils::OutputFormat::NoFormat => {
+                    // In case the output format is not specified, show a success message.
+                    println!("Node {} is being drained", id)
+                }
+            },
+            Err(e) => {
+                println!("Failed to drain node {}. Error {}", id, e)
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    operations::{Get, List},
+    operations::{Get, List, Maintenance},
     resources::{
         utils,
         utils::{CreateRows, GetHeaderRow},
@@ -92,3 +92,56 @@ impl Get for Pool {
         }
     }
 }
+
+#[async_trait(?Send)]
+impl Maintenance for Pool {
+    type ID = PoolId;
+    async fn start_maintenance(id: &Self::ID, output: &utils::OutputFormat) {
+        match RestClient::client()
+            .pools_api()
+            .put_pool_maintenance(id)
+            .await
+        {
+            Ok(pool) => match output {
+                utils::OutputFormat::Yaml | utils::OutputFormat::Json => {
+                    // Print json or yaml based on output format.
+                    utils::print_table(output, pool.into_body());
+                }
+                utils::OutputFormat::NoFormat => {
+                    // In case the output format is not specified, show a success message.
+                    println!(
+                        "Pool {} is in maintenance, its replicas are being moved",
+                        id
+                    )
+                }
+            },
+            Err(e) => {
+                println!(
+                    "Failed to start the maintenance of pool {}. Error {}",
+                    id, e
+                )
+            }
+        }
+    }
+    async fn stop_maintenance(id: &Self::ID, output: &utils::OutputFormat) {
+        match RestClient::client()
+            .pools_api()
+            .del_pool_maintenance(id)
+            .await
+        {
+            Ok(pool) => match output {
+                utils::OutputFormat::Yaml | utils::OutputFormat::Json => {
+                    // Print json or yaml based on output format.
+                    utils::print_table(output, pool.into_body());
+                }
+                utils::OutputFormat::NoFormat => {
+                    // In case the output format is not specified, show a success message.
+                    println!("Pool {} is no longer in maintenance", id)
+                }
+            },
+            Err(e) => {
+                println!("Failed to stop the maintenance of pool {}. Error {}", id, e)
+            }
+        }
+    }
+}
@@ -34,6 +34,8 @@ async fn setup() {
                 size: 5242880,
                 topology: None,
                 labels: None,
+                thin: None,
+                source: None,
             },
         )
         .await
@@ -233,16 +235,21 @@ fn volume_output(volume_spec: VolumeSpec, volume_state: VolumeState) -> String {
 }
 
 fn node_output(node_state: NodeState) -> String {
+    let width_status = node_state.status.to_string().len().max("STATUS".len()) + 2;
     format!(
-        " {:width_id$}{:width_grpc$}STATUS \n",
+        " {:width_id$}{:width_grpc$}{:width_status$}CORDONED \n",
         "ID",
         "GRPC ENDPOINT",
+        "STATUS",
         width_id = node_state.id.len() + 2,
-        width_grpc = node_state.grpc_endpoint.len() + 2
+        width_grpc = node_state.grpc_endpoint.len() + 2,
+        width_status = width_status
     ) + &*format!(
-        " {}  {}  {} \n",
+        " {}  {}  {:width_status$}{:8} \n",
         node_state.id,
         node_state.grpc_endpoint,
-        node_state.status.to_string()
+        node_state.status.to_string(),
+        "false",
+        width_status = width_status
     )
 }
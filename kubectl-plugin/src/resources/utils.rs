@@ -32,8 +32,18 @@ lazy_static! {
         "STATUS",
         "MANAGED"
     ];
-    pub static ref NODE_HEADERS: Row = row!["ID", "GRPC ENDPOINT", "STATUS",];
+    pub static ref NODE_HEADERS: Row = row!["ID", "GRPC ENDPOINT", "STATUS", "CORDONED"];
     pub static ref REPLICA_TOPOLOGY_HEADERS: Row = row!["ID", "NODE", "POOL", "STATUS"];
+    pub static ref EVENT_HEADERS: Row = row![
+        "ID",
+        "TIMESTAMP",
+        "KIND",
+        "RESOURCE",
+        "OPERATION",
+        "RESULT",
+        "SOURCE",
+        "ERROR"
+    ];
 }
 
 // table_printer takes the above defined headers and the rows created at execution,
@@ -14,8 +14,9 @@ fn main() {
         }
     }
 
+    // the server is used by the simulated mayastor nodes of the control-plane tests
     tonic_build::configure()
-        .build_server(false)
+        .build_server(true)
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
         .compile(
             &["mayastor-api/protobuf/mayastor.proto"],
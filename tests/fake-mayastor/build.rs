@@ -0,0 +1,7 @@
+extern crate tonic_build;
+
+fn main() {
+    tonic_build::configure()
+        .compile(&["proto/fault.proto"], &["proto/"])
+        .unwrap();
+}
@@ -0,0 +1,114 @@
+//! Fault injection into a simulated mayastor node.
+
+use crate::{
+    fault::{
+        fault_injection_client::FaultInjectionClient, fault_injection_server::FaultInjection,
+        FaultChildRequest, SetDelayRequest, SetOfflineRequest, SetPoolFullRequest,
+    },
+    FakeMayastor,
+};
+use std::time::Duration;
+use tonic::{transport::Channel, Request, Response, Status};
+
+#[tonic::async_trait]
+impl FaultInjection for FakeMayastor {
+    async fn fault_child(
+        &self,
+        request: Request<FaultChildRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+        tracing::info!(
+            "Faulting child '{}' of nexus '{}'",
+            request.uri,
+            request.nexus
+        );
+        self.state
+            .lock()
+            .fault_child(&request.nexus, &request.uri)
+            .map(Response::new)
+    }
+
+    async fn set_delay(&self, request: Request<SetDelayRequest>) -> Result<Response<()>, Status> {
+        let delay = Duration::from_millis(request.into_inner().delay_ms);
+        tracing::info!("Delaying the replies by {:?}", delay);
+        self.faults.lock().delay = delay;
+        Ok(Response::new(()))
+    }
+
+    async fn set_offline(
+        &self,
+        request: Request<SetOfflineRequest>,
+    ) -> Result<Response<()>, Status> {
+        let offline = request.into_inner().offline;
+        tracing::info!(
+            "Taking the node {}",
+            if offline { "offline" } else { "online" }
+        );
+        self.faults.lock().offline = offline;
+        Ok(Response::new(()))
+    }
+
+    async fn set_pool_full(
+        &self,
+        request: Request<SetPoolFullRequest>,
+    ) -> Result<Response<()>, Status> {
+        let request = request.into_inner();
+        tracing::info!("Setting pool '{}' full: {}", request.pool, request.full);
+        self.state
+            .lock()
+            .set_pool_full(&request.pool, request.full)
+            .map(Response::new)
+    }
+}
+
+/// Client of the fault injection service of a simulated mayastor node
+#[derive(Debug, Clone)]
+pub struct FaultClient {
+    client: FaultInjectionClient<Channel>,
+}
+
+impl FaultClient {
+    /// Connect to the simulated node serving on `endpoint`, eg: 10.1.0.3:10124
+    pub async fn connect(endpoint: &str) -> Result<Self, tonic::transport::Error> {
+        let client = FaultInjectionClient::connect(format!("http://{}", endpoint)).await?;
+        Ok(Self { client })
+    }
+
+    /// Fault the child `uri` of the nexus `nexus`, as if its IO had failed
+    pub async fn fault_child(&self, nexus: &str, uri: &str) -> Result<(), Status> {
+        let request = FaultChildRequest {
+            nexus: nexus.to_string(),
+            uri: uri.to_string(),
+        };
+        self.client.clone().fault_child(request).await?;
+        Ok(())
+    }
+
+    /// Delay every reply of the mayastor service by `delay`
+    pub async fn set_delay(&self, delay: Duration) -> Result<(), Status> {
+        let request = SetDelayRequest {
+            delay_ms: delay.as_millis() as u64,
+        };
+        self.client.clone().set_delay(request).await?;
+        Ok(())
+    }
+
+    /// Take the node offline, as if it had disappeared, or bring it back online
+    pub async fn set_offline(&self, offline: bool) -> Result<(), Status> {
+        self.client
+            .clone()
+            .set_offline(SetOfflineRequest { offline })
+            .await?;
+        Ok(())
+    }
+
+    /// Fill up the pool `pool`, or free it up again
+    pub async fn set_pool_full(&self, pool: &str, full: bool) -> Result<(), Status> {
+        let request = SetPoolFullRequest {
+            pool: pool.to_string(),
+            full,
+        };
+        self.client.clone().set_pool_full(request).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+//! Simulated mayastor node for the control-plane tests.
+//! The mayastor gRPC service is served from memory, so a cluster may have dozens of nodes
+//! without hugepages or block devices. Faults are injected through the `FaultInjection`
+//! gRPC service which is served alongside it, eg: with the `FaultClient`.
+
+mod faults;
+mod mayastor;
+mod node;
+
+pub use faults::FaultClient;
+pub use node::DEFAULT_DISK_CAPACITY;
+
+/// Fault injection gRPC service
+pub mod fault {
+    tonic::include_proto!("fake.mayastor");
+}
+
+use fault::fault_injection_server::FaultInjectionServer;
+use node::NodeState;
+use parking_lot::Mutex;
+use rpc::mayastor::mayastor_server::MayastorServer;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tonic::{Response, Status};
+
+/// Faults injected into the whole node
+#[derive(Debug, Default)]
+struct NodeFaults {
+    /// delay of every reply of the mayastor service
+    delay: Duration,
+    /// the mayastor service is unavailable and the node stops registering
+    offline: bool,
+}
+
+/// Simulated mayastor node
+#[derive(Debug, Clone)]
+pub struct FakeMayastor {
+    state: Arc<Mutex<NodeState>>,
+    faults: Arc<Mutex<NodeFaults>>,
+}
+
+impl FakeMayastor {
+    /// New node without any pool, reachable on the `address` ip for the shares
+    pub fn new(address: &str) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(NodeState::new(address))),
+            faults: Default::default(),
+        }
+    }
+
+    /// Whether the node has been taken offline
+    pub fn is_offline(&self) -> bool {
+        self.faults.lock().offline
+    }
+
+    /// Serve the mayastor and the fault injection services on `endpoint` while making
+    /// progress on the rebuilds every `rebuild_period`
+    pub async fn serve(
+        self,
+        endpoint: SocketAddr,
+        rebuild_period: Duration,
+    ) -> Result<(), tonic::transport::Error> {
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(rebuild_period);
+            loop {
+                interval.tick().await;
+                state.lock().tick();
+            }
+        });
+
+        tonic::transport::Server::builder()
+            .add_service(MayastorServer::new(self.clone()))
+            .add_service(FaultInjectionServer::new(self))
+            .serve(endpoint)
+            .await
+    }
+
+    /// Reply to a mayastor request by applying `operation` to the node, unless the injected
+    /// faults get in the way
+    async fn reply<T, F>(&self, operation: F) -> Result<Response<T>, Status>
+    where
+        F: FnOnce(&mut NodeState) -> Result<T, Status>,
+    {
+        let delay = {
+            let faults = self.faults.lock();
+            if faults.offline {
+                return Err(Status::unavailable("The node is offline"));
+            }
+            faults.delay
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let mut state = self.state.lock();
+        operation(&mut state).map(Response::new)
+    }
+}
@@ -0,0 +1,97 @@
+use common_lib::{
+    mbus_api::{message_bus_init, Message},
+    types::v0::message_bus::{Deregister, NodeId, Register},
+};
+use fake_mayastor::FakeMayastor;
+use std::net::SocketAddr;
+use structopt::StructOpt;
+use utils::package_info;
+
+#[derive(Debug, StructOpt)]
+#[structopt(version = package_info!())]
+struct CliArgs {
+    /// The Nats Server URL to connect to
+    /// (supports the nats schema)
+    /// Default: nats://127.0.0.1:4222
+    #[structopt(long, short, default_value = "nats://127.0.0.1:4222")]
+    nats: String,
+
+    /// The name of the node
+    #[structopt(long = "node-name", short = "N", parse(from_str))]
+    node: NodeId,
+
+    /// The gRPC endpoint which is served and registered with the control-plane,
+    /// eg: 10.1.0.3:10124
+    #[structopt(long, short)]
+    grpc_endpoint: SocketAddr,
+
+    /// The period at which the node registers with the control-plane
+    #[structopt(long, default_value = "5s")]
+    registration_period: humantime::Duration,
+
+    /// The period at which the rebuilding children make progress
+    #[structopt(long, default_value = "1s")]
+    rebuild_period: humantime::Duration,
+}
+
+/// Register the node periodically while it's online
+async fn register(node: FakeMayastor, args: &CliArgs) {
+    let mut interval = tokio::time::interval(args.registration_period.into());
+    let mut registered = false;
+    loop {
+        interval.tick().await;
+        if node.is_offline() {
+            if registered {
+                deregister(args).await;
+                registered = false;
+            }
+            continue;
+        }
+        let register = Register {
+            id: args.node.clone(),
+            grpc_endpoint: args.grpc_endpoint.to_string(),
+        };
+        match register.publish().await {
+            Ok(_) => registered = true,
+            Err(error) => tracing::error!(%error, "Failed to register node '{}'", args.node),
+        }
+    }
+}
+
+async fn deregister(args: &CliArgs) {
+    let deregister = Deregister {
+        id: args.node.clone(),
+    };
+    if let Err(error) = deregister.publish().await {
+        tracing::error!(%error, "Failed to deregister node '{}'", args.node);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = CliArgs::from_args();
+    tracing::info!(?args, "Starting the simulated mayastor node");
+
+    message_bus_init(args.nats.clone()).await;
+    let node = FakeMayastor::new(&args.grpc_endpoint.ip().to_string());
+
+    tokio::select! {
+        result = node.clone().serve(args.grpc_endpoint, args.rebuild_period.into()) => {
+            if let Err(error) = result {
+                tracing::error!(%error, "Failed to serve the mayastor gRPC service");
+            }
+        }
+        _ = register(node, &args) => {}
+        _ = tokio::signal::ctrl_c() => {
+            tracing::info!("Shutting down");
+        }
+    }
+    deregister(&args).await;
+}
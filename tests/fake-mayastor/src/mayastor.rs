@@ -0,0 +1,336 @@
+//! The mayastor gRPC service, as used by the control-plane.
+//! The requests which the control-plane does not make are unimplemented.
+
+use crate::FakeMayastor;
+use rpc::mayastor::{self as rpc, mayastor_server::Mayastor, Null};
+use tonic::{Request, Response, Status};
+
+/// Reply to the requests which a simulated node does not support
+fn unimplemented<T>(request: &str) -> Result<Response<T>, Status> {
+    Err(Status::unimplemented(format!(
+        "{} is not supported by the simulated mayastor",
+        request
+    )))
+}
+
+/// Nexus as returned by the v1 requests
+fn nexus_v1(nexus: rpc::NexusV2) -> rpc::Nexus {
+    rpc::Nexus {
+        uuid: nexus.name,
+        size: nexus.size,
+        state: nexus.state,
+        children: nexus.children,
+        device_uri: nexus.device_uri,
+        rebuilds: nexus.rebuilds,
+        ..Default::default()
+    }
+}
+
+#[tonic::async_trait]
+impl Mayastor for FakeMayastor {
+    async fn create_pool(
+        &self,
+        request: Request<rpc::CreatePoolRequest>,
+    ) -> Result<Response<rpc::Pool>, Status> {
+        self.reply(|node| node.create_pool(request.into_inner()))
+            .await
+    }
+
+    async fn destroy_pool(
+        &self,
+        request: Request<rpc::DestroyPoolRequest>,
+    ) -> Result<Response<Null>, Status> {
+        self.reply(|node| node.destroy_pool(request.into_inner()).map(|_| Null {}))
+            .await
+    }
+
+    async fn list_pools(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListPoolsReply>, Status> {
+        self.reply(|node| {
+            Ok(rpc::ListPoolsReply {
+                pools: node.list_pools(),
+            })
+        })
+        .await
+    }
+
+    async fn create_replica(
+        &self,
+        _request: Request<rpc::CreateReplicaRequest>,
+    ) -> Result<Response<rpc::Replica>, Status> {
+        unimplemented("CreateReplica")
+    }
+
+    async fn create_replica_v2(
+        &self,
+        request: Request<rpc::CreateReplicaRequestV2>,
+    ) -> Result<Response<rpc::ReplicaV2>, Status> {
+        self.reply(|node| node.create_replica(request.into_inner()))
+            .await
+    }
+
+    async fn destroy_replica(
+        &self,
+        request: Request<rpc::DestroyReplicaRequest>,
+    ) -> Result<Response<Null>, Status> {
+        self.reply(|node| node.destroy_replica(request.into_inner()).map(|_| Null {}))
+            .await
+    }
+
+    async fn list_replicas(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListReplicasReply>, Status> {
+        unimplemented("ListReplicas")
+    }
+
+    async fn list_replicas_v2(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListReplicasReplyV2>, Status> {
+        self.reply(|node| {
+            Ok(rpc::ListReplicasReplyV2 {
+                replicas: node.list_replicas(),
+            })
+        })
+        .await
+    }
+
+    async fn stat_replicas(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::StatReplicasReply>, Status> {
+        unimplemented("StatReplicas")
+    }
+
+    async fn share_replica(
+        &self,
+        request: Request<rpc::ShareReplicaRequest>,
+    ) -> Result<Response<rpc::ShareReplicaReply>, Status> {
+        self.reply(|node| {
+            node.share_replica(request.into_inner())
+                .map(|uri| rpc::ShareReplicaReply { uri })
+        })
+        .await
+    }
+
+    async fn create_nexus(
+        &self,
+        _request: Request<rpc::CreateNexusRequest>,
+    ) -> Result<Response<rpc::Nexus>, Status> {
+        unimplemented("CreateNexus")
+    }
+
+    async fn create_nexus_v2(
+        &self,
+        request: Request<rpc::CreateNexusV2Request>,
+    ) -> Result<Response<rpc::Nexus>, Status> {
+        self.reply(|node| node.create_nexus(request.into_inner()).map(nexus_v1))
+            .await
+    }
+
+    async fn destroy_nexus(
+        &self,
+        request: Request<rpc::DestroyNexusRequest>,
+    ) -> Result<Response<Null>, Status> {
+        self.reply(|node| node.destroy_nexus(request.into_inner()).map(|_| Null {}))
+            .await
+    }
+
+    async fn list_nexus(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListNexusReply>, Status> {
+        self.reply(|node| {
+            Ok(rpc::ListNexusReply {
+                nexus_list: node.list_nexuses().into_iter().map(nexus_v1).collect(),
+            })
+        })
+        .await
+    }
+
+    async fn list_nexus_v2(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListNexusV2Reply>, Status> {
+        self.reply(|node| {
+            Ok(rpc::ListNexusV2Reply {
+                nexus_list: node.list_nexuses(),
+            })
+        })
+        .await
+    }
+
+    async fn add_child_nexus(
+        &self,
+        request: Request<rpc::AddChildNexusRequest>,
+    ) -> Result<Response<rpc::Child>, Status> {
+        self.reply(|node| node.add_child(request.into_inner()))
+            .await
+    }
+
+    async fn remove_child_nexus(
+        &self,
+        request: Request<rpc::RemoveChildNexusRequest>,
+    ) -> Result<Response<Null>, Status> {
+        self.reply(|node| node.remove_child(request.into_inner()).map(|_| Null {}))
+            .await
+    }
+
+    async fn fault_nexus_child(
+        &self,
+        request: Request<rpc::FaultNexusChildRequest>,
+    ) -> Result<Response<Null>, Status> {
+        let request = request.into_inner();
+        self.reply(|node| {
+            node.fault_child(&request.uuid, &request.uri)
+                .map(|_| Null {})
+        })
+        .await
+    }
+
+    async fn publish_nexus(
+        &self,
+        request: Request<rpc::PublishNexusRequest>,
+    ) -> Result<Response<rpc::PublishNexusReply>, Status> {
+        self.reply(|node| {
+            node.publish_nexus(request.into_inner())
+                .map(|device_uri| rpc::PublishNexusReply { device_uri })
+        })
+        .await
+    }
+
+    async fn unpublish_nexus(
+        &self,
+        request: Request<rpc::UnpublishNexusRequest>,
+    ) -> Result<Response<Null>, Status> {
+        self.reply(|node| node.unpublish_nexus(request.into_inner()).map(|_| Null {}))
+            .await
+    }
+
+    async fn get_nvme_ana_state(
+        &self,
+        _request: Request<rpc::GetNvmeAnaStateRequest>,
+    ) -> Result<Response<rpc::GetNvmeAnaStateReply>, Status> {
+        unimplemented("GetNvmeAnaState")
+    }
+
+    async fn set_nvme_ana_state(
+        &self,
+        _request: Request<rpc::SetNvmeAnaStateRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("SetNvmeAnaState")
+    }
+
+    async fn child_operation(
+        &self,
+        _request: Request<rpc::ChildNexusRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("ChildOperation")
+    }
+
+    async fn start_rebuild(
+        &self,
+        _request: Request<rpc::StartRebuildRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("StartRebuild")
+    }
+
+    async fn stop_rebuild(
+        &self,
+        _request: Request<rpc::StopRebuildRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("StopRebuild")
+    }
+
+    async fn pause_rebuild(
+        &self,
+        _request: Request<rpc::PauseRebuildRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("PauseRebuild")
+    }
+
+    async fn resume_rebuild(
+        &self,
+        _request: Request<rpc::ResumeRebuildRequest>,
+    ) -> Result<Response<Null>, Status> {
+        unimplemented("ResumeRebuild")
+    }
+
+    async fn get_rebuild_state(
+        &self,
+        _request: Request<rpc::RebuildStateRequest>,
+    ) -> Result<Response<rpc::RebuildStateReply>, Status> {
+        unimplemented("GetRebuildState")
+    }
+
+    async fn get_rebuild_stats(
+        &self,
+        _request: Request<rpc::RebuildStatsRequest>,
+    ) -> Result<Response<rpc::RebuildStatsReply>, Status> {
+        unimplemented("GetRebuildStats")
+    }
+
+    async fn get_rebuild_progress(
+        &self,
+        _request: Request<rpc::RebuildProgressRequest>,
+    ) -> Result<Response<rpc::RebuildProgressReply>, Status> {
+        unimplemented("GetRebuildProgress")
+    }
+
+    async fn create_snapshot(
+        &self,
+        request: Request<rpc::CreateSnapshotRequest>,
+    ) -> Result<Response<rpc::CreateSnapshotReply>, Status> {
+        self.reply(|node| {
+            node.create_snapshot(request.into_inner())
+                .map(|name| rpc::CreateSnapshotReply { name })
+        })
+        .await
+    }
+
+    async fn list_block_devices(
+        &self,
+        _request: Request<rpc::ListBlockDevicesRequest>,
+    ) -> Result<Response<rpc::ListBlockDevicesReply>, Status> {
+        // a simulated node has no block devices
+        self.reply(|_| Ok(rpc::ListBlockDevicesReply { devices: vec![] }))
+            .await
+    }
+
+    async fn get_resource_usage(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::GetResourceUsageReply>, Status> {
+        unimplemented("GetResourceUsage")
+    }
+
+    async fn list_nvme_controllers(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::ListNvmeControllersReply>, Status> {
+        unimplemented("ListNvmeControllers")
+    }
+
+    async fn stat_nvme_controllers(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::StatNvmeControllersReply>, Status> {
+        unimplemented("StatNvmeControllers")
+    }
+
+    async fn get_mayastor_info(
+        &self,
+        _request: Request<Null>,
+    ) -> Result<Response<rpc::MayastorInfoRequest>, Status> {
+        self.reply(|_| {
+            Ok(rpc::MayastorInfoRequest {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,588 @@
+//! In-memory state of a simulated mayastor node.
+
+use rpc::mayastor as rpc;
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tonic::Status;
+
+/// Capacity of a pool disk which does not specify its size, eg: aio:///host/tmp/disk1.img
+pub const DEFAULT_DISK_CAPACITY: u64 = 10 * 1024 * 1024 * 1024;
+/// Rebuild progress made by the rebuilding children on every tick
+const REBUILD_STEP: i32 = 10;
+/// Rebuild progress of a child which is not being rebuilt
+const NO_REBUILD: i32 = -1;
+
+// Pool, replica, nexus and child states, as understood by the control-plane
+const STATE_ONLINE: i32 = 1;
+const STATE_DEGRADED: i32 = 2;
+const STATE_FAULTED: i32 = 3;
+
+// Replica and nexus share protocols, as understood by the control-plane
+const SHARE_NONE: i32 = 0;
+const SHARE_NVMF: i32 = 1;
+const SHARE_ISCSI: i32 = 2;
+
+/// Pool and the faults injected into it
+#[derive(Debug)]
+struct Pool {
+    pool: rpc::Pool,
+    /// a full pool has no free space left, whatever its replicas
+    full: bool,
+}
+
+/// Pools, replicas and nexuses of a simulated node
+#[derive(Debug)]
+pub(crate) struct NodeState {
+    /// ip address of the node, used by the share uri's
+    address: String,
+    pools: BTreeMap<String, Pool>,
+    /// replicas indexed by their name
+    replicas: BTreeMap<String, rpc::ReplicaV2>,
+    /// replica snapshots indexed by their name, which are reported as replicas like mayastor does
+    snapshots: BTreeMap<String, rpc::ReplicaV2>,
+    /// nexuses indexed by their uuid
+    nexuses: BTreeMap<String, rpc::NexusV2>,
+}
+
+impl NodeState {
+    /// New empty node reachable on `address`
+    pub(crate) fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            pools: Default::default(),
+            replicas: Default::default(),
+            snapshots: Default::default(),
+            nexuses: Default::default(),
+        }
+    }
+
+    /// Capacity of a pool disk, from the size_mb of malloc disks or the default capacity
+    fn disk_capacity(disk: &str) -> u64 {
+        disk.split(|c| c == '?' || c == '&')
+            .find_map(|param| param.strip_prefix("size_mb="))
+            .and_then(|size| size.parse::<u64>().ok())
+            .map(|size| size * 1024 * 1024)
+            .unwrap_or(DEFAULT_DISK_CAPACITY)
+    }
+    fn pool_mut(&mut self, name: &str) -> Result<&mut Pool, Status> {
+        self.pools
+            .get_mut(name)
+            .ok_or_else(|| Status::not_found(format!("Pool '{}' not found", name)))
+    }
+    /// Space used by the replicas of the pool
+    fn pool_used(&self, name: &str) -> u64 {
+        self.replicas
+            .values()
+            .filter(|r| r.pool == name)
+            .map(|r| r.size)
+            .sum()
+    }
+    /// Pool as reported to the control-plane
+    fn pool_state(&self, pool: &Pool) -> rpc::Pool {
+        let used = if pool.full {
+            pool.pool.capacity
+        } else {
+            self.pool_used(&pool.pool.name)
+        };
+        rpc::Pool {
+            used,
+            ..pool.pool.clone()
+        }
+    }
+    /// Make sure the pool can allocate `size` more bytes
+    fn pool_reserve(&self, name: &str, size: u64) -> Result<(), Status> {
+        let pool = self
+            .pools
+            .get(name)
+            .ok_or_else(|| Status::not_found(format!("Pool '{}' not found", name)))?;
+        let pool = self.pool_state(pool);
+        if pool.used + size > pool.capacity {
+            return Err(Status::resource_exhausted(format!(
+                "Pool '{}' has {} bytes free, {} requested",
+                name,
+                pool.capacity - pool.used,
+                size
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn list_pools(&self) -> Vec<rpc::Pool> {
+        self.pools.values().map(|p| self.pool_state(p)).collect()
+    }
+    pub(crate) fn create_pool(
+        &mut self,
+        request: rpc::CreatePoolRequest,
+    ) -> Result<rpc::Pool, Status> {
+        if request.disks.is_empty() {
+            return Err(Status::invalid_argument("Missing pool disks"));
+        }
+        if let Some(pool) = self.pools.get(&request.name) {
+            return if pool.pool.disks == request.disks {
+                Ok(self.pool_state(pool))
+            } else {
+                Err(Status::already_exists(format!(
+                    "Pool '{}' already exists with different disks",
+                    request.name
+                )))
+            };
+        }
+        let pool = Pool {
+            pool: rpc::Pool {
+                name: request.name.clone(),
+                capacity: request.disks.iter().map(|d| Self::disk_capacity(d)).sum(),
+                disks: request.disks,
+                state: STATE_ONLINE,
+                ..Default::default()
+            },
+            full: false,
+        };
+        let state = self.pool_state(&pool);
+        self.pools.insert(request.name, pool);
+        Ok(state)
+    }
+    pub(crate) fn destroy_pool(&mut self, request: rpc::DestroyPoolRequest) -> Result<(), Status> {
+        if self.pools.remove(&request.name).is_some() {
+            self.replicas.retain(|_, r| r.pool != request.name);
+            self.snapshots.retain(|_, s| s.pool != request.name);
+        }
+        Ok(())
+    }
+    /// Fill up the pool or free it up again
+    pub(crate) fn set_pool_full(&mut self, name: &str, full: bool) -> Result<(), Status> {
+        self.pool_mut(name)?.full = full;
+        Ok(())
+    }
+
+    /// Uri of a replica shared with the given protocol
+    fn replica_uri(&self, name: &str, uuid: &str, share: i32) -> Result<String, Status> {
+        match share {
+            SHARE_NONE => Ok(format!("bdev:///{}?uuid={}", name, uuid)),
+            SHARE_NVMF => Ok(format!(
+                "nvmf://{}:8420/nqn.2019-05.io.openebs:{}?uuid={}",
+                self.address, name, uuid
+            )),
+            other => Err(Status::invalid_argument(format!(
+                "Invalid replica share protocol {}",
+                other
+            ))),
+        }
+    }
+    fn replica_mut(&mut self, name: &str) -> Result<&mut rpc::ReplicaV2, Status> {
+        self.replicas
+            .get_mut(name)
+            .ok_or_else(|| Status::not_found(format!("Replica '{}' not found", name)))
+    }
+
+    pub(crate) fn list_replicas(&self) -> Vec<rpc::ReplicaV2> {
+        self.replicas
+            .values()
+            .chain(self.snapshots.values())
+            .cloned()
+            .collect()
+    }
+    pub(crate) fn create_replica(
+        &mut self,
+        request: rpc::CreateReplicaRequestV2,
+    ) -> Result<rpc::ReplicaV2, Status> {
+        if self.replicas.contains_key(&request.name) {
+            return Err(Status::already_exists(format!(
+                "Replica '{}' already exists",
+                request.name
+            )));
+        }
+        self.pool_reserve(&request.pool, if request.thin { 0 } else { request.size })?;
+        if self.pools[&request.pool].full {
+            return Err(Status::resource_exhausted(format!(
+                "Pool '{}' is full",
+                request.pool
+            )));
+        }
+        let replica = rpc::ReplicaV2 {
+            uri: self.replica_uri(&request.name, &request.uuid, request.share)?,
+            name: request.name.clone(),
+            uuid: request.uuid,
+            pool: request.pool,
+            thin: request.thin,
+            size: request.size,
+            share: request.share,
+            ..Default::default()
+        };
+        self.replicas.insert(request.name, replica.clone());
+        Ok(replica)
+    }
+    /// Destroy a replica or a replica snapshot
+    pub(crate) fn destroy_replica(
+        &mut self,
+        request: rpc::DestroyReplicaRequest,
+    ) -> Result<(), Status> {
+        if self.replicas.remove(&request.uuid).is_none() {
+            self.snapshots.remove(&request.uuid);
+        }
+        Ok(())
+    }
+    pub(crate) fn share_replica(
+        &mut self,
+        request: rpc::ShareReplicaRequest,
+    ) -> Result<String, Status> {
+        let replica = self.replica_mut(&request.uuid)?;
+        let (name, uuid) = (replica.name.clone(), replica.uuid.clone());
+        let uri = self.replica_uri(&name, &uuid, request.share)?;
+        let replica = self.replica_mut(&request.uuid)?;
+        replica.share = request.share;
+        replica.uri = uri.clone();
+        Ok(uri)
+    }
+
+    /// State of a nexus with the given children
+    fn nexus_state(children: &[rpc::Child]) -> i32 {
+        if children.iter().all(|c| c.state == STATE_FAULTED) {
+            STATE_FAULTED
+        } else if children.iter().all(|c| c.state == STATE_ONLINE) {
+            STATE_ONLINE
+        } else {
+            STATE_DEGRADED
+        }
+    }
+    fn nexus_mut(&mut self, uuid: &str) -> Result<&mut rpc::NexusV2, Status> {
+        self.nexuses
+            .get_mut(uuid)
+            .ok_or_else(|| Status::not_found(format!("Nexus '{}' not found", uuid)))
+    }
+    /// Update the nexus state and its rebuild count after a change of its children
+    fn nexus_refresh(nexus: &mut rpc::NexusV2) {
+        nexus.state = Self::nexus_state(&nexus.children);
+        nexus.rebuilds = nexus
+            .children
+            .iter()
+            .filter(|c| c.rebuild_progress != NO_REBUILD)
+            .count() as u32;
+    }
+
+    pub(crate) fn list_nexuses(&self) -> Vec<rpc::NexusV2> {
+        self.nexuses.values().cloned().collect()
+    }
+    pub(crate) fn create_nexus(
+        &mut self,
+        request: rpc::CreateNexusV2Request,
+    ) -> Result<rpc::NexusV2, Status> {
+        if let Some(nexus) = self.nexuses.get(&request.uuid) {
+            return Ok(nexus.clone());
+        }
+        if request.children.is_empty() {
+            return Err(Status::invalid_argument("Missing nexus children"));
+        }
+        let mut nexus = rpc::NexusV2 {
+            name: request.name,
+            uuid: request.uuid.clone(),
+            size: request.size,
+            children: request
+                .children
+                .into_iter()
+                .map(|uri| rpc::Child {
+                    uri,
+                    state: STATE_ONLINE,
+                    rebuild_progress: NO_REBUILD,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        Self::nexus_refresh(&mut nexus);
+        self.nexuses.insert(request.uuid, nexus.clone());
+        Ok(nexus)
+    }
+    pub(crate) fn destroy_nexus(
+        &mut self,
+        request: rpc::DestroyNexusRequest,
+    ) -> Result<(), Status> {
+        self.nexuses.remove(&request.uuid);
+        Ok(())
+    }
+    pub(crate) fn publish_nexus(
+        &mut self,
+        request: rpc::PublishNexusRequest,
+    ) -> Result<String, Status> {
+        let address = self.address.clone();
+        let nexus = self.nexus_mut(&request.uuid)?;
+        let device_uri = match request.share {
+            SHARE_NVMF => format!(
+                "nvmf://{}:8420/nqn.2019-05.io.openebs:{}",
+                address, request.uuid
+            ),
+            SHARE_ISCSI => format!(
+                "iscsi://{}:3260/iqn.2019-05.io.openebs:{}/0",
+                address, request.uuid
+            ),
+            _ => "nbd:///dev/nbd0".to_string(),
+        };
+        if !nexus.device_uri.is_empty() && nexus.device_uri != device_uri {
+            return Err(Status::already_exists(format!(
+                "Nexus '{}' is already published as '{}'",
+                request.uuid, nexus.device_uri
+            )));
+        }
+        nexus.device_uri = device_uri.clone();
+        Ok(device_uri)
+    }
+    pub(crate) fn unpublish_nexus(
+        &mut self,
+        request: rpc::UnpublishNexusRequest,
+    ) -> Result<(), Status> {
+        self.nexus_mut(&request.uuid)?.device_uri.clear();
+        Ok(())
+    }
+    pub(crate) fn add_child(
+        &mut self,
+        request: rpc::AddChildNexusRequest,
+    ) -> Result<rpc::Child, Status> {
+        let nexus = self.nexus_mut(&request.uuid)?;
+        if nexus.children.iter().any(|c| c.uri == request.uri) {
+            return Err(Status::already_exists(format!(
+                "Child '{}' is already part of nexus '{}'",
+                request.uri, request.uuid
+            )));
+        }
+        // the new child is out of sync until it's been rebuilt
+        let child = rpc::Child {
+            uri: request.uri,
+            state: STATE_DEGRADED,
+            rebuild_progress: if request.norebuild { NO_REBUILD } else { 0 },
+            ..Default::default()
+        };
+        nexus.children.push(child.clone());
+        Self::nexus_refresh(nexus);
+        Ok(child)
+    }
+    pub(crate) fn remove_child(
+        &mut self,
+        request: rpc::RemoveChildNexusRequest,
+    ) -> Result<(), Status> {
+        let nexus = self.nexus_mut(&request.uuid)?;
+        if !nexus.children.iter().any(|c| c.uri == request.uri) {
+            return Err(Status::not_found(format!(
+                "Child '{}' is not part of nexus '{}'",
+                request.uri, request.uuid
+            )));
+        }
+        if nexus.children.len() == 1 {
+            return Err(Status::failed_precondition(format!(
+                "Can't remove the last child of nexus '{}'",
+                request.uuid
+            )));
+        }
+        nexus.children.retain(|c| c.uri != request.uri);
+        Self::nexus_refresh(nexus);
+        Ok(())
+    }
+    /// Fault the child of a nexus, stopping its rebuild if any
+    pub(crate) fn fault_child(&mut self, uuid: &str, uri: &str) -> Result<(), Status> {
+        let nexus = self.nexus_mut(uuid)?;
+        let child = nexus
+            .children
+            .iter_mut()
+            .find(|c| c.uri == uri)
+            .ok_or_else(|| {
+                Status::not_found(format!("Child '{}' is not part of nexus '{}'", uri, uuid))
+            })?;
+        child.state = STATE_FAULTED;
+        child.rebuild_progress = NO_REBUILD;
+        Self::nexus_refresh(nexus);
+        Ok(())
+    }
+    /// Snapshot the nexus: every local replica which is an online child gets a snapshot named
+    /// after the replica and the snapshot time, like mayastor does
+    pub(crate) fn create_snapshot(
+        &mut self,
+        request: rpc::CreateSnapshotRequest,
+    ) -> Result<String, Status> {
+        let nexus = self.nexus_mut(&request.uuid)?.clone();
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        for child in nexus.children.iter().filter(|c| c.state == STATE_ONLINE) {
+            let replica = self.replicas.values().find(|r| {
+                r.uri == child.uri || child.uri.starts_with(&format!("bdev:///{}?", r.name))
+            });
+            if let Some(replica) = replica {
+                let name = format!("{}-snap-{}", replica.name, time);
+                let uuid = uuid::Uuid::new_v4().to_string();
+                let snapshot = rpc::ReplicaV2 {
+                    uri: format!("bdev:///{}?uuid={}", name, uuid),
+                    name: name.clone(),
+                    uuid,
+                    pool: replica.pool.clone(),
+                    thin: true,
+                    size: replica.size,
+                    share: SHARE_NONE,
+                    ..Default::default()
+                };
+                self.snapshots.insert(name, snapshot);
+            }
+        }
+        Ok(format!("{}-snap-{}", nexus.name, time))
+    }
+
+    /// Make progress on the rebuilding children, which are online once fully rebuilt
+    pub(crate) fn tick(&mut self) {
+        for nexus in self.nexuses.values_mut() {
+            let mut changed = false;
+            for child in nexus
+                .children
+                .iter_mut()
+                .filter(|c| c.rebuild_progress != NO_REBUILD)
+            {
+                child.rebuild_progress = (child.rebuild_progress + REBUILD_STEP).min(100);
+                if child.rebuild_progress == 100 {
+                    child.state = STATE_ONLINE;
+                    child.rebuild_progress = NO_REBUILD;
+                }
+                changed = true;
+            }
+            if changed {
+                Self::nexus_refresh(nexus);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn node_with_pool(disks: &[&str]) -> NodeState {
+        let mut node = NodeState::new("10.1.0.2");
+        node.create_pool(rpc::CreatePoolRequest {
+            name: "pool".to_string(),
+            disks: disks.iter().map(|d| d.to_string()).collect(),
+        })
+        .unwrap();
+        node
+    }
+    fn create_replica(node: &mut NodeState, name: &str, size: u64) -> Result<(), Status> {
+        node.create_replica(rpc::CreateReplicaRequestV2 {
+            name: name.to_string(),
+            uuid: name.to_string(),
+            pool: "pool".to_string(),
+            size,
+            thin: false,
+            share: SHARE_NONE,
+            ..Default::default()
+        })
+        .map(|_| ())
+    }
+    fn node_with_nexus(children: &[&str]) -> NodeState {
+        let mut node = NodeState::new("10.1.0.2");
+        node.create_nexus(rpc::CreateNexusV2Request {
+            name: "nexus".to_string(),
+            uuid: "nexus".to_string(),
+            size: 10 * MB,
+            children: children.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        })
+        .unwrap();
+        node
+    }
+    fn nexus(node: &NodeState) -> rpc::NexusV2 {
+        node.list_nexuses().pop().unwrap()
+    }
+
+    #[test]
+    fn pool_capacity() {
+        let mut node = node_with_pool(&["malloc:///disk0?size_mb=100", "aio:///dev/sda"]);
+        let pool = node.list_pools().pop().unwrap();
+        assert_eq!(pool.capacity, 100 * MB + DEFAULT_DISK_CAPACITY);
+        assert_eq!(pool.used, 0);
+
+        let mut node = node_with_pool(&["malloc:///disk0?size_mb=100"]);
+        create_replica(&mut node, "r1", 60 * MB).unwrap();
+        assert_eq!(node.list_pools()[0].used, 60 * MB);
+
+        let error = create_replica(&mut node, "r2", 60 * MB).unwrap_err();
+        assert_eq!(error.code(), tonic::Code::ResourceExhausted);
+        create_replica(&mut node, "r2", 40 * MB).unwrap();
+        assert_eq!(node.list_pools()[0].used, 100 * MB);
+
+        // destroying a replica frees up its space
+        node.destroy_replica(rpc::DestroyReplicaRequest {
+            uuid: "r2".to_string(),
+        })
+        .unwrap();
+        assert_eq!(node.list_pools()[0].used, 60 * MB);
+
+        // a full pool reports all of its capacity as used and can't allocate any more
+        node.set_pool_full("pool", true).unwrap();
+        assert_eq!(node.list_pools()[0].used, 100 * MB);
+        let error = create_replica(&mut node, "r2", MB).unwrap_err();
+        assert_eq!(error.code(), tonic::Code::ResourceExhausted);
+
+        node.set_pool_full("pool", false).unwrap();
+        assert_eq!(node.list_pools()[0].used, 60 * MB);
+        create_replica(&mut node, "r2", MB).unwrap();
+    }
+
+    #[test]
+    fn rebuild_tick() {
+        let mut node = node_with_nexus(&["bdev:///r1"]);
+        assert_eq!(nexus(&node).state, STATE_ONLINE);
+
+        let child = node
+            .add_child(rpc::AddChildNexusRequest {
+                uuid: "nexus".to_string(),
+                uri: "bdev:///r2".to_string(),
+                norebuild: false,
+            })
+            .unwrap();
+        assert_eq!(child.state, STATE_DEGRADED);
+        assert_eq!(child.rebuild_progress, 0);
+        assert_eq!(nexus(&node).state, STATE_DEGRADED);
+        assert_eq!(nexus(&node).rebuilds, 1);
+
+        for tick in 1 .. 100 / REBUILD_STEP {
+            node.tick();
+            let nexus = nexus(&node);
+            assert_eq!(nexus.state, STATE_DEGRADED);
+            assert_eq!(nexus.children[1].rebuild_progress, tick * REBUILD_STEP);
+        }
+        node.tick();
+        let nexus = nexus(&node);
+        assert_eq!(nexus.state, STATE_ONLINE);
+        assert_eq!(nexus.rebuilds, 0);
+        assert_eq!(nexus.children[1].state, STATE_ONLINE);
+        assert_eq!(nexus.children[1].rebuild_progress, NO_REBUILD);
+    }
+
+    #[test]
+    fn fault_child() {
+        let mut node = node_with_nexus(&["bdev:///r1"]);
+        node.add_child(rpc::AddChildNexusRequest {
+            uuid: "nexus".to_string(),
+            uri: "bdev:///r2".to_string(),
+            norebuild: false,
+        })
+        .unwrap();
+        node.tick();
+
+        // faulting the rebuilding child stops its rebuild
+        node.fault_child("nexus", "bdev:///r2").unwrap();
+        let faulted = nexus(&node);
+        assert_eq!(faulted.state, STATE_DEGRADED);
+        assert_eq!(faulted.rebuilds, 0);
+        assert_eq!(faulted.children[1].state, STATE_FAULTED);
+        assert_eq!(faulted.children[1].rebuild_progress, NO_REBUILD);
+        node.tick();
+        assert_eq!(nexus(&node), faulted);
+
+        // the nexus is faulted once all of its children are
+        node.fault_child("nexus", "bdev:///r1").unwrap();
+        assert_eq!(nexus(&node).state, STATE_FAULTED);
+
+        let error = node.fault_child("nexus", "bdev:///r3").unwrap_err();
+        assert_eq!(error.code(), tonic::Code::NotFound);
+    }
+}
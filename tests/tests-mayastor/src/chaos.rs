@@ -0,0 +1,404 @@
+//! Fault injection into the cluster and declarative chaos scenarios which apply the faults over
+//! time while checking the invariants of the control-plane, eg:
+//! ```ignore
+//! Scenario::new("target node restarts")
+//!     .with_fault(Duration::from_secs(1), Fault::KillNode(0))
+//!     .with_fault(Duration::from_secs(10), Fault::StartNode(0))
+//!     .with_invariant(Invariant::VolumeDataNodes { volume, nodes })
+//!     .with_duration(Duration::from_secs(20))
+//!     .with_eventual_invariant(Invariant::ReplicaCount)
+//!     .with_eventual_invariant(Invariant::NoOrphanedReplicas)
+//!     .run(&cluster)
+//!     .await
+//!     .unwrap();
+//! ```
+//! Note: the network faults are injected with `nsenter`, `iptables` and `tc` from the host, which
+//! requires the tests to run as root.
+
+use crate::Cluster;
+use common_lib::types::v0::message_bus::NodeId;
+use openapi::{apis::Uuid, models};
+use rpc::mayastor::FaultNexusChildRequest;
+use std::{collections::HashSet, time::Duration};
+
+/// Control-plane service which a node may be partitioned from
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Service {
+    /// The persistent store
+    Etcd,
+    /// The message bus
+    Nats,
+}
+impl Service {
+    /// Name of the service container
+    fn container(&self) -> &'static str {
+        match self {
+            Service::Etcd => "etcd",
+            Service::Nats => "nats",
+        }
+    }
+}
+
+/// Fault which is injected into the cluster
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Pause the mayastor container of the node `index`
+    PauseNode(u32),
+    /// Resume the paused mayastor container of the node `index`
+    ThawNode(u32),
+    /// Kill the mayastor container of the node `index`
+    KillNode(u32),
+    /// Start the killed mayastor container of the node `index`
+    StartNode(u32),
+    /// Drop all traffic between the node `index` and the service
+    Partition(u32, Service),
+    /// Restore the traffic between the node `index` and the service
+    Heal(u32, Service),
+    /// Delay all the store traffic by the given latency, or stop delaying it with a zero latency
+    StoreLatency(Duration),
+    /// Fault the child `child` (index) of the target nexus of the volume
+    FaultChild { volume: Uuid, child: usize },
+}
+
+/// Invariant of the control-plane which is checked while the faults are injected
+#[derive(Debug, Clone)]
+pub enum Invariant {
+    /// Every volume has as many replicas as its spec requests
+    ReplicaCount,
+    /// The replicas of the volume only live on the given nodes
+    VolumeDataNodes { volume: Uuid, nodes: Vec<NodeId> },
+    /// Every replica has a spec, and every managed replica is owned by an existing volume or
+    /// nexus
+    NoOrphanedReplicas,
+    /// The volume has the given status
+    VolumeStatus {
+        volume: Uuid,
+        status: models::VolumeStatus,
+    },
+}
+
+impl Invariant {
+    /// Check the invariant against the current state of the cluster
+    pub async fn check(&self, cluster: &Cluster) -> Result<(), String> {
+        let client = cluster.rest_v00();
+        match self {
+            Invariant::ReplicaCount => {
+                let volumes = client
+                    .volumes_api()
+                    .get_volumes()
+                    .await
+                    .map_err(rest_error)?;
+                for volume in volumes {
+                    let replicas = volume.state.replica_topology.len();
+                    if replicas != volume.spec.num_replicas as usize {
+                        return Err(format!(
+                            "Volume '{}' has {} replicas instead of {}",
+                            volume.spec.uuid, replicas, volume.spec.num_replicas
+                        ));
+                    }
+                }
+            }
+            Invariant::VolumeDataNodes { volume, nodes } => {
+                let volume = client
+                    .volumes_api()
+                    .get_volume(volume)
+                    .await
+                    .map_err(rest_error)?;
+                let unexpected = volume
+                    .state
+                    .replica_topology
+                    .values()
+                    .filter_map(|r| r.node.as_ref())
+                    .find(|node| !nodes.iter().any(|n| n.as_str() == node.as_str()));
+                if let Some(node) = unexpected {
+                    return Err(format!(
+                        "Volume '{}' has a replica on the unexpected node '{}'",
+                        volume.spec.uuid, node
+                    ));
+                }
+            }
+            Invariant::NoOrphanedReplicas => {
+                let specs = client.specs_api().get_specs().await.map_err(rest_error)?;
+                let replicas = client
+                    .replicas_api()
+                    .get_replicas()
+                    .await
+                    .map_err(rest_error)?;
+                let volumes = specs.volumes.iter().map(|v| v.uuid).collect::<HashSet<_>>();
+                let nexuses = specs.nexuses.iter().map(|n| n.uuid).collect::<HashSet<_>>();
+                if let Some(replica) = replicas
+                    .iter()
+                    .find(|r| !specs.replicas.iter().any(|s| s.uuid == r.uuid))
+                {
+                    return Err(format!(
+                        "Replica '{}' on node '{}' has no spec",
+                        replica.uuid, replica.node
+                    ));
+                }
+                let orphan = specs.replicas.iter().filter(|r| r.managed).find(|r| {
+                    let volume = r.owners.volume.as_ref().filter(|v| volumes.contains(v));
+                    let nexus = r.owners.nexuses.iter().find(|n| nexuses.contains(n));
+                    volume.is_none() && nexus.is_none()
+                });
+                if let Some(replica) = orphan {
+                    return Err(format!(
+                        "Replica '{}' has no owner: {:?}",
+                        replica.uuid, replica.owners
+                    ));
+                }
+            }
+            Invariant::VolumeStatus { volume, status } => {
+                let volume = client
+                    .volumes_api()
+                    .get_volume(volume)
+                    .await
+                    .map_err(rest_error)?;
+                if &volume.state.status != status {
+                    return Err(format!(
+                        "Volume '{}' is {:?} instead of {:?}",
+                        volume.spec.uuid, volume.state.status, status
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn rest_error<E: std::fmt::Debug>(error: E) -> String {
+    format!("Rest request failed: {:?}", error)
+}
+
+/// Declarative chaos scenario which injects faults into the cluster at given times while
+/// checking invariants:
+/// - the invariants are checked throughout the scenario, until all faults have been injected and
+/// the scenario has lasted for its minimum duration
+/// - the eventual invariants (and the invariants) must then all hold within the settle timeout
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    name: String,
+    faults: Vec<(Duration, Fault)>,
+    invariants: Vec<Invariant>,
+    eventual_invariants: Vec<Invariant>,
+    duration: Duration,
+    check_period: Duration,
+    settle_timeout: Duration,
+}
+
+impl Scenario {
+    /// New scenario with the given name, without any fault
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            faults: vec![],
+            invariants: vec![],
+            eventual_invariants: vec![],
+            duration: Duration::ZERO,
+            check_period: Duration::from_millis(500),
+            settle_timeout: Duration::from_secs(60),
+        }
+    }
+    /// Inject the `fault` `at` the given time from the start of the scenario
+    #[must_use]
+    pub fn with_fault(mut self, at: Duration, fault: Fault) -> Self {
+        self.faults.push((at, fault));
+        self
+    }
+    /// Check the `invariant` throughout the scenario
+    #[must_use]
+    pub fn with_invariant(mut self, invariant: Invariant) -> Self {
+        self.invariants.push(invariant);
+        self
+    }
+    /// Check that the `invariant` holds once all the faults have been injected, within the
+    /// settle timeout
+    #[must_use]
+    pub fn with_eventual_invariant(mut self, invariant: Invariant) -> Self {
+        self.eventual_invariants.push(invariant);
+        self
+    }
+    /// Carry on checking the invariants for at least `duration` before settling, eg: to give the
+    /// control-plane the time to notice the last fault
+    #[must_use]
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+    /// Check the invariants with this period
+    #[must_use]
+    pub fn with_check_period(mut self, period: Duration) -> Self {
+        self.check_period = period;
+        self
+    }
+    /// Wait for the invariants to hold for up to `timeout` once all faults have been injected
+    #[must_use]
+    pub fn with_settle_timeout(mut self, timeout: Duration) -> Self {
+        self.settle_timeout = timeout;
+        self
+    }
+
+    /// Run the scenario against the `cluster`
+    pub async fn run(&self, cluster: &Cluster) -> Result<(), String> {
+        let mut faults = self.faults.clone();
+        faults.sort_by_key(|(at, _)| *at);
+        let mut faults = faults.into_iter().peekable();
+
+        let start = std::time::Instant::now();
+        let error =
+            |error: String| format!("Scenario '{}' @{:?}: {}", self.name, start.elapsed(), error);
+        while faults.peek().is_some() || start.elapsed() < self.duration {
+            while let Some((_, fault)) = faults.next_if(|(at, _)| *at <= start.elapsed()) {
+                tracing::info!(scenario = %self.name, "Injecting fault {:?}", fault);
+                cluster.inject(&fault).await.map_err(error)?;
+            }
+            for invariant in &self.invariants {
+                invariant.check(cluster).await.map_err(error)?;
+            }
+            tokio::time::sleep(self.check_period).await;
+        }
+
+        let settled = std::time::Instant::now();
+        loop {
+            let mut result = Ok(());
+            for invariant in self.invariants.iter().chain(&self.eventual_invariants) {
+                result = invariant.check(cluster).await;
+                if result.is_err() {
+                    break;
+                }
+            }
+            match result {
+                Ok(()) => return Ok(()),
+                Err(reason) if settled.elapsed() > self.settle_timeout => {
+                    return Err(error(format!("Failed to settle: {}", reason)))
+                }
+                Err(_) => tokio::time::sleep(self.check_period).await,
+            }
+        }
+    }
+}
+
+impl Cluster {
+    /// Inject the `fault` into the cluster
+    pub async fn inject(&self, fault: &Fault) -> Result<(), String> {
+        let composer = &self.composer;
+        match fault {
+            Fault::PauseNode(index) => composer.pause(self.node(*index).as_str()).await,
+            Fault::ThawNode(index) => composer.thaw(self.node(*index).as_str()).await,
+            Fault::KillNode(index) => composer.kill(self.node(*index).as_str()).await,
+            Fault::StartNode(index) => composer.start(self.node(*index).as_str()).await,
+            Fault::Partition(index, service) => {
+                return self.partition(*index, *service, true).await
+            }
+            Fault::Heal(index, service) => return self.partition(*index, *service, false).await,
+            Fault::StoreLatency(latency) => return self.store_latency(*latency).await,
+            Fault::FaultChild { volume, child } => {
+                return self.fault_volume_child(volume, *child).await
+            }
+        }
+        .map_err(|error| format!("Failed to inject {:?}: {}", fault, error))
+    }
+
+    /// Drop, or restore, all traffic between the node `index` and the `service`
+    pub async fn partition(&self, index: u32, service: Service, drop: bool) -> Result<(), String> {
+        let node = self.node(index);
+        let ip = self.composer.container_ip(service.container());
+        let action = if drop { "-I" } else { "-D" };
+        self.nsenter(
+            node.as_str(),
+            &["iptables", action, "INPUT", "-s", &ip, "-j", "DROP"],
+        )
+        .await?;
+        self.nsenter(
+            node.as_str(),
+            &["iptables", action, "OUTPUT", "-d", &ip, "-j", "DROP"],
+        )
+        .await
+    }
+
+    /// Delay all the store traffic by `latency`, or stop delaying it with a zero latency
+    pub async fn store_latency(&self, latency: Duration) -> Result<(), String> {
+        let etcd = Service::Etcd.container();
+        // there's no netem qdisc to delete the first time around
+        let _ = self
+            .nsenter(etcd, &["tc", "qdisc", "del", "dev", "eth0", "root"])
+            .await;
+        if latency.is_zero() {
+            return Ok(());
+        }
+        let delay = format!("{}ms", latency.as_millis());
+        self.nsenter(
+            etcd,
+            &[
+                "tc", "qdisc", "add", "dev", "eth0", "root", "netem", "delay", &delay,
+            ],
+        )
+        .await
+    }
+
+    /// Fault the child `uri` of the nexus `nexus` on the node `node`
+    pub async fn fault_nexus_child(
+        &self,
+        node: &NodeId,
+        nexus: &Uuid,
+        uri: &str,
+    ) -> Result<(), String> {
+        let mut handle = self.grpc_handle(node.as_str()).await?;
+        handle
+            .mayastor
+            .fault_nexus_child(FaultNexusChildRequest {
+                uuid: nexus.to_string(),
+                uri: uri.to_string(),
+            })
+            .await
+            .map_err(|error| format!("Failed to fault child '{}': {}", uri, error))?;
+        Ok(())
+    }
+
+    /// Fault the child `child` (index) of the target nexus of the volume
+    pub async fn fault_volume_child(&self, volume: &Uuid, child: usize) -> Result<(), String> {
+        let target = self
+            .rest_v00()
+            .volumes_api()
+            .get_volume(volume)
+            .await
+            .map_err(rest_error)?
+            .state
+            .target
+            .ok_or_else(|| format!("Volume '{}' is not published", volume))?;
+        let uri = target
+            .children
+            .get(child)
+            .map(|c| c.uri.clone())
+            .ok_or_else(|| format!("Volume '{}' has no child {}", volume, child))?;
+        self.fault_nexus_child(&target.node.clone().into(), &target.uuid, &uri)
+            .await
+    }
+
+    /// Run the `command` in the network namespace of the container `name`
+    async fn nsenter(&self, name: &str, command: &[&str]) -> Result<(), String> {
+        let pid = self
+            .composer
+            .inspect(name)
+            .await
+            .map_err(|error| format!("Failed to inspect container '{}': {}", name, error))?
+            .state
+            .and_then(|state| state.pid)
+            .filter(|pid| *pid > 0)
+            .ok_or_else(|| format!("Container '{}' is not running", name))?;
+        let status = tokio::process::Command::new("nsenter")
+            .args(&["--target", &pid.to_string(), "--net"])
+            .args(command)
+            .status()
+            .await
+            .map_err(|error| format!("Failed to run nsenter: {}", error))?;
+        if !status.success() {
+            return Err(format!(
+                "'{}' failed in container '{}': {}",
+                command.join(" "),
+                name,
+                status
+            ));
+        }
+        Ok(())
+    }
+}
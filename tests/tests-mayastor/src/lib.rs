@@ -1,3 +1,4 @@
+pub mod chaos;
 pub mod rest_client;
 
 use composer::{Builder, ComposeTest};
@@ -24,6 +25,7 @@ use common_lib::{
 };
 pub use etcd_client;
 use etcd_client::DeleteOptions;
+use fake_mayastor::FaultClient;
 use grpc::{client::CoreClient, pool::traits::PoolOperations, replica::traits::ReplicaOperations};
 use rpc::mayastor::RpcHandle;
 use std::{
@@ -99,6 +101,17 @@ impl Cluster {
         }
     }
 
+    /// fault injection client to the simulated mayastor `index`
+    pub async fn fault_client(&self, index: u32) -> Result<FaultClient, String> {
+        if !self.builder.opts.fake_mayastor {
+            return Err("Faults can only be injected into simulated mayastors".to_string());
+        }
+        let endpoint = format!("{}:10124", self.node_ip(index));
+        FaultClient::connect(&endpoint)
+            .await
+            .map_err(|error| format!("Failed to connect to {}: {}", endpoint, error))
+    }
+
     /// restart the core agent
     pub async fn restart_core(&self) {
         self.remove_store_lock(ControlPlaneService::CoreAgent).await;
@@ -557,6 +570,13 @@ impl ClusterBuilder {
         self.opts = self.opts.with_mayastors(count);
         self
     }
+    /// Specify `count` simulated mayastors for the cluster, which serve the mayastor gRPC
+    /// service from memory and allow for injecting faults through `Cluster::fault_client`
+    #[must_use]
+    pub fn with_fake_mayastors(mut self, count: u32) -> Self {
+        self.opts = self.opts.with_mayastors(count).with_fake_mayastor(true);
+        self
+    }
     /// Specify which agents to use
     #[must_use]
     pub fn with_agents(mut self, agents: Vec<&str>) -> Self {
@@ -586,6 +606,25 @@ impl ClusterBuilder {
         self.opts = self.opts.with_reconcile_period(busy, idle);
         self
     }
+    /// With the grace period after which the volume targets are failed over to another node
+    #[must_use]
+    pub fn with_target_failover_grace_period(mut self, period: Duration) -> Self {
+        self.opts = self.opts.with_target_failover_grace_period(period);
+        self
+    }
+    /// With the rebalancing of the volume replicas enabled, moving the replicas off the pools
+    /// with more than `replica_skew` replicas over the least used pool, one every `interval`
+    #[must_use]
+    pub fn with_rebalance(mut self, replica_skew: usize, interval: Duration) -> Self {
+        self.opts = self.opts.with_rebalance(replica_skew, interval);
+        self
+    }
+    /// With the core agent serving its prometheus metrics
+    #[must_use]
+    pub fn with_core_metrics(mut self) -> Self {
+        self.opts = self.opts.with_core_metrics(true);
+        self
+    }
     /// With store operation timeout
     #[must_use]
     pub fn with_store_timeout(mut self, timeout: Duration) -> Self {
@@ -737,6 +776,7 @@ impl ClusterBuilder {
                         id: pool.id(),
                         disks: vec![pool.disk()],
                         labels: None,
+                        overcommit: None,
                     },
                     None,
                 )
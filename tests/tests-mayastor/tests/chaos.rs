@@ -0,0 +1,104 @@
+use common_lib::types::v0::message_bus::NodeId;
+use openapi::{apis::Uuid, models};
+use std::time::Duration;
+use testlib::{
+    chaos::{Fault, Invariant, Scenario, Service},
+    Cluster, ClusterBuilder,
+};
+
+/// Build a cluster of 4 simulated mayastors with the last one cordoned, so that the replicas
+/// of the volumes may only live on the first 3 nodes
+async fn cordoned_cluster() -> (Cluster, Vec<NodeId>) {
+    let cluster = ClusterBuilder::builder()
+        .with_fake_mayastors(4)
+        .with_pools(1)
+        .with_cache_period("1s")
+        .with_node_deadline("2s")
+        .with_reconcile_period(Duration::from_secs(1), Duration::from_secs(1))
+        .build()
+        .await
+        .unwrap();
+    cluster
+        .rest_v00()
+        .nodes_api()
+        .put_node_cordon(cluster.node(3).as_str())
+        .await
+        .unwrap();
+    let nodes = (0 .. 3).map(|i| cluster.node(i)).collect::<Vec<_>>();
+    (cluster, nodes)
+}
+
+/// Create a volume with 2 replicas and publish it on the first node
+async fn published_volume(cluster: &Cluster, uuid: &str) -> Uuid {
+    let volumes_api = cluster.rest_v00().volumes_api();
+    let volume = volumes_api
+        .put_volume(
+            &uuid.parse().unwrap(),
+            models::CreateVolumeBody::new(models::VolumePolicy::new(true), 2, 5242880u64),
+        )
+        .await
+        .unwrap();
+    let volume = volumes_api
+        .put_volume_target(
+            &volume.spec.uuid,
+            cluster.node(0).as_str(),
+            models::VolumeShareProtocol::Nvmf,
+            None,
+        )
+        .await
+        .unwrap();
+    volume.spec.uuid
+}
+
+#[tokio::test]
+async fn chaos_faulted_child_is_replaced() {
+    let (cluster, nodes) = cordoned_cluster().await;
+    let volume = published_volume(&cluster, "bc1c5d12-4e6f-4a5b-8a0d-3b2d6d0c9f41").await;
+
+    Scenario::new("faulted child is replaced")
+        .with_fault(
+            Duration::from_secs(1),
+            Fault::FaultChild { volume, child: 1 },
+        )
+        .with_duration(Duration::from_secs(5))
+        .with_invariant(Invariant::VolumeDataNodes { volume, nodes })
+        .with_eventual_invariant(Invariant::VolumeStatus {
+            volume,
+            status: models::VolumeStatus::Online,
+        })
+        .with_eventual_invariant(Invariant::ReplicaCount)
+        .with_eventual_invariant(Invariant::NoOrphanedReplicas)
+        .run(&cluster)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn chaos_node_and_store_faults() {
+    let (cluster, nodes) = cordoned_cluster().await;
+    let volume = published_volume(&cluster, "5d0e2a7c-91b4-4f3e-a6d8-2c7b9e1f4a63").await;
+
+    Scenario::new("node and store faults")
+        .with_fault(
+            Duration::from_secs(1),
+            Fault::StoreLatency(Duration::from_millis(200)),
+        )
+        .with_fault(Duration::from_secs(2), Fault::PauseNode(2))
+        .with_fault(Duration::from_secs(5), Fault::ThawNode(2))
+        .with_fault(Duration::from_secs(6), Fault::Partition(1, Service::Nats))
+        .with_fault(Duration::from_secs(9), Fault::Heal(1, Service::Nats))
+        .with_fault(Duration::from_secs(10), Fault::KillNode(2))
+        .with_fault(Duration::from_secs(13), Fault::StartNode(2))
+        .with_fault(Duration::from_secs(14), Fault::StoreLatency(Duration::ZERO))
+        .with_duration(Duration::from_secs(20))
+        .with_invariant(Invariant::VolumeDataNodes { volume, nodes })
+        .with_eventual_invariant(Invariant::VolumeStatus {
+            volume,
+            status: models::VolumeStatus::Online,
+        })
+        .with_eventual_invariant(Invariant::ReplicaCount)
+        .with_eventual_invariant(Invariant::NoOrphanedReplicas)
+        .run(&cluster)
+        .await
+        .unwrap();
+}
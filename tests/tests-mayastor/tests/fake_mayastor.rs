@@ -0,0 +1,74 @@
+use common_lib::types::v0::message_bus as v0;
+use grpc::replica::traits::ReplicaOperations;
+use openapi::models;
+use std::time::Duration;
+use testlib::ClusterBuilder;
+
+#[tokio::test]
+async fn fake_mayastor_pool_full() {
+    let cluster = ClusterBuilder::builder()
+        .with_fake_mayastors(1)
+        .with_pools(1)
+        .build()
+        .await
+        .unwrap();
+    let faults = cluster.fault_client(0).await.unwrap();
+    let rep_client = cluster.grpc_client().replica();
+
+    let replica = v0::CreateReplica {
+        node: cluster.node(0),
+        uuid: v0::ReplicaId::new(),
+        pool: cluster.pool(0, 0),
+        size: 5 * 1024 * 1024,
+        thin: false,
+        share: v0::Protocol::None,
+        ..Default::default()
+    };
+    faults
+        .set_pool_full(cluster.pool(0, 0).as_str(), true)
+        .await
+        .unwrap();
+    rep_client
+        .create(&replica, None)
+        .await
+        .expect_err("The pool is full");
+
+    faults
+        .set_pool_full(cluster.pool(0, 0).as_str(), false)
+        .await
+        .unwrap();
+    rep_client.create(&replica, None).await.unwrap();
+}
+
+#[tokio::test]
+async fn fake_mayastor_offline() {
+    let cluster = ClusterBuilder::builder()
+        .with_fake_mayastors(2)
+        .with_node_deadline("2s")
+        .build()
+        .await
+        .unwrap();
+    let faults = cluster.fault_client(1).await.unwrap();
+
+    faults.set_offline(true).await.unwrap();
+    wait_node_status(&cluster, 1, models::NodeStatus::Offline).await;
+
+    faults.set_offline(false).await.unwrap();
+    wait_node_status(&cluster, 1, models::NodeStatus::Online).await;
+}
+
+/// Wait up to 30 seconds for the node `index` to reach the `status`
+async fn wait_node_status(cluster: &testlib::Cluster, index: u32, status: models::NodeStatus) {
+    let nodes_api = cluster.rest_v00().nodes_api();
+    for _ in 0 .. 60 {
+        let node = nodes_api
+            .get_node(cluster.node(index).as_str())
+            .await
+            .unwrap();
+        if node.state.map(|s| s.status) == Some(status) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("Node {} did not become {:?}", cluster.node(index), status);
+}
@@ -93,6 +93,7 @@ async fn create_pool_idempotent() {
                 id: cluster.pool(0, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -106,6 +107,7 @@ async fn create_pool_idempotent() {
                 id: cluster.pool(0, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -130,6 +132,7 @@ async fn create_pool_idempotent_same_disk_different_query() {
                 id: cluster.pool(0, 0),
                 disks: vec!["malloc:///disk?size_mb=100&blk_size=512".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -143,6 +146,7 @@ async fn create_pool_idempotent_same_disk_different_query() {
                 id: cluster.pool(0, 0),
                 disks: vec!["malloc:///disk?size_mb=200&blk_size=4096".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -165,6 +169,7 @@ async fn create_pool_idempotent_different_nvmf_host() {
                 id: cluster.pool(1, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -178,6 +183,7 @@ async fn create_pool_idempotent_different_nvmf_host() {
                 id: cluster.pool(2, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -191,6 +197,7 @@ async fn create_pool_idempotent_different_nvmf_host() {
                 id: cluster.pool(2, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
@@ -204,6 +211,7 @@ async fn create_pool_idempotent_different_nvmf_host() {
                 id: cluster.pool(2, 0),
                 disks: vec!["malloc:///disk?size_mb=100".into()],
                 labels: None,
+                overcommit: None,
             },
             None,
         )
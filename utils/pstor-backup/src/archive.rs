@@ -0,0 +1,325 @@
+use anyhow::anyhow;
+use common_lib::types::v0::store::{
+    definitions::{key_prefix, ObjectKey, StorableObject, StorableObjectType, Store, StoreError},
+    registry::{CoreRegistryConfig, NodeRegistration},
+    schema::{object_value, STORE_SCHEMA_VERSION},
+    volume::VolumeSpec,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use strum::IntoEnumIterator;
+
+/// Version of the archive format, to be bumped on incompatible changes to the `Archive`.
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Archive of the objects of the persistent store.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct Archive {
+    /// Version of the archive format.
+    archive_version: u32,
+    /// Schema version of the control-plane which created the archive.
+    /// The objects themselves may be of an older schema version, as recorded in their envelope.
+    schema_version: u32,
+    /// The objects, as found in the store.
+    objects: Vec<ArchivedObject>,
+}
+
+/// An object of the persistent store, keyed independently of the store namespace so that it can
+/// be restored into another namespace.
+#[derive(Serialize, Deserialize, Debug)]
+struct ArchivedObject {
+    /// Type of the object.
+    obj_type: StorableObjectType,
+    /// Identifier of the object within its type, eg: the uuid of a volume.
+    id: String,
+    /// The stored value, within its versioned envelope if any.
+    value: Value,
+}
+
+impl ArchivedObject {
+    /// Key of the object in the store.
+    fn key(&self) -> String {
+        object_key(self.obj_type, &self.id)
+    }
+}
+
+/// Key of the object of `obj_type` with the given `id` in the store.
+fn object_key(obj_type: StorableObjectType, id: &str) -> String {
+    match obj_type {
+        // no key prefix as it's written by mayastor
+        StorableObjectType::NexusInfo => id.to_string(),
+        _ => format!("{}/{}", key_prefix(obj_type), id),
+    }
+}
+
+/// Key prefix of the objects of `obj_type`, if they're found by their prefix.
+/// The trailing slash keeps out the types which share the prefix, eg: Node and NodeSpec.
+fn object_prefix(obj_type: StorableObjectType) -> Option<String> {
+    match obj_type {
+        StorableObjectType::NexusInfo => None,
+        _ => Some(format!("{}/", key_prefix(obj_type))),
+    }
+}
+
+/// The lease of the running core agent is not archived, as it's not valid for another store.
+fn is_lease(obj_type: StorableObjectType) -> bool {
+    matches!(
+        obj_type,
+        StorableObjectType::StoreLeaseLock | StorableObjectType::StoreLeaseOwner
+    )
+}
+
+/// Make sure that the core agent is not running, ie: that it doesn't hold the lease lock of the
+/// `store`, as it may otherwise change the objects of the store while they're being archived or
+/// restored.
+async fn ensure_stopped<S: Store>(store: &mut S, operation: &str) -> anyhow::Result<()> {
+    if let Some(prefix) = object_prefix(StorableObjectType::StoreLeaseLock) {
+        if !store.get_values_prefix(&prefix).await?.is_empty() {
+            return Err(anyhow!(
+                "The core agent must be stopped before {} the store",
+                operation
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Archive {
+    /// Archive all the objects of the `store`.
+    /// The objects are read type by type, so the core agent must be stopped for the archive to
+    /// be consistent.
+    pub(crate) async fn backup<S: Store>(store: &mut S) -> anyhow::Result<Self> {
+        ensure_stopped(store, "backing up").await?;
+        let mut objects = vec![];
+        for obj_type in StorableObjectType::iter().filter(|t| !is_lease(*t)) {
+            let prefix = match object_prefix(obj_type) {
+                Some(prefix) => prefix,
+                None => continue,
+            };
+            for (key, value) in store.get_values_prefix(&prefix).await? {
+                let id = key.trim_start_matches(&prefix).to_string();
+                objects.push(ArchivedObject {
+                    obj_type,
+                    id,
+                    value,
+                });
+            }
+        }
+
+        // the nexus info has no prefix, so it's looked up through the nexus ids
+        for id in Self::nexus_info_ids(&objects)? {
+            match store.get_kv(&id).await {
+                Ok(value) => objects.push(ArchivedObject {
+                    obj_type: StorableObjectType::NexusInfo,
+                    id,
+                    value,
+                }),
+                Err(StoreError::MissingEntry { .. }) => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(Self {
+            archive_version: ARCHIVE_VERSION,
+            schema_version: STORE_SCHEMA_VERSION,
+            objects,
+        })
+    }
+
+    /// Ids of the nexuses which may have a `NexusInfo`: the nexuses with a spec and the last
+    /// nexus of each volume.
+    fn nexus_info_ids(objects: &[ArchivedObject]) -> anyhow::Result<BTreeSet<String>> {
+        let mut ids = BTreeSet::new();
+        for object in objects {
+            match object.obj_type {
+                StorableObjectType::NexusSpec => {
+                    ids.insert(object.id.clone());
+                }
+                StorableObjectType::VolumeSpec => {
+                    let value = object_value(object.obj_type, object.value.clone())?;
+                    let volume: VolumeSpec = serde_json::from_value(value)?;
+                    if let Some(nexus) = volume.last_nexus_id {
+                        ids.insert(nexus.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Restore all the objects into the `store`, which must be empty.
+    pub(crate) async fn restore<S: Store>(&self, store: &mut S) -> anyhow::Result<()> {
+        if self.archive_version != ARCHIVE_VERSION {
+            return Err(anyhow!(
+                "Unsupported archive version {}, expected {}",
+                self.archive_version,
+                ARCHIVE_VERSION
+            ));
+        }
+        if self.schema_version > STORE_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "The archive was created with schema version {} which is newer than the supported version {}",
+                self.schema_version,
+                STORE_SCHEMA_VERSION
+            ));
+        }
+        self.ensure_empty(store).await?;
+
+        // without the archived configuration, the schema version is unknown so the core agent
+        // has to migrate the restored objects again
+        if !self
+            .objects
+            .iter()
+            .any(|o| o.obj_type == StorableObjectType::CoreRegistryConfig)
+        {
+            let config = CoreRegistryConfig::new(NodeRegistration::Automatic);
+            match store.delete_kv(&config.key().key()).await {
+                Ok(()) | Err(StoreError::MissingEntry { .. }) => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        for object in &self.objects {
+            store.put_kv(&object.key(), &object.value).await?;
+        }
+        Ok(())
+    }
+
+    /// Make sure that restoring the archive does not overwrite any resource in the `store`.
+    /// The lease owner and the registry configuration are written by the core agent on startup
+    /// so they may already exist, in which case the latter is overwritten.
+    async fn ensure_empty<S: Store>(&self, store: &mut S) -> anyhow::Result<()> {
+        ensure_stopped(store, "restoring").await?;
+        for obj_type in StorableObjectType::iter().filter(|t| !is_lease(*t)) {
+            let prefix = match obj_type {
+                StorableObjectType::CoreRegistryConfig => continue,
+                _ => match object_prefix(obj_type) {
+                    Some(prefix) => prefix,
+                    None => continue,
+                },
+            };
+            if !store.get_values_prefix(&prefix).await?.is_empty() {
+                return Err(anyhow!(
+                    "The store is not empty, it already has {} objects",
+                    obj_type
+                ));
+            }
+        }
+        for object in &self.objects {
+            if object.obj_type == StorableObjectType::NexusInfo {
+                match store.get_kv(&object.key()).await {
+                    Err(StoreError::MissingEntry { .. }) => {}
+                    Err(error) => return Err(error.into()),
+                    Ok(_) => {
+                        return Err(anyhow!(
+                            "The store is not empty, it already has the NexusInfo of {}",
+                            object.id
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of archived objects of each type.
+    pub(crate) fn summary(&self) -> BTreeMap<String, usize> {
+        let mut summary = BTreeMap::new();
+        for object in &self.objects {
+            *summary.entry(object.obj_type.to_string()).or_default() += 1;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_lib::{
+        store::memory::MemoryStore,
+        types::v0::{
+            message_bus::{NexusId, VolumeId},
+            store::node::{NodeLabels, NodeSpec},
+        },
+    };
+
+    /// All the values of the `store` which are archived, by key.
+    async fn archived_values(store: &mut MemoryStore) -> BTreeMap<String, Value> {
+        let mut values = BTreeMap::new();
+        for obj_type in StorableObjectType::iter().filter(|t| !is_lease(*t)) {
+            if let Some(prefix) = object_prefix(obj_type) {
+                values.extend(store.get_values_prefix(&prefix).await.unwrap());
+            }
+        }
+        values
+    }
+
+    #[tokio::test]
+    async fn backup_restore() {
+        let mut store = MemoryStore::new();
+        let config = CoreRegistryConfig::new(NodeRegistration::Manual);
+        store.put_obj(&config).await.unwrap();
+        let node = NodeSpec::new("node-1".into(), "10.1.0.2:10124".into(), NodeLabels::new());
+        store.put_obj(&node).await.unwrap();
+        let nexus = NexusId::new();
+        let volume = VolumeSpec {
+            uuid: VolumeId::new(),
+            last_nexus_id: Some(nexus.clone()),
+            ..Default::default()
+        };
+        store.put_obj(&volume).await.unwrap();
+        let nexus_info = serde_json::json!({ "clean_shutdown": true, "children": [] });
+        store.put_kv(&nexus.to_string(), &nexus_info).await.unwrap();
+
+        // the archive is written to and read from a file
+        let backup = Archive::backup(&mut store).await.unwrap();
+        let backup: Archive =
+            serde_json::from_str(&serde_json::to_string(&backup).unwrap()).unwrap();
+        let summary = backup.summary();
+        assert_eq!(summary.get("CoreRegistryConfig"), Some(&1));
+        assert_eq!(summary.get("NodeSpec"), Some(&1));
+        assert_eq!(summary.get("VolumeSpec"), Some(&1));
+        assert_eq!(summary.get("NexusInfo"), Some(&1));
+
+        let mut restored = MemoryStore::new();
+        backup.restore(&mut restored).await.unwrap();
+        assert_eq!(
+            archived_values(&mut restored).await,
+            archived_values(&mut store).await
+        );
+        assert_eq!(
+            restored.get_kv(&nexus.to_string()).await.unwrap(),
+            nexus_info
+        );
+        let restored_volume: VolumeSpec = restored.get_obj(&volume.key()).await.unwrap();
+        assert_eq!(restored_volume, volume);
+
+        backup
+            .restore(&mut restored)
+            .await
+            .expect_err("The store is no longer empty");
+    }
+
+    #[tokio::test]
+    async fn core_agent_running() {
+        let mut store = MemoryStore::new();
+        let lock = format!(
+            "{}/CoreAgent",
+            key_prefix(StorableObjectType::StoreLeaseLock)
+        );
+        store
+            .put_kv(&lock, &serde_json::json!("lease"))
+            .await
+            .unwrap();
+
+        Archive::backup(&mut store)
+            .await
+            .expect_err("The core agent must be stopped");
+        let backup = Archive::backup(&mut MemoryStore::new()).await.unwrap();
+        backup
+            .restore(&mut store)
+            .await
+            .expect_err("The core agent must be stopped");
+    }
+}
@@ -0,0 +1,240 @@
+use common_lib::types::v0::store::{
+    definitions::{key_prefix, StorableObjectType, Store},
+    nexus::NexusSpec,
+    pool::PoolSpec,
+    replica::ReplicaSpec,
+    schema::object_value,
+    volume::VolumeSpec,
+    SpecStatus,
+};
+use openapi::{clients::tower::direct::ApiClient, models};
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+
+/// A mismatch between a spec in the persistent store and the resources reported by the nodes.
+pub(crate) struct Inconsistency {
+    /// Kind of the resource, eg: Replica.
+    pub(crate) kind: &'static str,
+    /// Id of the resource.
+    pub(crate) id: String,
+    /// What doesn't match.
+    pub(crate) issue: String,
+}
+
+impl Inconsistency {
+    fn new(kind: &'static str, id: impl ToString, issue: String) -> Self {
+        Self {
+            kind,
+            id: id.to_string(),
+            issue,
+        }
+    }
+}
+
+/// Load all the specs of `obj_type` from the `store`, upgraded to the current schema version.
+async fn load_specs<S: Store, T: DeserializeOwned>(
+    store: &mut S,
+    obj_type: StorableObjectType,
+) -> anyhow::Result<Vec<T>> {
+    let prefix = format!("{}/", key_prefix(obj_type));
+    let mut specs = vec![];
+    for (_, value) in store.get_values_prefix(&prefix).await? {
+        specs.push(serde_json::from_value(object_value(obj_type, value)?)?);
+    }
+    Ok(specs)
+}
+
+/// Compare the specs in the `store`, eg: once restored, with the pools, replicas and nexuses
+/// which the registry of the core agent fetches from the nodes, through the rest `client`.
+/// The resources of the nodes which are not online are not compared, as they're not known.
+pub(crate) async fn check<S: Store>(
+    store: &mut S,
+    client: &ApiClient,
+) -> anyhow::Result<Vec<Inconsistency>> {
+    let pools: Vec<PoolSpec> = load_specs(store, StorableObjectType::PoolSpec).await?;
+    let replicas: Vec<ReplicaSpec> = load_specs(store, StorableObjectType::ReplicaSpec).await?;
+    let nexuses: Vec<NexusSpec> = load_specs(store, StorableObjectType::NexusSpec).await?;
+    let volumes: Vec<VolumeSpec> = load_specs(store, StorableObjectType::VolumeSpec).await?;
+
+    let online = client
+        .nodes_api()
+        .get_nodes()
+        .await?
+        .into_iter()
+        .filter(|node| node.state.as_ref().map(|s| &s.status) == Some(&models::NodeStatus::Online))
+        .map(|node| node.id)
+        .collect::<HashSet<_>>();
+    let node_pools = client.pools_api().get_pools().await?;
+    let node_replicas = client.replicas_api().get_replicas().await?;
+    let node_nexuses = client.nexuses_api().get_nexuses().await?;
+
+    let mut issues = vec![];
+    let mut offline = HashSet::new();
+    let mut is_online = |node: &str, issues: &mut Vec<Inconsistency>| {
+        if online.contains(node) {
+            return true;
+        }
+        if offline.insert(node.to_string()) {
+            issues.push(Inconsistency::new(
+                "Node",
+                node,
+                "The node is not online so its resources are not checked".to_string(),
+            ));
+        }
+        false
+    };
+
+    // node of each pool, as per its spec
+    let pool_nodes = pools
+        .iter()
+        .map(|pool| (pool.id.to_string(), pool.node.to_string()))
+        .collect::<HashMap<_, _>>();
+
+    for pool in pools
+        .iter()
+        .filter(|p| matches!(p.status, SpecStatus::Created(_)))
+    {
+        if !is_online(pool.node.as_str(), &mut issues) {
+            continue;
+        }
+        let reported = node_pools.iter().find(|p| p.id == pool.id.as_str());
+        match reported.and_then(|p| p.state.as_ref()) {
+            Some(state) if state.node == pool.node.as_str() => {}
+            Some(state) => issues.push(Inconsistency::new(
+                "Pool",
+                &pool.id,
+                format!(
+                    "The pool is reported by node '{}' instead of '{}'",
+                    state.node, pool.node
+                ),
+            )),
+            None => issues.push(Inconsistency::new(
+                "Pool",
+                &pool.id,
+                format!("The pool is not reported by node '{}'", pool.node),
+            )),
+        }
+    }
+    for pool in &node_pools {
+        if pool.state.is_some() && !pool_nodes.contains_key(&pool.id) {
+            issues.push(Inconsistency::new(
+                "Pool",
+                &pool.id,
+                "The pool reported by the node has no spec".to_string(),
+            ));
+        }
+    }
+
+    let mut reported_replicas = HashSet::new();
+    for replica in replicas
+        .iter()
+        .filter(|r| matches!(r.status, SpecStatus::Created(_)))
+    {
+        let node = match pool_nodes.get(replica.pool.as_str()) {
+            Some(node) => node,
+            None => {
+                issues.push(Inconsistency::new(
+                    "Replica",
+                    &replica.uuid,
+                    format!("The pool '{}' of the replica has no spec", replica.pool),
+                ));
+                continue;
+            }
+        };
+        if !is_online(node, &mut issues) {
+            continue;
+        }
+        let reported = node_replicas
+            .iter()
+            .find(|r| r.uuid.to_string() == replica.uuid.to_string());
+        match reported {
+            Some(reported) if reported.pool == replica.pool.as_str() => {
+                reported_replicas.insert(replica.uuid.to_string());
+            }
+            Some(reported) => issues.push(Inconsistency::new(
+                "Replica",
+                &replica.uuid,
+                format!(
+                    "The replica is reported on pool '{}' instead of '{}'",
+                    reported.pool, replica.pool
+                ),
+            )),
+            None => issues.push(Inconsistency::new(
+                "Replica",
+                &replica.uuid,
+                format!(
+                    "The replica is not reported by node '{}' on pool '{}'",
+                    node, replica.pool
+                ),
+            )),
+        }
+    }
+    for replica in &node_replicas {
+        let uuid = replica.uuid.to_string();
+        if !replicas.iter().any(|r| r.uuid.to_string() == uuid) {
+            issues.push(Inconsistency::new(
+                "Replica",
+                uuid,
+                format!(
+                    "The replica reported by node '{}' on pool '{}' has no spec",
+                    replica.node, replica.pool
+                ),
+            ));
+        }
+    }
+
+    for nexus in nexuses
+        .iter()
+        .filter(|n| matches!(n.spec_status, SpecStatus::Created(_)))
+    {
+        if !is_online(nexus.node.as_str(), &mut issues) {
+            continue;
+        }
+        let uuid = nexus.uuid.to_string();
+        if !node_nexuses
+            .iter()
+            .any(|n| n.uuid.to_string() == uuid && n.node == nexus.node.as_str())
+        {
+            issues.push(Inconsistency::new(
+                "Nexus",
+                uuid,
+                format!("The nexus is not reported by node '{}'", nexus.node),
+            ));
+        }
+    }
+
+    for volume in volumes
+        .iter()
+        .filter(|v| matches!(v.status, SpecStatus::Created(_)))
+    {
+        let owned = replicas
+            .iter()
+            .filter(|r| r.owners.owned_by(&volume.uuid))
+            .collect::<Vec<_>>();
+        let reported = owned
+            .iter()
+            .filter(|r| reported_replicas.contains(&r.uuid.to_string()))
+            .count();
+        if reported == 0 {
+            issues.push(Inconsistency::new(
+                "Volume",
+                &volume.uuid,
+                format!(
+                    "None of the {} replicas of the volume is reported by the nodes",
+                    owned.len()
+                ),
+            ));
+        } else if reported < volume.num_replicas as usize {
+            issues.push(Inconsistency::new(
+                "Volume",
+                &volume.uuid,
+                format!(
+                    "Only {} of the {} replicas of the volume are reported by the nodes",
+                    reported, volume.num_replicas
+                ),
+            ));
+        }
+    }
+
+    Ok(issues)
+}
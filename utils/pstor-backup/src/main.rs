@@ -0,0 +1,128 @@
+mod archive;
+mod check;
+
+use crate::archive::Archive;
+use anyhow::anyhow;
+use common_lib::store::etcd::Etcd;
+use openapi::{
+    apis::Url,
+    clients::tower::{direct::ApiClient, Configuration},
+};
+use prettytable::{Cell, Row, Table};
+use std::{fs::File, path::PathBuf, time::Duration};
+use structopt::StructOpt;
+
+#[derive(structopt::StructOpt, Debug)]
+#[structopt(version = utils::package_info!())]
+struct CliArgs {
+    /// The persistent store (etcd) endpoint.
+    #[structopt(short, long, default_value = "http://localhost:2379")]
+    store: String,
+
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(structopt::StructOpt, Debug)]
+enum Command {
+    /// Archive all the objects of the persistent store into a file.
+    /// The core agent must be stopped while backing up.
+    Backup {
+        /// The archive file to create.
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+    },
+    /// Restore all the objects from an archive into an empty persistent store.
+    /// The core agent must be stopped while restoring.
+    Restore {
+        /// The archive file to restore.
+        #[structopt(parse(from_os_str))]
+        archive: PathBuf,
+    },
+    /// Compare the specs in the persistent store, eg: once restored and with the core agent
+    /// restarted, with the resources which the nodes report to the core agent.
+    Check {
+        /// The rest endpoint.
+        #[structopt(short, long)]
+        rest_url: Url,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = CliArgs::from_args();
+    utils::print_package_info!();
+
+    let mut store = Etcd::new(&args.store).await?;
+
+    match args.command {
+        Command::Backup { archive } => {
+            let backup = Archive::backup(&mut store).await?;
+            serde_json::to_writer_pretty(File::create(&archive)?, &backup)?;
+            println!("Archived the persistent store into {}", archive.display());
+            print_summary(&backup);
+        }
+        Command::Restore { archive } => {
+            let backup: Archive = serde_json::from_reader(File::open(&archive)?)?;
+            backup.restore(&mut store).await?;
+            println!("Restored the persistent store from {}", archive.display());
+            print_summary(&backup);
+        }
+        Command::Check { rest_url } => {
+            let config = Configuration::new(rest_url, Duration::from_secs(5), None, None, true)
+                .map_err(|e| anyhow!("Failed to create rest client config: '{:?}'", e))?;
+            let client = ApiClient::new(config);
+
+            let issues = check::check(&mut store, &client).await?;
+            if issues.is_empty() {
+                println!("The persistent store is consistent with the nodes");
+                return Ok(());
+            }
+            print_table(
+                &["Resource", "Id", "Issue"],
+                issues
+                    .iter()
+                    .map(|issue| {
+                        vec![
+                            issue.kind.to_string(),
+                            issue.id.clone(),
+                            issue.issue.clone(),
+                        ]
+                    })
+                    .collect(),
+            );
+            return Err(anyhow!("Found {} inconsistencies", issues.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the number of archived objects of each type.
+fn print_summary(archive: &Archive) {
+    print_table(
+        &["Type", "Objects"],
+        archive
+            .summary()
+            .into_iter()
+            .map(|(obj_type, count)| vec![obj_type, count.to_string()])
+            .collect(),
+    );
+}
+
+/// Print the `rows` as a table with the given `titles`, unless there are no rows.
+fn print_table(titles: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return;
+    }
+    let rows = rows
+        .iter()
+        .map(|row| Row::new(row.iter().map(|cell| Cell::new(cell)).collect()))
+        .collect();
+    let mut table = Table::init(rows);
+    table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(
+        titles.iter().map(|title| Cell::new(title)).collect(),
+    ));
+    table.printstd();
+}
@@ -0,0 +1,136 @@
+use common_lib::{
+    store::etcd::Etcd,
+    types::v0::store::{
+        definitions::{key_prefix, StorableObject, StorableObjectType, Store, StoreError},
+        registry::{CoreRegistryConfig, NodeRegistration},
+        schema::{
+            pending_migrations, VersionedObject, STORE_SCHEMA_VERSION, VERSIONED_OBJECT_TYPES,
+        },
+    },
+};
+use prettytable::{Cell, Row, Table};
+use structopt::StructOpt;
+
+#[derive(structopt::StructOpt, Debug)]
+#[structopt(version = utils::package_info!())]
+struct CliArgs {
+    /// The persistent store (etcd) endpoint.
+    #[structopt(short, long, default_value = "http://localhost:2379")]
+    store: String,
+
+    /// List each object which needs to be migrated, along with its pending migrations.
+    #[structopt(short, long)]
+    objects: bool,
+}
+
+/// Schema versions of the stored objects of a type.
+struct TypeReport {
+    obj_type: StorableObjectType,
+    /// Number of stored objects.
+    total: usize,
+    /// Keys and schema versions of the objects which are not at the current schema version.
+    outdated: Vec<(String, u32)>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = CliArgs::from_args();
+    utils::print_package_info!();
+
+    let mut store = Etcd::new(&args.store).await?;
+
+    let config_key = CoreRegistryConfig::new(NodeRegistration::Automatic).key();
+    match store.get_obj::<CoreRegistryConfig>(&config_key).await {
+        Ok(config) => println!(
+            "Store schema version: {}, current schema version: {}",
+            config.schema_version(),
+            STORE_SCHEMA_VERSION
+        ),
+        Err(StoreError::MissingEntry { .. }) => println!(
+            "Store schema version: none, current schema version: {}",
+            STORE_SCHEMA_VERSION
+        ),
+        Err(error) => return Err(error.into()),
+    }
+
+    let mut reports = Vec::with_capacity(VERSIONED_OBJECT_TYPES.len());
+    for obj_type in VERSIONED_OBJECT_TYPES {
+        // the trailing slash keeps out the types which share the prefix, eg: Node and NodeSpec
+        let values = store
+            .get_values_prefix(&format!("{}/", key_prefix(obj_type)))
+            .await?;
+        let total = values.len();
+        let outdated = values
+            .into_iter()
+            .map(|(key, value)| (key, VersionedObject::from_value(value).schema_version()))
+            .filter(|(_, version)| *version != STORE_SCHEMA_VERSION)
+            .collect();
+        reports.push(TypeReport {
+            obj_type,
+            total,
+            outdated,
+        });
+    }
+
+    print_table(
+        &["Type", "Objects", "Needs Migration"],
+        reports
+            .iter()
+            .map(|report| {
+                vec![
+                    report.obj_type.to_string(),
+                    report.total.to_string(),
+                    report.outdated.len().to_string(),
+                ]
+            })
+            .collect(),
+    );
+
+    if args.objects {
+        print_table(
+            &["Key", "Schema Version", "Migrations"],
+            reports
+                .iter()
+                .flat_map(|report| {
+                    report.outdated.iter().map(move |(key, version)| {
+                        vec![
+                            key.clone(),
+                            version.to_string(),
+                            migrations(report.obj_type, *version),
+                        ]
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Describe the migrations which upgrade an object of `obj_type` from schema `version`.
+fn migrations(obj_type: StorableObjectType, version: u32) -> String {
+    if version > STORE_SCHEMA_VERSION {
+        return "Unsupported: newer than the current schema version".to_string();
+    }
+    pending_migrations(obj_type, version)
+        .map(|migration| format!("{}: {}", migration.from, migration.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print the `rows` as a table with the given `titles`, unless there are no rows.
+fn print_table(titles: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return;
+    }
+    let rows = rows
+        .iter()
+        .map(|row| Row::new(row.iter().map(|cell| Cell::new(cell)).collect()))
+        .collect();
+    let mut table = Table::init(rows);
+    table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(
+        titles.iter().map(|title| Cell::new(title)).collect(),
+    ));
+    table.printstd();
+}
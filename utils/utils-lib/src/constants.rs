@@ -35,3 +35,6 @@ pub const DEFAULT_GRPC_SERVER_ADDR: &str = "https://0.0.0.0:50051";
 
 /// The default value to be assigned as GRPC client addr if not overridden
 pub const DEFAULT_GRPC_CLIENT_ADDR: &str = "https://core:50051";
+
+/// The address on which the core agent serves its prometheus metrics, when enabled
+pub const DEFAULT_METRICS_ADDR: &str = "0.0.0.0:9502";